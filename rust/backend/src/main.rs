@@ -4,12 +4,42 @@
  * Author: Samuel Gauthier
  */
 use clap::Parser;
-use log::info;
+use log::{info, LevelFilter};
 use ntex::time::sleep;
 use ntex::web;
-use simple_logger;
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
 use std::sync::{Arc, Mutex};
 
+/// Part of the backend server failing on demand, to test how the load balancer detects the
+/// unhealthy backend servers without killing them
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FailureMode {
+    /// The backend server answers normally
+    None,
+
+    /// The requests are answered with a 500, the health checks still succeed
+    Requests,
+
+    /// The health checks are answered with a 500, the requests still succeed
+    Health,
+
+    /// Both the requests and the health checks are answered with a 500
+    All,
+}
+
+impl FailureMode {
+    /// Returns true if the requests are answered with a 500
+    fn fails_requests(&self) -> bool {
+        matches!(self, FailureMode::Requests | FailureMode::All)
+    }
+
+    /// Returns true if the health checks are answered with a 500
+    fn fails_health_checks(&self) -> bool {
+        matches!(self, FailureMode::Health | FailureMode::All)
+    }
+}
+
 /// State of the backend server. Contains the name of the server and the number of times it has
 /// been called
 #[derive(Debug, Clone)]
@@ -22,6 +52,9 @@ struct State {
 
     /// Delay in seconds before responding to a request
     delay_ms: u64,
+
+    /// Part of the backend server failing, set by the admin endpoints
+    failure_mode: FailureMode,
 }
 
 impl State {
@@ -31,6 +64,7 @@ impl State {
             name,
             times_called: 0,
             delay_ms,
+            failure_mode: FailureMode::None,
         }
     }
 }
@@ -39,6 +73,10 @@ impl State {
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Args {
+    /// IPv4 or IPv6 address on which to run the backend server
+    #[arg(long, default_value = "127.0.0.1")]
+    listen_addr: IpAddr,
+
     /// Port on which to run the backend server
     #[arg(short, long, default_value = "8081")]
     port: u16,
@@ -47,16 +85,42 @@ struct Args {
     #[arg(short, long, default_value = "backend-server")]
     name: String,
 
-    /// Delay in seconds before responding to a request
+    /// Delay in milliseconds before responding to a request, unless the request asks for another
+    /// one with the delay_ms query parameter
     #[arg(short, long, default_value = "0")]
     delay_ms: u64,
+
+    /// Maximum level of the logged messages, one of off, error, warn, info, debug or trace. Takes
+    /// precedence over the RUST_LOG environment variable
+    #[arg(long, value_parser = parse_log_level)]
+    log_level: Option<LevelFilter>,
+}
+
+/// Parses the maximum level of the logged messages, case-insensitive
+fn parse_log_level(value: &str) -> Result<LevelFilter, String> {
+    LevelFilter::from_str(value).map_err(|_| {
+        format!(
+            "invalid log level {}: must be off, error, warn, info, debug or trace",
+            value
+        )
+    })
 }
 
-/// Prints information about the incoming request
+/// Logged in place of the address of a client which is not known
+const UNKNOWN_REMOTE_ADDRESS: &str = "unknown";
+
+/// Maximum delay in milliseconds a request can ask for with the delay_ms query parameter
+const MAX_DELAY_MS: u64 = 60_000;
+
+/// Prints information about the incoming request, header values which are not valid UTF-8
+/// included
 fn print_request_info(request: &web::HttpRequest) {
     info!(
         "Received request from {}",
-        request.connection_info().remote().unwrap()
+        request
+            .connection_info()
+            .remote()
+            .unwrap_or(UNKNOWN_REMOTE_ADDRESS)
     );
     info!(
         "{} {} {:?}",
@@ -65,23 +129,39 @@ fn print_request_info(request: &web::HttpRequest) {
         request.head().version
     );
     for (key, value) in request.headers().iter() {
-        info!("{}: {}", key, value.to_str().unwrap());
+        info!("{}: {}", key, String::from_utf8_lossy(value.as_bytes()));
     }
 }
 
-/// Index endpoint that returns a hello message containing the name of the backend server
+/// Returns the delay asked for by the delay_ms query parameter of the request, for example
+/// /?delay_ms=500, capped to MAX_DELAY_MS. None if the parameter is missing or not a number
+fn requested_delay_ms(request: &web::HttpRequest) -> Option<u64> {
+    request
+        .query_string()
+        .split('&')
+        .find_map(|parameter| parameter.strip_prefix("delay_ms="))
+        .and_then(|delay_ms| delay_ms.parse::<u64>().ok())
+        .map(|delay_ms| delay_ms.min(MAX_DELAY_MS))
+}
+
+/// Index endpoint that returns a hello message containing the name of the backend server, after
+/// the delay asked for by the request or the default delay of the backend server. Answers with a
+/// 500 instead while the requests fail
 async fn index(
     state: web::types::State<Arc<Mutex<State>>>,
     request: web::HttpRequest,
-) -> Result<String, web::Error> {
+) -> web::HttpResponse {
     print_request_info(&request);
-    let mut state = state.lock().unwrap();
+    // The lock is not held while sleeping, otherwise concurrent requests would be serialized
+    let delay_ms = requested_delay_ms(&request).unwrap_or_else(|| state.lock().unwrap().delay_ms);
 
-    if state.delay_ms > 0 {
-        info!("Sleeping for {} milliseconds", state.delay_ms);
-        sleep(std::time::Duration::from_millis(state.delay_ms)).await;
+    if delay_ms > 0 {
+        info!("Sleeping for {} milliseconds", delay_ms);
+        sleep(std::time::Duration::from_millis(delay_ms)).await;
     }
 
+    let mut state = state.lock().unwrap();
+
     info!("Replied with a hello message from {}", state.name);
     state.times_called += 1;
     info!(
@@ -89,34 +169,97 @@ async fn index(
         state.times_called
     );
 
-    Ok(format!("Hello from backend server: {}", state.name))
+    if state.failure_mode.fails_requests() {
+        info!("Failed the request on demand");
+        return web::HttpResponse::InternalServerError()
+            .body(format!("Failure of backend server: {}", state.name));
+    }
+
+    web::HttpResponse::Ok().body(format!("Hello from backend server: {}", state.name))
 }
 
-/// Health check endpoint that returns an empty string
+/// Health check endpoint that returns an empty string, or answers with a 500 while the health
+/// checks fail
 #[web::get("/health")]
-async fn health_check(request: web::HttpRequest) -> Result<String, web::Error> {
+async fn health_check(
+    state: web::types::State<Arc<Mutex<State>>>,
+    request: web::HttpRequest,
+) -> web::HttpResponse {
     info!(
         "Received health check request from {}",
-        request.connection_info().remote().unwrap()
+        request
+            .connection_info()
+            .remote()
+            .unwrap_or(UNKNOWN_REMOTE_ADDRESS)
     );
 
-    Ok("".to_string())
+    if state.lock().unwrap().failure_mode.fails_health_checks() {
+        info!("Failed the health check on demand");
+        return web::HttpResponse::InternalServerError().finish();
+    }
+
+    web::HttpResponse::Ok().finish()
+}
+
+/// Admin endpoint that makes the backend server fail until it recovers. The mode query parameter
+/// gives what fails: requests, health or all, the default
+#[web::post("/admin/fail")]
+async fn fail(
+    state: web::types::State<Arc<Mutex<State>>>,
+    request: web::HttpRequest,
+) -> web::HttpResponse {
+    let mode = request
+        .query_string()
+        .split('&')
+        .find_map(|parameter| parameter.strip_prefix("mode="));
+    let failure_mode = match mode {
+        None | Some("all") => FailureMode::All,
+        Some("requests") => FailureMode::Requests,
+        Some("health") => FailureMode::Health,
+        Some(mode) => {
+            return web::HttpResponse::BadRequest().body(format!(
+                "Unknown failure mode {}, expected requests, health or all",
+                mode
+            ))
+        }
+    };
+
+    info!("Failing on demand: {:?}", failure_mode);
+    state.lock().unwrap().failure_mode = failure_mode;
+    web::HttpResponse::Ok().body(format!("Failing: {:?}", failure_mode))
+}
+
+/// Admin endpoint that makes the backend server answer normally again
+#[web::post("/admin/recover")]
+async fn recover(state: web::types::State<Arc<Mutex<State>>>) -> web::HttpResponse {
+    info!("Recovered on demand");
+    state.lock().unwrap().failure_mode = FailureMode::None;
+    web::HttpResponse::Ok().body("Recovered")
 }
 
 #[ntex::main]
 async fn main() -> std::io::Result<()> {
-    simple_logger::SimpleLogger::new().env().init().unwrap();
-
     let args = Args::parse();
+    // The level given on the command line replaces the one of RUST_LOG
+    let logger = simple_logger::SimpleLogger::new().env();
+    match args.log_level {
+        Some(log_level) => logger.with_level(log_level),
+        None => logger,
+    }
+    .init()
+    .unwrap();
+
     let state = Arc::new(Mutex::new(State::new(args.name.clone(), args.delay_ms)));
 
     web::HttpServer::new(move || {
         web::App::new()
             .state(state.clone())
             .service(health_check)
+            .service(fail)
+            .service(recover)
             .default_service(web::to(index))
     })
-    .bind(("127.0.0.1", args.port))?
+    .bind(SocketAddr::new(args.listen_addr, args.port))?
     .run()
     .await
 }
@@ -0,0 +1,219 @@
+use crate::backend_config::BackendConfig;
+use crate::backend_definition::BackendDefinition;
+use crate::backend_pruner::BackendPruner;
+use crate::backend_snapshot::BackendSnapshot;
+use crate::evicted_backend::EvictedBackend;
+use crate::health::Health;
+use crate::load_balancer::LoadBalancer;
+use crate::simple_backend::SimpleBackend;
+use crate::stats_page;
+use crate::Timings;
+
+use actix_web::dev::Service;
+use actix_web::error::InternalError;
+use actix_web::http::header::{self, ContentType};
+use actix_web::web::{self, Data, Json, Path, ServiceConfig};
+use actix_web::{HttpRequest, HttpResponse};
+use log::warn;
+use std::sync::Arc;
+use tokio::sync::RwLock as TokioRwLock;
+
+/// Registers the routes of the admin API under /admin. When a token is given, the requests must
+/// carry it as a bearer token in their Authorization header, and are answered with a 401
+/// otherwise.
+pub fn configure(config: &mut ServiceConfig, token: Option<String>) {
+    config.service(
+        web::scope("/admin")
+            .wrap_fn(move |request, service| {
+                let response = match &token {
+                    Some(token) if !is_authorized(request.request(), token) => {
+                        warn!("Rejected unauthorized admin request {}", request.path());
+                        let response = HttpResponse::Unauthorized()
+                            .insert_header((header::WWW_AUTHENTICATE, "Bearer"))
+                            .body("Unauthorized");
+                        Err(actix_web::Error::from(InternalError::from_response(
+                            "Unauthorized",
+                            response,
+                        )))
+                    }
+                    _ => Ok(service.call(request)),
+                };
+                async move { response?.await }
+            })
+            .route("/backends", web::get().to(backends))
+            .route("/backends", web::post().to(add_backend))
+            // The address contains slashes, so it spans the rest of the path
+            .route("/backends/{address:.*}", web::delete().to(remove_backend))
+            .route("/draining/{address:.*}", web::put().to(start_draining))
+            .route("/draining/{address:.*}", web::delete().to(stop_draining))
+            .route("/weight/{address:.*}", web::put().to(set_weight))
+            .route("/evicted", web::get().to(evicted))
+            .route("/health-check", web::post().to(check_health))
+            .route("/stats", web::get().to(stats)),
+    );
+}
+
+/// Returns true if the request carries the given token as a bearer token.
+fn is_authorized(request: &HttpRequest, token: &str) -> bool {
+    request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.as_bytes().strip_prefix(b"Bearer "))
+        .is_some_and(|given| constant_time_eq(given, token.as_bytes()))
+}
+
+/// Compares the given bytes in a time which does not depend on where they differ, so that the
+/// token cannot be guessed byte by byte from the response times. Only its length can be.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Lists the backend servers of the load balancer with their health, average response time and
+/// circuit state.
+async fn backends(
+    load_balancer: Data<Arc<TokioRwLock<Arc<dyn LoadBalancer>>>>,
+) -> Json<Vec<BackendSnapshot>> {
+    let lb = load_balancer.read().await;
+    Json(lb.backends_snapshot().await)
+}
+
+/// Checks the health of the backend servers now instead of waiting for the next health check, and
+/// lists them with their health afterwards. As with the periodic health checks, a backend server
+/// only changes health once its healthy or unhealthy threshold is reached.
+async fn check_health(
+    load_balancer: Data<Arc<TokioRwLock<Arc<dyn LoadBalancer>>>>,
+) -> Json<Vec<BackendSnapshot>> {
+    // Release the lock before the health checks, so that the load balancer can be replaced while
+    // they run
+    let lb = load_balancer.read().await.clone();
+    lb.check_backends_healths().await;
+    Json(lb.backends_snapshot().await)
+}
+
+/// Lists the backend servers removed from the load balancer after being unhealthy for longer than
+/// the eviction timeout, the oldest first.
+async fn evicted(pruner: Data<Arc<BackendPruner>>) -> Json<Vec<EvictedBackend>> {
+    Json(pruner.evicted())
+}
+
+/// Returns an HTML dashboard of the backend servers, from the same snapshots as /admin/backends,
+/// reloading itself at every health check.
+async fn stats(
+    load_balancer: Data<Arc<TokioRwLock<Arc<dyn LoadBalancer>>>>,
+    timings: Data<Timings>,
+) -> HttpResponse {
+    let lb = load_balancer.read().await.clone();
+    let backends = lb.backends_snapshot().await;
+    HttpResponse::Ok()
+        .content_type(ContentType::html())
+        .body(stats_page::render(
+            &lb.strategy(),
+            &backends,
+            timings.health_check_interval,
+        ))
+}
+
+/// Adds a backend server to the load balancer, given as JSON in the same form as in the config
+/// file, for example {"address": "http://localhost:8081/", "max_connections": 10}. The backend
+/// server is unhealthy, and receives no requests, until a health check succeeds.
+async fn add_backend(
+    load_balancer: Data<Arc<TokioRwLock<Arc<dyn LoadBalancer>>>>,
+    backend_config: Data<BackendConfig>,
+    new_backend: Json<BackendDefinition>,
+) -> HttpResponse {
+    let backend = match SimpleBackend::new(
+        new_backend.address.clone(),
+        Health::Unhealthy,
+        &new_backend.config(&backend_config),
+    ) {
+        Ok(backend) => backend,
+        Err(e) => {
+            warn!("{}", e);
+            return HttpResponse::BadRequest().body(e);
+        }
+    };
+
+    let lb = load_balancer.read().await;
+    match lb.add_backend(Box::new(backend)).await {
+        Ok(()) => HttpResponse::Created().finish(),
+        Err(e) => {
+            warn!("{}", e);
+            HttpResponse::Conflict().body(e)
+        }
+    }
+}
+
+/// Removes the backend server with the given address from the load balancer.
+async fn remove_backend(
+    load_balancer: Data<Arc<TokioRwLock<Arc<dyn LoadBalancer>>>>,
+    address: Path<String>,
+) -> HttpResponse {
+    let lb = load_balancer.read().await;
+    match lb.remove_backend(&address).await {
+        Ok(()) => HttpResponse::NoContent().finish(),
+        Err(e) => {
+            warn!("{}", e);
+            HttpResponse::NotFound().body(e)
+        }
+    }
+}
+
+/// Drains the backend server with the given address: it receives no new requests, while the
+/// requests already sent to it complete.
+async fn start_draining(
+    load_balancer: Data<Arc<TokioRwLock<Arc<dyn LoadBalancer>>>>,
+    address: Path<String>,
+) -> HttpResponse {
+    set_draining(&load_balancer, &address, true).await
+}
+
+/// Stops draining the backend server with the given address, which receives requests again.
+async fn stop_draining(
+    load_balancer: Data<Arc<TokioRwLock<Arc<dyn LoadBalancer>>>>,
+    address: Path<String>,
+) -> HttpResponse {
+    set_draining(&load_balancer, &address, false).await
+}
+
+/// Starts or stops draining the backend server with the given address.
+async fn set_draining(
+    load_balancer: &TokioRwLock<Arc<dyn LoadBalancer>>,
+    address: &str,
+    draining: bool,
+) -> HttpResponse {
+    let lb = load_balancer.read().await;
+    match lb.set_draining(address, draining).await {
+        Ok(()) => HttpResponse::NoContent().finish(),
+        Err(e) => {
+            warn!("{}", e);
+            HttpResponse::NotFound().body(e)
+        }
+    }
+}
+
+/// Sets the weight of the backend server with the given address to the integer given as the body
+/// of the request. A weight of 0 parks the backend server, which is still health checked but
+/// receives no requests until its weight is raised.
+async fn set_weight(
+    load_balancer: Data<Arc<TokioRwLock<Arc<dyn LoadBalancer>>>>,
+    address: Path<String>,
+    body: String,
+) -> HttpResponse {
+    let Ok(weight) = body.trim().parse::<u32>() else {
+        let e = format!(
+            "Invalid weight {}: must be a non-negative integer",
+            body.trim()
+        );
+        warn!("{}", e);
+        return HttpResponse::BadRequest().body(e);
+    };
+
+    let lb = load_balancer.read().await;
+    match lb.set_weight(&address, weight).await {
+        Ok(()) => HttpResponse::NoContent().finish(),
+        Err(e) => {
+            warn!("{}", e);
+            HttpResponse::NotFound().body(e)
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use crate::backend::Backend;
+use crate::health::Health;
+use crate::request_context::RequestContext;
+use crate::tag_routing;
+
+use actix_web::HttpRequest;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Returns the ID of the backend server with the given address, given to the clients in the
+/// affinity cookie. It is a hash of the address, so that the addresses of the backend servers are
+/// not disclosed, and it stays the same across restarts of the load balancer.
+pub fn backend_id(address: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    address.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// Returns the value of the affinity cookie pinning a client to the backend server with the given
+/// ID. With a TTL, the time at which the client is pinned is appended in seconds since the Unix
+/// epoch, as ID.TIME, so that the pin expires even if the client keeps the cookie.
+pub fn cookie_value(backend_id: &str, ttl: Option<Duration>) -> String {
+    match ttl {
+        Some(_) => format!("{}.{}", backend_id, unix_time_secs()),
+        None => backend_id.to_string(),
+    }
+}
+
+/// Returns the ID of the backend server given in the affinity cookie with the given name of the
+/// request, None if the cookie is missing or no cookie name is given. With a TTL, None as well if
+/// the client was pinned longer ago than the TTL, so that it is balanced again.
+pub fn requested_backend_id(
+    request: &HttpRequest,
+    cookie_name: Option<&str>,
+    ttl: Option<Duration>,
+) -> Option<String> {
+    let cookie = request.cookie(cookie_name?)?;
+    let (backend_id, pinned_at) = match cookie.value().split_once('.') {
+        Some((backend_id, pinned_at)) => (backend_id, pinned_at.parse::<u64>().ok()),
+        None => (cookie.value(), None),
+    };
+    if let Some(ttl) = ttl {
+        // A cookie set before the TTL was given has no time, it is expired
+        let pinned_for = unix_time_secs().saturating_sub(pinned_at?);
+        if pinned_for >= ttl.as_secs() {
+            return None;
+        }
+    }
+    Some(backend_id.to_string())
+}
+
+/// Returns the current time in seconds since the Unix epoch.
+fn unix_time_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Returns true if the request is pinned to the given backend server by its affinity cookie and
+/// the backend server can receive it, that is it is healthy, not draining, not parked, has not
+/// reached its maximum number of connections and has the tags asked for by the request. Otherwise
+/// the request goes through the normal selection. The backup backend servers never pin the
+/// requests, so that the clients go back to the primary backend servers once they are available
+/// again.
+pub fn is_pinned(context: &RequestContext, backend: &dyn Backend) -> bool {
+    context
+        .affinity
+        .as_ref()
+        .is_some_and(|affinity| *affinity == backend_id(backend.address()))
+        && backend.health() == Health::Healthy
+        && !backend.is_draining()
+        && !backend.is_saturated()
+        && backend.weight() > 0
+        && !backend.is_backup()
+        && tag_routing::has_tags(backend, &context.tags)
+}
@@ -1,32 +1,130 @@
+use crate::circuit_breaker::CircuitState;
 use crate::health::Health;
+use crate::request_context::RequestContext;
+use crate::response_time_histogram::ResponseTimePercentiles;
+use crate::upgrade_request::UpgradeRequest;
 use async_trait::async_trait;
 use core::f32;
 use reqwest::{Error, Response};
+use std::collections::BTreeMap;
 use std::fmt::Debug;
+use std::time::Duration;
 
 /// Represents a backend server resource to which the load balancer can forward the requests.
 #[async_trait]
 pub trait Backend: Send + Sync + Debug + BackendClone {
-    // pub trait Backend: Send + Sync + Debug {
     /// Checks the health of the backend server by sending a request to the health check endpoint.
     /// If the server is healthy, the health status is set to Healthy, otherwise it is set to
     /// Unhealthy.
     async fn check_health(&self);
 
-    /// Returns the health status of the backend server.
-    async fn health(&self) -> Health;
+    /// Returns how long the health checks and the requests have found the backend server
+    /// unhealthy without interruption, zero if it is healthy.
+    fn unhealthy_for(&self) -> Duration;
 
-    /// Sends a request to the backend server and returns the response in case of success. If the
-    /// request succeeds, the health status is updated to healthy. If the request fails, the health
-    /// status of the backend server is set to Unhealthy.
-    ///
-    /// TODO: You should add arguments to this function to pass the request method, headers, body,
-    /// etc.
-    async fn send_request(&self) -> Result<Response, Error>;
+    /// Returns the health status of the backend server. A backend server whose circuit is open is
+    /// reported as Unhealthy. Reading the health status does not wait on a lock, so it is cheap
+    /// to call on every request.
+    fn health(&self) -> Health;
 
-    /// Returns the response time in milliseconds of the last request sent to the backend server.
+    /// Sends the request described by the context to the backend server, with its method, headers
+    /// and body, and returns the response in case of success. If the request succeeds, the health
+    /// status is updated to healthy and the circuit is closed. If the request fails, the failure is
+    /// recorded by the circuit breaker.
+    async fn send_request(&self, context: &RequestContext) -> Result<Response, Error>;
+
+    /// Returns the WebSocket upgrade request described by the context, to send to the backend
+    /// server with the same path and headers as its other requests. Returns an error if the
+    /// backend server cannot be sent a WebSocket.
+    fn upgrade_request(&self, context: &RequestContext) -> Result<UpgradeRequest, String>;
+
+    /// Returns the moving average of the response time of the backend server to the requests in
+    /// milliseconds, on which the routing is based. The health checks are not included.
     async fn response_time_ms(&self) -> f32;
 
+    /// Moves the moving average of the response time towards 0 by the given fraction of it, so
+    /// that a backend server which answered slowly once is tried again after a while.
+    async fn decay_response_time(&self, decay: f32);
+
+    /// Returns the moving average of the time taken by the health checks of the backend server in
+    /// milliseconds.
+    fn health_check_latency_ms(&self) -> f32;
+
+    /// Returns the state of the circuit breaker of the backend server.
+    fn circuit_state(&self) -> CircuitState;
+
+    /// Returns the number of consecutive failed requests sent to the backend server.
+    fn consecutive_failures(&self) -> u32;
+
+    /// Returns the number of consecutive successful requests sent to the backend server.
+    fn consecutive_successes(&self) -> u32;
+
+    /// Returns true if the backend server is ejected because too many of its recent requests
+    /// failed, in which case it is reported as Unhealthy until its cooldown has elapsed.
+    fn is_ejected(&self) -> bool;
+
+    /// Returns the number of requests currently sent to the backend server.
+    fn in_flight(&self) -> u32;
+
+    /// Returns the number of requests sent to the backend server since the load balancer started.
+    fn requests_total(&self) -> u64;
+
+    /// Returns the number of requests sent to the backend server which failed since the load
+    /// balancer started.
+    fn errors_total(&self) -> u64;
+
+    /// Returns the number of passed health checks of the backend server since the load balancer
+    /// started.
+    fn health_checks_passed(&self) -> u64;
+
+    /// Returns the number of failed health checks of the backend server since the load balancer
+    /// started.
+    fn health_checks_failed(&self) -> u64;
+
+    /// Returns the fraction of the recent health checks of the backend server which failed,
+    /// between 0 and 1.
+    fn health_check_failure_rate(&self) -> f32;
+
+    /// Returns the percentiles of the response time of the backend server to the requests since
+    /// the load balancer started.
+    fn response_time_percentiles(&self) -> ResponseTimePercentiles;
+
+    /// Returns true if the backend server has reached its maximum number of requests in flight,
+    /// in which case no more requests should be sent to it.
+    fn is_saturated(&self) -> bool;
+
+    /// Returns true if the backend server is draining, in which case no new requests should be
+    /// sent to it. The requests already sent to it complete and it is still health checked.
+    fn is_draining(&self) -> bool;
+
+    /// Starts or stops draining the backend server.
+    fn set_draining(&self, draining: bool);
+
+    /// Returns the weight of the backend server. A backend server with twice the weight of another
+    /// one can handle twice as many requests. A backend server with a weight of 0 is parked: it
+    /// receives no requests but is still health checked, until its weight is raised.
+    fn weight(&self) -> u32;
+
+    /// Sets the weight of the backend server, 0 parking it.
+    fn set_weight(&self, weight: u32);
+
+    /// Returns the weight of the backend server taking its slow start and its load into account.
+    /// It is lower than its weight for a while after it becomes healthy again, so that it does not
+    /// receive its full share of the requests at once, and while it reports a load.
+    fn effective_weight(&self) -> f32;
+
+    /// Returns the load last reported by the backend server in the answers to its health checks,
+    /// between 0 and 1. 0 if it reports none.
+    fn load(&self) -> f32;
+
+    /// Returns true if the backend server is a backup, which only receives requests when no
+    /// primary backend server is available.
+    fn is_backup(&self) -> bool;
+
+    /// Returns the tags of the backend server, such as zone=us-east or version=canary, matched
+    /// against the tags asked for by the requests.
+    fn tags(&self) -> &BTreeMap<String, String>;
+
     /// Returns the address of the backend server.
     fn address(&self) -> &str;
 }
@@ -49,3 +147,67 @@ impl Clone for Box<dyn Backend> {
         self.clone_box()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend_config::BackendConfig;
+    use crate::continent::Continent;
+    use crate::geo_backend::GeoBackend;
+    use crate::simple_backend::SimpleBackend;
+
+    /// Returns a simple and a geo backend server, both healthy, behind the Backend trait.
+    fn backends() -> Vec<Box<dyn Backend>> {
+        let config = BackendConfig::default();
+        let simple = SimpleBackend::new(
+            "http://localhost:8081/".to_string(),
+            Health::Healthy,
+            &config,
+        )
+        .unwrap();
+        let geo = GeoBackend::new(
+            Continent::Europe,
+            "http://localhost:8082/".to_string(),
+            Health::Healthy,
+            &config,
+        )
+        .unwrap();
+        vec![Box::new(simple), Box::new(geo)]
+    }
+
+    #[tokio::test]
+    async fn calls_the_simple_and_geo_backends_through_the_trait() {
+        let expected = [
+            ("http://localhost:8081/", "localhost:8081"),
+            ("http://localhost:8082/", "localhost:8082"),
+        ];
+        for (backend, (address, authority)) in backends().iter().zip(expected) {
+            assert_eq!(backend.address(), address);
+            assert_eq!(backend.health(), Health::Healthy);
+            assert_eq!(backend.unhealthy_for(), Duration::ZERO);
+            assert_eq!(backend.circuit_state(), CircuitState::Closed);
+            assert_eq!(backend.response_time_ms().await, 0.0);
+            assert_eq!(backend.in_flight(), 0);
+            assert_eq!(backend.requests_total(), 0);
+            assert_eq!(backend.weight(), 1);
+            assert!(!backend.is_backup());
+            assert!(!backend.is_saturated());
+            assert!(backend.tags().is_empty());
+
+            let upgrade_request = backend.upgrade_request(&RequestContext::default());
+            assert_eq!(upgrade_request.unwrap().authority, authority);
+        }
+    }
+
+    #[tokio::test]
+    async fn shares_the_state_of_a_backend_with_its_clones() {
+        for backend in backends() {
+            let clone = backend.clone();
+
+            backend.set_draining(true);
+
+            assert!(clone.is_draining());
+            assert_eq!(clone.address(), backend.address());
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use crate::backend_protocol::BackendProtocol;
+use crate::backend_tls::BackendTls;
+use crate::header_filter::HeaderFilter;
+use crate::health_check_kind::HealthCheckKind;
+use crate::health_event::HealthWebhook;
+use crate::outlier_detector::OutlierDetection;
+
+use reqwest::header::HeaderMap;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Settings applied to the backend servers created by the load balancer.
+#[derive(Clone, Debug)]
+pub struct BackendConfig {
+    /// How the health of the backend servers is checked.
+    pub health_check: HealthCheckKind,
+
+    /// Number of consecutive successful health checks after which an unhealthy backend server
+    /// becomes healthy, greater than 0.
+    pub healthy_threshold: u32,
+
+    /// Number of consecutive failed health checks after which a healthy backend server becomes
+    /// unhealthy, greater than 0.
+    pub unhealthy_threshold: u32,
+
+    /// Number of consecutive failed requests after which the circuit of the backend server opens.
+    /// 0 disables the circuit breaker.
+    pub circuit_breaker_threshold: u32,
+
+    /// Time during which the circuit stays open before a single probe request is let through.
+    pub circuit_breaker_cooldown: Duration,
+
+    /// Settings of the ejection of the backend servers answering too many requests with an error.
+    /// None disables the outlier detection.
+    pub outlier_detection: Option<OutlierDetection>,
+
+    /// Weight of the last sample in the moving average of the response time of the backend
+    /// servers, between 0 excluded and 1 included.
+    pub response_time_smoothing: f32,
+
+    /// Name of the header carrying the request ID sent to the backend server.
+    pub request_id_header: String,
+
+    /// Filter of the headers of the client requests forwarded to the backend servers.
+    pub request_headers: HeaderFilter,
+
+    /// Headers set on every request sent to the backend servers, replacing the ones of the client
+    /// with the same name.
+    pub header_overrides: HeaderMap,
+
+    /// Maximum number of requests sent to a backend server at the same time. A backend server
+    /// having as many requests in flight is skipped. None means no limit.
+    pub max_connections: Option<u32>,
+
+    /// HTTP version used to send the requests to the backend servers.
+    pub protocol: BackendProtocol,
+
+    /// Maximum number of redirects of the backend servers followed before answering the client.
+    /// 0 passes the redirects through to the client.
+    pub max_redirects: usize,
+
+    /// Maximum number of idle connections kept open to each backend server, to be reused by the
+    /// following requests. 0 opens a connection per request.
+    pub pool_max_idle_per_host: usize,
+
+    /// Time after which an idle connection to a backend server is closed. None keeps them open
+    /// until the backend server closes them.
+    pub pool_idle_timeout: Option<Duration>,
+
+    /// Moving average of the response time of the requests, or time taken by a health check,
+    /// above which a backend server is unhealthy even though it answers. None means no limit.
+    pub max_response_time: Option<Duration>,
+
+    /// Maximum time given to the backend servers to accept a connection. None leaves it to the
+    /// operating system.
+    pub connect_timeout: Option<Duration>,
+
+    /// Maximum time given to the backend servers to answer a health check, or to accept the
+    /// connection of a TCP health check. None means no limit.
+    pub health_check_timeout: Option<Duration>,
+
+    /// Webhook to which the changes of health of the backend servers are posted. None only logs
+    /// them.
+    pub health_webhook: Option<HealthWebhook>,
+
+    /// Client certificate and certificate authorities used to connect to the HTTPS backend
+    /// servers.
+    pub tls: BackendTls,
+
+    /// Weight of the backend server. The least response load balancer divides the response time of
+    /// the backend server by its weight. 0 parks the backend server, which is health checked but
+    /// receives no requests.
+    pub weight: u32,
+
+    /// Time during which the effective weight of a backend server becoming healthy again ramps up
+    /// to its weight. Zero disables the slow start.
+    pub slow_start: Duration,
+
+    /// Whether the backend server is a backup, which only receives requests when no primary
+    /// backend server is available. Only used by the round robin and least response load
+    /// balancers.
+    pub backup: bool,
+
+    /// Tags of the backend server, such as zone=us-east or version=canary. The requests asking
+    /// for tags with the route tag headers only go to the backend servers having them. Empty for
+    /// the backend servers given on the command line.
+    pub tags: BTreeMap<String, String>,
+
+    /// Whether the answers to the HTTP health checks other than a 200 are accepted silently, for
+    /// the backend servers without health endpoint. Otherwise they are warned about once per
+    /// backend server. False for the backend servers given on the command line.
+    pub quiet_health_check: bool,
+
+    /// Path under which the backend server is mounted, such as /app, prepended to the path of the
+    /// forwarded requests. None sends the requests to the address of the backend server, and for
+    /// the backend servers given on the command line.
+    pub base_path: Option<String>,
+}
+
+/// Settings of the backend servers created by the unit tests: health checked over TCP, without
+/// circuit breaker, outlier detection or limits, with a weight of 1.
+#[cfg(test)]
+impl Default for BackendConfig {
+    fn default() -> Self {
+        Self {
+            health_check: HealthCheckKind::Tcp,
+            healthy_threshold: 1,
+            unhealthy_threshold: 1,
+            circuit_breaker_threshold: 0,
+            circuit_breaker_cooldown: Duration::from_secs(30),
+            outlier_detection: None,
+            response_time_smoothing: 0.3,
+            request_id_header: "X-Request-Id".to_string(),
+            request_headers: HeaderFilter::default(),
+            header_overrides: HeaderMap::new(),
+            max_connections: None,
+            protocol: BackendProtocol::default(),
+            max_redirects: 0,
+            pool_max_idle_per_host: 32,
+            pool_idle_timeout: None,
+            max_response_time: None,
+            connect_timeout: None,
+            health_check_timeout: None,
+            health_webhook: None,
+            tls: BackendTls::default(),
+            weight: 1,
+            slow_start: Duration::ZERO,
+            backup: false,
+            tags: BTreeMap::new(),
+            quiet_health_check: false,
+            base_path: None,
+        }
+    }
+}
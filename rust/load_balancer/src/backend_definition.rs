@@ -0,0 +1,173 @@
+use crate::backend_config::BackendConfig;
+use crate::health_check_assertion::HealthCheckAssertion;
+use crate::health_check_kind::HealthCheckKind;
+use crate::health_check_method::HealthCheckMethod;
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// A backend server given in the config file or to the admin API, with its own settings
+/// overriding the ones of the command line. It is given either as its address only, or as a table
+/// with its address and settings. For example:
+///
+/// ```toml
+/// backends = [
+///     "http://localhost:8081/",
+///     { address = "http://localhost:8082/", max_connections = 10, weight = 2 },
+///     { address = "http://localhost:8083/", backup = true, max_response_time_ms = 2000 },
+///     { address = "http://localhost:8084/", tags = { version = "canary" } },
+///     { address = "http://localhost:8085/", quiet_health_check = true },
+///     { address = "http://localhost:8086/", health_check_method = "head" },
+///     { address = "http://localhost:8087/", base_path = "/app" },
+/// ]
+/// ```
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(from = "BackendEntry")]
+pub struct BackendDefinition {
+    /// Address of the backend server, in the same format as on the command line.
+    pub address: String,
+
+    /// Maximum number of requests sent to the backend server at the same time, overriding
+    /// --max-connections.
+    pub max_connections: Option<u32>,
+
+    /// Weight of the backend server, a backend server with twice the weight of another one can
+    /// handle twice as many requests. 0 parks it, health checked but without requests. 1 by
+    /// default.
+    pub weight: Option<u32>,
+
+    /// Whether the backend server is a backup, which only receives requests when no primary
+    /// backend server is available. False by default.
+    pub backup: Option<bool>,
+
+    /// Response time in milliseconds above which the backend server is unhealthy, overriding
+    /// --max-response-time-ms. 0 means no limit.
+    pub max_response_time_ms: Option<u64>,
+
+    /// Conditions the answers to the HTTP health checks of the backend server must meet,
+    /// overriding the ones of the command line. Ignored with TCP health checks.
+    pub health_check: Option<HealthCheckAssertion>,
+
+    /// HTTP method of the health checks of the backend server, overriding --health-check-method.
+    /// Ignored with TCP health checks.
+    pub health_check_method: Option<HealthCheckMethod>,
+
+    /// Tags of the backend server, matched against the tags asked for by the requests. No tags by
+    /// default.
+    pub tags: BTreeMap<String, String>,
+
+    /// Whether the answers to the health checks other than a 200 are accepted without warning,
+    /// for a backend server without health endpoint. False by default.
+    pub quiet_health_check: Option<bool>,
+
+    /// Path under which the backend server is mounted, prepended to the path of the forwarded
+    /// requests. None by default, the requests are then sent to the address of the backend server.
+    pub base_path: Option<String>,
+}
+
+impl BackendDefinition {
+    /// Returns the settings of the backend server: the given default settings, overridden by the
+    /// ones of the definition.
+    pub fn config(&self, default: &BackendConfig) -> BackendConfig {
+        let mut config = default.clone();
+        if self.max_connections.is_some() {
+            config.max_connections = self.max_connections;
+        }
+        if let Some(weight) = self.weight {
+            config.weight = weight;
+        }
+        if let Some(backup) = self.backup {
+            config.backup = backup;
+        }
+        if let Some(max_response_time_ms) = self.max_response_time_ms {
+            config.max_response_time =
+                Some(Duration::from_millis(max_response_time_ms)).filter(|max| !max.is_zero());
+        }
+        if let (Some(health_check), HealthCheckKind::Http { assertion, .. }) =
+            (&self.health_check, &mut config.health_check)
+        {
+            *assertion = health_check.clone();
+        }
+        if let (Some(health_check_method), HealthCheckKind::Http { method, .. }) =
+            (self.health_check_method, &mut config.health_check)
+        {
+            *method = health_check_method;
+        }
+        config.tags = self.tags.clone();
+        if let Some(quiet_health_check) = self.quiet_health_check {
+            config.quiet_health_check = quiet_health_check;
+        }
+        config.base_path = self.base_path.clone();
+        config
+    }
+}
+
+impl From<String> for BackendDefinition {
+    /// Creates the definition of a backend server given by its address only.
+    fn from(address: String) -> Self {
+        Self {
+            address,
+            max_connections: None,
+            weight: None,
+            backup: None,
+            max_response_time_ms: None,
+            health_check: None,
+            health_check_method: None,
+            tags: BTreeMap::new(),
+            quiet_health_check: None,
+            base_path: None,
+        }
+    }
+}
+
+/// The forms in which a backend server can be given.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum BackendEntry {
+    Address(String),
+    Table {
+        address: String,
+        max_connections: Option<u32>,
+        weight: Option<u32>,
+        backup: Option<bool>,
+        max_response_time_ms: Option<u64>,
+        health_check: Option<HealthCheckAssertion>,
+        health_check_method: Option<HealthCheckMethod>,
+        #[serde(default)]
+        tags: BTreeMap<String, String>,
+        quiet_health_check: Option<bool>,
+        base_path: Option<String>,
+    },
+}
+
+impl From<BackendEntry> for BackendDefinition {
+    fn from(entry: BackendEntry) -> Self {
+        match entry {
+            BackendEntry::Address(address) => Self::from(address),
+            BackendEntry::Table {
+                address,
+                max_connections,
+                weight,
+                backup,
+                max_response_time_ms,
+                health_check,
+                health_check_method,
+                tags,
+                quiet_health_check,
+                base_path,
+            } => Self {
+                address,
+                max_connections,
+                weight,
+                backup,
+                max_response_time_ms,
+                health_check,
+                health_check_method,
+                tags,
+                quiet_health_check,
+                base_path,
+            },
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use crate::backend_definition::BackendDefinition;
+use crate::strategy::Strategy;
+
+use serde::Deserialize;
+
+/// Named group of backend servers of the config file, with its own load balancer, to which the
+/// requests are routed by the prefix of their path. For example:
+///
+/// ```toml
+/// [groups.api]
+/// strategy = "least-response"
+/// backends = ["http://localhost:8082/", "http://localhost:8083/"]
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BackendGroup {
+    /// Strategy used to choose the backend server of each request of the group, round robin by
+    /// default.
+    #[serde(default)]
+    pub strategy: Strategy,
+
+    /// List of backend servers of the group, in the same format as the other backend servers of
+    /// the config file.
+    pub backends: Vec<BackendDefinition>,
+}
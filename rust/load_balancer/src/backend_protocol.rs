@@ -0,0 +1,15 @@
+use clap::ValueEnum;
+
+/// HTTP version used to send the requests to the backend servers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum BackendProtocol {
+    /// HTTP/2 when an HTTPS backend server offers it during the TLS handshake (ALPN), HTTP/1.1
+    /// otherwise.
+    #[default]
+    Auto,
+    /// Always HTTP/1.1.
+    Http1,
+    /// Always HTTP/2 without negotiating it first (prior knowledge), including over plain HTTP.
+    /// The backend servers must support HTTP/2.
+    Http2,
+}
@@ -0,0 +1,72 @@
+use crate::evicted_backend::EvictedBackend;
+use crate::load_balancer::LoadBalancer;
+
+use log::warn;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Removes the backend servers which have been unhealthy for longer than the eviction timeout,
+/// so that the health checks stop probing decommissioned hosts, and remembers them for the admin
+/// API.
+#[derive(Debug)]
+pub struct BackendPruner {
+    /// Time after which an unhealthy backend server is evicted, None to never evict them.
+    eviction_timeout: Option<Duration>,
+
+    /// Backend servers evicted since the load balancer started, the oldest first. The lock is
+    /// never held across an await point.
+    evicted: Mutex<Vec<EvictedBackend>>,
+}
+
+impl BackendPruner {
+    /// Creates a pruner evicting the backend servers unhealthy for longer than the given timeout,
+    /// or none if it is None.
+    pub fn new(eviction_timeout: Option<Duration>) -> Self {
+        Self {
+            eviction_timeout,
+            evicted: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Removes the backend servers of the given load balancer which have been unhealthy for
+    /// longer than the eviction timeout.
+    pub async fn prune(&self, load_balancer: &dyn LoadBalancer) {
+        let Some(eviction_timeout) = self.eviction_timeout else {
+            return;
+        };
+
+        for snapshot in load_balancer.backends_snapshot().await {
+            let unhealthy_for = Duration::from_millis(snapshot.unhealthy_for_ms);
+            if unhealthy_for <= eviction_timeout {
+                continue;
+            }
+            // The backend server may have been removed meanwhile, for example by the admin API
+            if load_balancer
+                .remove_backend(&snapshot.address)
+                .await
+                .is_err()
+            {
+                continue;
+            }
+
+            warn!(
+                "Evicted backend server {}, unhealthy for {:.0}s",
+                snapshot.address,
+                unhealthy_for.as_secs_f32()
+            );
+            let evicted_at = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |time| time.as_secs());
+            self.evicted.lock().unwrap().push(EvictedBackend {
+                address: snapshot.address,
+                unhealthy_for_ms: snapshot.unhealthy_for_ms,
+                evicted_at,
+            });
+        }
+    }
+
+    /// Returns the backend servers evicted since the load balancer started, the oldest first.
+    pub fn evicted(&self) -> Vec<EvictedBackend> {
+        self.evicted.lock().unwrap().clone()
+    }
+}
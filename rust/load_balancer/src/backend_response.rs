@@ -0,0 +1,12 @@
+use reqwest::Response;
+
+/// Response of a backend server to a request forwarded by the load balancer.
+#[derive(Debug)]
+pub struct BackendResponse {
+    /// Address of the backend server which answered the request.
+    pub address: String,
+
+    /// Response of the backend server, whose body has not been read yet so that it can be
+    /// streamed to the client.
+    pub response: Response,
+}
@@ -0,0 +1,100 @@
+use crate::backend::Backend;
+use crate::circuit_breaker::CircuitState;
+use crate::health::Health;
+use crate::response_time_histogram::ResponseTimePercentiles;
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// State of a backend server at a given time, as reported by the admin API.
+#[derive(Clone, Debug, Serialize)]
+pub struct BackendSnapshot {
+    /// Address of the backend server.
+    pub address: String,
+
+    /// Health status of the backend server.
+    pub health: Health,
+
+    /// Time in milliseconds during which the backend server has been unhealthy, 0 if it is
+    /// healthy.
+    pub unhealthy_for_ms: u64,
+
+    /// Moving average of the response time of the backend server to the requests in
+    /// milliseconds.
+    pub response_time_ms: f32,
+
+    /// Moving average of the time taken by the health checks of the backend server in
+    /// milliseconds.
+    pub health_check_latency_ms: f32,
+
+    /// Percentiles of the response time of the backend server since the load balancer started.
+    pub response_time_percentiles: ResponseTimePercentiles,
+
+    /// State of the circuit breaker of the backend server.
+    pub circuit_state: CircuitState,
+
+    /// Whether the backend server is ejected because too many of its recent requests failed.
+    pub ejected: bool,
+
+    /// Number of requests currently sent to the backend server.
+    pub in_flight: u32,
+
+    /// Number of requests sent to the backend server since the load balancer started.
+    pub requests_total: u64,
+
+    /// Number of failed requests sent to the backend server since the load balancer started.
+    pub errors_total: u64,
+
+    /// Number of passed health checks of the backend server since the load balancer started.
+    pub health_checks_passed: u64,
+
+    /// Number of failed health checks of the backend server since the load balancer started.
+    pub health_checks_failed: u64,
+
+    /// Fraction of the recent health checks of the backend server which failed, between 0 and 1.
+    /// High for a flapping backend server, even while it is healthy.
+    pub health_check_failure_rate: f32,
+
+    /// Whether the backend server is draining and receives no new requests.
+    pub draining: bool,
+
+    /// Weight of the backend server.
+    pub weight: u32,
+
+    /// Load last reported by the backend server in the answers to its health checks, between 0
+    /// and 1.
+    pub load: f32,
+
+    /// Whether the backend server is a backup.
+    pub backup: bool,
+
+    /// Tags of the backend server.
+    pub tags: BTreeMap<String, String>,
+}
+
+impl BackendSnapshot {
+    /// Takes a snapshot of the current state of the given backend server.
+    pub async fn new(backend: &dyn Backend) -> Self {
+        Self {
+            address: backend.address().to_string(),
+            health: backend.health(),
+            unhealthy_for_ms: backend.unhealthy_for().as_millis() as u64,
+            response_time_ms: backend.response_time_ms().await,
+            health_check_latency_ms: backend.health_check_latency_ms(),
+            response_time_percentiles: backend.response_time_percentiles(),
+            circuit_state: backend.circuit_state(),
+            ejected: backend.is_ejected(),
+            in_flight: backend.in_flight(),
+            requests_total: backend.requests_total(),
+            errors_total: backend.errors_total(),
+            health_checks_passed: backend.health_checks_passed(),
+            health_checks_failed: backend.health_checks_failed(),
+            health_check_failure_rate: backend.health_check_failure_rate(),
+            draining: backend.is_draining(),
+            weight: backend.weight(),
+            load: backend.load(),
+            backup: backend.is_backup(),
+            tags: backend.tags().clone(),
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use reqwest::{Certificate, ClientBuilder, Identity};
+use std::path::Path;
+
+/// TLS settings of the connections to the HTTPS backend servers: the client certificate presented
+/// to them for mutual TLS, and the certificate authorities their certificates must be signed by.
+#[derive(Clone, Debug, Default)]
+pub struct BackendTls {
+    /// Client certificate and private key presented to the backend servers, None to present none.
+    identity: Option<Identity>,
+
+    /// Certificate authorities trusted to sign the certificates of the backend servers, instead of
+    /// the ones of the system. None to trust the ones of the system.
+    ca_certificates: Option<Vec<Certificate>>,
+}
+
+impl BackendTls {
+    /// Loads the PEM encoded client certificate and PKCS #8 private key, and the PEM encoded
+    /// certificate authorities, from the given paths. Returns a readable error if a file cannot be
+    /// read, or if the certificate and the key are invalid or do not match.
+    pub fn load(
+        cert_path: Option<&Path>,
+        key_path: Option<&Path>,
+        ca_path: Option<&Path>,
+    ) -> Result<Self, String> {
+        let identity = match (cert_path, key_path) {
+            (Some(cert_path), Some(key_path)) => {
+                let cert = read(cert_path, "client certificate")?;
+                let key = read(key_path, "client key")?;
+                let identity = Identity::from_pkcs8_pem(&cert, &key).map_err(|e| {
+                    format!(
+                        "Invalid client certificate {} or key {}: {}",
+                        cert_path.display(),
+                        key_path.display(),
+                        e
+                    )
+                })?;
+                Some(identity)
+            }
+            _ => None,
+        };
+
+        let ca_certificates = match ca_path {
+            Some(ca_path) => {
+                let certificates = Certificate::from_pem_bundle(&read(ca_path, "CA certificate")?)
+                    .map_err(|e| format!("Invalid CA certificate {}: {}", ca_path.display(), e))?;
+                if certificates.is_empty() {
+                    return Err(format!("No certificate found in {}", ca_path.display()));
+                }
+                Some(certificates)
+            }
+            None => None,
+        };
+
+        let backend_tls = Self {
+            identity,
+            ca_certificates,
+        };
+        // The certificate and the key are only matched when a client is built
+        backend_tls
+            .apply(ClientBuilder::new())
+            .build()
+            .map_err(|e| format!("Invalid backend TLS settings: {}", e))?;
+        Ok(backend_tls)
+    }
+
+    /// Returns the given HTTP client builder presenting the client certificate, if any, and only
+    /// trusting the certificate authorities, if any.
+    pub fn apply(&self, mut builder: ClientBuilder) -> ClientBuilder {
+        if let Some(identity) = &self.identity {
+            builder = builder.identity(identity.clone());
+        }
+        if let Some(ca_certificates) = &self.ca_certificates {
+            builder = builder.tls_built_in_root_certs(false);
+            for certificate in ca_certificates {
+                builder = builder.add_root_certificate(certificate.clone());
+            }
+        }
+        builder
+    }
+}
+
+/// Reads the file at the given path, described by the given name in the error.
+fn read(path: &Path, name: &str) -> Result<Vec<u8>, String> {
+    std::fs::read(path).map_err(|e| format!("Failed to read {} {}: {}", name, path.display(), e))
+}
@@ -0,0 +1,63 @@
+use crate::config_file::ConfigFile;
+use crate::health::Health;
+use crate::load_balancer_settings::LoadBalancerSettings;
+
+use log::info;
+
+/// Checks the health of each backend server of the given config once, groups included, as the load
+/// balancer would, and prints a table of the reachable and unreachable ones. Returns an error if a
+/// backend server, a route or a setting is invalid, or if a backend server is unreachable.
+///
+/// The backend servers are considered reachable or unreachable after a single health check,
+/// whatever the healthy and unhealthy thresholds.
+pub async fn check_backends(
+    settings: &LoadBalancerSettings,
+    config: &ConfigFile,
+) -> Result<(), String> {
+    let mut settings = settings.clone();
+    settings.backend_config.healthy_threshold = 1;
+    settings.backend_config.unhealthy_threshold = 1;
+    let load_balancer = settings.build_config(config)?;
+
+    info!("Checking the health of the backend servers once");
+    load_balancer.check_backends_healths().await;
+    let mut backends = load_balancer.backends_snapshot().await;
+    backends.sort_by(|a, b| a.address.cmp(&b.address));
+
+    let address_width = backends
+        .iter()
+        .map(|backend| backend.address.len())
+        .chain(std::iter::once("BACKEND".len()))
+        .max()
+        .unwrap_or_default();
+    println!(
+        "{:<address_width$}  {:<11}  HEALTH CHECK",
+        "BACKEND", "STATUS"
+    );
+    let mut unreachable_addresses = Vec::new();
+    for backend in &backends {
+        let status = if backend.health == Health::Healthy {
+            "reachable"
+        } else {
+            unreachable_addresses.push(backend.address.as_str());
+            "unreachable"
+        };
+        println!(
+            "{:<address_width$}  {:<11}  {:.1}ms",
+            backend.address, status, backend.health_check_latency_ms
+        );
+    }
+    println!(
+        "{} of {} backend servers are reachable",
+        backends.len() - unreachable_addresses.len(),
+        backends.len()
+    );
+
+    if !unreachable_addresses.is_empty() {
+        return Err(format!(
+            "Unreachable backend servers: {}",
+            unreachable_addresses.join(", ")
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,181 @@
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// State of the circuit breaker of a backend server.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub enum CircuitState {
+    /// Requests are forwarded to the backend server.
+    Closed,
+    /// The backend server failed too many times in a row, no request is forwarded to it until the
+    /// cooldown has elapsed.
+    Open,
+    /// The cooldown has elapsed, the next request is used as a probe to decide whether the circuit
+    /// should be closed again. The circuit is reported open while the probe is in flight, so that
+    /// a single request is let through.
+    HalfOpen,
+}
+
+/// Passive health check of a backend server. Counts the consecutive failures and successes of the
+/// requests sent to the backend and opens the circuit once the number of consecutive failures
+/// reaches the threshold.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    /// Number of consecutive failures after which the circuit opens. A threshold of 0 disables the
+    /// circuit breaker.
+    failure_threshold: u32,
+
+    /// Time during which the circuit stays open before a probe request is let through.
+    cooldown: Duration,
+
+    /// Number of consecutive failed requests.
+    consecutive_failures: u32,
+
+    /// Number of consecutive successful requests.
+    consecutive_successes: u32,
+
+    /// Time at which the circuit was opened, `None` if the circuit is closed.
+    opened_at: Option<Instant>,
+
+    /// Whether the probe request of the half-open circuit is in flight.
+    probing: bool,
+}
+
+impl CircuitBreaker {
+    /// Creates a new closed circuit breaker.
+    pub fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            failure_threshold,
+            cooldown,
+            consecutive_failures: 0,
+            consecutive_successes: 0,
+            opened_at: None,
+            probing: false,
+        }
+    }
+
+    /// Returns the state of the circuit. An open circuit is reported as half-open once its
+    /// cooldown has elapsed, until a probe request is let through.
+    pub fn state(&self) -> CircuitState {
+        match self.opened_at {
+            None => CircuitState::Closed,
+            Some(opened_at) if opened_at.elapsed() >= self.cooldown && !self.probing => {
+                CircuitState::HalfOpen
+            }
+            Some(_) => CircuitState::Open,
+        }
+    }
+
+    /// Lets a request through as the probe of the half-open circuit. Returns false if the circuit
+    /// is not half-open, including when another probe is already in flight.
+    pub fn start_probe(&mut self) -> bool {
+        if self.state() != CircuitState::HalfOpen {
+            return false;
+        }
+        self.probing = true;
+        true
+    }
+
+    /// Ends the probe in flight without recording its result, when it was cancelled. The circuit
+    /// is half-open again.
+    pub fn cancel_probe(&mut self) {
+        self.probing = false;
+    }
+
+    /// Records a successful request. Closes the circuit if it was open.
+    pub fn record_success(&mut self) {
+        self.consecutive_failures = 0;
+        self.consecutive_successes = self.consecutive_successes.saturating_add(1);
+        self.opened_at = None;
+        self.probing = false;
+    }
+
+    /// Records a failed request. Opens the circuit when the failure threshold is reached, or
+    /// re-opens it when the half-open probe failed.
+    pub fn record_failure(&mut self) {
+        self.consecutive_successes = 0;
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+        let probe_failed = std::mem::take(&mut self.probing);
+
+        if self.failure_threshold == 0 {
+            return;
+        }
+
+        if probe_failed
+            || self.state() == CircuitState::HalfOpen
+            || self.consecutive_failures >= self.failure_threshold
+        {
+            self.opened_at = Some(Instant::now());
+        }
+    }
+
+    /// Returns the number of consecutive failed requests.
+    pub fn consecutive_failures(&self) -> u32 {
+        self.consecutive_failures
+    }
+
+    /// Returns the number of consecutive successful requests.
+    pub fn consecutive_successes(&self) -> u32 {
+        self.consecutive_successes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a circuit breaker opened by a failure, half-open once its cooldown has elapsed.
+    fn opened_circuit_breaker(cooldown: Duration) -> CircuitBreaker {
+        let mut circuit_breaker = CircuitBreaker::new(1, cooldown);
+        circuit_breaker.record_failure();
+        circuit_breaker
+    }
+
+    #[test]
+    fn opens_once_the_threshold_is_reached() {
+        let mut circuit_breaker = CircuitBreaker::new(2, Duration::from_secs(30));
+
+        circuit_breaker.record_failure();
+        assert_eq!(circuit_breaker.state(), CircuitState::Closed);
+
+        circuit_breaker.record_failure();
+        assert_eq!(circuit_breaker.state(), CircuitState::Open);
+        assert!(!circuit_breaker.start_probe());
+    }
+
+    #[test]
+    fn lets_a_single_probe_through_once_half_open() {
+        let mut circuit_breaker = opened_circuit_breaker(Duration::ZERO);
+        assert_eq!(circuit_breaker.state(), CircuitState::HalfOpen);
+
+        assert!(circuit_breaker.start_probe());
+        assert_eq!(circuit_breaker.state(), CircuitState::Open);
+        assert!(!circuit_breaker.start_probe());
+
+        circuit_breaker.record_success();
+        assert_eq!(circuit_breaker.state(), CircuitState::Closed);
+        assert!(!circuit_breaker.start_probe());
+    }
+
+    #[test]
+    fn reopens_when_the_probe_fails() {
+        let mut circuit_breaker = opened_circuit_breaker(Duration::from_millis(50));
+        std::thread::sleep(Duration::from_millis(60));
+
+        assert!(circuit_breaker.start_probe());
+        circuit_breaker.record_failure();
+
+        assert_eq!(circuit_breaker.state(), CircuitState::Open);
+        assert!(!circuit_breaker.start_probe());
+    }
+
+    #[test]
+    fn is_half_open_again_when_the_probe_is_cancelled() {
+        let mut circuit_breaker = opened_circuit_breaker(Duration::ZERO);
+
+        assert!(circuit_breaker.start_probe());
+        circuit_breaker.cancel_probe();
+
+        assert_eq!(circuit_breaker.state(), CircuitState::HalfOpen);
+        assert!(circuit_breaker.start_probe());
+    }
+}
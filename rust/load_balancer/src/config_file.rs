@@ -0,0 +1,63 @@
+use crate::backend_definition::BackendDefinition;
+use crate::backend_group::BackendGroup;
+use crate::default_response::DefaultResponse;
+use crate::strategy::Strategy;
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Configuration of the load balancer read from a TOML file. For example:
+///
+/// ```toml
+/// strategy = "least-response"
+/// backends = ["http://localhost:8081/", "http://localhost:8082/"]
+///
+/// [groups.api]
+/// backends = ["http://localhost:8083/"]
+///
+/// [routes]
+/// "/api" = "api"
+///
+/// [default_response]
+/// status = 404
+/// body = "Not found"
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    /// Strategy used to choose the backend server of each request, round robin by default.
+    #[serde(default)]
+    pub strategy: Strategy,
+
+    /// List of backend servers, given by their address in the same format as on the command line,
+    /// or by a table with their address and settings. They receive the requests matching no route,
+    /// so they can only be omitted when a default response is given.
+    #[serde(default)]
+    pub backends: Vec<BackendDefinition>,
+
+    /// Groups of backend servers by name, each with its own strategy, receiving the requests
+    /// routed to them by the routes.
+    #[serde(default)]
+    pub groups: BTreeMap<String, BackendGroup>,
+
+    /// Routing table from a path prefix to the name of a group. A request goes to the group of the
+    /// longest prefix matching its path, and to the backend servers above if none matches.
+    #[serde(default)]
+    pub routes: BTreeMap<String, String>,
+
+    /// Response sent by the load balancer itself to the requests matching no route, instead of
+    /// forwarding them to the backend servers above.
+    #[serde(default)]
+    pub default_response: Option<DefaultResponse>,
+}
+
+impl ConfigFile {
+    /// Reads and parses the config file at the given path.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read config file {}: {}", path.display(), e))?;
+        toml::from_str(&content)
+            .map_err(|e| format!("Invalid config file {}: {}", path.display(), e))
+    }
+}
@@ -0,0 +1,391 @@
+use crate::affinity;
+use crate::backend::Backend;
+use crate::backend_response::BackendResponse;
+use crate::backend_snapshot::BackendSnapshot;
+use crate::forwarding;
+use crate::health::Health;
+use crate::health_sweep;
+use crate::internal_error::InternalError;
+use crate::load_balancer::LoadBalancer;
+use crate::metrics::Metrics;
+use crate::request_context::RequestContext;
+use crate::strategy::Strategy;
+use crate::tag_routing;
+
+use async_trait::async_trait;
+use log::{debug, info};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use tokio::sync::RwLock as TokioRwLock;
+use tokio::time::Duration;
+
+/// Load balancer routing the requests of a client to the same backend server. The backend servers
+/// are placed on a hash ring through several virtual nodes each, and a request goes to the first
+/// healthy backend server found on the ring after the hash of the client address. Adding or
+/// removing a backend server only remaps the clients next to its virtual nodes.
+#[derive(Debug)]
+pub struct ConsistentHashLoadBalancer {
+    /// Backend servers and the hash ring on which they are placed, updated together when a
+    /// backend server is added or removed.
+    hash_ring: TokioRwLock<HashRing>,
+
+    /// Number of virtual nodes of each backend server on the hash ring.
+    virtual_nodes: u32,
+
+    /// Maximum time spent selecting the backend server to which a request is sent.
+    selection_timeout: Duration,
+
+    /// Metrics updated on each request.
+    metrics: Arc<Metrics>,
+
+    /// Maximum number of backend servers health checked at the same time.
+    health_check_concurrency: usize,
+}
+
+/// List of backend servers and their virtual nodes on the hash ring.
+#[derive(Debug)]
+struct HashRing {
+    /// List of backend servers
+    backends: Vec<Box<dyn Backend>>,
+
+    /// Hash ring, maps the hash of each virtual node to the index of its backend server.
+    ring: BTreeMap<u64, usize>,
+}
+
+impl HashRing {
+    /// Places each backend server `virtual_nodes` times on a new hash ring.
+    fn new(backends: Vec<Box<dyn Backend>>, virtual_nodes: u32) -> Self {
+        let mut ring = BTreeMap::new();
+        for (index, backend) in backends.iter().enumerate() {
+            for virtual_node in 0..virtual_nodes {
+                ring.insert(
+                    hash(&format!("{}#{}", backend.address(), virtual_node)),
+                    index,
+                );
+            }
+        }
+
+        Self { backends, ring }
+    }
+}
+
+impl ConsistentHashLoadBalancer {
+    /// Creates a new load balancer with the given list of backend servers to route the requests
+    /// to. Each backend server is placed `virtual_nodes` times on the hash ring.
+    pub fn new(
+        backends: Vec<Box<dyn Backend>>,
+        virtual_nodes: u32,
+        selection_timeout: Duration,
+        metrics: Arc<Metrics>,
+        health_check_concurrency: usize,
+    ) -> Self {
+        Self {
+            hash_ring: TokioRwLock::new(HashRing::new(backends, virtual_nodes)),
+            virtual_nodes,
+            selection_timeout,
+            metrics,
+            health_check_concurrency,
+        }
+    }
+}
+
+/// Hashes a key to a position on the hash ring.
+fn hash(key: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[async_trait]
+impl LoadBalancer for ConsistentHashLoadBalancer {
+    /// Returns the backend server to which the client is pinned by its affinity if it can receive
+    /// the request, otherwise the first healthy backend server which is neither draining nor parked
+    /// following the hash of the client address on the hash ring. Only the backend servers having
+    /// the tags asked for by the request are selected, unless none of them is available. If none
+    /// are available, an error is returned.
+    async fn next_available_backend(
+        &self,
+        context: &RequestContext,
+    ) -> Result<Box<dyn Backend>, String> {
+        let client_address = context.client_address.as_deref().unwrap_or("unknown");
+        let client_hash = hash(client_address);
+
+        let hash_ring = self.hash_ring.read().await;
+        if let Some(backend) = hash_ring
+            .backends
+            .iter()
+            .find(|backend| affinity::is_pinned(context, backend.as_ref()))
+        {
+            return Ok(backend.clone());
+        }
+
+        let required_tags = tag_routing::required_tags(
+            context,
+            hash_ring.backends.iter().map(|backend| backend.as_ref()),
+        );
+        // Walk the ring clockwise from the client hash, wrapping around at the end
+        let mut tried_backends = HashSet::new();
+        for (_, &backend_index) in hash_ring
+            .ring
+            .range(client_hash..)
+            .chain(hash_ring.ring.range(..client_hash))
+        {
+            if !tried_backends.insert(backend_index) {
+                continue;
+            }
+
+            let backend = &hash_ring.backends[backend_index];
+            if backend.health() == Health::Healthy
+                && !backend.is_draining()
+                && backend.weight() > 0
+                && tag_routing::matches(required_tags, backend.as_ref())
+            {
+                debug!(
+                    "selected backend {} for client {}",
+                    backend.address(),
+                    client_address
+                );
+                return Ok(backend.clone());
+            }
+
+            if tried_backends.len() == hash_ring.backends.len() {
+                break;
+            }
+        }
+
+        Err("No backend server available".to_string())
+    }
+
+    /// Sends a request to the backend server assigned to the client. Returns an error if no
+    /// backend server is reachable.
+    async fn send_request(
+        &self,
+        context: &RequestContext,
+    ) -> Result<BackendResponse, InternalError> {
+        forwarding::send_to_selected_backend(
+            self.next_available_backend(context),
+            self.selection_timeout,
+            &self.metrics,
+            context,
+        )
+        .await
+    }
+
+    /// Checks and update the health status of all backend servers.
+    async fn check_backends_healths(&self) {
+        // This is used for profiling only
+        let start_time = std::time::Instant::now();
+
+        // Check a copy of the list so that backend servers can be added or removed meanwhile
+        let backends = self.hash_ring.read().await.backends.clone();
+        let health_checks = backends
+            .iter()
+            .map(|backend| backend.check_health())
+            .collect();
+        health_sweep::check_healths(health_checks, self.health_check_concurrency).await;
+
+        // For profiling only, measures how much time it took to check all backends health
+        let end_time = std::time::Instant::now();
+        let elapsed_time = end_time.duration_since(start_time).as_millis();
+        info!("checking all backends health took {}ms", elapsed_time);
+    }
+
+    /// Returns the current state of all backend servers.
+    async fn backends_snapshot(&self) -> Vec<BackendSnapshot> {
+        let hash_ring = self.hash_ring.read().await;
+        let mut snapshots = Vec::with_capacity(hash_ring.backends.len());
+        for backend in hash_ring.backends.iter() {
+            snapshots.push(BackendSnapshot::new(backend.as_ref()).await);
+        }
+        snapshots
+    }
+
+    /// Returns the number of healthy backend servers.
+    async fn healthy_count(&self) -> usize {
+        let hash_ring = self.hash_ring.read().await;
+        hash_ring
+            .backends
+            .iter()
+            .filter(|backend| backend.health() == Health::Healthy)
+            .count()
+    }
+
+    /// Adds a backend server to which the requests can be sent and places it on the hash ring.
+    /// Returns an error if a backend server with the same address already exists.
+    async fn add_backend(&self, backend: Box<dyn Backend>) -> Result<(), String> {
+        let mut hash_ring = self.hash_ring.write().await;
+        if hash_ring
+            .backends
+            .iter()
+            .any(|b| b.address() == backend.address())
+        {
+            return Err(format!(
+                "Backend server {} already exists",
+                backend.address()
+            ));
+        }
+
+        info!("Adding backend server {}", backend.address());
+        let mut backends = hash_ring.backends.clone();
+        backends.push(backend);
+        *hash_ring = HashRing::new(backends, self.virtual_nodes);
+        Ok(())
+    }
+
+    /// Removes the backend server with the given address and its virtual nodes from the hash ring.
+    /// Returns an error if there is no backend server with this address.
+    async fn remove_backend(&self, address: &str) -> Result<(), String> {
+        let mut hash_ring = self.hash_ring.write().await;
+        let Some(index) = hash_ring
+            .backends
+            .iter()
+            .position(|b| b.address() == address)
+        else {
+            return Err(format!("No backend server with address {}", address));
+        };
+
+        info!("Removing backend server {}", address);
+        let mut backends = hash_ring.backends.clone();
+        backends.remove(index);
+        *hash_ring = HashRing::new(backends, self.virtual_nodes);
+        Ok(())
+    }
+
+    /// Starts or stops draining the backend server with the given address. Returns an error if
+    /// there is no backend server with this address.
+    async fn set_draining(&self, address: &str, draining: bool) -> Result<(), String> {
+        let hash_ring = self.hash_ring.read().await;
+        let Some(backend) = hash_ring.backends.iter().find(|b| b.address() == address) else {
+            return Err(format!("No backend server with address {}", address));
+        };
+
+        info!(
+            "Setting draining of backend server {} to {}",
+            address, draining
+        );
+        backend.set_draining(draining);
+        Ok(())
+    }
+
+    /// Sets the weight of the backend server with the given address. Returns an error if there is
+    /// no backend server with this address.
+    async fn set_weight(&self, address: &str, weight: u32) -> Result<(), String> {
+        let hash_ring = self.hash_ring.read().await;
+        let Some(backend) = hash_ring.backends.iter().find(|b| b.address() == address) else {
+            return Err(format!("No backend server with address {}", address));
+        };
+
+        info!("Setting weight of backend server {} to {}", address, weight);
+        backend.set_weight(weight);
+        Ok(())
+    }
+
+    fn strategy(&self) -> String {
+        Strategy::ConsistentHash.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend_config::BackendConfig;
+    use crate::simple_backend::SimpleBackend;
+
+    /// Returns a healthy backend server listening on the given port of localhost.
+    fn backend(port: u16) -> Box<dyn Backend> {
+        let address = format!("http://localhost:{}/", port);
+        Box::new(SimpleBackend::new(address, Health::Healthy, &BackendConfig::default()).unwrap())
+    }
+
+    /// Returns a load balancer with a backend server on each of the given ports.
+    fn load_balancer(ports: &[u16]) -> ConsistentHashLoadBalancer {
+        ConsistentHashLoadBalancer::new(
+            ports.iter().map(|&port| backend(port)).collect(),
+            100,
+            Duration::from_secs(1),
+            Arc::new(Metrics::new()),
+            1,
+        )
+    }
+
+    /// Returns the address of the backend server selected for each of the client addresses.
+    async fn selected(load_balancer: &ConsistentHashLoadBalancer, keys: &[String]) -> Vec<String> {
+        let mut addresses = Vec::with_capacity(keys.len());
+        for key in keys {
+            let context = RequestContext {
+                client_address: Some(key.clone()),
+                ..Default::default()
+            };
+            let backend = load_balancer.next_available_backend(&context).await;
+            addresses.push(backend.unwrap().address().to_string());
+        }
+        addresses
+    }
+
+    /// Returns a thousand client addresses.
+    fn keys() -> Vec<String> {
+        (0..1000)
+            .map(|i| format!("10.0.{}.{}", i / 256, i % 256))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn maps_the_same_key_to_the_same_backend() {
+        let load_balancer = load_balancer(&[8081, 8082, 8083]);
+        let keys = keys();
+
+        let first = selected(&load_balancer, &keys).await;
+        let second = selected(&load_balancer, &keys).await;
+
+        assert_eq!(first, second);
+        // The keys are spread over all the backend servers
+        assert_eq!(first.iter().collect::<HashSet<_>>().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn only_remaps_the_keys_of_an_added_backend() {
+        let load_balancer = load_balancer(&[8081, 8082, 8083, 8084]);
+        let keys = keys();
+        let before = selected(&load_balancer, &keys).await;
+
+        load_balancer.add_backend(backend(8085)).await.unwrap();
+        let after = selected(&load_balancer, &keys).await;
+
+        let remapped: Vec<&String> = before
+            .iter()
+            .zip(&after)
+            .filter(|(before, after)| before != after)
+            .map(|(_, after)| after)
+            .collect();
+        // A fifth backend server takes about a fifth of the keys, all from the others
+        assert!(remapped.iter().all(|a| *a == "http://localhost:8085/"));
+        assert!(
+            (50..350).contains(&remapped.len()),
+            "{} keys remapped",
+            remapped.len()
+        );
+    }
+
+    #[tokio::test]
+    async fn only_remaps_the_keys_of_a_removed_backend() {
+        let load_balancer = load_balancer(&[8081, 8082, 8083, 8084]);
+        let keys = keys();
+        let before = selected(&load_balancer, &keys).await;
+
+        load_balancer
+            .remove_backend("http://localhost:8082/")
+            .await
+            .unwrap();
+        let after = selected(&load_balancer, &keys).await;
+
+        for (before, after) in before.iter().zip(&after) {
+            if before == "http://localhost:8082/" {
+                assert_ne!(after, "http://localhost:8082/");
+            } else {
+                assert_eq!(before, after);
+            }
+        }
+    }
+}
@@ -1,5 +1,10 @@
-#[derive(Debug, PartialEq)]
-enum Continent {
+use std::str::FromStr;
+
+/// Mean radius of the Earth in kilometers.
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Continent {
     Africa,
     Antarctica,
     Asia,
@@ -10,16 +15,145 @@ enum Continent {
 }
 
 impl Continent {
-    fn from_str(s: &str) -> Option<Self> {
+    /// Returns the approximate center of the continent as a (latitude, longitude) pair in degrees.
+    fn center(&self) -> (f64, f64) {
+        match self {
+            Self::Africa => (2.0, 16.0),
+            Self::Antarctica => (-80.0, 0.0),
+            Self::Asia => (34.0, 100.0),
+            Self::Europe => (50.0, 15.0),
+            Self::NorthAmerica => (45.0, -100.0),
+            Self::Oceania => (-25.0, 135.0),
+            Self::SouthAmerica => (-15.0, -60.0),
+        }
+    }
+
+    /// Returns the great-circle distance in kilometers between the centers of the two continents.
+    /// The distance of a continent to itself is 0.
+    pub fn distance_km(&self, other: &Continent) -> f64 {
+        let (latitude, longitude) = self.center();
+        let (other_latitude, other_longitude) = other.center();
+
+        let delta_latitude = (other_latitude - latitude).to_radians();
+        let delta_longitude = (other_longitude - longitude).to_radians();
+        let a = (delta_latitude / 2.0).sin().powi(2)
+            + latitude.to_radians().cos()
+                * other_latitude.to_radians().cos()
+                * (delta_longitude / 2.0).sin().powi(2);
+
+        2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+    }
+}
+
+impl FromStr for Continent {
+    type Err = String;
+
+    /// Parses a continent from its two letters code as used by the GeoIP databases.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
-            "AF" => Some(Self::Africa),
-            "AN" => Some(Self::Antarctica),
-            "AS" => Some(Self::Asia),
-            "EU" => Some(Self::Europe),
-            "NA" => Some(Self::NorthAmerica),
-            "OC" => Some(Self::Oceania),
-            "SA" => Some(Self::SouthAmerica),
-            _ => None,
+            "AF" => Ok(Self::Africa),
+            "AN" => Ok(Self::Antarctica),
+            "AS" => Ok(Self::Asia),
+            "EU" => Ok(Self::Europe),
+            "NA" => Ok(Self::NorthAmerica),
+            "OC" => Ok(Self::Oceania),
+            "SA" => Ok(Self::SouthAmerica),
+            _ => Err(format!("Unknown continent code {}", s)),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTINENTS: [Continent; 7] = [
+        Continent::Africa,
+        Continent::Antarctica,
+        Continent::Asia,
+        Continent::Europe,
+        Continent::NorthAmerica,
+        Continent::Oceania,
+        Continent::SouthAmerica,
+    ];
+
+    /// Returns the continents in the order in which the geo load balancer falls back to them for a
+    /// client on the given continent, from the closest to the farthest.
+    fn fallback_order(continent: Continent) -> Vec<Continent> {
+        let mut continents = CONTINENTS.to_vec();
+        continents.sort_by(|a, b| {
+            continent
+                .distance_km(a)
+                .total_cmp(&continent.distance_km(b))
+        });
+        continents
+    }
+
+    #[test]
+    fn falls_back_from_europe_to_africa_then_asia() {
+        assert_eq!(
+            fallback_order(Continent::Europe),
+            [
+                Continent::Europe,
+                Continent::Africa,
+                Continent::Asia,
+                Continent::NorthAmerica,
+                Continent::SouthAmerica,
+                Continent::Oceania,
+                Continent::Antarctica,
+            ]
+        );
+    }
+
+    #[test]
+    fn falls_back_from_north_america_to_europe_then_south_america() {
+        assert_eq!(
+            fallback_order(Continent::NorthAmerica),
+            [
+                Continent::NorthAmerica,
+                Continent::Europe,
+                Continent::SouthAmerica,
+                Continent::Asia,
+                Continent::Africa,
+                Continent::Oceania,
+                Continent::Antarctica,
+            ]
+        );
+    }
+
+    #[test]
+    fn falls_back_from_oceania_to_asia_then_antarctica() {
+        assert_eq!(
+            fallback_order(Continent::Oceania),
+            [
+                Continent::Oceania,
+                Continent::Asia,
+                Continent::Antarctica,
+                Continent::Africa,
+                Continent::Europe,
+                Continent::NorthAmerica,
+                Continent::SouthAmerica,
+            ]
+        );
+    }
+
+    #[test]
+    fn measures_the_same_distance_both_ways_and_none_to_itself() {
+        for a in CONTINENTS {
+            assert_eq!(a.distance_km(&a), 0.0);
+            for b in CONTINENTS {
+                assert_eq!(a.distance_km(&b), b.distance_km(&a));
+                if a != b {
+                    assert!(a.distance_km(&b) > 1000.0, "{:?} to {:?}", a, b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn parses_the_continent_codes() {
+        assert_eq!("EU".parse(), Ok(Continent::Europe));
+        assert_eq!("SA".parse(), Ok(Continent::SouthAmerica));
+        assert!("XX".parse::<Continent>().is_err());
+    }
+}
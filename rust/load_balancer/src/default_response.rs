@@ -0,0 +1,26 @@
+use serde::Deserialize;
+
+/// Response of the config file sent by the load balancer itself to the requests whose path matches
+/// no route, instead of forwarding them to the backend servers. For example:
+///
+/// ```toml
+/// [default_response]
+/// status = 404
+/// body = "Not found"
+/// ```
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct DefaultResponse {
+    /// Status code of the response, 404 by default.
+    #[serde(default = "default_status")]
+    pub status: u16,
+
+    /// Plain text body of the response, empty by default.
+    #[serde(default)]
+    pub body: String,
+}
+
+/// Returns the status of a default response which does not give one.
+fn default_status() -> u16 {
+    404
+}
@@ -0,0 +1,164 @@
+use crate::backend_config::BackendConfig;
+use crate::backend_definition::BackendDefinition;
+use crate::dns_target::DnsTarget;
+use crate::health::Health;
+use crate::load_balancer::LoadBalancer;
+use crate::simple_backend::SimpleBackend;
+
+use hickory_resolver::config::{NameServerConfigGroup, ResolverConfig, ResolverOpts};
+use hickory_resolver::TokioAsyncResolver;
+use log::{info, warn};
+use std::collections::BTreeSet;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+
+/// Keeps the backend servers of the load balancer in line with the records of a DNS name, so that
+/// the backend servers added or removed by a scaling event are picked up without a restart.
+pub struct DnsDiscovery {
+    /// DNS name resolved into the backend servers.
+    target: DnsTarget,
+
+    /// Resolver sending the DNS queries.
+    resolver: TokioAsyncResolver,
+
+    /// Addresses of the backend servers added from the DNS records and not removed since, the
+    /// draining ones included. The lock is never held across an await point.
+    discovered: Mutex<BTreeSet<String>>,
+}
+
+impl DnsDiscovery {
+    /// Creates a discovery of the backend servers of the given DNS target, sending the DNS queries
+    /// to the given name server, or to the ones of the system if None. Returns an error if the
+    /// system configuration cannot be read.
+    pub fn new(target: DnsTarget, name_server: Option<SocketAddr>) -> Result<Self, String> {
+        let resolver = match name_server {
+            Some(name_server) => TokioAsyncResolver::tokio(
+                ResolverConfig::from_parts(
+                    None,
+                    vec![],
+                    NameServerConfigGroup::from_ips_clear(
+                        &[name_server.ip()],
+                        name_server.port(),
+                        true,
+                    ),
+                ),
+                ResolverOpts::default(),
+            ),
+            None => TokioAsyncResolver::tokio_from_system_conf().map_err(|e| {
+                format!("Failed to read the DNS configuration of the system: {}", e)
+            })?,
+        };
+        Ok(Self {
+            target,
+            resolver,
+            discovered: Mutex::new(BTreeSet::new()),
+        })
+    }
+
+    /// Resolves the DNS target into the backend servers the load balancer starts with. Returns an
+    /// error if the resolution fails.
+    pub async fn discover(&self) -> Result<Vec<BackendDefinition>, String> {
+        let addresses = self.resolve().await?;
+        info!(
+            "Resolved {} into the backend servers {:?}",
+            self.target, addresses
+        );
+        *self.discovered.lock().unwrap() = addresses.clone();
+        Ok(addresses.into_iter().map(BackendDefinition::from).collect())
+    }
+
+    /// Resolves the DNS target again and updates the backend servers of the given load balancer:
+    /// the new addresses become backend servers with the given settings, and the ones which
+    /// disappeared are drained, then removed once they have no request in flight. The backend
+    /// servers are kept as they are if the resolution fails.
+    pub async fn refresh(&self, load_balancer: &dyn LoadBalancer, backend_config: &BackendConfig) {
+        let addresses = match self.resolve().await {
+            Ok(addresses) => addresses,
+            Err(e) => {
+                warn!("{}, keeping the known backend servers", e);
+                return;
+            }
+        };
+
+        let snapshots = load_balancer.backends_snapshot().await;
+        let mut discovered = self.discovered.lock().unwrap().clone();
+        for address in &addresses {
+            match snapshots
+                .iter()
+                .find(|snapshot| snapshot.address == *address)
+            {
+                Some(snapshot) if snapshot.draining => {
+                    info!("Backend server {} is back in the DNS records", address);
+                    let _ = load_balancer.set_draining(address, false).await;
+                }
+                Some(_) => {}
+                // A new backend server receives requests once a health check finds it healthy
+                None => {
+                    match SimpleBackend::new(address.clone(), Health::Unhealthy, backend_config) {
+                        Ok(backend) => match load_balancer.add_backend(Box::new(backend)).await {
+                            Ok(()) => info!("Discovered backend server {}", address),
+                            Err(e) => warn!("Failed to add discovered backend server: {}", e),
+                        },
+                        Err(e) => warn!("Failed to add discovered backend server: {}", e),
+                    }
+                }
+            }
+            discovered.insert(address.clone());
+        }
+
+        for address in discovered.clone().difference(&addresses) {
+            match snapshots
+                .iter()
+                .find(|snapshot| snapshot.address == *address)
+            {
+                Some(snapshot) if !snapshot.draining => {
+                    info!(
+                        "Backend server {} is no longer in the DNS records, draining it",
+                        address
+                    );
+                    let _ = load_balancer.set_draining(address, true).await;
+                }
+                Some(snapshot) if snapshot.in_flight > 0 => {}
+                // Drained, or already removed, for example by the admin API
+                _ => {
+                    let _ = load_balancer.remove_backend(address).await;
+                    discovered.remove(address);
+                }
+            }
+        }
+        *self.discovered.lock().unwrap() = discovered;
+    }
+
+    /// Returns the addresses of the backend servers given by the DNS records of the target.
+    /// Returns an error if the resolution fails or gives no backend server.
+    async fn resolve(&self) -> Result<BTreeSet<String>, String> {
+        let error = |e| format!("Failed to resolve {}: {}", self.target, e);
+        let addresses: BTreeSet<String> = match &self.target {
+            DnsTarget::Addresses { host, port } => self
+                .resolver
+                .lookup_ip(host.as_str())
+                .await
+                .map_err(error)?
+                .iter()
+                // IPv6 addresses are written in brackets
+                .map(|ip| format!("http://{}/", SocketAddr::new(ip, *port)))
+                .collect(),
+            DnsTarget::Service { name } => self
+                .resolver
+                .srv_lookup(name.as_str())
+                .await
+                .map_err(error)?
+                .iter()
+                .map(|srv| {
+                    let target = srv.target().to_utf8();
+                    format!("http://{}:{}/", target.trim_end_matches('.'), srv.port())
+                })
+                .collect(),
+        };
+
+        if addresses.is_empty() {
+            return Err(format!("Failed to resolve {}: no records", self.target));
+        }
+        Ok(addresses)
+    }
+}
@@ -0,0 +1,60 @@
+use reqwest::Url;
+use std::fmt;
+use std::str::FromStr;
+
+/// DNS name resolved into the backend servers of the load balancer.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DnsTarget {
+    /// A name whose A and AAAA records give the IP addresses of the backend servers, all listening
+    /// on the same port. Given as dns://service.example.com:8081, the port being 80 by default.
+    Addresses { host: String, port: u16 },
+
+    /// A name whose SRV records give the host and port of each backend server. Given as
+    /// dns+srv://_http._tcp.service.example.com.
+    Service { name: String },
+}
+
+impl FromStr for DnsTarget {
+    type Err = String;
+
+    /// Parses a DNS target given as dns://HOST[:PORT] or dns+srv://NAME.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let url = Url::parse(value).map_err(|e| format!("invalid DNS target {}: {}", value, e))?;
+        let Some(host) = url.host_str().filter(|host| !host.is_empty()) else {
+            return Err(format!("invalid DNS target {}: no name to resolve", value));
+        };
+        if !matches!(url.path(), "" | "/") {
+            return Err(format!(
+                "invalid DNS target {}: a path is not allowed",
+                value
+            ));
+        }
+
+        match url.scheme() {
+            "dns" => Ok(DnsTarget::Addresses {
+                host: host.to_string(),
+                port: url.port().unwrap_or(80),
+            }),
+            "dns+srv" if url.port().is_none() => Ok(DnsTarget::Service {
+                name: host.to_string(),
+            }),
+            "dns+srv" => Err(format!(
+                "invalid DNS target {}: the SRV records give the ports",
+                value
+            )),
+            _ => Err(format!(
+                "invalid DNS target {}, expected dns://HOST[:PORT] or dns+srv://NAME",
+                value
+            )),
+        }
+    }
+}
+
+impl fmt::Display for DnsTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DnsTarget::Addresses { host, port } => write!(f, "dns://{}:{}", host, port),
+            DnsTarget::Service { name } => write!(f, "dns+srv://{}", name),
+        }
+    }
+}
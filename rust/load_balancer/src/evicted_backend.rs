@@ -0,0 +1,16 @@
+use serde::Serialize;
+
+/// A backend server removed from the load balancer after being unhealthy for too long, as
+/// reported by the admin API.
+#[derive(Clone, Debug, Serialize)]
+pub struct EvictedBackend {
+    /// Address of the backend server.
+    pub address: String,
+
+    /// Time in milliseconds during which the backend server had been unhealthy when it was
+    /// evicted.
+    pub unhealthy_for_ms: u64,
+
+    /// Time at which the backend server was evicted, in seconds since the Unix epoch.
+    pub evicted_at: u64,
+}
@@ -0,0 +1,111 @@
+/// Exponentially weighted moving average of a series of samples. Each new sample moves the average
+/// towards it by the smoothing factor: average = smoothing * sample + (1 - smoothing) * average.
+#[derive(Clone, Copy, Debug)]
+pub struct Ewma {
+    /// Weight of a new sample, between 0 excluded and 1 included. 1 keeps only the last sample.
+    smoothing: f32,
+
+    /// Current average, None until the first sample.
+    average: Option<f32>,
+}
+
+impl Ewma {
+    /// Creates an empty average with the given smoothing factor.
+    pub fn new(smoothing: f32) -> Self {
+        Self {
+            smoothing,
+            average: None,
+        }
+    }
+
+    /// Adds a sample to the average. The first sample becomes the average. A NaN sample is
+    /// ignored, as it would make the average NaN forever.
+    pub fn add(&mut self, sample: f32) {
+        if sample.is_nan() {
+            return;
+        }
+        self.average = Some(match self.average {
+            Some(average) => self.smoothing * sample + (1.0 - self.smoothing) * average,
+            None => sample,
+        });
+    }
+
+    /// Moves the average towards 0 by the given fraction of it, between 0 and 1, as if the samples
+    /// were getting older. An empty average stays empty.
+    pub fn decay(&mut self, decay: f32) {
+        if let Some(average) = &mut self.average {
+            *average *= 1.0 - decay;
+        }
+    }
+
+    /// Returns the current average, or 0 if no sample was added yet.
+    pub fn value(&self) -> f32 {
+        self.average.unwrap_or(0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the successive values of an average with the given smoothing factor fed the samples.
+    fn smoothed(smoothing: f32, samples: &[f32]) -> Vec<f32> {
+        let mut ewma = Ewma::new(smoothing);
+        samples
+            .iter()
+            .map(|&sample| {
+                ewma.add(sample);
+                ewma.value()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn starts_at_0_then_at_the_first_sample() {
+        let mut ewma = Ewma::new(0.25);
+        assert_eq!(ewma.value(), 0.0);
+
+        ewma.add(80.0);
+
+        assert_eq!(ewma.value(), 80.0);
+    }
+
+    #[test]
+    fn moves_towards_each_sample_by_the_smoothing_factor() {
+        assert_eq!(
+            smoothed(0.25, &[80.0, 40.0, 10.0, 70.0]),
+            [80.0, 70.0, 55.0, 58.75]
+        );
+        assert_eq!(
+            smoothed(0.5, &[100.0, 50.0, 25.0, 50.0]),
+            [100.0, 75.0, 50.0, 50.0]
+        );
+    }
+
+    #[test]
+    fn keeps_only_the_last_sample_with_a_smoothing_factor_of_1() {
+        assert_eq!(smoothed(1.0, &[80.0, 40.0, 10.0]), [80.0, 40.0, 10.0]);
+    }
+
+    #[test]
+    fn ignores_the_nan_samples() {
+        assert_eq!(
+            smoothed(0.5, &[f32::NAN, 100.0, f32::NAN, 50.0]),
+            [0.0, 100.0, 100.0, 75.0]
+        );
+    }
+
+    #[test]
+    fn decays_towards_0_and_leaves_an_empty_average_empty() {
+        let mut ewma = Ewma::new(0.5);
+        ewma.decay(0.5);
+        assert_eq!(ewma.value(), 0.0);
+        ewma.add(40.0);
+        assert_eq!(ewma.value(), 40.0);
+
+        ewma.add(80.0);
+        ewma.decay(0.25);
+
+        assert_eq!(ewma.value(), 45.0);
+    }
+}
@@ -0,0 +1,177 @@
+use crate::proxy_protocol;
+
+use actix_web::http::header::{self, HeaderName};
+use actix_web::HttpRequest;
+use std::net::IpAddr;
+
+/// Name of the header carrying the addresses of the client and of the proxies the request went
+/// through.
+pub const X_FORWARDED_FOR: HeaderName = HeaderName::from_static("x-forwarded-for");
+
+/// Name of the header carrying the protocol used by the client to reach the load balancer.
+pub const X_FORWARDED_PROTO: HeaderName = HeaderName::from_static("x-forwarded-proto");
+
+/// Name of the header carrying the host requested by the client.
+pub const X_FORWARDED_HOST: HeaderName = HeaderName::from_static("x-forwarded-host");
+
+/// Characters which can appear in a value of the Forwarded header without quotes, besides the
+/// alphanumeric ones.
+const TOKEN_CHARACTERS: &str = "!#$%&'*+-.^_`|~";
+
+/// Headers telling a backend server how the client reached the load balancer, which the backend
+/// server cannot see as the load balancer sends it a new request.
+#[derive(Clone, Debug, Default)]
+pub struct ForwardedHeaders {
+    /// Value of X-Forwarded-For: the X-Forwarded-For headers of the request, if any, followed by
+    /// the address of the client. None if neither is known.
+    pub forwarded_for: Option<String>,
+
+    /// Value of X-Forwarded-Proto: https if the client used TLS, http otherwise.
+    pub proto: &'static str,
+
+    /// Value of X-Forwarded-Host: the host requested by the client, if any.
+    pub host: Option<String>,
+
+    /// Value of the Forwarded header of RFC 7239: the Forwarded headers of the request, if any,
+    /// followed by an element with the address of the client, the host it requested and its
+    /// protocol. None if the Forwarded header is not sent.
+    pub forwarded: Option<String>,
+}
+
+impl ForwardedHeaders {
+    /// Creates the forwarded headers of the given request, with the Forwarded header if asked for.
+    /// The X-Forwarded-Proto and X-Forwarded-Host headers of the request are not trusted and are
+    /// replaced.
+    pub fn new(request: &HttpRequest, with_forwarded: bool) -> Self {
+        // A proxy may have sent several X-Forwarded-For headers, they are joined in order
+        let mut forwarded_for: Vec<&str> = request
+            .headers()
+            .get_all(X_FORWARDED_FOR)
+            .filter_map(|value| value.to_str().ok())
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .collect();
+        let client_ip = proxy_protocol::client_ip(request);
+        let client_address = client_ip.map(|ip| ip.to_string());
+        forwarded_for.extend(client_address.as_deref());
+        let forwarded_for = (!forwarded_for.is_empty()).then(|| forwarded_for.join(", "));
+
+        // HTTP/2 requests carry the host in the URI instead of the Host header
+        let host = request
+            .headers()
+            .get(header::HOST)
+            .and_then(|value| value.to_str().ok())
+            .or_else(|| {
+                request
+                    .uri()
+                    .authority()
+                    .map(|authority| authority.as_str())
+            })
+            .map(str::to_string);
+
+        let proto = if request.app_config().secure() {
+            "https"
+        } else {
+            "http"
+        };
+
+        // Like X-Forwarded-For, the element of the client is appended to the ones of the proxies
+        let forwarded = with_forwarded.then(|| {
+            let mut elements: Vec<&str> = request
+                .headers()
+                .get_all(header::FORWARDED)
+                .filter_map(|value| value.to_str().ok())
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .collect();
+            let element = forwarded_element(client_ip, host.as_deref(), proto);
+            elements.push(&element);
+            elements.join(", ")
+        });
+
+        Self {
+            forwarded_for,
+            proto,
+            host,
+            forwarded,
+        }
+    }
+
+    /// Returns the names and values of the headers to send to the backend server.
+    pub fn headers(&self) -> Vec<(HeaderName, &str)> {
+        let mut headers = Vec::new();
+        if let Some(forwarded_for) = &self.forwarded_for {
+            headers.push((X_FORWARDED_FOR, forwarded_for.as_str()));
+        }
+        headers.push((X_FORWARDED_PROTO, self.proto));
+        if let Some(host) = &self.host {
+            headers.push((X_FORWARDED_HOST, host.as_str()));
+        }
+        if let Some(forwarded) = &self.forwarded {
+            headers.push((header::FORWARDED, forwarded.as_str()));
+        }
+        headers
+    }
+}
+
+/// Returns the element of the Forwarded header describing a client with the given address, which
+/// requested the given host with the given protocol.
+fn forwarded_element(client_ip: Option<IpAddr>, host: Option<&str>, proto: &str) -> String {
+    let mut pairs = Vec::new();
+    match client_ip {
+        Some(IpAddr::V4(ip)) => pairs.push(format!("for={}", ip)),
+        // The IPv6 addresses are enclosed in brackets, which must be quoted
+        Some(IpAddr::V6(ip)) => pairs.push(format!("for=\"[{}]\"", ip)),
+        None => {}
+    }
+    if let Some(host) = host {
+        pairs.push(format!("host={}", quote(host)));
+    }
+    pairs.push(format!("proto={}", proto));
+    pairs.join(";")
+}
+
+/// Returns the given value as is if it is a token, otherwise as a quoted string.
+fn quote(value: &str) -> String {
+    let is_token = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || TOKEN_CHARACTERS.contains(c));
+    if is_token {
+        return value.to_string();
+    }
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    #[test]
+    fn forwarded_element_has_the_client_host_and_protocol() {
+        let ip = Some(IpAddr::V4(Ipv4Addr::new(203, 0, 113, 7)));
+
+        assert_eq!(
+            forwarded_element(ip, Some("example.com"), "https"),
+            "for=203.0.113.7;host=example.com;proto=https"
+        );
+        assert_eq!(forwarded_element(None, None, "http"), "proto=http");
+    }
+
+    #[test]
+    fn forwarded_element_quotes_the_ipv6_addresses_and_the_ports() {
+        let ip = Some(IpAddr::V6(Ipv6Addr::LOCALHOST));
+
+        assert_eq!(
+            forwarded_element(ip, Some("localhost:8443"), "https"),
+            "for=\"[::1]\";host=\"localhost:8443\";proto=https"
+        );
+    }
+
+    #[test]
+    fn quote_escapes_the_quotes_and_backslashes() {
+        assert_eq!(quote("a\"b\\c"), "\"a\\\"b\\\\c\"");
+        assert_eq!(quote(""), "\"\"");
+    }
+}
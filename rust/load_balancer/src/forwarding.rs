@@ -0,0 +1,54 @@
+use crate::backend::Backend;
+use crate::backend_response::BackendResponse;
+use crate::internal_error::InternalError;
+use crate::metrics::Metrics;
+use crate::request_context::RequestContext;
+
+use log::{error, info};
+use std::future::Future;
+use tokio::time::{timeout, Duration};
+
+/// Sends the request to the backend server returned by the given selection, without retry, and
+/// records the request and its outcome in the metrics. Answers with a selection timeout if the
+/// selection takes longer than the given time, and with no backend available if it fails.
+pub async fn send_to_selected_backend(
+    selection: impl Future<Output = Result<Box<dyn Backend>, String>>,
+    selection_timeout: Duration,
+    metrics: &Metrics,
+    context: &RequestContext,
+) -> Result<BackendResponse, InternalError> {
+    metrics.record_request();
+
+    let backend = match timeout(selection_timeout, selection).await {
+        Err(_) => {
+            error!(
+                "Selecting a backend took more than {}ms",
+                selection_timeout.as_millis()
+            );
+            return Err(InternalError::SelectionTimeout);
+        }
+        Ok(Err(_)) => return Err(InternalError::NoBackendAvailable { tried: Vec::new() }),
+        Ok(Ok(backend)) => backend,
+    };
+
+    info!("Sending request to backend {:?}", backend);
+    match backend.send_request(context).await {
+        Ok(response) => {
+            info!("{:?}", response);
+            metrics
+                .record_backend_response(backend.address(), backend.response_time_ms().await)
+                .await;
+            Ok(BackendResponse {
+                address: backend.address().to_string(),
+                response,
+            })
+        }
+        Err(e) => {
+            metrics.record_backend_error(backend.address()).await;
+            Err(InternalError::BackendUnreachable {
+                address: backend.address().to_string(),
+                source: e,
+            })
+        }
+    }
+}
@@ -1,118 +1,195 @@
 use crate::backend::Backend;
+use crate::backend_config::BackendConfig;
+use crate::circuit_breaker::CircuitState;
+use crate::continent::Continent;
 use crate::health::Health;
+use crate::request_context::RequestContext;
+use crate::response_time_histogram::ResponseTimePercentiles;
+use crate::simple_backend::SimpleBackend;
+use crate::upgrade_request::UpgradeRequest;
 use async_trait::async_trait;
-use reqwest::{Client, Error, Response, StatusCode};
+use reqwest::{Error, Response};
+use std::collections::BTreeMap;
+use std::time::Duration;
 
-use log::{error, info, warn};
-
-/// Represents a backend server resource to which the load balancer can forward the requests.
+/// Represents a backend server located on a continent, used by the geo load balancer. The health,
+/// response time and circuit breaker are shared between the clones of the backend server.
 #[derive(Clone, Debug)]
 pub struct GeoBackend {
-    /// Address of the backend server, contains the protocol, hostname and port. For example:
-    /// http://localhost:8081
-    address: String,
-
-    /// [Unused for now] Weight of the backend server. The higher the weight, the more requests
-    /// will be forwarded to.
-    weight: u32,
+    /// Continent on which the backend server is located.
+    continent: Continent,
 
-    /// Health status of the backend server.
-    health: Health,
+    /// Backend server to which the requests are forwarded.
+    backend: SimpleBackend,
 }
 
 impl GeoBackend {
-    /// Creates a new backend server with the given address, weight and health status.
-    pub fn new(address: String, weight: u32, health: Health) -> Self {
-        GeoBackend {
-            address,
-            weight,
-            health,
-        }
+    /// Creates a new backend server located on the given continent with the given address and
+    /// initial health status. Returns an error if the address is not a valid URL.
+    pub fn new(
+        continent: Continent,
+        address: String,
+        health: Health,
+        config: &BackendConfig,
+    ) -> Result<Self, String> {
+        Ok(Self {
+            continent,
+            backend: SimpleBackend::new(address, health, config)?,
+        })
+    }
+
+    /// Returns the continent on which the backend server is located.
+    pub fn continent(&self) -> Continent {
+        self.continent
     }
 }
 
 #[async_trait]
 impl Backend for GeoBackend {
     /// Checks the health of the backend server by sending a request to the health check endpoint.
-    /// If the server is healthy, the health status is set to Healthy, otherwise it is set to
-    /// Unhealthy.
-    async fn check_health(&mut self) {
-        // This is used for profiling only
-        let start_time = std::time::Instant::now();
-
-        // Sends a health check
-        let health_check_address = self.address.clone() + "health";
-        let client = Client::new();
-        let response = client.get(&health_check_address).send().await;
-
-        // For profiling only, measures the time it took to send the request
-        let end_time = std::time::Instant::now();
-        let elapsed_time = end_time.duration_since(start_time).as_millis();
-        info!("checking backend health took {}ms", elapsed_time);
-
-        match response {
-            // The server is considered healthy if the health enpoint returns anything.
-            Ok(r) => {
-                if r.status() != StatusCode::OK {
-                    warn!(
-                        "GeoBackend server {} does not support health checks on address {}",
-                        self.address, health_check_address
-                    );
-                }
-
-                info!("Response: {:?}", r);
-                info!("GeoBackend server {} is healthy", self.address);
-                self.health = Health::Healthy;
-            }
-            // The server cannot be reached, it is considered unhealthy.
-            Err(e) => {
-                error!("Failed to send request to backend server: {:?}", e);
-                info!("GeoBackend server {} is unhealthy", self.address);
-                self.health = Health::Unhealthy;
-            }
-        }
+    async fn check_health(&self) {
+        self.backend.check_health().await
+    }
+
+    /// Returns how long the backend server has been unhealthy, zero if it is healthy.
+    fn unhealthy_for(&self) -> Duration {
+        self.backend.unhealthy_for()
     }
 
     /// Returns the health status of the backend server.
     fn health(&self) -> Health {
-        self.health.clone()
-    }
-
-    /// Sends a request to the backend server and returns the response in case of success. If the
-    /// request succeeds, the health status is updated to healthy. If the request fails, the health
-    /// status of the backend server is set to Unhealthy.
-    ///
-    /// You should add arguments to this function to pass the request method, headers, body, etc.
-    async fn send_request(&mut self) -> Result<Response, Error> {
-        info!("Sending request to backend server {}", self.address);
-        // This is used for profiling only
-        let start_time = std::time::Instant::now();
-
-        // Sends a request to the backend server
-        let client = Client::new();
-        let response = client.get(&self.address).send().await;
-
-        // For profiling only, measures the time it took to send the request
-        let end_time = std::time::Instant::now();
-        let elapsed_time = end_time.duration_since(start_time).as_millis();
-        info!("sending request to backend took {}ms", elapsed_time);
-
-        match response {
-            // The backend server responded, so it is considered healthy.
-            Ok(r) => {
-                if self.health != Health::Healthy {
-                    self.health = Health::Healthy;
-                }
-                Ok(r)
-            }
-            // The server cannot be reached, it is considered unhealthy.
-            Err(e) => {
-                error!("Failed to send request to backend server: {:?}", e);
-                if self.health != Health::Unhealthy {
-                    self.health = Health::Unhealthy;
-                }
-                Err(e)
-            }
-        }
+        self.backend.health()
+    }
+
+    /// Sends the request described by the context to the backend server and returns the response
+    /// in case of success.
+    async fn send_request(&self, context: &RequestContext) -> Result<Response, Error> {
+        self.backend.send_request(context).await
+    }
+
+    /// Returns the WebSocket upgrade request described by the context.
+    fn upgrade_request(&self, context: &RequestContext) -> Result<UpgradeRequest, String> {
+        self.backend.upgrade_request(context)
+    }
+
+    /// Returns the moving average of the response time of the backend server in milliseconds.
+    async fn response_time_ms(&self) -> f32 {
+        self.backend.response_time_ms().await
+    }
+
+    /// Moves the moving average of the response time towards 0 by the given fraction of it.
+    async fn decay_response_time(&self, decay: f32) {
+        self.backend.decay_response_time(decay).await
+    }
+
+    /// Returns the moving average of the time taken by the health checks in milliseconds.
+    fn health_check_latency_ms(&self) -> f32 {
+        self.backend.health_check_latency_ms()
+    }
+
+    /// Returns the state of the circuit breaker of the backend server.
+    fn circuit_state(&self) -> CircuitState {
+        self.backend.circuit_state()
+    }
+
+    /// Returns the number of consecutive failed requests sent to the backend server.
+    fn consecutive_failures(&self) -> u32 {
+        self.backend.consecutive_failures()
+    }
+
+    /// Returns the number of consecutive successful requests sent to the backend server.
+    fn consecutive_successes(&self) -> u32 {
+        self.backend.consecutive_successes()
+    }
+
+    /// Returns true if the backend server is ejected as an outlier.
+    fn is_ejected(&self) -> bool {
+        self.backend.is_ejected()
+    }
+
+    /// Returns the number of requests currently sent to the backend server.
+    fn in_flight(&self) -> u32 {
+        self.backend.in_flight()
+    }
+
+    /// Returns the number of requests sent to the backend server since the load balancer started.
+    fn requests_total(&self) -> u64 {
+        self.backend.requests_total()
+    }
+
+    /// Returns the number of failed requests sent to the backend server since the load balancer
+    /// started.
+    fn errors_total(&self) -> u64 {
+        self.backend.errors_total()
+    }
+
+    /// Returns the number of passed health checks of the backend server.
+    fn health_checks_passed(&self) -> u64 {
+        self.backend.health_checks_passed()
+    }
+
+    /// Returns the number of failed health checks of the backend server.
+    fn health_checks_failed(&self) -> u64 {
+        self.backend.health_checks_failed()
+    }
+
+    /// Returns the fraction of the recent health checks of the backend server which failed.
+    fn health_check_failure_rate(&self) -> f32 {
+        self.backend.health_check_failure_rate()
+    }
+
+    /// Returns the percentiles of the response time of the backend server.
+    fn response_time_percentiles(&self) -> ResponseTimePercentiles {
+        self.backend.response_time_percentiles()
+    }
+
+    /// Returns true if the backend server has reached its maximum number of requests in flight.
+    fn is_saturated(&self) -> bool {
+        self.backend.is_saturated()
+    }
+
+    /// Returns true if the backend server is draining.
+    fn is_draining(&self) -> bool {
+        self.backend.is_draining()
+    }
+
+    /// Starts or stops draining the backend server.
+    fn set_draining(&self, draining: bool) {
+        self.backend.set_draining(draining)
+    }
+
+    /// Sets the weight of the backend server, 0 parking it.
+    fn set_weight(&self, weight: u32) {
+        self.backend.set_weight(weight)
+    }
+
+    /// Returns the weight of the backend server.
+    fn weight(&self) -> u32 {
+        self.backend.weight()
+    }
+
+    /// Returns the weight of the backend server taking its slow start and its load into account.
+    fn effective_weight(&self) -> f32 {
+        self.backend.effective_weight()
+    }
+
+    /// Returns the load last reported by the backend server.
+    fn load(&self) -> f32 {
+        self.backend.load()
+    }
+
+    /// Returns true if the backend server is a backup.
+    fn is_backup(&self) -> bool {
+        self.backend.is_backup()
+    }
+
+    /// Returns the tags of the backend server.
+    fn tags(&self) -> &BTreeMap<String, String> {
+        self.backend.tags()
+    }
+
+    /// Returns the address of the backend server.
+    fn address(&self) -> &str {
+        self.backend.address()
     }
 }
@@ -0,0 +1,269 @@
+use crate::affinity;
+use crate::backend::Backend;
+use crate::backend_response::BackendResponse;
+use crate::backend_snapshot::BackendSnapshot;
+use crate::continent::Continent;
+use crate::forwarding;
+use crate::geo_backend::GeoBackend;
+use crate::health::Health;
+use crate::health_sweep;
+use crate::internal_error::InternalError;
+use crate::load_balancer::LoadBalancer;
+use crate::metrics::Metrics;
+use crate::request_context::RequestContext;
+use crate::strategy::Strategy;
+use crate::tag_routing;
+
+use async_trait::async_trait;
+use log::{debug, info, warn};
+use maxminddb::geoip2;
+use std::net::IpAddr;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::RwLock as TokioRwLock;
+use tokio::time::Duration;
+
+/// Load balancer sending the requests to the backend servers located on the continent closest to
+/// the client. The continent of the client is resolved from its IP address with a GeoIP database.
+#[derive(Debug)]
+pub struct GeoLoadBalancer {
+    /// List of backend servers, each located on a continent
+    backends: TokioRwLock<Vec<GeoBackend>>,
+
+    /// GeoIP database used to find the continent of the clients
+    geoip_reader: maxminddb::Reader<Vec<u8>>,
+
+    /// Maximum time spent selecting the backend server to which a request is sent.
+    selection_timeout: Duration,
+
+    /// Metrics updated on each request.
+    metrics: Arc<Metrics>,
+
+    /// Maximum number of backend servers health checked at the same time.
+    health_check_concurrency: usize,
+}
+
+impl GeoLoadBalancer {
+    /// Creates a new load balancer with the given list of backend servers. The GeoIP database
+    /// (GeoLite2 Country or City) is loaded from the given path.
+    pub fn new(
+        backends: Vec<GeoBackend>,
+        geoip_database: &Path,
+        selection_timeout: Duration,
+        metrics: Arc<Metrics>,
+        health_check_concurrency: usize,
+    ) -> Result<Self, String> {
+        let geoip_reader = maxminddb::Reader::open_readfile(geoip_database).map_err(|e| {
+            format!(
+                "Failed to open GeoIP database {}: {}",
+                geoip_database.display(),
+                e
+            )
+        })?;
+
+        Ok(Self {
+            backends: TokioRwLock::new(backends),
+            geoip_reader,
+            selection_timeout,
+            metrics,
+            health_check_concurrency,
+        })
+    }
+
+    /// Returns the continent of the client, or None if the client address is unknown or not
+    /// found in the GeoIP database.
+    fn client_continent(&self, context: &RequestContext) -> Option<Continent> {
+        let address: IpAddr = context.client_address.as_deref()?.parse().ok()?;
+        match self.geoip_reader.lookup::<geoip2::Country>(address) {
+            Ok(country) => country.continent?.code?.parse().ok(),
+            Err(e) => {
+                debug!("No GeoIP entry for {}: {}", address, e);
+                None
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl LoadBalancer for GeoLoadBalancer {
+    /// Returns the backend server to which the client is pinned by its affinity if it can receive
+    /// the request, otherwise the healthy backend server closest to the client, draining and parked
+    /// backend servers excluded. The continents are tried from the closest to the farthest from the
+    /// one of the client, and among the backend servers on the closest continent, the one with the
+    /// lowest response time is chosen. When the continent of the client is unknown, all healthy
+    /// backend servers are considered equally close. Only the backend servers having the tags asked
+    /// for by the request are selected, unless none of them is available. If none are available, an
+    /// error is returned.
+    async fn next_available_backend(
+        &self,
+        context: &RequestContext,
+    ) -> Result<Box<dyn Backend>, String> {
+        let client_continent = self.client_continent(context);
+        if client_continent.is_none() {
+            warn!(
+                "Could not resolve the continent of client {:?}",
+                context.client_address
+            );
+        }
+
+        let backends = self.backends.read().await;
+        if let Some(backend) = backends
+            .iter()
+            .find(|backend| affinity::is_pinned(context, *backend))
+        {
+            return Ok(Box::new(backend.clone()));
+        }
+
+        let required_tags = tag_routing::required_tags(
+            context,
+            backends.iter().map(|backend| backend as &dyn Backend),
+        );
+        let mut best_backend: Option<(f64, f32, &GeoBackend)> = None;
+        for backend in backends.iter() {
+            if backend.health() != Health::Healthy
+                || backend.is_draining()
+                || backend.weight() == 0
+                || !tag_routing::matches(required_tags, backend)
+            {
+                continue;
+            }
+
+            let distance = client_continent
+                .map(|client_continent| client_continent.distance_km(&backend.continent()))
+                .unwrap_or(0.0);
+            let response_time = backend.response_time_ms().await;
+
+            let is_better = match best_backend {
+                None => true,
+                Some((best_distance, best_response_time, _)) => {
+                    distance < best_distance
+                        || (distance == best_distance && response_time < best_response_time)
+                }
+            };
+            if is_better {
+                best_backend = Some((distance, response_time, backend));
+            }
+        }
+
+        match best_backend {
+            Some((distance, _, backend)) => {
+                debug!(
+                    "selected backend {} at {:.0}km from the client continent {:?}",
+                    backend.address(),
+                    distance,
+                    client_continent
+                );
+                Ok(Box::new(backend.clone()))
+            }
+            None => Err("No backend server available".to_string()),
+        }
+    }
+
+    /// Sends a request to the backend server closest to the client. Returns an error if no
+    /// backend server is reachable.
+    async fn send_request(
+        &self,
+        context: &RequestContext,
+    ) -> Result<BackendResponse, InternalError> {
+        forwarding::send_to_selected_backend(
+            self.next_available_backend(context),
+            self.selection_timeout,
+            &self.metrics,
+            context,
+        )
+        .await
+    }
+
+    /// Checks and update the health status of all backend servers.
+    async fn check_backends_healths(&self) {
+        // This is used for profiling only
+        let start_time = std::time::Instant::now();
+
+        // Check a copy of the list so that backend servers can be added or removed meanwhile
+        let backends = self.backends.read().await.clone();
+        let health_checks = backends
+            .iter()
+            .map(|backend| backend.check_health())
+            .collect();
+        health_sweep::check_healths(health_checks, self.health_check_concurrency).await;
+
+        // For profiling only, measures how much time it took to check all backends health
+        let end_time = std::time::Instant::now();
+        let elapsed_time = end_time.duration_since(start_time).as_millis();
+        info!("checking all backends health took {}ms", elapsed_time);
+    }
+
+    /// Returns the current state of all backend servers.
+    async fn backends_snapshot(&self) -> Vec<BackendSnapshot> {
+        let backends = self.backends.read().await;
+        let mut snapshots = Vec::with_capacity(backends.len());
+        for backend in backends.iter() {
+            snapshots.push(BackendSnapshot::new(backend).await);
+        }
+        snapshots
+    }
+
+    /// Returns the number of healthy backend servers.
+    async fn healthy_count(&self) -> usize {
+        let backends = self.backends.read().await;
+        backends
+            .iter()
+            .filter(|backend| backend.health() == Health::Healthy)
+            .count()
+    }
+
+    /// Backend servers cannot be added at runtime to the geo load balancer, as their continent is
+    /// unknown. Always returns an error.
+    async fn add_backend(&self, backend: Box<dyn Backend>) -> Result<(), String> {
+        Err(format!(
+            "Cannot add backend server {} to the geo load balancer at runtime",
+            backend.address()
+        ))
+    }
+
+    /// Removes the backend server with the given address. Returns an error if there is no backend
+    /// server with this address.
+    async fn remove_backend(&self, address: &str) -> Result<(), String> {
+        let mut backends = self.backends.write().await;
+        let Some(index) = backends.iter().position(|b| b.address() == address) else {
+            return Err(format!("No backend server with address {}", address));
+        };
+
+        info!("Removing backend server {}", address);
+        backends.remove(index);
+        Ok(())
+    }
+
+    /// Starts or stops draining the backend server with the given address. Returns an error if
+    /// there is no backend server with this address.
+    async fn set_draining(&self, address: &str, draining: bool) -> Result<(), String> {
+        let backends = self.backends.read().await;
+        let Some(backend) = backends.iter().find(|b| b.address() == address) else {
+            return Err(format!("No backend server with address {}", address));
+        };
+
+        info!(
+            "Setting draining of backend server {} to {}",
+            address, draining
+        );
+        backend.set_draining(draining);
+        Ok(())
+    }
+
+    /// Sets the weight of the backend server with the given address. Returns an error if there is
+    /// no backend server with this address.
+    async fn set_weight(&self, address: &str, weight: u32) -> Result<(), String> {
+        let backends = self.backends.read().await;
+        let Some(backend) = backends.iter().find(|b| b.address() == address) else {
+            return Err(format!("No backend server with address {}", address));
+        };
+
+        info!("Setting weight of backend server {} to {}", address, weight);
+        backend.set_weight(weight);
+        Ok(())
+    }
+
+    fn strategy(&self) -> String {
+        Strategy::Geo.to_string()
+    }
+}
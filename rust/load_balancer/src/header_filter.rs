@@ -0,0 +1,93 @@
+use actix_web::http::header::{self, HeaderName};
+use actix_web::HttpRequest;
+use reqwest::header::{HeaderMap, HeaderValue};
+
+/// Headers which only apply to a single connection and are never forwarded, as well as the
+/// headers listed in the Connection header.
+const HOP_BY_HOP_HEADERS: [&str; 8] = [
+    "connection",
+    "keep-alive",
+    "proxy-authenticate",
+    "proxy-authorization",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+];
+
+/// Allow and deny lists deciding which headers are forwarded, their names being compared
+/// case-insensitively. The hop-by-hop headers are never forwarded, whatever the lists.
+#[derive(Clone, Debug, Default)]
+pub struct HeaderFilter {
+    /// Names of the only headers forwarded, all of them if empty.
+    allow: Vec<String>,
+
+    /// Names of the headers never forwarded.
+    deny: Vec<String>,
+}
+
+impl HeaderFilter {
+    /// Creates a filter forwarding only the allowed headers, or all of them if none are given,
+    /// except the denied ones.
+    pub fn new(allow: &[HeaderName], deny: &[HeaderName]) -> Self {
+        let names = |names: &[HeaderName]| names.iter().map(|name| name.to_string()).collect();
+        Self {
+            allow: names(allow),
+            deny: names(deny),
+        }
+    }
+
+    /// Returns true if the header with the given name is forwarded.
+    pub fn allows(&self, name: &str) -> bool {
+        let listed = |names: &[String]| names.iter().any(|n| n.eq_ignore_ascii_case(name));
+        (self.allow.is_empty() || listed(&self.allow)) && !listed(&self.deny)
+    }
+
+    /// Returns the end-to-end headers of the given headers which pass the filter, without the
+    /// hop-by-hop ones.
+    pub fn filter<'a>(
+        &'a self,
+        headers: &'a HeaderMap,
+    ) -> impl Iterator<Item = (&'a reqwest::header::HeaderName, &'a HeaderValue)> {
+        let hop_by_hop = hop_by_hop_headers(headers);
+        headers.iter().filter(move |(name, _)| {
+            !hop_by_hop.iter().any(|n| n == name.as_str()) && self.allows(name.as_str())
+        })
+    }
+}
+
+/// Returns the names of the hop-by-hop headers of a message with the given headers: the standard
+/// ones and the ones listed in its Connection header.
+fn hop_by_hop_headers(headers: &HeaderMap) -> Vec<String> {
+    let mut names: Vec<String> = HOP_BY_HOP_HEADERS.iter().map(|n| n.to_string()).collect();
+    names.extend(
+        headers
+            .get_all(reqwest::header::CONNECTION)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(|name| name.trim().to_ascii_lowercase())
+            .filter(|name| !name.is_empty()),
+    );
+    names
+}
+
+/// Returns the headers of the client request to forward to the backend server, before filtering.
+/// The Host and Content-Length headers are left out, as the request sent to the backend server has
+/// its own host and no body.
+pub fn request_headers(request: &HttpRequest) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    for (name, value) in request.headers().iter() {
+        if name == header::HOST || name == header::CONTENT_LENGTH {
+            continue;
+        }
+        // The names and values are valid in the request, so they are valid here too
+        if let (Ok(name), Ok(value)) = (
+            reqwest::header::HeaderName::from_bytes(name.as_str().as_bytes()),
+            HeaderValue::from_bytes(value.as_bytes()),
+        ) {
+            headers.append(name, value);
+        }
+    }
+    headers
+}
@@ -1,7 +1,27 @@
+use serde::Serialize;
+
 /// Servers are defined as either healthy or unhealthy. In the case of unhealthy servers, the load
 /// balancer will not forward requests to them.
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
 pub enum Health {
     Healthy,
     Unhealthy,
 }
+
+impl Health {
+    /// Converts the health status to the value stored in an atomic.
+    pub fn to_u8(self) -> u8 {
+        match self {
+            Health::Healthy => 0,
+            Health::Unhealthy => 1,
+        }
+    }
+
+    /// Converts a value stored in an atomic back to a health status.
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            0 => Health::Healthy,
+            _ => Health::Unhealthy,
+        }
+    }
+}
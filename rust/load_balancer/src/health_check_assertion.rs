@@ -0,0 +1,140 @@
+use reqwest::StatusCode;
+use serde::Deserialize;
+
+/// Conditions the answer to an HTTP health check must meet for the backend server to be healthy,
+/// so that a backend server which answers but is in a bad internal state is found unhealthy. Any
+/// answer is accepted by default. In the config file, for example:
+///
+/// ```toml
+/// [[backends]]
+/// address = "http://localhost:8081/"
+/// health_check = { status = "200-299", json = "/status=ok" }
+/// ```
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(try_from = "HealthCheckEntry")]
+pub struct HealthCheckAssertion {
+    /// Lowest and highest status accepted, None to accept any status.
+    pub status: Option<(u16, u16)>,
+
+    /// Text the body must contain, None to not check it.
+    pub body: Option<String>,
+
+    /// JSON pointer to a field of the body, such as /status, and the value it must have, None to
+    /// not check it. A string field is compared without its quotes, any other field with its JSON
+    /// text.
+    pub json_field: Option<(String, String)>,
+}
+
+impl HealthCheckAssertion {
+    /// Returns true if the body of the answer is needed to check it.
+    pub fn reads_body(&self) -> bool {
+        self.body.is_some() || self.json_field.is_some()
+    }
+
+    /// Checks the status and body of an answer to a health check. Returns why the answer does not
+    /// meet the conditions, if it does not. The body is empty if it is not read.
+    pub fn check(&self, status: StatusCode, body: &str) -> Result<(), String> {
+        if let Some((min, max)) = self.status {
+            if !(min..=max).contains(&status.as_u16()) {
+                return Err(format!(
+                    "status {} is not in {}-{}",
+                    status.as_u16(),
+                    min,
+                    max
+                ));
+            }
+        }
+        if let Some(text) = &self.body {
+            if !body.contains(text.as_str()) {
+                return Err(format!("body does not contain {}", text));
+            }
+        }
+        if let Some((pointer, expected)) = &self.json_field {
+            let json: serde_json::Value =
+                serde_json::from_str(body).map_err(|e| format!("body is not JSON: {}", e))?;
+            let value = match json.pointer(pointer) {
+                Some(serde_json::Value::String(value)) => value.clone(),
+                Some(value) => value.to_string(),
+                None => return Err(format!("body has no field {}", pointer)),
+            };
+            if value != *expected {
+                return Err(format!("field {} is {}, not {}", pointer, value, expected));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parses a range of accepted statuses given as MIN-MAX, such as 200-299, or as a single status.
+/// The statuses are between 100 and 599.
+pub fn parse_status_range(value: &str) -> Result<(u16, u16), String> {
+    let (min, max) = value.split_once('-').unwrap_or((value, value));
+    let status = |status: &str| match status.trim().parse::<u16>() {
+        Ok(status) if (100..600).contains(&status) => Ok(status),
+        _ => Err(format!(
+            "invalid status range {}, expected MIN-MAX with statuses between 100 and 599",
+            value
+        )),
+    };
+    let (min, max) = (status(min)?, status(max)?);
+    if min > max {
+        return Err(format!(
+            "invalid status range {}: {} is above {}",
+            value, min, max
+        ));
+    }
+    Ok((min, max))
+}
+
+/// Parses the expected value of a JSON field given as FIELD=VALUE, such as status=ok or
+/// /checks/database=up. The field is a JSON pointer, a leading / being added if missing.
+pub fn parse_json_field(value: &str) -> Result<(String, String), String> {
+    let Some((field, expected)) = value.split_once('=') else {
+        return Err(format!(
+            "invalid JSON field {}, expected FIELD=VALUE",
+            value
+        ));
+    };
+    let pointer =
+        parse_json_pointer(field).map_err(|e| format!("invalid JSON field {}: {}", value, e))?;
+    Ok((pointer, expected.trim().to_string()))
+}
+
+/// Parses a JSON pointer to a field of a body, such as /checks/database, or the name of a
+/// top-level field such as load, a leading / being added if missing.
+pub fn parse_json_pointer(value: &str) -> Result<String, String> {
+    let field = value.trim();
+    if field.is_empty() {
+        return Err("the field is empty".to_string());
+    }
+    if field.starts_with('/') {
+        Ok(field.to_string())
+    } else {
+        Ok(format!("/{}", field))
+    }
+}
+
+/// The form in which the conditions are given in the config file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct HealthCheckEntry {
+    status: Option<String>,
+    body: Option<String>,
+    json: Option<String>,
+}
+
+impl TryFrom<HealthCheckEntry> for HealthCheckAssertion {
+    type Error = String;
+
+    fn try_from(entry: HealthCheckEntry) -> Result<Self, Self::Error> {
+        Ok(Self {
+            status: entry
+                .status
+                .as_deref()
+                .map(parse_status_range)
+                .transpose()?,
+            body: entry.body,
+            json_field: entry.json.as_deref().map(parse_json_field).transpose()?,
+        })
+    }
+}
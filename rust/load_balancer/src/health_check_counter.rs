@@ -0,0 +1,67 @@
+use crate::health::Health;
+
+/// Active health check of a backend server. Counts the consecutive failed and successful health
+/// checks so that a backend server only changes health once the number of consecutive health
+/// checks contradicting its health reaches the threshold, instead of flapping on a single slow
+/// answer.
+#[derive(Debug)]
+pub struct HealthCheckCounter {
+    /// Number of consecutive successful health checks after which an unhealthy backend server
+    /// becomes healthy, greater than 0.
+    healthy_threshold: u32,
+
+    /// Number of consecutive failed health checks after which a healthy backend server becomes
+    /// unhealthy, greater than 0.
+    unhealthy_threshold: u32,
+
+    /// Number of consecutive successful health checks since the last change of health.
+    consecutive_successes: u32,
+
+    /// Number of consecutive failed health checks since the last change of health.
+    consecutive_failures: u32,
+}
+
+impl HealthCheckCounter {
+    /// Creates a new counter without any health check recorded.
+    pub fn new(healthy_threshold: u32, unhealthy_threshold: u32) -> Self {
+        Self {
+            healthy_threshold,
+            unhealthy_threshold,
+            consecutive_successes: 0,
+            consecutive_failures: 0,
+        }
+    }
+
+    /// Records the result of a health check of a backend server having the given health and
+    /// returns its new health. The counters are reset when the health changes.
+    pub fn record(&mut self, health: Health, is_healthy: bool) -> Health {
+        if is_healthy {
+            self.consecutive_successes += 1;
+            self.consecutive_failures = 0;
+        } else {
+            self.consecutive_failures += 1;
+            self.consecutive_successes = 0;
+        }
+
+        let new_health = match health {
+            Health::Unhealthy if self.consecutive_successes >= self.healthy_threshold => {
+                Health::Healthy
+            }
+            Health::Healthy if self.consecutive_failures >= self.unhealthy_threshold => {
+                Health::Unhealthy
+            }
+            health => health,
+        };
+        if new_health != health {
+            self.reset();
+        }
+        new_health
+    }
+
+    /// Forgets the health checks recorded so far, for example when the health of the backend
+    /// server is changed by a successful request.
+    pub fn reset(&mut self) {
+        self.consecutive_successes = 0;
+        self.consecutive_failures = 0;
+    }
+}
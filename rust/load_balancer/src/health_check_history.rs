@@ -0,0 +1,65 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Number of the most recent health checks over which the failure rate is computed, one per bit
+/// of the history.
+const WINDOW: u64 = u64::BITS as u64;
+
+/// Outcomes of the health checks of a backend server, to spot a backend server which flaps while
+/// its thresholds keep it healthy. Counts the passed and failed health checks since the load
+/// balancer started, and keeps the outcomes of the last ones as the bits of an integer, so that the
+/// failure rate is tracked in a fixed size and without lock.
+#[derive(Debug, Default)]
+pub struct HealthCheckHistory {
+    /// Number of passed health checks since the load balancer started.
+    passed: AtomicU64,
+
+    /// Number of failed health checks since the load balancer started.
+    failed: AtomicU64,
+
+    /// Outcomes of the last health checks, a set bit for a failure, the most recent one in the
+    /// lowest bit.
+    recent_failures: AtomicU64,
+}
+
+impl HealthCheckHistory {
+    /// Creates a history without any health check recorded.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the outcome of a health check.
+    pub fn record(&self, is_healthy: bool) {
+        if is_healthy {
+            self.passed.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.failed.fetch_add(1, Ordering::Relaxed);
+        }
+        // Never fails, the closure always returns a value
+        let _ = self.recent_failures.fetch_update(
+            Ordering::Relaxed,
+            Ordering::Relaxed,
+            |recent_failures| Some(recent_failures << 1 | u64::from(!is_healthy)),
+        );
+    }
+
+    /// Returns the number of passed health checks since the load balancer started.
+    pub fn passed(&self) -> u64 {
+        self.passed.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of failed health checks since the load balancer started.
+    pub fn failed(&self) -> u64 {
+        self.failed.load(Ordering::Relaxed)
+    }
+
+    /// Returns the fraction of the last health checks which failed, up to the last 64, between 0
+    /// and 1. 0 before the first health check.
+    pub fn failure_rate(&self) -> f32 {
+        let recorded = (self.passed() + self.failed()).min(WINDOW);
+        if recorded == 0 {
+            return 0.0;
+        }
+        let failures = self.recent_failures.load(Ordering::Relaxed).count_ones();
+        (failures as f32 / recorded as f32).min(1.0)
+    }
+}
@@ -0,0 +1,19 @@
+use crate::health_check_assertion::HealthCheckAssertion;
+use crate::health_check_method::HealthCheckMethod;
+
+/// How the health of the backend servers is checked.
+#[derive(Clone, Debug, PartialEq)]
+pub enum HealthCheckKind {
+    /// Sends an HTTP request with the given method to the given path, the backend server is
+    /// healthy if its answer meets the conditions of the assertion. The load reported by the
+    /// backend server is read from the JSON field of the answer at the given pointer, if any.
+    Http {
+        method: HealthCheckMethod,
+        path: String,
+        assertion: HealthCheckAssertion,
+        load_field: Option<String>,
+    },
+    /// Opens a TCP connection to the host and port of the backend server, the backend server is
+    /// healthy if the connection succeeds.
+    Tcp,
+}
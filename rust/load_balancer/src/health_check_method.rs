@@ -0,0 +1,14 @@
+use clap::ValueEnum;
+use serde::Deserialize;
+
+/// HTTP method of the health checks sent to the backend servers.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthCheckMethod {
+    /// Asks for the whole answer, whose body can be checked.
+    #[default]
+    Get,
+    /// Only asks for the status and headers of the answer, cheaper for a backend server computing
+    /// its health check body. The body cannot be checked.
+    Head,
+}
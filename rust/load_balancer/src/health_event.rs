@@ -0,0 +1,89 @@
+use crate::health::Health;
+
+use log::{debug, info, warn};
+use reqwest::{Client, Url};
+use serde::Serialize;
+use std::time::{Duration, SystemTime};
+
+/// Maximum time given to the webhook to answer an event.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Change of the health of a backend server, found by a health check or by a request sent to it.
+#[derive(Clone, Debug, Serialize)]
+pub struct HealthEvent {
+    /// Address of the backend server.
+    pub address: String,
+
+    /// Health of the backend server before the change.
+    pub old_health: Health,
+
+    /// Health of the backend server after the change.
+    pub new_health: Health,
+
+    /// Time of the change in the RFC 3339 format, in UTC, for example 2024-07-14T09:30:00Z.
+    pub timestamp: String,
+}
+
+impl HealthEvent {
+    /// Creates the event of the given backend server changing health now.
+    pub fn new(address: &str, old_health: Health, new_health: Health) -> Self {
+        Self {
+            address: address.to_string(),
+            old_health,
+            new_health,
+            timestamp: humantime::format_rfc3339_seconds(SystemTime::now()).to_string(),
+        }
+    }
+
+    /// Logs the event on a line of its own, which alerting can match, and posts it as JSON to the
+    /// given webhook, if any, without waiting for its answer.
+    pub fn emit(self, webhook: Option<&HealthWebhook>) {
+        info!(
+            "Health event: backend server {} changed from {:?} to {:?} at {}",
+            self.address, self.old_health, self.new_health, self.timestamp
+        );
+        if let Some(webhook) = webhook {
+            webhook.post(self);
+        }
+    }
+}
+
+/// URL to which the health events are posted, with the HTTP client posting them.
+#[derive(Clone, Debug)]
+pub struct HealthWebhook {
+    url: Url,
+    client: Client,
+}
+
+impl HealthWebhook {
+    /// Creates the webhook posting the events to the given URL. Returns an error if the URL is not
+    /// a valid HTTP or HTTPS URL, or if the HTTP client cannot be created.
+    pub fn new(url: &str) -> Result<Self, String> {
+        let url = Url::parse(url)
+            .ok()
+            .filter(|url| matches!(url.scheme(), "http" | "https"))
+            .ok_or_else(|| format!("Invalid health webhook URL {}", url))?;
+        let client = Client::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            .build()
+            .map_err(|e| format!("Failed to create the HTTP client of the webhook: {}", e))?;
+        Ok(Self { url, client })
+    }
+
+    /// Posts the event in the background. A failure is logged, the event is not sent again.
+    fn post(&self, event: HealthEvent) {
+        let request = self.client.post(self.url.clone()).json(&event);
+        tokio::spawn(async move {
+            match request.send().await.and_then(|r| r.error_for_status()) {
+                Ok(_) => debug!(
+                    "Posted the health event of {} to the webhook",
+                    event.address
+                ),
+                Err(e) => warn!(
+                    "Failed to post the health event of {} to the webhook: {}",
+                    event.address, e
+                ),
+            }
+        });
+    }
+}
@@ -0,0 +1,16 @@
+use futures_util::stream::{self, StreamExt};
+use std::future::Future;
+use std::pin::Pin;
+
+/// Health check of a backend server, as returned by `Backend::check_health`.
+pub type HealthCheck<'a> = Pin<Box<dyn Future<Output = ()> + Send + 'a>>;
+
+/// Runs the given health checks of backend servers, at most `concurrency` of them at the same
+/// time, so that a sweep over many slow backend servers takes about as long as its slowest checks
+/// instead of the sum of all of them. Each backend server updates its own health.
+pub async fn check_healths(health_checks: Vec<HealthCheck<'_>>, concurrency: usize) {
+    stream::iter(health_checks)
+        .buffer_unordered(concurrency)
+        .collect::<Vec<()>>()
+        .await;
+}
@@ -0,0 +1,95 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::{timeout, Duration};
+
+/// Keeps track of the requests currently forwarded to the backend servers, so that they can be
+/// drained when the load balancer shuts down, and bounds their number. A request arriving when
+/// the maximum number of requests are in flight waits for one of them to end, up to the queue
+/// timeout.
+#[derive(Debug, Default)]
+pub struct InFlightRequests {
+    /// Number of requests currently being handled.
+    in_flight: AtomicUsize,
+
+    /// Number of requests that were handled until the end.
+    completed: AtomicUsize,
+
+    /// Slots of the requests in flight. None means no limit.
+    slots: Option<Arc<Semaphore>>,
+
+    /// Maximum time a request waits for a slot.
+    queue_timeout: Duration,
+}
+
+impl InFlightRequests {
+    /// Creates the requests in flight, at most the given number at the same time, None for no
+    /// limit, a request waiting at most the given queue timeout for another one to end.
+    pub fn new(max_in_flight: Option<usize>, queue_timeout: Duration) -> Self {
+        Self {
+            slots: max_in_flight.map(|max_in_flight| Arc::new(Semaphore::new(max_in_flight))),
+            queue_timeout,
+            ..Self::default()
+        }
+    }
+
+    /// Registers a new request once a slot is free. The request is no longer in flight, and its
+    /// slot is freed, once the returned guard is dropped. Returns an error if no slot was freed
+    /// within the queue timeout.
+    pub async fn start(self: &Arc<Self>) -> Result<InFlightRequest, String> {
+        let slot = match &self.slots {
+            // A free slot is taken at once, even with a zero queue timeout
+            Some(slots) => {
+                match timeout(self.queue_timeout, Arc::clone(slots).acquire_owned()).await {
+                    Ok(Ok(slot)) => Some(slot),
+                    Ok(Err(e)) => return Err(format!("The request slots are closed: {}", e)),
+                    Err(_) => {
+                        return Err(format!(
+                            "No request ended within {}ms",
+                            self.queue_timeout.as_millis()
+                        ))
+                    }
+                }
+            }
+            None => None,
+        };
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        Ok(InFlightRequest {
+            requests: Arc::clone(self),
+            _slot: slot,
+        })
+    }
+
+    /// Returns the number of requests currently being handled.
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of requests that were handled until the end.
+    pub fn completed(&self) -> usize {
+        self.completed.load(Ordering::SeqCst)
+    }
+}
+
+/// Guard of a request in flight. Dropping it without calling `finish` means the request was
+/// cancelled.
+#[derive(Debug)]
+pub struct InFlightRequest {
+    requests: Arc<InFlightRequests>,
+
+    /// Slot of the request, freed with the guard. None when the requests are not limited.
+    _slot: Option<OwnedSemaphorePermit>,
+}
+
+impl InFlightRequest {
+    /// Marks the request as handled until the end.
+    pub fn finish(self) {
+        self.requests.completed.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+impl Drop for InFlightRequest {
+    fn drop(&mut self) {
+        self.requests.in_flight.fetch_sub(1, Ordering::SeqCst);
+    }
+}
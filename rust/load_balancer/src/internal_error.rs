@@ -1,23 +1,84 @@
+use actix_web::http::StatusCode;
 use std::error::Error;
 use std::fmt;
+use std::time::Duration;
 
 #[derive(Debug)]
 pub enum InternalError {
-    NoBackendAvailable,
-    BackendUnreachable,
+    /// No backend server could take the request. The addresses of the backend servers which were
+    /// tried and failed before none remained are given, if any.
+    NoBackendAvailable {
+        tried: Vec<String>,
+    },
+    /// The backend server with the given address did not answer.
+    BackendUnreachable {
+        address: String,
+        source: reqwest::Error,
+    },
+    SelectionTimeout,
+    /// No backend server started answering the request within the given deadline, retries
+    /// included.
+    RequestTimeout {
+        timeout: Duration,
+    },
+    /// The path of the request matches no route, it is answered with the given default response
+    /// instead of being forwarded.
+    NoRoute {
+        status: StatusCode,
+        body: String,
+    },
 }
 
 impl fmt::Display for InternalError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            InternalError::NoBackendAvailable => {
-                write!(f, "No load balancer available")
+            InternalError::NoBackendAvailable { tried } if tried.is_empty() => {
+                write!(f, "No backend server available")
             }
-            InternalError::BackendUnreachable => {
-                write!(f, "Backend server unreachable")
+            InternalError::NoBackendAvailable { tried } => {
+                write!(f, "No backend server available, tried {}", tried.join(", "))
+            }
+            InternalError::BackendUnreachable { address, source } => {
+                write!(f, "Backend server {} unreachable: {}", address, source)
+            }
+            InternalError::SelectionTimeout => {
+                write!(f, "Backend server selection timed out")
+            }
+            InternalError::RequestTimeout { timeout } => {
+                write!(
+                    f,
+                    "No backend server answered within {}",
+                    humantime::format_duration(*timeout)
+                )
+            }
+            InternalError::NoRoute { .. } => {
+                write!(f, "No route matches the path")
             }
         }
     }
 }
 
-impl Error for InternalError {}
+impl InternalError {
+    /// Returns the status code of the response sent to the client. The load balancer is a gateway,
+    /// so a backend server failing to answer is a 502, no backend server being available, for
+    /// now, is a 503 and no backend server answering in time is a 504. A request matching no route
+    /// gets the status of the default response.
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            InternalError::NoBackendAvailable { .. } => StatusCode::SERVICE_UNAVAILABLE,
+            InternalError::BackendUnreachable { .. } => StatusCode::BAD_GATEWAY,
+            InternalError::SelectionTimeout => StatusCode::SERVICE_UNAVAILABLE,
+            InternalError::RequestTimeout { .. } => StatusCode::GATEWAY_TIMEOUT,
+            InternalError::NoRoute { status, .. } => *status,
+        }
+    }
+}
+
+impl Error for InternalError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            InternalError::BackendUnreachable { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
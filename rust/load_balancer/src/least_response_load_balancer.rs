@@ -1,30 +1,68 @@
+use crate::affinity;
 use crate::backend::Backend;
+use crate::backend_response::BackendResponse;
+use crate::backend_snapshot::BackendSnapshot;
 use crate::health::Health;
+use crate::health_sweep;
 use crate::internal_error::InternalError;
 use crate::load_balancer::LoadBalancer;
+use crate::metrics::Metrics;
 use crate::min_heap_item::MinHeapItem;
+use crate::request_context::RequestContext;
+use crate::retry_policy::RetryPolicy;
+use crate::selection::{self, Candidate};
+use crate::strategy::Strategy;
+use crate::tag_routing;
 
 use async_trait::async_trait;
 use log::{error, info, warn};
 use std::collections::BinaryHeap;
+use std::sync::Arc;
 use tokio::sync::RwLock as TokioRwLock;
+use tokio::time::{sleep, timeout, Duration};
 
-/// Represents a very basic load balancer. Sends the requests to healthy backend servers in a round
-/// robin fashion.
+/// Load balancer sending the requests to the healthy backend server with the lowest response time
+/// relative to its weight.
 #[derive(Debug)]
 pub struct LeastResponseLoadBalancer {
     /// List of unhealthy backends servers
     unhealthy_backends: TokioRwLock<Vec<Box<dyn Backend>>>,
 
     /// Min heap of healthy backend servers. The heap is ordered by the response time of the
-    /// backends
+    /// backends divided by their weight
     healthy_backends: TokioRwLock<BinaryHeap<MinHeapItem<Box<dyn Backend>>>>,
+
+    /// Maximum time spent waiting for the heap of healthy backends to select the backend server
+    /// to which a request is sent.
+    selection_timeout: Duration,
+
+    /// Metrics updated on each request.
+    metrics: Arc<Metrics>,
+
+    /// Policy deciding whether a failed request is retried on the next best backend server.
+    retry_policy: RetryPolicy,
+
+    /// Maximum number of backend servers health checked at the same time.
+    health_check_concurrency: usize,
+
+    /// Fraction by which the response time of the backend servers decays at each health check
+    /// sweep, so that a backend server starved after a slow response gets requests again.
+    response_time_decay: f32,
 }
 
 impl LeastResponseLoadBalancer {
     /// Creates a new load balancer with the given list of backend servers to route the requests
-    /// to.
-    pub fn new(backends: Vec<Box<dyn Backend>>) -> Self {
+    /// to. Selecting a backend server fails if it takes longer than the selection timeout. The
+    /// failed requests are retried as allowed by the retry policy. The response time of the
+    /// backend servers decays by the given fraction at each health check sweep.
+    pub fn new(
+        backends: Vec<Box<dyn Backend>>,
+        selection_timeout: Duration,
+        metrics: Arc<Metrics>,
+        retry_policy: RetryPolicy,
+        health_check_concurrency: usize,
+        response_time_decay: f32,
+    ) -> Self {
         let mut healthy_backends = BinaryHeap::new();
         for backend in backends.into_iter() {
             healthy_backends.push(MinHeapItem {
@@ -35,61 +73,210 @@ impl LeastResponseLoadBalancer {
         Self {
             unhealthy_backends: TokioRwLock::new(Vec::new()),
             healthy_backends: TokioRwLock::new(healthy_backends),
+            selection_timeout,
+            metrics,
+            retry_policy,
+            health_check_concurrency,
+            response_time_decay,
         }
     }
 }
 
+/// Returns the priority of the backend server in the heap: its response time divided by its
+/// effective weight, so that at equal response times the backend servers with a greater weight are
+/// preferred and the ones in their slow start or reporting a load are avoided. A parked backend
+/// server, with a weight of 0, is last instead of dividing by 0.
+async fn weighted_response_time(backend: &dyn Backend) -> f32 {
+    if backend.weight() == 0 {
+        return f32::INFINITY;
+    }
+    backend.response_time_ms().await / backend.effective_weight()
+}
+
+/// Returns the index of the candidate with the lowest response time, or when only backup backend
+/// servers are available, of a backup one picked at random in proportion to its weight.
+fn select_index(candidates: &[Candidate]) -> Option<usize> {
+    let index = selection::least_response_index(candidates)?;
+    if !candidates[index].backup {
+        return Some(index);
+    }
+    selection::weighted_backup_index(candidates, rand::random::<f32>()).or(Some(index))
+}
+
 #[async_trait]
 impl LoadBalancer for LeastResponseLoadBalancer {
-    // Returns the next available backend server to which the request can be sent. If none are
-    // available, an error is returned.
-    async fn next_available_backend(&self) -> Result<Box<dyn Backend>, String> {
+    /// Returns the backend server to which the client is pinned by its affinity if it can receive
+    /// the request, otherwise the healthy backend server with the lowest response time relative to
+    /// its weight which is not draining and has not reached its maximum number of connections. The
+    /// backup backend servers are only selected when no primary one is available, at random in
+    /// proportion to their weights, and the busy ones when no idle one is. Only the backend servers
+    /// having the tags asked for by the request are selected, unless none of them is available. If
+    /// none are available, an error is returned.
+    async fn next_available_backend(
+        &self,
+        context: &RequestContext,
+    ) -> Result<Box<dyn Backend>, String> {
         let r_healthy_backends = self.healthy_backends.read().await;
-        if r_healthy_backends.is_empty() {
-            return Err("No backend server available".to_string());
+
+        if let Some(MinHeapItem { element, .. }) = r_healthy_backends
+            .iter()
+            .find(|item| affinity::is_pinned(context, item.element.as_ref()))
+        {
+            return Ok(element.clone());
         }
 
-        let MinHeapItem { element, .. } = r_healthy_backends.peek().unwrap();
+        // The priority of the items is the response time of the candidates. The backup backend
+        // servers are only selected when no primary one is available
+        let required_tags = tag_routing::required_tags(
+            context,
+            r_healthy_backends.iter().map(|item| item.element.as_ref()),
+        );
+        let items: Vec<&MinHeapItem<Box<dyn Backend>>> = r_healthy_backends.iter().collect();
+        let candidates: Vec<Candidate> = items
+            .iter()
+            .map(|item| Candidate {
+                response_time: item.priority,
+                ..Candidate::new(item.element.as_ref(), required_tags)
+            })
+            .collect();
+        let Some(index) = select_index(&candidates) else {
+            return Err("No backend server available".to_string());
+        };
 
-        Ok(element.clone())
+        Ok(items[index].element.clone())
     }
 
-    async fn send_request(&self) -> Result<String, InternalError> {
-        let mut w_healthy_backends = self.healthy_backends.write().await;
-        if w_healthy_backends.is_empty() {
-            return Err(InternalError::NoBackendAvailable);
-        }
+    /// Sends the request to the backend selected like by next_available_backend, the healthy
+    /// backend with the lowest response time relative to its weight. Backends failing to answer are
+    /// moved to the unhealthy list and, as allowed by the retry policy and its retry budget, the
+    /// request is retried on the next best one, until one succeeds or no healthy backend remains.
+    /// The backup backends are tried after all the primary ones, picked at random in proportion to
+    /// their weights, and the backends without requests in flight before the busy ones. Draining
+    /// backends and backends which reached their maximum number of connections are skipped. Only
+    /// the backends having the tags asked for by the request are tried, unless none of them is
+    /// available. The backends are only moved once their request completed, so that cancelling the
+    /// request leaves them in place.
+    async fn send_request(
+        &self,
+        context: &RequestContext,
+    ) -> Result<BackendResponse, InternalError> {
+        self.metrics.record_request();
+        self.retry_policy.record_request();
 
-        let MinHeapItem {
-            element: backend, ..
-        } = w_healthy_backends.pop().unwrap();
-
-        // Send the request to the backend server
-        let response = backend.send_request().await;
-        match response {
-            Ok(r) => {
-                info!("{:?}", r);
-                w_healthy_backends.push(MinHeapItem {
-                    priority: backend.response_time_ms().await,
-                    element: backend,
-                });
-                drop(w_healthy_backends);
-                let body = r.text_with_charset("utf-8").await.unwrap();
-                Ok(body)
-            }
-            Err(e) => {
-                error!(
-                    "Failed to send request to backend server: {:?}, trying next one",
-                    e
-                );
-                let mut w_unhealthy_backends = self.unhealthy_backends.write().await;
-                w_unhealthy_backends.push(backend);
-                drop(w_unhealthy_backends);
-                drop(w_healthy_backends);
+        let Ok(r_healthy_backends) =
+            timeout(self.selection_timeout, self.healthy_backends.read()).await
+        else {
+            error!(
+                "Acquiring the healthy backends took more than {}ms",
+                self.selection_timeout.as_millis()
+            );
+            return Err(InternalError::SelectionTimeout);
+        };
+
+        // The backend servers are copied, so that the lock is not held during the requests, which
+        // are sent in parallel
+        let required_tags = tag_routing::required_tags(
+            context,
+            r_healthy_backends.iter().map(|item| item.element.as_ref()),
+        );
+        let items: Vec<MinHeapItem<Box<dyn Backend>>> =
+            r_healthy_backends.iter().cloned().collect();
+        drop(r_healthy_backends);
+
+        // A client pinned to a backend server by its affinity tries it first, then the others in
+        // the order of the selection, each one at most once
+        let mut pinned_index = items
+            .iter()
+            .position(|item| affinity::is_pinned(context, item.element.as_ref()));
+        let mut tried = vec![false; items.len()];
+        let mut failed_addresses = Vec::new();
+        let mut retry = 0;
+        loop {
+            let candidates: Vec<Candidate> = items
+                .iter()
+                .zip(&tried)
+                .map(|(item, tried)| {
+                    let candidate = Candidate::new(item.element.as_ref(), required_tags);
+                    Candidate {
+                        available: candidate.available && !tried,
+                        response_time: item.priority,
+                        ..candidate
+                    }
+                })
+                .collect();
+            let Some(index) = pinned_index.take().or_else(|| select_index(&candidates)) else {
+                break;
+            };
+            tried[index] = true;
+            let backend = items[index].element.clone();
 
-                self.send_request().await
+            // Send the request to the backend server
+            match backend.send_request(context).await {
+                Ok(r) => {
+                    info!("{:?}", r);
+                    let response_time = backend.response_time_ms().await;
+                    self.metrics
+                        .record_backend_response(backend.address(), response_time)
+                        .await;
+                    let priority = weighted_response_time(backend.as_ref()).await;
+                    let address = backend.address().to_string();
+                    // The backend server may have been removed or found unhealthy during the
+                    // request, in which case it is not put back
+                    let mut w_healthy_backends = self.healthy_backends.write().await;
+                    let healthy_backends_count = w_healthy_backends.len();
+                    w_healthy_backends.retain(|item| item.element.address() != address);
+                    if w_healthy_backends.len() != healthy_backends_count {
+                        w_healthy_backends.push(MinHeapItem {
+                            priority,
+                            element: backend,
+                        });
+                    }
+                    return Ok(BackendResponse {
+                        address,
+                        response: r,
+                    });
+                }
+                Err(e) => {
+                    error!("Failed to send request to backend server: {:?}", e);
+                    self.metrics.record_backend_error(backend.address()).await;
+                    failed_addresses.push(backend.address().to_string());
+                    let is_retryable = self.retry_policy.is_retryable(context, &e);
+                    // Same locking order as the health checks. The backend server is only moved
+                    // if it is still healthy, not if another request or a removal moved it first
+                    let mut w_healthy_backends = self.healthy_backends.write().await;
+                    let mut w_unhealthy_backends = self.unhealthy_backends.write().await;
+                    let healthy_backends_count = w_healthy_backends.len();
+                    w_healthy_backends.retain(|item| item.element.address() != backend.address());
+                    if w_healthy_backends.len() != healthy_backends_count {
+                        w_unhealthy_backends.push(backend.clone());
+                    }
+                    drop(w_unhealthy_backends);
+                    drop(w_healthy_backends);
+
+                    if retry >= self.retry_policy.max_retries
+                        || !is_retryable
+                        || !self.retry_policy.spend_retry()
+                    {
+                        return Err(InternalError::BackendUnreachable {
+                            address: backend.address().to_string(),
+                            source: e,
+                        });
+                    }
+                    let backoff = self.retry_policy.backoff(retry);
+                    warn!(
+                        "Retrying request in {}ms after backend {} failed",
+                        backoff.as_millis(),
+                        backend.address()
+                    );
+                    sleep(backoff).await;
+                    retry += 1;
+                }
             }
         }
+
+        Err(InternalError::NoBackendAvailable {
+            tried: failed_addresses,
+        })
     }
 
     /// Checks and update the health status of all backend servers.
@@ -97,24 +284,47 @@ impl LoadBalancer for LeastResponseLoadBalancer {
         // This is used for profiling only
         let start_time = std::time::Instant::now();
 
+        // Check a copy of the backend servers without holding the locks, so that the requests and
+        // the backend servers being added or removed are not blocked during the health checks
+        let backends: Vec<Box<dyn Backend>> = {
+            let r_healthy_backends = self.healthy_backends.read().await;
+            let r_unhealthy_backends = self.unhealthy_backends.read().await;
+            r_healthy_backends
+                .iter()
+                .map(|item| item.element.clone())
+                .chain(r_unhealthy_backends.iter().cloned())
+                .collect()
+        };
+        let health_checks = backends
+            .iter()
+            .map(|backend| backend.check_health())
+            .collect();
+        health_sweep::check_healths(health_checks, self.health_check_concurrency).await;
+
+        // Sort the backend servers by their new health, including the ones added meanwhile
         let mut new_healthy_backends = BinaryHeap::new();
         let mut new_unhealthy_backends: Vec<Box<dyn Backend>> = Vec::new();
-
         let mut w_healthy_backends = self.healthy_backends.write().await;
-        // check healthy backends
-        while let Some(MinHeapItem {
-            element: backend, ..
-        }) = w_healthy_backends.pop()
-        {
-            backend.check_health().await;
-            if backend.health().await == Health::Healthy {
+        let mut w_unhealthy_backends = self.unhealthy_backends.write().await;
+        let current_backends: Vec<Box<dyn Backend>> = w_healthy_backends
+            .drain()
+            .map(|item| item.element)
+            .chain(w_unhealthy_backends.drain(..))
+            .collect();
+        for backend in current_backends {
+            if backend.health() == Health::Healthy {
+                // Without new requests, the response time of a starved backend server only
+                // changes by decaying, until it is the lowest one and the backend server is tried
+                if self.response_time_decay > 0.0 {
+                    backend.decay_response_time(self.response_time_decay).await;
+                }
                 let response_time = backend.response_time_ms().await;
                 info!(
                     "Backend {:?} is healthy with response time {}ms",
                     backend, response_time
                 );
                 new_healthy_backends.push(MinHeapItem {
-                    priority: response_time,
+                    priority: weighted_response_time(backend.as_ref()).await,
                     element: backend,
                 });
             } else {
@@ -123,22 +333,6 @@ impl LoadBalancer for LeastResponseLoadBalancer {
             }
         }
 
-        // check unhealthy backends
-        let mut w_unhealthy_backends = self.unhealthy_backends.write().await;
-        while let Some(backend) = w_unhealthy_backends.pop() {
-            backend.check_health().await;
-            if backend.health().await == Health::Healthy {
-                info!("Backend {:?} is now healthy", backend);
-                new_healthy_backends.push(MinHeapItem {
-                    priority: backend.response_time_ms().await,
-                    element: backend,
-                });
-            } else {
-                info!("Backend {:?} is still unhealthy", backend);
-                new_unhealthy_backends.push(backend);
-            }
-        }
-
         *w_healthy_backends = new_healthy_backends;
         *w_unhealthy_backends = new_unhealthy_backends;
         let healthy_backends_count = w_healthy_backends.len();
@@ -146,14 +340,9 @@ impl LoadBalancer for LeastResponseLoadBalancer {
 
         let best_backend = w_healthy_backends.peek();
 
-        let best_backend_priority: Option<f32> = match best_backend {
-            Some(MinHeapItem { priority, .. }) => Some(*priority),
-            None => None,
-        };
-        let best_backend_address: Option<String> = match best_backend {
-            Some(MinHeapItem { element, .. }) => Some(element.address().to_string()),
-            None => None,
-        };
+        let best_backend_priority: Option<f32> = best_backend.map(|item| item.priority);
+        let best_backend_address: Option<String> =
+            best_backend.map(|item| item.element.address().to_string());
 
         drop(w_healthy_backends);
         drop(w_unhealthy_backends);
@@ -168,7 +357,7 @@ impl LoadBalancer for LeastResponseLoadBalancer {
                 info!("Best backend: {}ms, {}", priority, address);
             }
             _ => {
-                error!("No backend available");
+                warn!("No healthy backend available");
             }
         }
         info!(
@@ -176,4 +365,265 @@ impl LoadBalancer for LeastResponseLoadBalancer {
             healthy_backends_count, unhealthy_backends_count
         );
     }
+
+    /// Returns the current state of all backend servers, the healthy ones first.
+    async fn backends_snapshot(&self) -> Vec<BackendSnapshot> {
+        let mut snapshots = Vec::new();
+        for MinHeapItem { element, .. } in self.healthy_backends.read().await.iter() {
+            snapshots.push(BackendSnapshot::new(element.as_ref()).await);
+        }
+        for backend in self.unhealthy_backends.read().await.iter() {
+            snapshots.push(BackendSnapshot::new(backend.as_ref()).await);
+        }
+        snapshots
+    }
+
+    /// Returns the number of healthy backend servers.
+    async fn healthy_count(&self) -> usize {
+        // The backend servers are only sorted by health during the health checks, so the health of
+        // each one is read
+        let healthy_backends = self
+            .healthy_backends
+            .read()
+            .await
+            .iter()
+            .filter(|item| item.element.health() == Health::Healthy)
+            .count();
+        let unhealthy_backends = self
+            .unhealthy_backends
+            .read()
+            .await
+            .iter()
+            .filter(|backend| backend.health() == Health::Healthy)
+            .count();
+        healthy_backends + unhealthy_backends
+    }
+
+    /// Adds a backend server to the unhealthy backends, it receives requests once a health check
+    /// finds it healthy. Returns an error if a backend server with the same address already exists.
+    async fn add_backend(&self, backend: Box<dyn Backend>) -> Result<(), String> {
+        // Same locking order as the health checks
+        let w_healthy_backends = self.healthy_backends.write().await;
+        let mut w_unhealthy_backends = self.unhealthy_backends.write().await;
+        let exists = w_healthy_backends
+            .iter()
+            .any(|item| item.element.address() == backend.address())
+            || w_unhealthy_backends
+                .iter()
+                .any(|b| b.address() == backend.address());
+        if exists {
+            return Err(format!(
+                "Backend server {} already exists",
+                backend.address()
+            ));
+        }
+
+        info!("Adding backend server {}", backend.address());
+        w_unhealthy_backends.push(backend);
+        Ok(())
+    }
+
+    /// Removes the backend server with the given address. Returns an error if there is no backend
+    /// server with this address.
+    async fn remove_backend(&self, address: &str) -> Result<(), String> {
+        // Same locking order as the health checks
+        let mut w_healthy_backends = self.healthy_backends.write().await;
+        let mut w_unhealthy_backends = self.unhealthy_backends.write().await;
+        let backends_count = w_healthy_backends.len() + w_unhealthy_backends.len();
+        w_healthy_backends.retain(|item| item.element.address() != address);
+        w_unhealthy_backends.retain(|b| b.address() != address);
+        if w_healthy_backends.len() + w_unhealthy_backends.len() == backends_count {
+            return Err(format!("No backend server with address {}", address));
+        }
+
+        info!("Removing backend server {}", address);
+        Ok(())
+    }
+
+    /// Starts or stops draining the backend server with the given address. Returns an error if
+    /// there is no backend server with this address.
+    async fn set_draining(&self, address: &str, draining: bool) -> Result<(), String> {
+        // Same locking order as the health checks
+        let r_healthy_backends = self.healthy_backends.read().await;
+        let r_unhealthy_backends = self.unhealthy_backends.read().await;
+        let Some(backend) = r_healthy_backends
+            .iter()
+            .map(|item| &item.element)
+            .chain(r_unhealthy_backends.iter())
+            .find(|b| b.address() == address)
+        else {
+            return Err(format!("No backend server with address {}", address));
+        };
+
+        info!(
+            "Setting draining of backend server {} to {}",
+            address, draining
+        );
+        backend.set_draining(draining);
+        Ok(())
+    }
+
+    /// Sets the weight of the backend server with the given address, and its priority in the heap
+    /// if it is healthy. Returns an error if there is no backend server with this address.
+    async fn set_weight(&self, address: &str, weight: u32) -> Result<(), String> {
+        // Same locking order as the health checks
+        let mut w_healthy_backends = self.healthy_backends.write().await;
+        let r_unhealthy_backends = self.unhealthy_backends.read().await;
+        let Some(backend) = w_healthy_backends
+            .iter()
+            .map(|item| &item.element)
+            .chain(r_unhealthy_backends.iter())
+            .find(|b| b.address() == address)
+            .cloned()
+        else {
+            return Err(format!("No backend server with address {}", address));
+        };
+
+        info!("Setting weight of backend server {} to {}", address, weight);
+        backend.set_weight(weight);
+        let mut items = std::mem::take(&mut *w_healthy_backends).into_vec();
+        for item in items.iter_mut() {
+            if item.element.address() == address {
+                item.priority = weighted_response_time(item.element.as_ref()).await;
+            }
+        }
+        *w_healthy_backends = items.into();
+        Ok(())
+    }
+
+    fn strategy(&self) -> String {
+        Strategy::LeastResponse.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend_config::BackendConfig;
+    use crate::retry_budget::RetryBudget;
+    use crate::simple_backend::SimpleBackend;
+    use actix_web::http::StatusCode;
+
+    /// Returns a load balancer with a healthy backend server at each of the given addresses, whose
+    /// selection times out after 50ms and which retries the failed requests up to 3 times.
+    fn load_balancer(addresses: &[String]) -> LeastResponseLoadBalancer {
+        let backends: Vec<Box<dyn Backend>> = addresses
+            .iter()
+            .map(|address| {
+                let config = BackendConfig::default();
+                let backend = SimpleBackend::new(address.clone(), Health::Healthy, &config);
+                Box::new(backend.unwrap()) as Box<dyn Backend>
+            })
+            .collect();
+        LeastResponseLoadBalancer::new(
+            backends,
+            Duration::from_millis(50),
+            Arc::new(Metrics::new()),
+            RetryPolicy {
+                max_retries: 3,
+                base_backoff: Duration::ZERO,
+                retry_non_idempotent: false,
+                retry_statuses: Vec::new(),
+                max_tries: 1,
+                budget: None,
+            },
+            1,
+            0.0,
+        )
+    }
+
+    #[tokio::test]
+    async fn answers_503_when_the_selection_stalls() {
+        let load_balancer = load_balancer(&["http://localhost:8081/".to_string()]);
+        // The selection waits for the heap of healthy backend servers until the timeout
+        let _stall = load_balancer.healthy_backends.write().await;
+
+        let result = load_balancer.send_request(&RequestContext::default()).await;
+
+        let error = result.err().unwrap();
+        assert!(
+            matches!(error, InternalError::SelectionTimeout),
+            "{}",
+            error
+        );
+        assert_eq!(error.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    /// Returns the address of a backend server refusing the connections, on a port which was just
+    /// free.
+    fn dead_address() -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        format!("http://{}/", listener.local_addr().unwrap())
+    }
+
+    #[tokio::test]
+    async fn fails_after_trying_every_dead_backend_once() {
+        let addresses = [dead_address(), dead_address()];
+        let load_balancer = load_balancer(&addresses);
+
+        let result = load_balancer.send_request(&RequestContext::default()).await;
+
+        let Err(InternalError::NoBackendAvailable { mut tried }) = result else {
+            panic!("expected no backend server to be available");
+        };
+        tried.sort();
+        let mut expected = addresses.to_vec();
+        expected.sort();
+        assert_eq!(tried, expected);
+        // The dead backend servers are moved to the unhealthy list
+        assert!(load_balancer.healthy_backends.read().await.is_empty());
+        assert_eq!(load_balancer.unhealthy_backends.read().await.len(), 2);
+        assert_eq!(load_balancer.healthy_count().await, 0);
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_a_non_idempotent_request() {
+        let load_balancer = load_balancer(&[dead_address(), dead_address()]);
+        let context = RequestContext {
+            method: reqwest::Method::POST,
+            ..RequestContext::default()
+        };
+
+        let result = load_balancer.send_request(&context).await;
+
+        assert!(matches!(
+            result,
+            Err(InternalError::BackendUnreachable { .. })
+        ));
+        assert_eq!(load_balancer.unhealthy_backends.read().await.len(), 1);
+        assert_eq!(load_balancer.healthy_backends.read().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn retries_at_most_max_retries_times() {
+        let addresses = [dead_address(), dead_address(), dead_address()];
+        let mut load_balancer = load_balancer(&addresses);
+        load_balancer.retry_policy.max_retries = 1;
+
+        let result = load_balancer.send_request(&RequestContext::default()).await;
+
+        assert!(matches!(
+            result,
+            Err(InternalError::BackendUnreachable { .. })
+        ));
+        assert_eq!(load_balancer.unhealthy_backends.read().await.len(), 2);
+        assert_eq!(load_balancer.healthy_backends.read().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_once_the_retry_budget_is_spent() {
+        let addresses = [dead_address(), dead_address()];
+        let mut load_balancer = load_balancer(&addresses);
+        // A tenth of a retry per request, without minimum, allows no retry for a single request
+        load_balancer.retry_policy.budget =
+            Some(Arc::new(RetryBudget::new(0.1, Duration::from_secs(10), 0)));
+
+        let result = load_balancer.send_request(&RequestContext::default()).await;
+
+        assert!(matches!(
+            result,
+            Err(InternalError::BackendUnreachable { .. })
+        ));
+        assert_eq!(load_balancer.unhealthy_backends.read().await.len(), 1);
+    }
 }
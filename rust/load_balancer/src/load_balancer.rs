@@ -1,5 +1,8 @@
 use crate::backend::Backend;
+use crate::backend_response::BackendResponse;
+use crate::backend_snapshot::BackendSnapshot;
 use crate::internal_error::InternalError;
+use crate::request_context::RequestContext;
 use async_trait::async_trait;
 
 /// Load balancer interface
@@ -7,9 +10,46 @@ use async_trait::async_trait;
 pub trait LoadBalancer: Send + Sync {
     /// Returns the next available backend server to which the request can be sent. If none are
     /// available, an error is returned.
-    async fn next_available_backend(&self) -> Result<Box<dyn Backend>, String>;
+    async fn next_available_backend(
+        &self,
+        context: &RequestContext,
+    ) -> Result<Box<dyn Backend>, String>;
 
-    async fn send_request(&self) -> Result<String, InternalError>;
+    /// Forwards the client request described by the context to a backend server and returns its
+    /// response with the address of the backend server which answered. A request pinned to a
+    /// backend server by its affinity is sent to it if it can receive it.
+    async fn send_request(
+        &self,
+        context: &RequestContext,
+    ) -> Result<BackendResponse, InternalError>;
 
     async fn check_backends_healths(&self);
+
+    /// Returns the current state of all backend servers.
+    async fn backends_snapshot(&self) -> Vec<BackendSnapshot>;
+
+    /// Returns the number of healthy backend servers, including the draining ones.
+    async fn healthy_count(&self) -> usize;
+
+    /// Adds a backend server to which the requests can be sent. Returns an error if a backend
+    /// server with the same address already exists.
+    async fn add_backend(&self, backend: Box<dyn Backend>) -> Result<(), String>;
+
+    /// Removes the backend server with the given address. Returns an error if there is no backend
+    /// server with this address.
+    async fn remove_backend(&self, address: &str) -> Result<(), String>;
+
+    /// Starts or stops draining the backend server with the given address. A draining backend
+    /// server receives no new requests but is still health checked. Returns an error if there is
+    /// no backend server with this address.
+    async fn set_draining(&self, address: &str, draining: bool) -> Result<(), String>;
+
+    /// Sets the weight of the backend server with the given address. A backend server with a
+    /// weight of 0 is parked: it receives no requests but is still health checked, and receives
+    /// requests again once its weight is raised. Returns an error if there is no backend server
+    /// with this address.
+    async fn set_weight(&self, address: &str, weight: u32) -> Result<(), String>;
+
+    /// Returns the strategy used to choose the backend servers, as given with --strategy.
+    fn strategy(&self) -> String;
 }
@@ -0,0 +1,236 @@
+use crate::backend::Backend;
+use crate::backend_config::BackendConfig;
+use crate::backend_definition::BackendDefinition;
+use crate::config_file::ConfigFile;
+use crate::consistent_hash_load_balancer::ConsistentHashLoadBalancer;
+use crate::continent::Continent;
+use crate::geo_backend::GeoBackend;
+use crate::geo_load_balancer::GeoLoadBalancer;
+use crate::health::Health;
+use crate::least_response_load_balancer::LeastResponseLoadBalancer;
+use crate::load_balancer::LoadBalancer;
+use crate::metrics::Metrics;
+use crate::path_router::PathRouter;
+use crate::power_of_two_choices_load_balancer::PowerOfTwoChoicesLoadBalancer;
+use crate::retry_policy::RetryPolicy;
+use crate::round_robin_load_balancer::RoundRobinLoadBalancer;
+use crate::simple_backend::SimpleBackend;
+use crate::strategy::Strategy;
+use crate::weighted_random_load_balancer::WeightedRandomLoadBalancer;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Settings shared by all the load balancers, used to create a load balancer for a strategy and a
+/// list of backend servers, at startup or when the config file is reloaded.
+#[derive(Clone, Debug)]
+pub struct LoadBalancerSettings {
+    /// Settings applied to the backend servers.
+    pub backend_config: BackendConfig,
+
+    /// Maximum time spent selecting the backend server to which a request is sent.
+    pub selection_timeout: Duration,
+
+    /// Metrics updated on each request.
+    pub metrics: Arc<Metrics>,
+
+    /// Policy deciding when a failed request is retried, used by the round robin and least
+    /// response load balancers.
+    pub retry_policy: RetryPolicy,
+
+    /// Number of virtual nodes of each backend server, used by the consistent hash load balancer.
+    pub virtual_nodes: u32,
+
+    /// Path of the GeoIP database, required by the geo load balancer.
+    pub geoip_database: Option<PathBuf>,
+
+    /// Maximum number of backend servers health checked at the same time during a health check
+    /// sweep.
+    pub health_check_concurrency: usize,
+
+    /// Fraction by which the response time of the backend servers decays at each health check
+    /// sweep, used by the least response load balancer.
+    pub response_time_decay: f32,
+
+    /// Seed of the random number generators of the power of two choices and weighted random load
+    /// balancers, so that they pick the same backend servers from one run to the next. Seeded from
+    /// the system by default.
+    pub random_seed: Option<u64>,
+}
+
+impl LoadBalancerSettings {
+    /// Creates a load balancer using the given strategy to route the requests to the given backend
+    /// servers. Returns an error if no backend server is given or if a backend server address or
+    /// setting is invalid.
+    pub fn build(
+        &self,
+        strategy: Strategy,
+        backend_definitions: &[BackendDefinition],
+    ) -> Result<Box<dyn LoadBalancer>, String> {
+        if backend_definitions.is_empty() {
+            return Err("At least one backend server is required".to_string());
+        }
+
+        let selection_timeout = self.selection_timeout;
+        let metrics = self.metrics.clone();
+        let health_check_concurrency = self.health_check_concurrency;
+        Ok(match strategy {
+            Strategy::RoundRobin => Box::new(RoundRobinLoadBalancer::new(
+                self.backends(backend_definitions)?,
+                selection_timeout,
+                metrics,
+                self.retry_policy.clone(),
+                health_check_concurrency,
+            )),
+            Strategy::LeastResponse => Box::new(LeastResponseLoadBalancer::new(
+                self.backends(backend_definitions)?,
+                selection_timeout,
+                metrics,
+                self.retry_policy.clone(),
+                health_check_concurrency,
+                self.response_time_decay,
+            )),
+            Strategy::PowerOfTwoChoices => Box::new(PowerOfTwoChoicesLoadBalancer::new(
+                self.backends(backend_definitions)?,
+                self.rng(),
+                selection_timeout,
+                metrics,
+                health_check_concurrency,
+            )),
+            Strategy::WeightedRandom => Box::new(WeightedRandomLoadBalancer::new(
+                self.backends(backend_definitions)?,
+                self.rng(),
+                selection_timeout,
+                metrics,
+                health_check_concurrency,
+            )),
+            Strategy::ConsistentHash => Box::new(ConsistentHashLoadBalancer::new(
+                self.backends(backend_definitions)?,
+                self.virtual_nodes,
+                selection_timeout,
+                metrics,
+                health_check_concurrency,
+            )),
+            Strategy::Geo => return self.build_geo(backend_definitions),
+        })
+    }
+
+    /// Creates the load balancer of the given config: the one of its strategy and backend servers,
+    /// or a router sending the requests to its groups by the prefix of their path when it has
+    /// routes or a default response. Returns an error if a group, a route or the default response
+    /// is invalid.
+    pub fn build_config(&self, config: &ConfigFile) -> Result<Box<dyn LoadBalancer>, String> {
+        // The backend servers would never receive a request
+        if config.default_response.is_some() && !config.backends.is_empty() {
+            return Err(
+                "The backend servers and the default response both answer the requests matching \
+                 no route, only one of them can be given"
+                    .to_string(),
+            );
+        }
+        let default = match &config.default_response {
+            Some(_) => None,
+            None => Some(self.build(config.strategy, &config.backends)?),
+        };
+        let default = match default {
+            Some(default) if config.routes.is_empty() && config.groups.is_empty() => {
+                return Ok(default);
+            }
+            default => default,
+        };
+
+        let mut groups = BTreeMap::new();
+        for (name, group) in &config.groups {
+            let load_balancer = self
+                .build(group.strategy, &group.backends)
+                .map_err(|e| format!("Invalid backend group {}: {}", name, e))?;
+            groups.insert(name.clone(), load_balancer);
+        }
+        Ok(Box::new(PathRouter::new(
+            default,
+            groups,
+            &config.routes,
+            config.default_response.as_ref(),
+        )?))
+    }
+
+    /// Creates the given backend server with the given initial health status.
+    pub fn backend(
+        &self,
+        definition: &BackendDefinition,
+        health: Health,
+    ) -> Result<Box<dyn Backend>, String> {
+        let backend = SimpleBackend::new(
+            definition.address.clone(),
+            health,
+            &definition.config(&self.backend_config),
+        )?;
+        Ok(Box::new(backend))
+    }
+
+    /// Returns a random number generator seeded with the random seed, if any.
+    fn rng(&self) -> StdRng {
+        match self.random_seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        }
+    }
+
+    /// Creates the given backend servers, healthy.
+    fn backends(
+        &self,
+        backend_definitions: &[BackendDefinition],
+    ) -> Result<Vec<Box<dyn Backend>>, String> {
+        backend_definitions
+            .iter()
+            .map(|definition| self.backend(definition, Health::Healthy))
+            .collect()
+    }
+
+    /// Creates a geo load balancer, the backend servers are given as CONTINENT=ADDRESS.
+    fn build_geo(
+        &self,
+        backend_definitions: &[BackendDefinition],
+    ) -> Result<Box<dyn LoadBalancer>, String> {
+        let Some(geoip_database) = &self.geoip_database else {
+            return Err("The geo load balancer requires a GeoIP database".to_string());
+        };
+
+        let mut backends = Vec::new();
+        for definition in backend_definitions {
+            let (continent, address) = parse_geo_backend(&definition.address)?;
+            backends.push(GeoBackend::new(
+                continent,
+                address.to_string(),
+                Health::Healthy,
+                &definition.config(&self.backend_config),
+            )?);
+        }
+
+        let geo_load_balancer = GeoLoadBalancer::new(
+            backends,
+            geoip_database,
+            self.selection_timeout,
+            self.metrics.clone(),
+            self.health_check_concurrency,
+        )?;
+        Ok(Box::new(geo_load_balancer))
+    }
+}
+
+/// Splits a backend server of the geo load balancer given as CONTINENT=ADDRESS into its continent
+/// and address.
+fn parse_geo_backend(backend: &str) -> Result<(Continent, &str), String> {
+    let (continent, address) = backend.split_once('=').ok_or_else(|| {
+        format!(
+            "Backend {} of the geo load balancer must be given as CONTINENT=ADDRESS",
+            backend
+        )
+    })?;
+    Ok((continent.parse()?, address))
+}
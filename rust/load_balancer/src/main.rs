@@ -1,41 +1,130 @@
 /*
- * A simple load balancer listening on port 8080 and forwarding requests to a backend server
+ * A simple load balancer listening by default on port 8080 and forwarding requests to a backend
+ * server
  *
  * Author: Samuel Gauthier
  */
+mod admin;
+mod affinity;
 mod backend;
+mod backend_config;
+mod backend_definition;
+mod backend_group;
+mod backend_protocol;
+mod backend_pruner;
+mod backend_response;
+mod backend_snapshot;
+mod backend_tls;
+mod check;
+mod circuit_breaker;
+mod config_file;
+mod consistent_hash_load_balancer;
+mod continent;
+mod default_response;
+mod dns_discovery;
+mod dns_target;
+mod evicted_backend;
+mod ewma;
+mod forwarded_headers;
+mod forwarding;
+mod geo_backend;
 mod geo_load_balancer;
+mod header_filter;
 mod health;
+mod health_check_assertion;
+mod health_check_counter;
+mod health_check_history;
+mod health_check_kind;
+mod health_check_method;
+mod health_event;
+mod health_sweep;
+mod in_flight;
 mod internal_error;
 mod least_response_load_balancer;
 mod load_balancer;
+mod load_balancer_settings;
+mod metrics;
 mod min_heap_item;
+mod outlier_detector;
+mod path_router;
+mod power_of_two_choices_load_balancer;
+mod proxy_protocol;
+mod rate_limiter;
+mod reload;
+mod request_context;
+mod request_id;
+mod response_body;
+mod response_time_histogram;
+mod retry_budget;
+mod retry_policy;
 mod round_robin_load_balancer;
+mod selection;
 mod simple_backend;
+mod stats_page;
+mod strategy;
+mod tag_routing;
+mod tls;
+mod tls_version;
+mod upgrade_request;
+mod websocket;
+mod weighted_random_load_balancer;
 
-use backend::Backend;
-use health::Health;
-use least_response_load_balancer::LeastResponseLoadBalancer;
+use backend_config::BackendConfig;
+use backend_definition::BackendDefinition;
+use backend_protocol::BackendProtocol;
+use backend_pruner::BackendPruner;
+use backend_response::BackendResponse;
+use backend_tls::BackendTls;
+use check::check_backends;
+use config_file::ConfigFile;
+use dns_discovery::DnsDiscovery;
+use dns_target::DnsTarget;
+use forwarded_headers::ForwardedHeaders;
+use header_filter::HeaderFilter;
+use health_check_assertion::HealthCheckAssertion;
+use health_check_kind::HealthCheckKind;
+use health_check_method::HealthCheckMethod;
+use health_event::HealthWebhook;
+use in_flight::InFlightRequests;
 use load_balancer::LoadBalancer;
-use round_robin_load_balancer::RoundRobinLoadBalancer;
-use simple_backend::SimpleBackend;
+use load_balancer_settings::LoadBalancerSettings;
+use metrics::Metrics;
+use outlier_detector::OutlierDetection;
+use rate_limiter::{RateLimit, RateLimiter};
+use reload::reload_config;
+use request_context::RequestContext;
+use response_body::ResponseBody;
+use retry_budget::RetryBudget;
+use retry_policy::RetryPolicy;
+use strategy::Strategy;
+use tls_version::TlsVersion;
 
-use actix_web;
+use actix_web::cookie::Cookie;
 use actix_web::error::InternalError;
+use actix_web::http::header::{self, ContentType, HeaderName};
 use actix_web::http::StatusCode;
+use actix_web::HttpResponse;
 use clap::Parser;
-use log::{error, info};
-use simple_logger;
+use log::{error, info, warn, LevelFilter};
+use std::collections::BTreeMap;
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use std::str::FromStr;
 use std::sync::Arc;
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::sync::watch;
 use tokio::sync::RwLock as TokioRwLock;
 use tokio::task::spawn;
-use tokio::time::{interval, Duration};
+use tokio::time::{interval_at, sleep_until, timeout, Duration, Instant};
 
-/// Prints the request information to the log. Used for debugging purposes only.
-async fn print_request_info(request: actix_web::HttpRequest) {
+/// Prints the request information to the log. Used for debugging purposes only. A request whose
+/// client address is unknown, or with header values which are not valid UTF-8, is still logged.
+async fn print_request_info(request: actix_web::HttpRequest, request_id: &str) {
     info!(
-        "Received request from {}",
-        request.connection_info().peer_addr().unwrap()
+        "Received request {} from {}",
+        request_id,
+        proxy_protocol::client_ip(&request)
+            .map_or_else(|| UNKNOWN_PEER_ADDRESS.to_string(), |ip| ip.to_string())
     );
     info!(
         "{} {} {:?}",
@@ -44,95 +133,1688 @@ async fn print_request_info(request: actix_web::HttpRequest) {
         request.head().version,
     );
     for (key, value) in request.headers().iter() {
-        info!("{}: {}", key, value.to_str().unwrap());
+        info!("{}: {}", key, String::from_utf8_lossy(value.as_bytes()));
     }
 }
 
-/// Index route of the load balancer. Forwards the request to the next available backend server.
+/// Logged in place of the address of a client which is not known, for example on a Unix socket.
+const UNKNOWN_PEER_ADDRESS: &str = "unknown";
+
+/// Header carrying the address of the backend server which answered, sent with the debug headers.
+const UPSTREAM_HEADER: &str = "X-Upstream";
+
+/// Headers read from the requests of the index route, added to its responses, or passed through
+/// from the backend servers, and the page answering when no backend server is available.
+struct ResponseHeaders {
+    /// Header carrying the request ID.
+    request_id: HeaderName,
+
+    /// Header carrying the cause of the failure of a request, None to not send it.
+    error: Option<HeaderName>,
+
+    /// Whether the address of the backend server which answered is sent in the X-Upstream header.
+    upstream: bool,
+
+    /// HTML page answering the requests when no backend server is available, with its status.
+    /// None to answer with a plain 503.
+    maintenance_page: Option<(StatusCode, actix_web::web::Bytes)>,
+
+    /// Cookie pinning a client to a backend server, None to not pin the clients.
+    affinity_cookie: Option<String>,
+
+    /// Time after which a client pinned to a backend server is balanced again, None to pin it
+    /// until its backend server cannot receive its requests.
+    affinity_ttl: Option<Duration>,
+
+    /// Headers of the requests asking for a tag of the backend servers, with the name of the tag.
+    route_tags: Vec<(HeaderName, String)>,
+
+    /// Whether the Forwarded header of RFC 7239 is sent to the backend servers along with the
+    /// X-Forwarded-* ones.
+    forwarded_header: bool,
+
+    /// Filter of the headers of the backend responses passed through to the client.
+    backend_headers: HeaderFilter,
+}
+
+/// Durations used to answer the requests of the index route.
+struct Timings {
+    /// Time between the health checks, after which the backend servers may be available again.
+    health_check_interval: Duration,
+
+    /// Time given to the backend servers to start answering a request, retries included. None
+    /// for no deadline.
+    request_timeout: Option<Duration>,
+}
+
+/// Index route of the load balancer. Forwards the request to the next available backend server and
+/// streams its response back, or answers with a 429 if the request exceeds the rate limit. Answers
+/// with a 503 when the maximum number of concurrent requests is still reached after the queue
+/// timeout, when no backend server is available and with a 502 when the backend server does not
+/// answer. The request ID given by the client in the request ID header is reused, otherwise a new
+/// one is generated. It is sent to the backend server and returned to the client in the same
+/// header. When an error header is given, a failed request is answered with the cause of the
+/// failure in that header. When no backend server is available, the maintenance page is served if
+/// one is given. With the debug headers, the address of the backend server which answered is sent
+/// in X-Upstream. A request which no backend server starts answering within the request timeout is
+/// answered with a 504. When an affinity cookie is given, the request goes to the backend server
+/// identified by the cookie if it can receive it, and the cookie is set to the backend server which
+/// answered. The route tag headers of the request narrow the backend servers to the ones having the
+/// tags asked for. A request whose path matches no route is answered with the default response of
+/// the config file, if any. A request body larger than the maximum body size is answered with a 413
+/// before reaching this route. The request is forwarded with its method and body. The headers of
+/// the request and of the response are forwarded, except the hop-by-hop and the filtered out ones.
 async fn index(
     // load_balancer: actix_web::web::Data<Arc<TokioMutex<Box<dyn LoadBalancer>>>>,
-    load_balancer: actix_web::web::Data<Arc<TokioRwLock<Box<dyn LoadBalancer>>>>,
+    load_balancer: actix_web::web::Data<Arc<TokioRwLock<Arc<dyn LoadBalancer>>>>,
+    in_flight_requests: actix_web::web::Data<Arc<InFlightRequests>>,
+    response_headers: actix_web::web::Data<ResponseHeaders>,
+    rate_limiter: actix_web::web::Data<RateLimiter>,
+    timings: actix_web::web::Data<Timings>,
     request: actix_web::HttpRequest,
-) -> Result<String, actix_web::Error> {
-    print_request_info(request).await;
+    // Read up to the maximum body size and forwarded to the backend servers
+    body: actix_web::web::Bytes,
+) -> Result<HttpResponse, actix_web::Error> {
+    if let Err(retry_after) = rate_limiter.check(proxy_protocol::client_ip(&request)) {
+        info!(
+            "Rejected request from {:?}, rate limit exceeded",
+            proxy_protocol::client_ip(&request)
+        );
+        return Ok(HttpResponse::TooManyRequests()
+            .content_type(ContentType::plaintext())
+            .insert_header((header::RETRY_AFTER, retry_after_secs(&retry_after)))
+            .body("Too many requests"));
+    }
 
-    // Extract the load balancer from the state and get the next available backend server
-    let lb = load_balancer.read().await;
-    let request_response = lb.send_request().await;
+    // The request stays in flight until its whole response is sent
+    let in_flight_request = match in_flight_requests.start().await {
+        Ok(in_flight_request) => in_flight_request,
+        Err(e) => {
+            warn!("Rejected request, too many requests in flight: {}", e);
+            return Ok(HttpResponse::ServiceUnavailable()
+                .content_type(ContentType::plaintext())
+                .body("Too many requests in flight"));
+        }
+    };
+    let request_id = request
+        .headers()
+        .get(&response_headers.request_id)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+        .unwrap_or_else(request_id::generate);
+    // Read before the connection info, which borrows the request until the context is built
+    let affinity = affinity::requested_backend_id(
+        &request,
+        response_headers.affinity_cookie.as_deref(),
+        response_headers.affinity_ttl,
+    );
+    let context = RequestContext {
+        client_address: proxy_protocol::client_ip(&request).map(|ip| ip.to_string()),
+        method: reqwest::Method::from_bytes(request.method().as_str().as_bytes())
+            .unwrap_or_default(),
+        path: request.path().to_string(),
+        query: request.query_string().to_string(),
+        request_id: request_id.clone(),
+        affinity,
+        tags: tag_routing::requested_tags(&request, &response_headers.route_tags),
+        forwarded: ForwardedHeaders::new(&request, response_headers.forwarded_header),
+        headers: header_filter::request_headers(&request),
+        body,
+    };
+    print_request_info(request, &request_id).await;
+
+    // Clone the load balancer out of the state so that a reload is not blocked by the requests in
+    // flight, then get the next available backend server
+    let lb = load_balancer.read().await.clone();
+    // Dropping the request on timeout cancels the request sent to the backend server
+    let request_response = match timings.request_timeout {
+        Some(request_timeout) => timeout(request_timeout, lb.send_request(&context))
+            .await
+            .unwrap_or(Err(internal_error::InternalError::RequestTimeout {
+                timeout: request_timeout,
+            })),
+        None => lb.send_request(&context).await,
+    };
+    let request_id_header = (response_headers.request_id.clone(), request_id);
     match request_response {
-        Ok(r) => Ok(r),
+        Ok(BackendResponse {
+            address,
+            response: r,
+        }) => {
+            let status = StatusCode::from_u16(r.status().as_u16()).unwrap_or(StatusCode::OK);
+            let mut response = HttpResponse::build(status);
+            response.insert_header(request_id_header);
+            // Only set when the client is not pinned yet, was moved to another backend server or
+            // its pin expired, so that the TTL runs from the time the client was pinned
+            let backend_id = affinity::backend_id(&address);
+            if let Some(cookie_name) = &response_headers.affinity_cookie {
+                if context.affinity.as_ref() != Some(&backend_id) {
+                    let value = affinity::cookie_value(&backend_id, response_headers.affinity_ttl);
+                    let mut cookie = Cookie::build(cookie_name.clone(), value)
+                        .path("/")
+                        .http_only(true)
+                        .finish();
+                    // The clients drop the cookie once the pin expired
+                    if let Some(affinity_ttl) = response_headers.affinity_ttl {
+                        cookie.set_max_age(actix_web::cookie::time::Duration::seconds(
+                            affinity_ttl.as_secs() as i64,
+                        ));
+                    }
+                    response.cookie(cookie);
+                }
+            }
+            // The body is streamed as it is received, so a compressed body keeps its encoding. The
+            // length of the body and the request ID are set by the load balancer
+            let mut has_content_type = false;
+            for (name, value) in response_headers.backend_headers.filter(r.headers()) {
+                if name == reqwest::header::CONTENT_LENGTH
+                    || name.as_str() == response_headers.request_id.as_str()
+                {
+                    continue;
+                }
+                has_content_type |= name == reqwest::header::CONTENT_TYPE;
+                response.append_header((name.as_str(), value.as_bytes()));
+            }
+            if !has_content_type {
+                response.content_type(ContentType::plaintext());
+            }
+            // Replaces the header the backend server may have sent
+            if response_headers.upstream {
+                response.insert_header((UPSTREAM_HEADER, address));
+            }
+            // Keep the length of the body given by the backend server, otherwise it is chunked
+            if let Some(content_length) = r.content_length() {
+                response.no_chunking(content_length);
+            }
+            Ok(response.streaming(ResponseBody::new(r, in_flight_request)))
+        }
+        Err(internal_error::InternalError::NoRoute { status, body }) => {
+            in_flight_request.finish();
+            info!(
+                "No route matches {}, sending the default response",
+                context.path
+            );
+            Ok(HttpResponse::build(status)
+                .content_type(ContentType::plaintext())
+                .insert_header(request_id_header)
+                .body(body))
+        }
         Err(e) => {
-            error!("Failed to send request to backend server: {:?}", e);
-            return Err(InternalError::new(
-                "Failed to send request to backend server",
-                StatusCode::INTERNAL_SERVER_ERROR,
-            )
-            .into());
+            in_flight_request.finish();
+            error!("Failed to send request to backend server: {}", e);
+            let message = "Failed to send request to backend server";
+            let mut response = HttpResponse::build(e.status_code());
+            response
+                .content_type(ContentType::plaintext())
+                .insert_header(request_id_header);
+            if let Some(error_header) = &response_headers.error {
+                response.insert_header((error_header.clone(), e.to_string()));
+            }
+            // The backend servers are available again at the earliest after the next health check
+            if let internal_error::InternalError::NoBackendAvailable { .. } = e {
+                response.insert_header((
+                    header::RETRY_AFTER,
+                    retry_after_secs(&timings.health_check_interval),
+                ));
+                if let Some((status, page)) = &response_headers.maintenance_page {
+                    return Ok(response
+                        .status(*status)
+                        .content_type(ContentType::html())
+                        .body(page.clone()));
+                }
+            }
+            let response = response.body(message);
+            Err(InternalError::from_response(message, response).into())
+        }
+    }
+}
+
+/// Returns the value of the Retry-After header telling the client to retry after the given time.
+/// It is given in whole seconds, rounded up so that the client does not retry too early.
+fn retry_after_secs(retry_after: &Duration) -> u64 {
+    retry_after.as_secs_f64().ceil().max(1.0) as u64
+}
+
+/// Metrics route of the load balancer. Returns the metrics in the Prometheus text format, with
+/// the response time percentiles of the current backend servers.
+async fn prometheus_metrics(
+    load_balancer: actix_web::web::Data<Arc<TokioRwLock<Arc<dyn LoadBalancer>>>>,
+    metrics: actix_web::web::Data<Arc<Metrics>>,
+) -> actix_web::HttpResponse {
+    let backends = load_balancer.read().await.backends_snapshot().await;
+    actix_web::HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(metrics.render(&backends).await)
+}
+
+/// Liveness route of the load balancer. Always answers with a 200 while the process is up.
+async fn livez() -> actix_web::HttpResponse {
+    actix_web::HttpResponse::Ok()
+        .content_type(ContentType::plaintext())
+        .body("OK")
+}
+
+/// Readiness route of the load balancer. Answers with a 200 when at least one backend server is
+/// healthy, and with a 503 otherwise.
+async fn readyz(
+    load_balancer: actix_web::web::Data<Arc<TokioRwLock<Arc<dyn LoadBalancer>>>>,
+) -> actix_web::HttpResponse {
+    let healthy_count = load_balancer.read().await.healthy_count().await;
+    let mut response = if healthy_count > 0 {
+        actix_web::HttpResponse::Ok()
+    } else {
+        actix_web::HttpResponse::ServiceUnavailable()
+    };
+    response
+        .content_type(ContentType::plaintext())
+        .body(format!("{} healthy backend servers", healthy_count))
+}
+
+/// Checks the health of the backend servers, then checks it again after the given delay while
+/// none of them is healthy, at most the given number of times. Once it returns, /readyz answers
+/// with a 200 if a backend server became healthy, and with a 503 if the retries were exhausted.
+async fn wait_for_backends(load_balancer: &dyn LoadBalancer, retries: u32, delay: Duration) {
+    load_balancer.check_backends_healths().await;
+    for retry in 1..=retries {
+        if load_balancer.healthy_count().await > 0 {
+            return;
         }
+        info!(
+            "No backend server is healthy, checking them again in {}ms ({}/{})",
+            delay.as_millis(),
+            retry,
+            retries
+        );
+        tokio::time::sleep(delay).await;
+        load_balancer.check_backends_healths().await;
+    }
+    if retries > 0 && load_balancer.healthy_count().await == 0 {
+        warn!("No backend server is healthy, accepting requests anyway");
     }
 }
 
-/// Load balancer listening on port 8080 and forwarding requests to a list of backend servers
+/// Waits until the process receives SIGINT or SIGTERM.
+async fn shutdown_signal() -> std::io::Result<()> {
+    let mut sigint = signal(SignalKind::interrupt())?;
+    let mut sigterm = signal(SignalKind::terminate())?;
+    tokio::select! {
+        _ = sigint.recv() => info!("Received SIGINT"),
+        _ = sigterm.recv() => info!("Received SIGTERM"),
+    }
+    Ok(())
+}
+
+/// Parses the maximum level of the logged messages, case-insensitive.
+fn parse_log_level(value: &str) -> Result<LevelFilter, String> {
+    LevelFilter::from_str(value).map_err(|_| {
+        format!(
+            "invalid log level {}: must be off, error, warn, info, debug or trace",
+            value
+        )
+    })
+}
+
+/// Parses a duration given on the command line. Accepts humantime durations such as 10s, 500ms
+/// or 1m 30s, and plain numbers which are interpreted as seconds.
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Ok(Duration::from_secs(seconds));
+    }
+    humantime::parse_duration(value).map_err(|e| format!("invalid duration {}: {}", value, e))
+}
+
+/// Parses a socket address on which the load balancer listens, an IPv4 or IPv6 address with a
+/// port. IPv6 addresses are given in brackets, for example [::1]:8080.
+fn parse_listen_address(value: &str) -> Result<SocketAddr, String> {
+    SocketAddr::from_str(value.trim()).map_err(|e| {
+        format!(
+            "invalid listen address {}, expected IP:PORT such as 127.0.0.1:8080 or [::1]:8080: {}",
+            value, e
+        )
+    })
+}
+
+/// Parses the name of a header given on the command line.
+fn parse_header_name(value: &str) -> Result<HeaderName, String> {
+    HeaderName::from_str(value).map_err(|e| format!("invalid header name {}: {}", value, e))
+}
+
+/// Parses the value of a header sent to the backend servers given on the command line.
+fn parse_header_value(value: &str) -> Result<reqwest::header::HeaderValue, String> {
+    reqwest::header::HeaderValue::from_str(value)
+        .map_err(|e| format!("invalid header value {}: {}", value, e))
+}
+
+/// Parses a header sent to the backend servers given on the command line as NAME: VALUE, for
+/// example X-Env: production.
+fn parse_header_override(
+    value: &str,
+) -> Result<(reqwest::header::HeaderName, reqwest::header::HeaderValue), String> {
+    let Some((name, header_value)) = value.split_once(':') else {
+        return Err(format!("invalid header {}, expected NAME: VALUE", value));
+    };
+    let name = reqwest::header::HeaderName::from_str(name.trim())
+        .map_err(|e| format!("invalid header name {}: {}", name.trim(), e))?;
+    Ok((name, parse_header_value(header_value.trim())?))
+}
+
+/// Parses a route tag header given on the command line as HEADER=TAG, for example
+/// X-Route-Version=version.
+fn parse_route_tag_header(value: &str) -> Result<(HeaderName, String), String> {
+    let Some((name, tag)) = value.split_once('=') else {
+        return Err(format!(
+            "invalid route tag header {}, expected HEADER=TAG",
+            value
+        ));
+    };
+    let tag = tag.trim();
+    if tag.is_empty() {
+        return Err(format!(
+            "invalid route tag header {}: the tag is empty",
+            value
+        ));
+    }
+    Ok((parse_header_name(name.trim())?, tag.to_string()))
+}
+
+/// Status codes given on the command line as a single value separated by commas, so that an
+/// empty value gives no status code.
+type StatusCodes = Vec<u16>;
+
+/// Parses status codes separated by commas, for example 502,503, each between 100 and 599.
+fn parse_status_codes(value: &str) -> Result<StatusCodes, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|status| !status.is_empty())
+        .map(|status| match status.parse::<u16>() {
+            Ok(status) if (100..600).contains(&status) => Ok(status),
+            _ => Err(format!(
+                "invalid status code {}: must be between 100 and 599",
+                status
+            )),
+        })
+        .collect()
+}
+
+/// Parses a backend server given on the command line, as its address optionally followed by
+/// |weight, where the weight is a non-negative integer, 0 parking the backend server.
+fn parse_backend_definition(value: &str) -> Result<BackendDefinition, String> {
+    let Some((address, weight)) = value.rsplit_once('|') else {
+        return Ok(BackendDefinition::from(value.to_string()));
+    };
+    match weight.parse::<u32>() {
+        Ok(weight) => Ok(BackendDefinition {
+            weight: Some(weight),
+            ..BackendDefinition::from(address.to_string())
+        }),
+        Err(_) => Err(format!(
+            "invalid weight {} of backend server {}: must be a non-negative integer",
+            weight, address
+        )),
+    }
+}
+
+/// Parses the smoothing factor of the response time, which must be in ]0, 1].
+fn parse_smoothing(value: &str) -> Result<f32, String> {
+    match value.parse::<f32>() {
+        Ok(smoothing) if smoothing > 0.0 && smoothing <= 1.0 => Ok(smoothing),
+        _ => Err(format!(
+            "invalid smoothing factor {}: must be greater than 0 and at most 1",
+            value
+        )),
+    }
+}
+
+/// Parses the fraction of jitter of the health check interval, which must be in [0, 1[.
+fn parse_jitter(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(jitter) if (0.0..1.0).contains(&jitter) => Ok(jitter),
+        _ => Err(format!(
+            "invalid jitter {}: must be at least 0 and less than 1",
+            value
+        )),
+    }
+}
+
+/// Returns the given interval lengthened or shortened at random by at most the given fraction of
+/// it.
+fn jittered_interval(interval: Duration, jitter: f64) -> Duration {
+    interval.mul_f64(1.0 + jitter * (2.0 * rand::random::<f64>() - 1.0))
+}
+
+/// Parses the fraction of decay of the response time, which must be in [0, 1[.
+fn parse_decay(value: &str) -> Result<f32, String> {
+    match value.parse::<f32>() {
+        Ok(decay) if (0.0..1.0).contains(&decay) => Ok(decay),
+        _ => Err(format!(
+            "invalid decay {}: must be at least 0 and less than 1",
+            value
+        )),
+    }
+}
+
+/// Parses the error rate above which a backend server is ejected, which must be in [0, 1[.
+fn parse_error_rate(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(error_rate) if (0.0..1.0).contains(&error_rate) => Ok(error_rate),
+        _ => Err(format!(
+            "invalid error rate {}: must be at least 0 and less than 1",
+            value
+        )),
+    }
+}
+
+/// Parses the ratio of retries to requests of the retry budget, which must be in ]0, 1].
+fn parse_retry_budget(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(ratio) if ratio > 0.0 && ratio <= 1.0 => Ok(ratio),
+        _ => Err(format!(
+            "invalid retry budget {}: must be greater than 0 and at most 1",
+            value
+        )),
+    }
+}
+
+/// Parses a number of requests per second, which must be greater than 0.
+fn parse_rate(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(rate) if rate > 0.0 && rate.is_finite() => Ok(rate),
+        _ => Err(format!(
+            "invalid rate {}: must be a number of requests per second greater than 0",
+            value
+        )),
+    }
+}
+
+/// Returns the rate limit with the given rate and burst. The burst defaults to the number of
+/// requests allowed in one second.
+fn rate_limit(requests_per_second: Option<f64>, burst: Option<u32>) -> Option<RateLimit> {
+    requests_per_second.map(|requests_per_second| RateLimit {
+        requests_per_second,
+        burst: burst.unwrap_or(requests_per_second.ceil() as u32).max(1),
+    })
+}
+
+/// Logs the error and turns it into an invalid input error stopping the load balancer.
+fn invalid_input(message: String) -> std::io::Error {
+    error!("{}", message);
+    std::io::Error::new(std::io::ErrorKind::InvalidInput, message)
+}
+
+/// Load balancer listening by default on 127.0.0.1:8080 and forwarding requests to a list of
+/// backend servers
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Args {
-    /// Time interval in miliseconds between health checks
-    #[arg(short, long, default_value = "10")]
-    interval_health_check: u64,
+    /// Time interval between health checks, for example 10s or 500ms. A number without unit is
+    /// in seconds
+    #[arg(short, long, default_value = "10s", value_parser = parse_duration)]
+    interval_health_check: Duration,
+
+    /// Fraction of the health check interval by which each interval is lengthened or shortened at
+    /// random, at least 0 and less than 1, for example 0.2 for ±20%. Spreads the health checks of
+    /// several load balancers over time instead of sending them all at once. 0 disables the jitter
+    #[arg(long, default_value = "0", value_parser = parse_jitter)]
+    health_check_jitter: f64,
+
+    /// List of backend servers, each optionally followed by |weight, for example
+    /// http://localhost:8081/|3 (1 by default). With --strategy geo, each backend server is
+    /// prefixed by the code of the continent on which it is located, for example
+    /// EU=http://localhost:8081/
+    #[arg(value_parser = parse_backend_definition)]
+    backend_adresses: Vec<BackendDefinition>,
 
-    /// List of backend servers
-    backend_adresses: Vec<String>,
+    /// Backup backend server, in the same format as the other backend servers, only receiving
+    /// requests when none of the other ones is available. Can be repeated. Only used by the round
+    /// robin and least response load balancers
+    #[arg(long = "backup", value_parser = parse_backend_definition)]
+    backup_backends: Vec<BackendDefinition>,
 
-    /// Dynamic load balancer
+    /// Path of a TOML config file giving the strategy and the backend servers, instead of the
+    /// command line, and optionally groups of backend servers to which the requests are routed by
+    /// the prefix of their path. The config file is reloaded on SIGHUP
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "backend_adresses",
+            "backup_backends",
+            "strategy",
+            "dynamic",
+            "power_of_two_choices",
+            "consistent_hash",
+            "geo",
+        ]
+    )]
+    config: Option<PathBuf>,
+
+    /// DNS name resolved into backend servers, added to the ones given on the command line, for
+    /// example dns://service.example.com:8081 for the IP addresses of its A and AAAA records with
+    /// the port 8081 (80 by default), or dns+srv://_http._tcp.service.example.com for the host and
+    /// port of its SRV records. The name is resolved again every --resolve-interval
+    #[arg(long, conflicts_with = "config")]
+    resolve: Option<DnsTarget>,
+
+    /// Time between two resolutions of the --resolve DNS name. The new addresses become backend
+    /// servers, and the backend servers whose address disappeared are drained then removed
+    #[arg(long, default_value = "30s", value_parser = parse_duration)]
+    resolve_interval: Duration,
+
+    /// Address of the DNS server resolving the --resolve DNS name, for example 10.0.0.2:53. The
+    /// DNS servers of the system are used by default
+    #[arg(long, requires = "resolve")]
+    dns_server: Option<SocketAddr>,
+
+    /// Validates the arguments and the config file, checks the health of each backend server
+    /// once, prints which ones are reachable and exits, without listening. Exits with an error if
+    /// a backend server is unreachable
+    #[arg(long, default_value = "false")]
+    check: bool,
+
+    /// Strategy used to choose the backend server of each request
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Strategy::RoundRobin,
+        conflicts_with_all = ["dynamic", "power_of_two_choices", "consistent_hash", "geo"]
+    )]
+    strategy: Strategy,
+
+    /// Seed of the random picks of the power-of-two-choices and weighted-random strategies, to
+    /// reproduce the same sequence of backend servers. Seeded from the system by default
+    #[arg(long)]
+    random_seed: Option<u64>,
+
+    /// Deprecated, same as --strategy least-response
     #[arg(short, long, default_value = "false")]
     dynamic: bool,
+
+    /// Deprecated, same as --strategy power-of-two-choices
+    #[arg(long, default_value = "false", conflicts_with = "dynamic")]
+    power_of_two_choices: bool,
+
+    /// Deprecated, same as --strategy consistent-hash
+    #[arg(
+        long,
+        default_value = "false",
+        conflicts_with_all = ["dynamic", "power_of_two_choices"]
+    )]
+    consistent_hash: bool,
+
+    /// Deprecated, same as --strategy geo
+    #[arg(
+        long,
+        default_value = "false",
+        conflicts_with_all = ["dynamic", "power_of_two_choices", "consistent_hash"],
+        requires = "geoip_database"
+    )]
+    geo: bool,
+
+    /// Path of the GeoIP database (GeoLite2 Country or City) used by the geo load balancer
+    #[arg(long)]
+    geoip_database: Option<PathBuf>,
+
+    /// Number of virtual nodes of each backend server on the consistent hash ring
+    #[arg(long, default_value = "100", value_parser = clap::value_parser!(u32).range(1..))]
+    virtual_nodes: u32,
+
+    /// Number of consecutive failed requests after which the circuit of a backend server opens and
+    /// no more requests are sent to it. 0 disables the circuit breaker
+    #[arg(long, default_value = "5")]
+    circuit_breaker_threshold: u32,
+
+    /// Time during which the circuit of a backend server stays open before a single probe request
+    /// is let through, for example 30s
+    #[arg(long, default_value = "30s", value_parser = parse_duration)]
+    circuit_breaker_cooldown: Duration,
+
+    /// Error rate, between 0 and 1, above which a backend server is ejected for
+    /// --outlier-cooldown, even if it passes its health checks. Failed requests and 5xx responses
+    /// are errors. The outlier detection is disabled when no error rate is given
+    #[arg(long, value_parser = parse_error_rate)]
+    outlier_error_rate: Option<f64>,
+
+    /// Number of most recent requests of a backend server over which its error rate is computed
+    #[arg(long, default_value = "20", value_parser = clap::value_parser!(u64).range(1..))]
+    outlier_window: u64,
+
+    /// Time during which an ejected backend server receives no requests before a probe request is
+    /// let through, for example 30s
+    #[arg(long, default_value = "30s", value_parser = parse_duration)]
+    outlier_cooldown: Duration,
+
+    /// Path of the health check endpoint of the backend servers
+    #[arg(long, default_value = "/health")]
+    health_check_path: String,
+
+    /// Check the health of the backend servers by opening a TCP connection to them instead of
+    /// sending an HTTP request to the health check path
+    #[arg(long, default_value = "false", conflicts_with = "health_check_path")]
+    tcp_health_check: bool,
+
+    /// Range of statuses of the answers to the HTTP health checks for which a backend server is
+    /// healthy, given as MIN-MAX such as 200-299 or as a single status. Any answer is accepted by
+    /// default
+    #[arg(
+        long,
+        conflicts_with = "tcp_health_check",
+        value_parser = health_check_assertion::parse_status_range
+    )]
+    health_check_status: Option<(u16, u16)>,
+
+    /// URL to which each change of health of a backend server is posted as JSON, with its
+    /// address, its old and new health and the time of the change. The changes are only logged
+    /// by default
+    #[arg(long)]
+    health_webhook: Option<String>,
+
+    /// HTTP method of the health checks. head only asks for the status of the answer, which is
+    /// cheaper for a backend server computing its health check body, but cannot be combined with
+    /// the conditions on the body
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = HealthCheckMethod::Get,
+        conflicts_with = "tcp_health_check"
+    )]
+    health_check_method: HealthCheckMethod,
+
+    /// Text the body of the answers to the HTTP health checks must contain for a backend server to
+    /// be healthy
+    #[arg(long, conflicts_with = "tcp_health_check")]
+    health_check_body: Option<String>,
+
+    /// Value a field of the JSON body of the answers to the HTTP health checks must have for a
+    /// backend server to be healthy, given as FIELD=VALUE such as status=ok. The field is a JSON
+    /// pointer such as /checks/database, or the name of a top-level field
+    #[arg(
+        long,
+        conflicts_with = "tcp_health_check",
+        value_parser = health_check_assertion::parse_json_field
+    )]
+    health_check_json: Option<(String, String)>,
+
+    /// Field of the JSON body of the answers to the HTTP health checks giving the load of the
+    /// backend server between 0 and 1, such as load or /metrics/load. The weight of a backend
+    /// server is reduced in proportion to its load, so that a loaded backend server receives fewer
+    /// requests
+    #[arg(
+        long,
+        conflicts_with = "tcp_health_check",
+        value_parser = health_check_assertion::parse_json_pointer
+    )]
+    health_check_load_field: Option<String>,
+
+    /// Time during which a backend server becoming healthy again receives a growing share of the
+    /// requests, from a tenth of its share up to its full share, for example 30s. 0 disables the
+    /// slow start
+    #[arg(long, default_value = "0", value_parser = parse_duration)]
+    slow_start: Duration,
+
+    /// Number of consecutive successful health checks after which an unhealthy backend server
+    /// becomes healthy again
+    #[arg(long, default_value = "1", value_parser = clap::value_parser!(u32).range(1..))]
+    healthy_threshold: u32,
+
+    /// Number of consecutive failed health checks after which a healthy backend server becomes
+    /// unhealthy
+    #[arg(long, default_value = "1", value_parser = clap::value_parser!(u32).range(1..))]
+    unhealthy_threshold: u32,
+
+    /// Maximum number of backend servers health checked at the same time. The health checks of
+    /// all the backend servers run concurrently up to this limit, instead of one after the other
+    #[arg(long, default_value = "16", value_parser = clap::value_parser!(u32).range(1..))]
+    health_check_concurrency: u32,
+
+    /// Time after which a backend server unhealthy without interruption is removed from the load
+    /// balancer, so that the health checks stop probing it, for example 1h. The evicted backend
+    /// servers are listed on /admin/evicted. 0 never removes them
+    #[arg(long, default_value = "0", value_parser = parse_duration)]
+    eviction_timeout: Duration,
+
+    /// Number of times the health of the backend servers is checked again at startup while none of
+    /// them is healthy, for when the load balancer starts before its backend servers. The load
+    /// balancer only accepts requests, /readyz included, once a backend server is healthy or the
+    /// retries are exhausted. 0 does not wait
+    #[arg(long, default_value_t = 0)]
+    backend_connect_retries: u32,
+
+    /// Time between two health checks of the backend servers at startup, for example 500ms
+    #[arg(long, default_value = "1s", value_parser = parse_duration)]
+    backend_connect_retry_delay: Duration,
+
+    /// HTTP version used to send the requests to the backend servers. auto uses HTTP/2 when an
+    /// HTTPS backend server offers it and HTTP/1.1 otherwise, http2 uses HTTP/2 without
+    /// negotiating it, which requires all the backend servers to support it
+    #[arg(long, value_enum, default_value_t = BackendProtocol::Auto)]
+    backend_protocol: BackendProtocol,
+
+    /// Maximum number of redirects of the backend servers followed by the load balancer before
+    /// answering the client. 0 passes the redirects through to the client, as a proxy should
+    #[arg(long, default_value_t = 0)]
+    max_redirects: usize,
+
+    /// Weight of the last sample in the moving average of the response time of the backend
+    /// servers, greater than 0 and at most 1. 1 only keeps the last sample
+    #[arg(long, default_value = "0.3", value_parser = parse_smoothing)]
+    response_time_smoothing: f32,
+
+    /// Fraction by which the response time of each backend server moves towards 0 at each health
+    /// check, at least 0 and less than 1, for example 0.5. A backend server starved of requests
+    /// after answering slowly then gets requests again after a few health checks. Only used by
+    /// the least response load balancer. 0 disables the decay
+    #[arg(long, default_value = "0", value_parser = parse_decay)]
+    response_time_decay: f32,
+
+    /// Maximum time spent selecting a backend server before answering with a 503, for example
+    /// 500ms
+    #[arg(long, default_value = "1s", value_parser = parse_duration)]
+    selection_timeout: Duration,
+
+    /// Maximum time given to the backend servers to start answering a request, retries included,
+    /// for example 30s. The request is then cancelled and answered with a 504. 0 disables the
+    /// deadline
+    #[arg(long, default_value = "30s", value_parser = parse_duration)]
+    request_timeout: Duration,
+
+    /// Maximum time given to a backend server to accept a connection, for requests and health
+    /// checks, for example 500ms. A backend server failing to accept a connection in time is
+    /// unhealthy. 0 leaves it to the operating system
+    #[arg(long, default_value = "2s", value_parser = parse_duration)]
+    connect_timeout: Duration,
+
+    /// Maximum number of idle connections kept open to each backend server, reused by the
+    /// following requests instead of opening a new connection. 0 opens a connection per request
+    #[arg(long, default_value_t = 32)]
+    pool_max_idle_per_host: usize,
+
+    /// Time after which an idle connection to a backend server is closed, for example 30s, shorter
+    /// than the keep-alive timeout of the backend servers so that a request is not sent on a
+    /// connection they are closing. 0 keeps the idle connections open until the backend servers
+    /// close them
+    #[arg(long, default_value = "90s", value_parser = parse_duration)]
+    pool_idle_timeout: Duration,
+
+    /// Maximum time given to a backend server to answer a health check, for example 1s, shorter
+    /// than the request timeout so that a backend server hanging is found unhealthy quickly. 0
+    /// disables the limit
+    #[arg(long, default_value = "5s", value_parser = parse_duration)]
+    health_check_timeout: Duration,
+
+    /// Maximum number of times a request is retried on another backend server when its backend
+    /// server cannot be reached or answers with a --retry-on-status status. Only used by the round
+    /// robin and least response load balancers, the latter only retrying when the backend server
+    /// cannot be reached
+    #[arg(long, default_value = "2")]
+    max_retries: u32,
+
+    /// Time in milliseconds waited before the first retry, doubled before each following retry
+    #[arg(long, default_value = "100")]
+    retry_base_backoff_ms: u64,
+
+    /// Also retry the requests with a non-idempotent method, such as POST or PATCH
+    #[arg(long, default_value = "false")]
+    retry_non_idempotent: bool,
+
+    /// Status codes of the backend responses retried on another backend server, like the
+    /// connection errors, separated by commas. The response is returned to the client when no
+    /// other backend server can take the request. An empty value retries no response. Only used
+    /// by the round robin load balancer
+    #[arg(
+        long = "retry-on-status",
+        default_value = "502,503,504",
+        value_parser = parse_status_codes
+    )]
+    retry_statuses: StatusCodes,
+
+    /// Maximum number of backend servers tried to select the backend server of a request, and of
+    /// each retry, whatever the number of backend servers. The request is answered with a 503 once
+    /// they are all unavailable, unless a backup backend server is available, in which case all the
+    /// primary ones are searched before failing over. Only used by the round robin load balancer
+    #[arg(long, default_value = "10", value_parser = clap::value_parser!(u32).range(1..))]
+    max_tries: u32,
+
+    /// Maximum number of retries per request received over --retry-budget-window, greater than 0
+    /// and at most 1, for example 0.1. Once the retries reach it, the failed requests are answered
+    /// without retry, so that the retries do not multiply the load during an outage. No budget by
+    /// default
+    #[arg(long, value_parser = parse_retry_budget)]
+    retry_budget: Option<f64>,
+
+    /// Sliding window over which the requests and the retries of --retry-budget are counted, for
+    /// example 10s
+    #[arg(long, default_value = "10s", value_parser = parse_duration, requires = "retry_budget")]
+    retry_budget_window: Duration,
+
+    /// Number of retries allowed over --retry-budget-window whatever the number of requests, so
+    /// that the requests can still be retried when there are few of them
+    #[arg(long, default_value = "3", requires = "retry_budget")]
+    retry_budget_min_retries: u32,
+
+    /// Time in seconds given to the in-flight requests to complete when shutting down
+    #[arg(long, default_value = "30")]
+    shutdown_grace_period: u64,
+
+    /// IPv4 or IPv6 address on which the load balancer listens
+    #[arg(long, default_value = "127.0.0.1")]
+    listen_addr: IpAddr,
+
+    /// Port on which the load balancer listens
+    #[arg(long, default_value = "8080")]
+    listen_port: u16,
+
+    /// Socket addresses on which the load balancer listens, instead of --listen-addr and
+    /// --listen-port, for example 127.0.0.1:8080,[::1]:8080. Can be repeated
+    #[arg(
+        long,
+        value_delimiter = ',',
+        value_parser = parse_listen_address,
+        conflicts_with_all = ["listen_addr", "listen_port"]
+    )]
+    listen: Vec<SocketAddr>,
+
+    /// Maximum number of requests sent to each backend server at the same time. A backend server
+    /// at its limit is skipped by the round robin, least response and power of two choices load
+    /// balancers. Can be overridden per backend server in the config file
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    max_connections: Option<u32>,
+
+    /// Response time in milliseconds above which a backend server is unhealthy even though it
+    /// answers: the moving average of the response time of its requests, or the time taken by a
+    /// health check. Can be overridden per backend server in the config file. 0 means no limit
+    #[arg(long, default_value_t = 0)]
+    max_response_time_ms: u64,
+
+    /// Maximum number of requests per second accepted by the load balancer, from all the clients.
+    /// The requests over the limit are answered with a 429. No limit by default
+    #[arg(long, value_parser = parse_rate)]
+    rate_limit: Option<f64>,
+
+    /// Maximum number of requests accepted at once by --rate-limit after a period without
+    /// requests. Defaults to the number of requests accepted per second
+    #[arg(long, requires = "rate_limit", value_parser = clap::value_parser!(u32).range(1..))]
+    rate_limit_burst: Option<u32>,
+
+    /// Maximum number of requests per second accepted by the load balancer from each client IP
+    /// address. The requests over the limit are answered with a 429. No limit by default
+    #[arg(long, value_parser = parse_rate)]
+    client_rate_limit: Option<f64>,
+
+    /// Maximum number of requests accepted at once from a client by --client-rate-limit after a
+    /// period without requests. Defaults to the number of requests accepted per second
+    #[arg(
+        long,
+        requires = "client_rate_limit",
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    client_rate_limit_burst: Option<u32>,
+
+    /// Maximum size in bytes of the body of a request. Larger requests are answered with a 413
+    #[arg(long, default_value = "1048576")]
+    max_body_size: usize,
+
+    /// Maximum number of requests handled at the same time, their response included. The
+    /// following requests wait for one of them to end, up to --queue-timeout, and are then
+    /// answered with a 503. No limit by default
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    max_concurrent_requests: Option<u32>,
+
+    /// Maximum time a request waits for one of the --max-concurrent-requests to end, for example
+    /// 500ms. 0 answers with a 503 at once
+    #[arg(long, default_value = "0", value_parser = parse_duration)]
+    queue_timeout: Duration,
+
+    /// Name of the header carrying the ID of each request. The ID given by the client is reused,
+    /// otherwise a new one is generated. It is sent to the backend server and back to the client
+    #[arg(long, default_value = "X-Request-Id", value_parser = parse_header_name)]
+    request_id_header: HeaderName,
+
+    /// Name of the header carrying the cause of the failure of a request back to the client, for
+    /// debugging. The cause is not sent when no header is given
+    #[arg(long, value_parser = parse_header_name)]
+    error_header: Option<HeaderName>,
+
+    /// Sends the address of the backend server which answered each request back to the client in
+    /// the X-Upstream header, for debugging the routing. Not meant for production, as it exposes
+    /// the backend servers
+    #[arg(long)]
+    debug_headers: bool,
+
+    /// Also sends the address, host and protocol of the client to the backend servers in the
+    /// Forwarded header of RFC 7239, appended to the one given by the client if any
+    #[arg(long)]
+    forwarded_header: bool,
+
+    /// HTML file answering the requests when no backend server is available, instead of a plain
+    /// 503, for example a maintenance page. It is read once at startup
+    #[arg(long)]
+    maintenance_page: Option<PathBuf>,
+
+    /// Status of the responses carrying the --maintenance-page
+    #[arg(
+        long,
+        default_value = "503",
+        requires = "maintenance_page",
+        value_parser = clap::value_parser!(u16).range(100..600)
+    )]
+    maintenance_status: u16,
+
+    /// Name of a header of the client requests forwarded to the backend servers, case-insensitive.
+    /// Can be repeated, the other headers are then dropped. All the headers are forwarded by
+    /// default, except the hop-by-hop ones which never are
+    #[arg(long = "allow-request-header", value_parser = parse_header_name)]
+    allowed_request_headers: Vec<HeaderName>,
+
+    /// Name of a header of the client requests not forwarded to the backend servers,
+    /// case-insensitive, for example an internal authentication header. Can be repeated
+    #[arg(long = "deny-request-header", value_parser = parse_header_name)]
+    denied_request_headers: Vec<HeaderName>,
+
+    /// Name of a header of the backend responses passed through to the clients, case-insensitive.
+    /// Can be repeated, the other headers are then dropped. All the headers are passed through by
+    /// default, except the hop-by-hop ones which never are
+    #[arg(long = "allow-response-header", value_parser = parse_header_name)]
+    allowed_response_headers: Vec<HeaderName>,
+
+    /// Name of a header of the backend responses not passed through to the clients,
+    /// case-insensitive, for example Server to hide the version of the backend servers. Can be
+    /// repeated
+    #[arg(long = "deny-response-header", value_parser = parse_header_name)]
+    denied_response_headers: Vec<HeaderName>,
+
+    /// User-Agent sent to the backend servers instead of the one of the client, which is passed
+    /// through by default
+    #[arg(long, value_parser = parse_header_value)]
+    user_agent: Option<reqwest::header::HeaderValue>,
+
+    /// Header set on every request sent to the backend servers, given as NAME: VALUE, replacing
+    /// the header of the client with the same name. Can be repeated
+    #[arg(long = "set-request-header", value_parser = parse_header_override)]
+    request_header_overrides: Vec<(reqwest::header::HeaderName, reqwest::header::HeaderValue)>,
+
+    /// Name of the cookie pinning a client to the backend server which answered its first
+    /// request, for example LB_BACKEND. The requests of the client go to that backend server while
+    /// it can receive them. The clients are not pinned when no cookie is given
+    #[arg(long)]
+    affinity_cookie: Option<String>,
+
+    /// Time after which a client pinned by the affinity cookie is balanced again, for example
+    /// 30m, so that the clients spread again over the backend servers. 0 keeps the clients pinned
+    #[arg(long, default_value = "0", value_parser = parse_duration, requires = "affinity_cookie")]
+    affinity_ttl: Duration,
+
+    /// Header of the client requests asking for a tag of the backend servers, given as HEADER=TAG,
+    /// for example X-Route-Version=version. A request with X-Route-Version: canary then only goes
+    /// to the backend servers tagged version = "canary" in the config file, or to any backend
+    /// server if none of them is available. Can be repeated
+    #[arg(long = "route-tag-header", value_parser = parse_route_tag_header)]
+    route_tag_headers: Vec<(HeaderName, String)>,
+
+    /// Port on which the admin API listens, for example to list the backend servers on
+    /// /admin/backends. The admin API is disabled when no port is given
+    #[arg(long)]
+    admin_port: Option<u16>,
+
+    /// IPv4 or IPv6 address on which the admin API listens
+    #[arg(long, default_value = "127.0.0.1")]
+    admin_addr: IpAddr,
+
+    /// Token which the requests to the admin API must carry in an Authorization: Bearer header,
+    /// otherwise they are answered with a 401. Better given in the LB_ADMIN_TOKEN environment
+    /// variable, which the other processes cannot read. The admin API is open when no token is
+    /// given
+    #[arg(long, env = "LB_ADMIN_TOKEN", hide_env_values = true)]
+    admin_token: Option<String>,
+
+    /// Path of the PEM encoded certificate chain used to serve HTTPS. Requires --tls-key
+    #[arg(long, requires = "tls_key")]
+    tls_cert: Option<PathBuf>,
+
+    /// Path of the PEM encoded private key used to serve HTTPS. Requires --tls-cert
+    #[arg(long, requires = "tls_cert")]
+    tls_key: Option<PathBuf>,
+
+    /// Minimum TLS version accepted from the clients when serving HTTPS, 1.2 or 1.3. TLS 1.0 and
+    /// 1.1 are never accepted
+    #[arg(long, value_enum, default_value_t = TlsVersion::Tls12, requires = "tls_cert")]
+    tls_min_version: TlsVersion,
+
+    /// Read the PROXY protocol header, version 1 or 2, sent at the start of each connection by a
+    /// proxy in front of the load balancer, such as HAProxy or an AWS NLB, and use the client
+    /// address it gives in X-Forwarded-For, the rate limits and the logs. The connections without
+    /// a valid header are closed. Not supported with HTTPS
+    #[arg(long, default_value = "false", conflicts_with = "tls_cert")]
+    proxy_protocol: bool,
+
+    /// Path of the PEM encoded client certificate presented to the HTTPS backend servers requiring
+    /// mutual TLS. Requires --backend-tls-key
+    #[arg(long, requires = "backend_tls_key")]
+    backend_tls_cert: Option<PathBuf>,
+
+    /// Path of the PEM encoded PKCS #8 private key of the client certificate presented to the
+    /// HTTPS backend servers. Requires --backend-tls-cert
+    #[arg(long, requires = "backend_tls_cert")]
+    backend_tls_key: Option<PathBuf>,
+
+    /// Path of the PEM encoded certificate authorities which must have signed the certificates of
+    /// the HTTPS backend servers, instead of the ones trusted by the system
+    #[arg(long)]
+    backend_ca: Option<PathBuf>,
+
+    /// Number of workers accepting and serving the requests, which is also the number of threads
+    /// running the health checks and the requests to the backend servers. The number of CPUs
+    /// available by default
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    workers: Option<u32>,
+
+    /// Maximum level of the logged messages, one of off, error, warn, info, debug or trace. Takes
+    /// precedence over the RUST_LOG environment variable
+    #[arg(long, value_parser = parse_log_level)]
+    log_level: Option<LevelFilter>,
 }
 
-// #[actix_web::main]
-#[tokio::main(flavor = "multi_thread", worker_threads = 4)]
-async fn main() -> std::io::Result<()> {
-    simple_logger::SimpleLogger::new().env().init().unwrap();
+impl Args {
+    /// Returns the number of workers serving the requests, and of threads of the runtime: the
+    /// given one, or the number of CPUs available by default.
+    fn workers(&self) -> usize {
+        self.workers
+            .map(|workers| workers as usize)
+            .unwrap_or_else(|| {
+                std::thread::available_parallelism()
+                    .map(|parallelism| parallelism.get())
+                    .unwrap_or(1)
+            })
+    }
+
+    /// Returns the headers set on every request sent to the backend servers, including the
+    /// User-Agent if one is given.
+    fn header_overrides(&self) -> reqwest::header::HeaderMap {
+        let mut headers: reqwest::header::HeaderMap =
+            self.request_header_overrides.iter().cloned().collect();
+        if let Some(user_agent) = &self.user_agent {
+            headers.insert(reqwest::header::USER_AGENT, user_agent.clone());
+        }
+        headers
+    }
+
+    /// Returns the socket addresses on which the load balancer listens, given with --listen or
+    /// with --listen-addr and --listen-port. Returns an error if an address is given twice.
+    fn listen_addresses(&self) -> Result<Vec<SocketAddr>, String> {
+        if self.listen.is_empty() {
+            return Ok(vec![SocketAddr::new(self.listen_addr, self.listen_port)]);
+        }
+
+        let mut listen_addresses: Vec<SocketAddr> = Vec::new();
+        for address in &self.listen {
+            if listen_addresses.contains(address) {
+                return Err(format!("The listen address {} is given twice", address));
+            }
+            listen_addresses.push(*address);
+        }
+        Ok(listen_addresses)
+    }
+
+    /// Returns the strategy selected on the command line, with --strategy or one of the deprecated
+    /// flags, round robin by default.
+    fn strategy(&self) -> Strategy {
+        if self.dynamic {
+            Strategy::LeastResponse
+        } else if self.geo {
+            Strategy::Geo
+        } else if self.consistent_hash {
+            Strategy::ConsistentHash
+        } else if self.power_of_two_choices {
+            Strategy::PowerOfTwoChoices
+        } else {
+            self.strategy
+        }
+    }
+}
 
+fn main() -> std::io::Result<()> {
     let args = Args::parse();
+    // The level given on the command line replaces the one of RUST_LOG
+    let logger = simple_logger::SimpleLogger::new().env();
+    match args.log_level {
+        Some(log_level) => logger.with_level(log_level),
+        None => logger,
+    }
+    .init()
+    .unwrap();
 
-    let backends = args
-        .backend_adresses
-        .iter()
-        .map(|address| {
-            Box::new(SimpleBackend::new(address.clone(), Health::Healthy)) as Box<dyn Backend>
-        })
-        .collect();
+    // The runtime is built by hand, so that its number of threads follows --workers
+    let workers = args.workers();
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(workers)
+        .enable_all()
+        .build()?
+        .block_on(run(args, workers))
+}
+
+/// Runs the load balancer with the given arguments, serving the requests with the given number of
+/// workers, until it shuts down.
+async fn run(args: Args, workers: usize) -> std::io::Result<()> {
+    let listen_addresses = args.listen_addresses().map_err(invalid_input)?;
 
-    let load_balancer: Arc<TokioRwLock<Box<dyn LoadBalancer>>> =
-        Arc::new(TokioRwLock::new(if args.dynamic {
-            Box::new(LeastResponseLoadBalancer::new(backends))
+    // Load the TLS configuration first so that an invalid certificate fails fast
+    let tls_config = match (&args.tls_cert, &args.tls_key) {
+        (Some(cert_path), Some(key_path)) => {
+            match tls::load_server_config(cert_path, key_path, args.tls_min_version) {
+                Ok(config) => Some(config),
+                Err(e) => return Err(invalid_input(e)),
+            }
+        }
+        _ => None,
+    };
+    let backend_tls = BackendTls::load(
+        args.backend_tls_cert.as_deref(),
+        args.backend_tls_key.as_deref(),
+        args.backend_ca.as_deref(),
+    )
+    .map_err(invalid_input)?;
+    let health_webhook = args
+        .health_webhook
+        .as_deref()
+        .map(HealthWebhook::new)
+        .transpose()
+        .map_err(invalid_input)?;
+    // Read once, so that the page is still served if the file goes away
+    let maintenance_page = match &args.maintenance_page {
+        Some(path) => {
+            let page = std::fs::read(path).map_err(|e| {
+                invalid_input(format!(
+                    "Failed to read the maintenance page {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+            let status = StatusCode::from_u16(args.maintenance_status).map_err(|e| {
+                invalid_input(format!(
+                    "Invalid maintenance status {}: {}",
+                    args.maintenance_status, e
+                ))
+            })?;
+            Some((status, actix_web::web::Bytes::from(page)))
+        }
+        None => None,
+    };
+
+    let backend_config = BackendConfig {
+        health_check: if args.tcp_health_check {
+            HealthCheckKind::Tcp
         } else {
-            Box::new(RoundRobinLoadBalancer::new(backends))
-        }));
+            HealthCheckKind::Http {
+                method: args.health_check_method,
+                path: args.health_check_path.clone(),
+                assertion: HealthCheckAssertion {
+                    status: args.health_check_status,
+                    body: args.health_check_body.clone(),
+                    json_field: args.health_check_json.clone(),
+                },
+                load_field: args.health_check_load_field.clone(),
+            }
+        },
+        healthy_threshold: args.healthy_threshold,
+        unhealthy_threshold: args.unhealthy_threshold,
+        circuit_breaker_threshold: args.circuit_breaker_threshold,
+        circuit_breaker_cooldown: args.circuit_breaker_cooldown,
+        outlier_detection: args
+            .outlier_error_rate
+            .map(|max_error_rate| OutlierDetection {
+                window: args.outlier_window as usize,
+                max_error_rate,
+                cooldown: args.outlier_cooldown,
+            }),
+        response_time_smoothing: args.response_time_smoothing,
+        request_id_header: args.request_id_header.to_string(),
+        request_headers: HeaderFilter::new(
+            &args.allowed_request_headers,
+            &args.denied_request_headers,
+        ),
+        header_overrides: args.header_overrides(),
+        max_connections: args.max_connections,
+        max_response_time: Some(Duration::from_millis(args.max_response_time_ms))
+            .filter(|max| !max.is_zero()),
+        protocol: args.backend_protocol,
+        max_redirects: args.max_redirects,
+        connect_timeout: Some(args.connect_timeout).filter(|timeout| !timeout.is_zero()),
+        pool_max_idle_per_host: args.pool_max_idle_per_host,
+        pool_idle_timeout: Some(args.pool_idle_timeout).filter(|timeout| !timeout.is_zero()),
+        health_check_timeout: Some(args.health_check_timeout).filter(|timeout| !timeout.is_zero()),
+        health_webhook,
+        tls: backend_tls,
+        // The weight, the backup, the tags, the quiet health checks and the base path are only
+        // given per backend server
+        weight: 1,
+        slow_start: args.slow_start,
+        backup: false,
+        tags: BTreeMap::new(),
+        quiet_health_check: false,
+        base_path: None,
+    };
+
+    // The command line is a config without groups, routes nor default response
+    let mut config = match &args.config {
+        Some(config_path) => ConfigFile::load(config_path).map_err(invalid_input)?,
+        None => {
+            let backup_backends = args
+                .backup_backends
+                .iter()
+                .map(|definition| BackendDefinition {
+                    backup: Some(true),
+                    ..definition.clone()
+                });
+            let backend_definitions = args.backend_adresses.iter().cloned().chain(backup_backends);
+            ConfigFile {
+                strategy: args.strategy(),
+                backends: backend_definitions.collect(),
+                groups: BTreeMap::new(),
+                routes: BTreeMap::new(),
+                default_response: None,
+            }
+        }
+    };
+    let dns_discovery = match &args.resolve {
+        Some(_) if args.resolve_interval.is_zero() => {
+            return Err(invalid_input(
+                "The resolve interval must be greater than zero".to_string(),
+            ));
+        }
+        Some(target) => {
+            let dns_discovery =
+                DnsDiscovery::new(target.clone(), args.dns_server).map_err(invalid_input)?;
+            config
+                .backends
+                .extend(dns_discovery.discover().await.map_err(invalid_input)?);
+            Some(Arc::new(dns_discovery))
+        }
+        None => None,
+    };
+    info!("Starting a {:?} load balancer", config.strategy);
+
+    let metrics = Arc::new(Metrics::new());
+    let settings = LoadBalancerSettings {
+        backend_config: backend_config.clone(),
+        selection_timeout: args.selection_timeout,
+        metrics: metrics.clone(),
+        retry_policy: RetryPolicy {
+            max_retries: args.max_retries,
+            base_backoff: Duration::from_millis(args.retry_base_backoff_ms),
+            retry_non_idempotent: args.retry_non_idempotent,
+            retry_statuses: args.retry_statuses.clone(),
+            max_tries: args.max_tries as usize,
+            budget: args.retry_budget.map(|ratio| {
+                Arc::new(RetryBudget::new(
+                    ratio,
+                    args.retry_budget_window,
+                    args.retry_budget_min_retries,
+                ))
+            }),
+        },
+        virtual_nodes: args.virtual_nodes,
+        geoip_database: args.geoip_database.clone(),
+        health_check_concurrency: args.health_check_concurrency as usize,
+        response_time_decay: args.response_time_decay,
+        random_seed: args.random_seed,
+    };
+    let load_balancer: Arc<TokioRwLock<Arc<dyn LoadBalancer>>> = Arc::new(TokioRwLock::new(
+        settings
+            .build_config(&config)
+            .map(Arc::from)
+            .map_err(invalid_input)?,
+    ));
+
+    let health_check_interval = args.interval_health_check;
+    if health_check_interval.is_zero() {
+        return Err(invalid_input(
+            "The health check interval must be greater than zero".to_string(),
+        ));
+    }
+
+    if args.check {
+        return check_backends(&settings, &config)
+            .await
+            .map_err(std::io::Error::other);
+    }
+
+    // Reload the config file on SIGHUP, the running load balancer is kept if it is invalid
+    if let Some(config_path) = args.config.clone() {
+        let mut sighup = signal(SignalKind::hangup())?;
+        let reload_load_balancer = load_balancer.clone();
+        spawn(async move {
+            let mut config = config;
+            while sighup.recv().await.is_some() {
+                info!("Received SIGHUP, reloading {}", config_path.display());
+                match reload_config(&config_path, &config, &reload_load_balancer, &settings).await {
+                    Ok(reloaded_config) => config = reloaded_config,
+                    Err(e) => error!("Failed to reload the config file: {}", e),
+                }
+            }
+        });
+    }
+
+    // Resolve the DNS name again at regular intervals, so that scaling events are picked up
+    if let Some(dns_discovery) = dns_discovery {
+        let discovery_load_balancer = load_balancer.clone();
+        let discovery_backend_config = backend_config.clone();
+        let resolve_interval = args.resolve_interval;
+        spawn(async move {
+            let mut interval = interval_at(Instant::now() + resolve_interval, resolve_interval);
+            loop {
+                interval.tick().await;
+                let lb = discovery_load_balancer.read().await.clone();
+                dns_discovery
+                    .refresh(lb.as_ref(), &discovery_backend_config)
+                    .await;
+            }
+        });
+    }
 
     let shared_load_balancer = load_balancer.clone();
+    // Holds the number of completed requests at the time the shutdown started
+    let (shutdown_sender, mut shutdown_receiver) = watch::channel(None);
+    let shutdown_state = shutdown_receiver.clone();
+
+    // Check the health of the backend servers before accepting requests, so that the first
+    // requests do not go to unhealthy ones. Their response time is only measured by the requests
+    info!("Checking the health of the backend servers before accepting requests");
+    // The lock is released before waiting, so that the config file can be reloaded meanwhile
+    let startup_load_balancer = load_balancer.read().await.clone();
+    wait_for_backends(
+        startup_load_balancer.as_ref(),
+        args.backend_connect_retries,
+        args.backend_connect_retry_delay,
+    )
+    .await;
 
     // Start a background task that checks the health of the backend servers at regular
-    // intervals. The interval can be specified in the command line arguments.
-    spawn(async move {
-        let mut interval = interval(Duration::from_secs(args.interval_health_check));
-        // The loop will run indefinitely
+    // intervals. The interval and its jitter can be specified in the command line arguments.
+    let pruner = Arc::new(BackendPruner::new(
+        Some(args.eviction_timeout).filter(|timeout| !timeout.is_zero()),
+    ));
+    let health_check_pruner = pruner.clone();
+    let health_check_jitter = args.health_check_jitter;
+    let health_check_task = spawn(async move {
+        // Each health check is scheduled from the previous one, so that the time spent checking
+        // does not delay the following ones
+        let mut next_check = Instant::now();
+        // The loop runs until the load balancer shuts down
         loop {
-            interval.tick().await;
-            let lb = shared_load_balancer.read().await;
-            lb.check_backends_healths().await;
+            next_check += jittered_interval(health_check_interval, health_check_jitter);
+            tokio::select! {
+                _ = sleep_until(next_check) => {
+                    // Release the lock before the health checks, so that the load balancer can be
+                    // replaced while they run. The replaced one is still checked until they end
+                    let lb = shared_load_balancer.read().await.clone();
+                    lb.check_backends_healths().await;
+                    health_check_pruner.prune(lb.as_ref()).await;
+                }
+                Ok(()) = shutdown_receiver.changed() => {
+                    info!("Stopping the backend health checks");
+                    break;
+                }
+            }
         }
     });
 
     let state = actix_web::web::Data::new(load_balancer);
+    let metrics_state = actix_web::web::Data::new(metrics);
+    let in_flight_requests = Arc::new(InFlightRequests::new(
+        args.max_concurrent_requests
+            .map(|max_concurrent_requests| max_concurrent_requests as usize),
+        args.queue_timeout,
+    ));
+    let in_flight_state = actix_web::web::Data::new(in_flight_requests.clone());
+    let response_headers_state = actix_web::web::Data::new(ResponseHeaders {
+        request_id: args.request_id_header.clone(),
+        error: args.error_header.clone(),
+        upstream: args.debug_headers,
+        maintenance_page,
+        affinity_cookie: args.affinity_cookie.clone(),
+        affinity_ttl: Some(args.affinity_ttl).filter(|ttl| !ttl.is_zero()),
+        route_tags: args.route_tag_headers.clone(),
+        forwarded_header: args.forwarded_header,
+        backend_headers: HeaderFilter::new(
+            &args.allowed_response_headers,
+            &args.denied_response_headers,
+        ),
+    });
+    let timings_state = actix_web::web::Data::new(Timings {
+        health_check_interval,
+        // A zero request timeout disables the deadline
+        request_timeout: Some(args.request_timeout).filter(|timeout| !timeout.is_zero()),
+    });
+    let max_body_size = args.max_body_size;
+    let rate_limiter_state = actix_web::web::Data::new(RateLimiter::new(
+        rate_limit(args.rate_limit, args.rate_limit_burst),
+        rate_limit(args.client_rate_limit, args.client_rate_limit_burst),
+    ));
 
-    actix_web::HttpServer::new(move || {
+    let server_state = state.clone();
+    // The stats page of the admin API reloads itself at every health check
+    let admin_timings_state = timings_state.clone();
+    let app = move || {
         actix_web::App::new()
-            .app_data(state.clone())
+            .app_data(server_state.clone())
+            .app_data(metrics_state.clone())
+            .app_data(in_flight_state.clone())
+            .app_data(response_headers_state.clone())
+            .app_data(rate_limiter_state.clone())
+            .app_data(timings_state.clone())
+            .app_data(actix_web::web::PayloadConfig::new(max_body_size))
+            .route("/metrics", actix_web::web::get().to(prometheus_metrics))
+            .route("/livez", actix_web::web::get().to(livez))
+            .route("/readyz", actix_web::web::get().to(readyz))
+            .route(
+                "/{path:.*}",
+                actix_web::web::get()
+                    .guard(actix_web::guard::fn_guard(websocket::is_upgrade))
+                    .to(websocket::proxy),
+            )
             .default_service(actix_web::web::to(index))
-    })
-    .workers(4)
-    .bind(("127.0.0.1", 8080))?
-    .run()
-    .await
+    };
+
+    // Bind every address before serving, so that the load balancer does not start half listening
+    let server = if args.proxy_protocol {
+        // The PROXY protocol header comes before the first request of each connection and is read
+        // before the connection is given to the HTTP server
+        let server =
+            proxy_protocol::server(app, &listen_addresses, workers, args.shutdown_grace_period)?;
+        for listen_address in &listen_addresses {
+            info!("Serving HTTP with the PROXY protocol on {}", listen_address);
+        }
+        server
+    } else {
+        let mut server = actix_web::HttpServer::new(app)
+            .workers(workers)
+            .disable_signals()
+            .shutdown_timeout(args.shutdown_grace_period);
+        for listen_address in &listen_addresses {
+            let bound_server = match &tls_config {
+                Some(config) => server.bind_rustls_0_23(listen_address, config.clone()),
+                None => server.bind(listen_address),
+            };
+            server = bound_server.map_err(|e| {
+                std::io::Error::new(
+                    e.kind(),
+                    format!("Failed to listen on {}: {}", listen_address, e),
+                )
+            })?;
+        }
+        let scheme = if tls_config.is_some() {
+            "HTTPS"
+        } else {
+            "HTTP"
+        };
+        for bound_address in server.addrs() {
+            info!("Serving {} on {}", scheme, bound_address);
+        }
+        server.run()
+    };
+    info!("Serving the requests with {} workers", workers);
+
+    // The admin API is served on its own address so that it is not exposed with the load balancer
+    let admin_server_handle = match args.admin_port {
+        Some(admin_port) => {
+            let admin_address = SocketAddr::new(args.admin_addr, admin_port);
+            info!("Serving the admin API on {}", admin_address);
+            if args.admin_token.is_none() {
+                warn!("The admin API is open to anyone reaching its address, see --admin-token");
+            }
+            let admin_token = args.admin_token.clone();
+            let admin_state = state.clone();
+            let backend_config_state = actix_web::web::Data::new(backend_config.clone());
+            let pruner_state = actix_web::web::Data::new(pruner.clone());
+            let admin_server = actix_web::HttpServer::new(move || {
+                actix_web::App::new()
+                    .app_data(admin_state.clone())
+                    .app_data(backend_config_state.clone())
+                    .app_data(pruner_state.clone())
+                    .app_data(admin_timings_state.clone())
+                    .configure(|config| admin::configure(config, admin_token.clone()))
+            })
+            .workers(1)
+            .disable_signals()
+            .bind(admin_address)?
+            .run();
+            let admin_server_handle = admin_server.handle();
+            spawn(admin_server);
+            Some(admin_server_handle)
+        }
+        None => None,
+    };
+
+    // Stop accepting new connections on SIGINT or SIGTERM and give the in-flight requests the
+    // grace period to complete
+    let server_handle = server.handle();
+    let signal_in_flight_requests = in_flight_requests.clone();
+    spawn(async move {
+        if let Err(e) = shutdown_signal().await {
+            error!("Failed to listen for shutdown signals: {:?}", e);
+            return;
+        }
+        info!(
+            "Shutting down, draining {} in-flight requests for up to {}s",
+            signal_in_flight_requests.in_flight(),
+            args.shutdown_grace_period
+        );
+        let _ = shutdown_sender.send(Some(signal_in_flight_requests.completed()));
+        if let Some(admin_server_handle) = admin_server_handle {
+            admin_server_handle.stop(true).await;
+        }
+        server_handle.stop(true).await;
+    });
+
+    server.await?;
+
+    let _ = health_check_task.await;
+    let completed_before_shutdown = shutdown_state
+        .borrow()
+        .unwrap_or(in_flight_requests.completed());
+    info!(
+        "Drained {} in-flight requests, {} were still in flight after the grace period",
+        in_flight_requests.completed() - completed_before_shutdown,
+        in_flight_requests.in_flight()
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_the_humantime_durations() {
+        assert_eq!(parse_duration("10s"), Ok(Duration::from_secs(10)));
+        assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration("1m 30s"), Ok(Duration::from_secs(90)));
+    }
+
+    #[test]
+    fn parses_a_bare_number_as_seconds() {
+        assert_eq!(parse_duration("10"), Ok(Duration::from_secs(10)));
+        assert_eq!(parse_duration("0"), Ok(Duration::ZERO));
+    }
+
+    #[test]
+    fn rejects_an_invalid_duration() {
+        let error = parse_duration("ten seconds").unwrap_err();
+
+        assert!(
+            error.starts_with("invalid duration ten seconds"),
+            "{}",
+            error
+        );
+        assert!(parse_duration("-5").is_err());
+        assert!(parse_duration("").is_err());
+    }
+
+    #[test]
+    fn parses_the_log_levels_case_insensitively() {
+        for (value, expected) in [
+            ("off", LevelFilter::Off),
+            ("error", LevelFilter::Error),
+            ("warn", LevelFilter::Warn),
+            ("info", LevelFilter::Info),
+            ("debug", LevelFilter::Debug),
+            ("trace", LevelFilter::Trace),
+            ("DEBUG", LevelFilter::Debug),
+        ] {
+            assert_eq!(parse_log_level(value), Ok(expected));
+        }
+    }
+
+    #[test]
+    fn rejects_an_invalid_log_level() {
+        let error = parse_log_level("verbose").unwrap_err();
+
+        assert!(error.starts_with("invalid log level verbose"), "{}", error);
+    }
+
+    #[test]
+    fn parses_an_ipv4_or_ipv6_listen_address() {
+        assert_eq!(
+            parse_listen_address("0.0.0.0:8080"),
+            Ok(SocketAddr::from(([0, 0, 0, 0], 8080)))
+        );
+        assert_eq!(
+            parse_listen_address("[::1]:8080"),
+            Ok(SocketAddr::from((std::net::Ipv6Addr::LOCALHOST, 8080)))
+        );
+    }
+
+    #[test]
+    fn rejects_a_listen_address_without_port() {
+        let error = parse_listen_address("0.0.0.0").unwrap_err();
+
+        assert!(
+            error.starts_with("invalid listen address 0.0.0.0"),
+            "{}",
+            error
+        );
+        assert!(parse_listen_address("[::1]").is_err());
+    }
+
+    #[test]
+    fn parses_the_weight_of_a_backend_server() {
+        let parked = parse_backend_definition("http://localhost:8081/|0").unwrap();
+        assert_eq!(parked.address, "http://localhost:8081/");
+        assert_eq!(parked.weight, Some(0));
+
+        assert_eq!(
+            parse_backend_definition("http://localhost:8081/|3")
+                .unwrap()
+                .weight,
+            Some(3)
+        );
+        assert_eq!(
+            parse_backend_definition("http://localhost:8081/")
+                .unwrap()
+                .weight,
+            None
+        );
+        assert!(parse_backend_definition("http://localhost:8081/|-1").is_err());
+    }
 }
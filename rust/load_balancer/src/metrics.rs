@@ -0,0 +1,252 @@
+use crate::backend_snapshot::BackendSnapshot;
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::RwLock as TokioRwLock;
+
+/// Upper bounds in milliseconds of the buckets of the response time histogram.
+const RESPONSE_TIME_BUCKETS_MS: [f32; 10] = [
+    5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 2500.0, 5000.0,
+];
+
+/// Counters of a single backend server.
+#[derive(Debug, Default)]
+struct BackendMetrics {
+    /// Number of requests sent to the backend server, failed ones included.
+    requests: AtomicU64,
+
+    /// Number of requests that failed.
+    errors: AtomicU64,
+
+    /// Number of successful requests per response time bucket. The buckets are not cumulative,
+    /// they are summed up when rendered.
+    response_time_buckets: [AtomicU64; RESPONSE_TIME_BUCKETS_MS.len()],
+
+    /// Number of successful requests slower than the last bucket.
+    response_time_overflow: AtomicU64,
+
+    /// Sum of the response times in milliseconds of the successful requests.
+    response_time_sum_ms: AtomicU64,
+}
+
+/// Metrics of the load balancer, exposed in the Prometheus text format. The counters are updated
+/// by the load balancers when sending requests.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    /// Number of requests received by the load balancer.
+    requests_total: AtomicU64,
+
+    /// Counters of each backend server, indexed by address.
+    backends: TokioRwLock<HashMap<String, Arc<BackendMetrics>>>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a request received by the load balancer.
+    pub fn record_request(&self) {
+        self.requests_total.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records a successful request sent to the backend server with the given address.
+    pub async fn record_backend_response(&self, address: &str, response_time_ms: f32) {
+        let backend = self.backend(address).await;
+        backend.requests.fetch_add(1, Ordering::Relaxed);
+
+        match RESPONSE_TIME_BUCKETS_MS
+            .iter()
+            .position(|upper_bound| response_time_ms <= *upper_bound)
+        {
+            Some(bucket) => backend.response_time_buckets[bucket].fetch_add(1, Ordering::Relaxed),
+            None => backend
+                .response_time_overflow
+                .fetch_add(1, Ordering::Relaxed),
+        };
+        backend
+            .response_time_sum_ms
+            .fetch_add(response_time_ms.max(0.0) as u64, Ordering::Relaxed);
+    }
+
+    /// Records a failed request sent to the backend server with the given address.
+    pub async fn record_backend_error(&self, address: &str) {
+        let backend = self.backend(address).await;
+        backend.requests.fetch_add(1, Ordering::Relaxed);
+        backend.errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns the counters of the backend server with the given address, creating them on first
+    /// use.
+    async fn backend(&self, address: &str) -> Arc<BackendMetrics> {
+        let r_backends = self.backends.read().await;
+        if let Some(backend) = r_backends.get(address) {
+            return Arc::clone(backend);
+        }
+        drop(r_backends);
+
+        let mut w_backends = self.backends.write().await;
+        Arc::clone(w_backends.entry(address.to_string()).or_default())
+    }
+
+    /// Renders the metrics in the Prometheus text exposition format, with the response time
+    /// percentiles and the health check outcomes of the given backend servers.
+    pub async fn render(&self, backends: &[BackendSnapshot]) -> String {
+        let mut output = String::new();
+
+        let _ = writeln!(
+            output,
+            "# HELP lb_requests_total Number of requests received by the load balancer."
+        );
+        let _ = writeln!(output, "# TYPE lb_requests_total counter");
+        let _ = writeln!(
+            output,
+            "lb_requests_total {}",
+            self.requests_total.load(Ordering::Relaxed)
+        );
+
+        let r_backends = self.backends.read().await;
+        let mut addresses: Vec<&String> = r_backends.keys().collect();
+        addresses.sort();
+
+        let _ = writeln!(
+            output,
+            "# HELP lb_backend_requests_total Number of requests sent to a backend server."
+        );
+        let _ = writeln!(output, "# TYPE lb_backend_requests_total counter");
+        for address in &addresses {
+            let _ = writeln!(
+                output,
+                "lb_backend_requests_total{{backend=\"{}\"}} {}",
+                escape_label_value(address),
+                r_backends[*address].requests.load(Ordering::Relaxed)
+            );
+        }
+
+        let _ = writeln!(
+            output,
+            "# HELP lb_backend_errors_total Number of failed requests sent to a backend server."
+        );
+        let _ = writeln!(output, "# TYPE lb_backend_errors_total counter");
+        for address in &addresses {
+            let _ = writeln!(
+                output,
+                "lb_backend_errors_total{{backend=\"{}\"}} {}",
+                escape_label_value(address),
+                r_backends[*address].errors.load(Ordering::Relaxed)
+            );
+        }
+
+        let _ = writeln!(
+            output,
+            "# HELP lb_backend_response_time_ms Response time in milliseconds of a backend server."
+        );
+        let _ = writeln!(output, "# TYPE lb_backend_response_time_ms histogram");
+        for address in &addresses {
+            let backend = &r_backends[*address];
+            let label = escape_label_value(address);
+
+            let mut cumulative_count = 0;
+            for (upper_bound, bucket) in RESPONSE_TIME_BUCKETS_MS
+                .iter()
+                .zip(backend.response_time_buckets.iter())
+            {
+                cumulative_count += bucket.load(Ordering::Relaxed);
+                let _ = writeln!(
+                    output,
+                    "lb_backend_response_time_ms_bucket{{backend=\"{}\",le=\"{}\"}} {}",
+                    label, upper_bound, cumulative_count
+                );
+            }
+            cumulative_count += backend.response_time_overflow.load(Ordering::Relaxed);
+            let _ = writeln!(
+                output,
+                "lb_backend_response_time_ms_bucket{{backend=\"{}\",le=\"+Inf\"}} {}",
+                label, cumulative_count
+            );
+            let _ = writeln!(
+                output,
+                "lb_backend_response_time_ms_sum{{backend=\"{}\"}} {}",
+                label,
+                backend.response_time_sum_ms.load(Ordering::Relaxed)
+            );
+            let _ = writeln!(
+                output,
+                "lb_backend_response_time_ms_count{{backend=\"{}\"}} {}",
+                label, cumulative_count
+            );
+        }
+
+        let _ = writeln!(
+            output,
+            "# HELP lb_backend_response_time_percentile_ms Percentile of the response time in \
+             milliseconds of a backend server since the load balancer started."
+        );
+        let _ = writeln!(
+            output,
+            "# TYPE lb_backend_response_time_percentile_ms gauge"
+        );
+        for backend in backends {
+            let label = escape_label_value(&backend.address);
+            let percentiles = backend.response_time_percentiles;
+            for (quantile, value) in [
+                ("0.5", percentiles.p50_ms),
+                ("0.95", percentiles.p95_ms),
+                ("0.99", percentiles.p99_ms),
+            ] {
+                let _ = writeln!(
+                    output,
+                    "lb_backend_response_time_percentile_ms{{backend=\"{}\",quantile=\"{}\"}} {}",
+                    label, quantile, value
+                );
+            }
+        }
+
+        let _ = writeln!(
+            output,
+            "# HELP lb_backend_health_checks_total Number of health checks of a backend server \
+             since the load balancer started, by outcome."
+        );
+        let _ = writeln!(output, "# TYPE lb_backend_health_checks_total counter");
+        for backend in backends {
+            let label = escape_label_value(&backend.address);
+            for (outcome, count) in [
+                ("passed", backend.health_checks_passed),
+                ("failed", backend.health_checks_failed),
+            ] {
+                let _ = writeln!(
+                    output,
+                    "lb_backend_health_checks_total{{backend=\"{}\",outcome=\"{}\"}} {}",
+                    label, outcome, count
+                );
+            }
+        }
+
+        let _ = writeln!(
+            output,
+            "# HELP lb_backend_health_check_failure_rate Fraction of the last 64 health checks of \
+             a backend server which failed."
+        );
+        let _ = writeln!(output, "# TYPE lb_backend_health_check_failure_rate gauge");
+        for backend in backends {
+            let _ = writeln!(
+                output,
+                "lb_backend_health_check_failure_rate{{backend=\"{}\"}} {}",
+                escape_label_value(&backend.address),
+                backend.health_check_failure_rate
+            );
+        }
+
+        output
+    }
+}
+
+/// Escapes a label value as required by the Prometheus text format.
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
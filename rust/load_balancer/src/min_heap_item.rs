@@ -1,5 +1,7 @@
 use std::cmp::Ordering;
 
+/// Element of a min heap, popped from a `BinaryHeap` in increasing order of priority. A NaN
+/// priority is the worst possible one, so that an item with a NaN priority is popped last.
 #[derive(Debug, Clone)]
 pub struct MinHeapItem<T> {
     pub priority: f32,
@@ -7,11 +9,15 @@ pub struct MinHeapItem<T> {
 }
 
 impl<T> Ord for MinHeapItem<T> {
+    /// Orders the items in decreasing order of priority, NaN priorities first, which is a total
+    /// order as required by `BinaryHeap`.
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        other
-            .priority
-            .partial_cmp(&self.priority)
-            .unwrap_or(Ordering::Equal)
+        match (self.priority.is_nan(), other.priority.is_nan()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            (false, false) => other.priority.total_cmp(&self.priority),
+        }
     }
 }
 
@@ -25,6 +31,41 @@ impl<T> Eq for MinHeapItem<T> {}
 
 impl<T> PartialEq for MinHeapItem<T> {
     fn eq(&self, other: &Self) -> bool {
-        self.priority == other.priority
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BinaryHeap;
+
+    #[test]
+    fn pops_a_finite_priority_before_a_nan_one() {
+        let mut heap = BinaryHeap::new();
+        heap.push(MinHeapItem {
+            priority: f32::NAN,
+            element: "nan",
+        });
+        heap.push(MinHeapItem {
+            priority: 120.0,
+            element: "finite",
+        });
+
+        assert_eq!(heap.pop().unwrap().element, "finite");
+        assert_eq!(heap.pop().unwrap().element, "nan");
+    }
+
+    #[test]
+    fn pops_the_items_in_increasing_order_of_priority() {
+        let mut heap: BinaryHeap<MinHeapItem<u32>> =
+            [(30.0, 3), (f32::NAN, 4), (10.0, 1), (20.0, 2)]
+                .into_iter()
+                .map(|(priority, element)| MinHeapItem { priority, element })
+                .collect();
+
+        let popped: Vec<u32> = std::iter::from_fn(|| heap.pop().map(|item| item.element)).collect();
+
+        assert_eq!(popped, [1, 2, 3, 4]);
     }
 }
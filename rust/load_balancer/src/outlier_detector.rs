@@ -0,0 +1,99 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Settings of the outlier detection of the backend servers.
+#[derive(Clone, Copy, Debug)]
+pub struct OutlierDetection {
+    /// Number of most recent requests over which the error rate is computed, greater than 0.
+    pub window: usize,
+
+    /// Error rate above which the backend server is ejected, between 0 and 1.
+    pub max_error_rate: f64,
+
+    /// Time during which an ejected backend server receives no requests before a probe request is
+    /// let through.
+    pub cooldown: Duration,
+}
+
+/// Detects a backend server answering too many requests with an error, even though it still
+/// passes its health checks. Keeps the outcomes of the most recent requests and ejects the
+/// backend server once their error rate exceeds the maximum.
+#[derive(Debug)]
+pub struct OutlierDetector {
+    /// Settings of the outlier detection.
+    detection: OutlierDetection,
+
+    /// Outcomes of the most recent requests, true for an error. Holds at most a window of
+    /// requests.
+    outcomes: VecDeque<bool>,
+
+    /// Number of errors among the outcomes.
+    errors: usize,
+
+    /// Time at which the backend server was ejected, `None` if it is not ejected.
+    ejected_at: Option<Instant>,
+}
+
+impl OutlierDetector {
+    /// Creates a new outlier detector without any request recorded.
+    pub fn new(detection: OutlierDetection) -> Self {
+        Self {
+            detection,
+            outcomes: VecDeque::with_capacity(detection.window),
+            errors: 0,
+            ejected_at: None,
+        }
+    }
+
+    /// Returns true if the backend server is ejected and its cooldown has not elapsed yet. Once
+    /// the cooldown has elapsed, requests are let through as probes until one of them completes.
+    pub fn is_ejected(&self) -> bool {
+        self.ejected_at
+            .is_some_and(|ejected_at| ejected_at.elapsed() < self.detection.cooldown)
+    }
+
+    /// Returns the error rate over the most recent requests, 0 if there are none.
+    pub fn error_rate(&self) -> f64 {
+        if self.outcomes.is_empty() {
+            return 0.0;
+        }
+        self.errors as f64 / self.outcomes.len() as f64
+    }
+
+    /// Records the outcome of a request. Ejects the backend server when the window is full and its
+    /// error rate exceeds the maximum. After the cooldown, a successful probe re-admits the
+    /// backend server with an empty window and a failed one ejects it again. Returns true if the
+    /// backend server was ejected by this request.
+    pub fn record(&mut self, is_error: bool) -> bool {
+        if let Some(ejected_at) = self.ejected_at {
+            // Requests sent before the ejection are ignored
+            if ejected_at.elapsed() < self.detection.cooldown {
+                return false;
+            }
+            if is_error {
+                self.ejected_at = Some(Instant::now());
+                return true;
+            }
+            self.ejected_at = None;
+            self.outcomes.clear();
+            self.errors = 0;
+            return false;
+        }
+
+        self.outcomes.push_back(is_error);
+        if is_error {
+            self.errors += 1;
+        }
+        if self.outcomes.len() > self.detection.window && self.outcomes.pop_front() == Some(true) {
+            self.errors -= 1;
+        }
+
+        if self.outcomes.len() == self.detection.window
+            && self.error_rate() > self.detection.max_error_rate
+        {
+            self.ejected_at = Some(Instant::now());
+            return true;
+        }
+        false
+    }
+}
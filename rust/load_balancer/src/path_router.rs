@@ -0,0 +1,246 @@
+use crate::backend::Backend;
+use crate::backend_response::BackendResponse;
+use crate::backend_snapshot::BackendSnapshot;
+use crate::default_response::DefaultResponse;
+use crate::internal_error::InternalError;
+use crate::load_balancer::LoadBalancer;
+use crate::request_context::RequestContext;
+
+use actix_web::http::StatusCode;
+use async_trait::async_trait;
+use futures_util::future::join_all;
+use std::collections::BTreeMap;
+
+/// Routes the requests to a group of backend servers by the prefix of their path, each group
+/// having its own load balancer. A request goes to the group of the longest prefix matching its
+/// path, and to the default load balancer if none matches, or is answered with the default
+/// response.
+pub struct PathRouter {
+    /// Load balancer of the requests whose path matches no route, None when they are answered
+    /// with the default response.
+    default: Option<Box<dyn LoadBalancer>>,
+
+    /// Status and body of the response to the requests whose path matches no route, when there is
+    /// no default load balancer.
+    default_response: Option<(StatusCode, String)>,
+
+    /// Load balancers of the groups, by name.
+    groups: BTreeMap<String, Box<dyn LoadBalancer>>,
+
+    /// Path prefixes with the name of the group to which their requests are routed, the longest
+    /// prefixes first.
+    routes: Vec<(String, String)>,
+}
+
+impl PathRouter {
+    /// Creates a router sending the requests to the groups of the given routes, from a path prefix
+    /// to the name of a group, or to the default load balancer, or answering them with the default
+    /// response without default load balancer. Returns an error if a prefix does not start with a
+    /// /, if a route refers to an unknown group, if a group has no route or if there is neither a
+    /// default load balancer nor a valid default response.
+    pub fn new(
+        default: Option<Box<dyn LoadBalancer>>,
+        groups: BTreeMap<String, Box<dyn LoadBalancer>>,
+        routes: &BTreeMap<String, String>,
+        default_response: Option<&DefaultResponse>,
+    ) -> Result<Self, String> {
+        for (prefix, group) in routes {
+            if !prefix.starts_with('/') {
+                return Err(format!("The route prefix {} must start with a /", prefix));
+            }
+            if !groups.contains_key(group) {
+                return Err(format!(
+                    "The route {} refers to the unknown backend group {}",
+                    prefix, group
+                ));
+            }
+        }
+        if let Some(group) = groups
+            .keys()
+            .find(|group| !routes.values().any(|routed| routed == *group))
+        {
+            return Err(format!("No route refers to the backend group {}", group));
+        }
+        let default_response = default_response
+            .map(|response| {
+                StatusCode::from_u16(response.status)
+                    .map(|status| (status, response.body.clone()))
+                    .map_err(|_| format!("Invalid default response status {}", response.status))
+            })
+            .transpose()?;
+        if default.is_none() && default_response.is_none() {
+            return Err("At least one backend server is required".to_string());
+        }
+
+        let mut routes: Vec<(String, String)> = routes.clone().into_iter().collect();
+        routes.sort_by_key(|(prefix, _)| std::cmp::Reverse(prefix.len()));
+        Ok(Self {
+            default,
+            default_response,
+            groups,
+            routes,
+        })
+    }
+
+    /// Returns the load balancer of the group of the longest prefix matching the given path, or
+    /// the default load balancer if none matches. Returns None if none matches and the request is
+    /// answered with the default response.
+    fn route(&self, path: &str) -> Option<&dyn LoadBalancer> {
+        self.routes
+            .iter()
+            .find(|(prefix, _)| matches_prefix(path, prefix))
+            .and_then(|(_, group)| self.groups.get(group))
+            .or(self.default.as_ref())
+            .map(|load_balancer| load_balancer.as_ref())
+    }
+
+    /// Returns the error answering the request with the default response, a 404 without body if
+    /// there is none.
+    fn no_route(&self) -> InternalError {
+        let (status, body) = self
+            .default_response
+            .clone()
+            .unwrap_or((StatusCode::NOT_FOUND, String::new()));
+        InternalError::NoRoute { status, body }
+    }
+
+    /// Returns the default load balancer, if any, followed by the ones of the groups.
+    fn load_balancers(&self) -> impl Iterator<Item = &dyn LoadBalancer> {
+        self.default
+            .iter()
+            .chain(self.groups.values())
+            .map(|load_balancer| load_balancer.as_ref())
+    }
+}
+
+/// Returns whether the path starts with the prefix on a segment boundary, so that the prefix /api
+/// matches /api and /api/users but not /apiary.
+fn matches_prefix(path: &str, prefix: &str) -> bool {
+    match path.strip_prefix(prefix) {
+        Some(rest) => prefix.ends_with('/') || rest.is_empty() || rest.starts_with('/'),
+        None => false,
+    }
+}
+
+#[async_trait]
+impl LoadBalancer for PathRouter {
+    /// Returns the next available backend server of the group to which the request is routed.
+    async fn next_available_backend(
+        &self,
+        context: &RequestContext,
+    ) -> Result<Box<dyn Backend>, String> {
+        match self.route(&context.path) {
+            Some(load_balancer) => load_balancer.next_available_backend(context).await,
+            None => Err(format!("No route matches the path {}", context.path)),
+        }
+    }
+
+    /// Sends the request to a backend server of the group to which it is routed. Returns the
+    /// default response as an error if it is not routed to any.
+    async fn send_request(
+        &self,
+        context: &RequestContext,
+    ) -> Result<BackendResponse, InternalError> {
+        match self.route(&context.path) {
+            Some(load_balancer) => load_balancer.send_request(context).await,
+            None => Err(self.no_route()),
+        }
+    }
+
+    /// Checks the health of the backend servers of all the groups at the same time.
+    async fn check_backends_healths(&self) {
+        join_all(
+            self.load_balancers()
+                .map(|load_balancer| load_balancer.check_backends_healths()),
+        )
+        .await;
+    }
+
+    /// Returns the current state of the backend servers of all the groups.
+    async fn backends_snapshot(&self) -> Vec<BackendSnapshot> {
+        let mut snapshots = Vec::new();
+        for load_balancer in self.load_balancers() {
+            snapshots.extend(load_balancer.backends_snapshot().await);
+        }
+        snapshots
+    }
+
+    /// Returns the number of healthy backend servers of all the groups.
+    async fn healthy_count(&self) -> usize {
+        let mut healthy_count = 0;
+        for load_balancer in self.load_balancers() {
+            healthy_count += load_balancer.healthy_count().await;
+        }
+        healthy_count
+    }
+
+    /// Adds the backend server to the default load balancer, receiving the requests matching no
+    /// route. Returns an error if they are answered with the default response instead.
+    async fn add_backend(&self, backend: Box<dyn Backend>) -> Result<(), String> {
+        match &self.default {
+            Some(default) => default.add_backend(backend).await,
+            None => Err(format!(
+                "Cannot add backend server {}, the requests matching no route are answered with \
+                 the default response",
+                backend.address()
+            )),
+        }
+    }
+
+    /// Removes the backend server with the given address from all the groups. Returns an error if
+    /// no group has a backend server with this address.
+    async fn remove_backend(&self, address: &str) -> Result<(), String> {
+        let mut removed = false;
+        for load_balancer in self.load_balancers() {
+            removed |= load_balancer.remove_backend(address).await.is_ok();
+        }
+        if removed {
+            Ok(())
+        } else {
+            Err(format!("No backend server with address {}", address))
+        }
+    }
+
+    /// Starts or stops draining the backend server with the given address in all the groups.
+    /// Returns an error if no group has a backend server with this address.
+    async fn set_draining(&self, address: &str, draining: bool) -> Result<(), String> {
+        let mut found = false;
+        for load_balancer in self.load_balancers() {
+            found |= load_balancer.set_draining(address, draining).await.is_ok();
+        }
+        if found {
+            Ok(())
+        } else {
+            Err(format!("No backend server with address {}", address))
+        }
+    }
+
+    /// Sets the weight of the backend server with the given address in all the groups. Returns an
+    /// error if no group has a backend server with this address.
+    async fn set_weight(&self, address: &str, weight: u32) -> Result<(), String> {
+        let mut found = false;
+        for load_balancer in self.load_balancers() {
+            found |= load_balancer.set_weight(address, weight).await.is_ok();
+        }
+        if found {
+            Ok(())
+        } else {
+            Err(format!("No backend server with address {}", address))
+        }
+    }
+
+    /// Returns the strategy of the default load balancer followed by the one of each group, such
+    /// as round-robin, api: least-response.
+    fn strategy(&self) -> String {
+        self.default
+            .iter()
+            .map(|default| default.strategy())
+            .chain(
+                self.groups
+                    .iter()
+                    .map(|(name, group)| format!("{}: {}", name, group.strategy())),
+            )
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
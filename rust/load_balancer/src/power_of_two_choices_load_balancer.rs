@@ -0,0 +1,311 @@
+use crate::affinity;
+use crate::backend::Backend;
+use crate::backend_response::BackendResponse;
+use crate::backend_snapshot::BackendSnapshot;
+use crate::forwarding;
+use crate::health::Health;
+use crate::health_sweep;
+use crate::internal_error::InternalError;
+use crate::load_balancer::LoadBalancer;
+use crate::metrics::Metrics;
+use crate::request_context::RequestContext;
+use crate::strategy::Strategy;
+use crate::tag_routing;
+
+use async_trait::async_trait;
+use log::{debug, info};
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::sync::{Arc, Mutex};
+use tokio::sync::RwLock as TokioRwLock;
+use tokio::time::Duration;
+
+/// Load balancer picking two random healthy backend servers for each request and sending it to
+/// the one with the lowest response time ("power of two choices").
+#[derive(Debug)]
+pub struct PowerOfTwoChoicesLoadBalancer {
+    /// List of backend servers
+    backends: TokioRwLock<Vec<Box<dyn Backend>>>,
+
+    /// Random number generator used to pick the two candidates.
+    rng: Mutex<StdRng>,
+
+    /// Maximum time spent selecting the backend server to which a request is sent.
+    selection_timeout: Duration,
+
+    /// Metrics updated on each request.
+    metrics: Arc<Metrics>,
+
+    /// Maximum number of backend servers health checked at the same time.
+    health_check_concurrency: usize,
+}
+
+impl PowerOfTwoChoicesLoadBalancer {
+    /// Creates a new load balancer with the given list of backend servers to route the requests
+    /// to, picked with the given random number generator. Selecting a backend server fails if it
+    /// takes longer than the selection timeout.
+    pub fn new(
+        backends: Vec<Box<dyn Backend>>,
+        rng: StdRng,
+        selection_timeout: Duration,
+        metrics: Arc<Metrics>,
+        health_check_concurrency: usize,
+    ) -> Self {
+        Self {
+            backends: TokioRwLock::new(backends),
+            rng: Mutex::new(rng),
+            selection_timeout,
+            metrics,
+            health_check_concurrency,
+        }
+    }
+}
+
+#[async_trait]
+impl LoadBalancer for PowerOfTwoChoicesLoadBalancer {
+    /// Returns the backend server to which the client is pinned by its affinity if it can receive
+    /// the request, otherwise the fastest of two randomly picked healthy backend servers, excluding
+    /// the draining and parked ones and those at their maximum number of connections. Only the
+    /// backend servers having the tags asked for by the request are picked, unless none of them is
+    /// available. If only one backend server is available it is returned, if none are an error is
+    /// returned.
+    async fn next_available_backend(
+        &self,
+        context: &RequestContext,
+    ) -> Result<Box<dyn Backend>, String> {
+        let backends = self.backends.read().await;
+        if let Some(backend) = backends
+            .iter()
+            .find(|backend| affinity::is_pinned(context, backend.as_ref()))
+        {
+            return Ok(backend.clone());
+        }
+
+        let required_tags =
+            tag_routing::required_tags(context, backends.iter().map(|backend| backend.as_ref()));
+        let mut healthy_backends = Vec::new();
+        for backend in backends.iter() {
+            if backend.health() == Health::Healthy
+                && !backend.is_draining()
+                && !backend.is_saturated()
+                && backend.weight() > 0
+                && tag_routing::matches(required_tags, backend.as_ref())
+            {
+                healthy_backends.push(backend);
+            }
+        }
+
+        let (first, second) = match healthy_backends.len() {
+            0 => return Err("No backend server available".to_string()),
+            1 => return Ok(healthy_backends[0].clone()),
+            len => {
+                let mut rng = self.rng.lock().unwrap();
+                let first = rng.gen_range(0..len);
+                // Pick the second candidate among the other backends so both are distinct
+                let second = (first + rng.gen_range(1..len)) % len;
+                (healthy_backends[first], healthy_backends[second])
+            }
+        };
+
+        let first_response_time = first.response_time_ms().await;
+        let second_response_time = second.response_time_ms().await;
+        debug!(
+            "choosing between {} ({}ms) and {} ({}ms)",
+            first.address(),
+            first_response_time,
+            second.address(),
+            second_response_time
+        );
+
+        if second_response_time < first_response_time {
+            Ok(second.clone())
+        } else {
+            Ok(first.clone())
+        }
+    }
+
+    /// Sends a request to the selected backend server. Returns an error if no backend server is
+    /// reachable.
+    async fn send_request(
+        &self,
+        context: &RequestContext,
+    ) -> Result<BackendResponse, InternalError> {
+        forwarding::send_to_selected_backend(
+            self.next_available_backend(context),
+            self.selection_timeout,
+            &self.metrics,
+            context,
+        )
+        .await
+    }
+
+    /// Checks and update the health status of all backend servers.
+    async fn check_backends_healths(&self) {
+        // This is used for profiling only
+        let start_time = std::time::Instant::now();
+
+        // Check a copy of the list so that backend servers can be added or removed meanwhile
+        let backends = self.backends.read().await.clone();
+        let health_checks = backends
+            .iter()
+            .map(|backend| backend.check_health())
+            .collect();
+        health_sweep::check_healths(health_checks, self.health_check_concurrency).await;
+
+        // For profiling only, measures how much time it took to check all backends health
+        let end_time = std::time::Instant::now();
+        let elapsed_time = end_time.duration_since(start_time).as_millis();
+        info!("checking all backends health took {}ms", elapsed_time);
+    }
+
+    /// Returns the current state of all backend servers.
+    async fn backends_snapshot(&self) -> Vec<BackendSnapshot> {
+        let backends = self.backends.read().await;
+        let mut snapshots = Vec::with_capacity(backends.len());
+        for backend in backends.iter() {
+            snapshots.push(BackendSnapshot::new(backend.as_ref()).await);
+        }
+        snapshots
+    }
+
+    /// Returns the number of healthy backend servers.
+    async fn healthy_count(&self) -> usize {
+        let backends = self.backends.read().await;
+        backends
+            .iter()
+            .filter(|backend| backend.health() == Health::Healthy)
+            .count()
+    }
+
+    /// Adds a backend server to which the requests can be sent. Returns an error if a backend
+    /// server with the same address already exists.
+    async fn add_backend(&self, backend: Box<dyn Backend>) -> Result<(), String> {
+        let mut backends = self.backends.write().await;
+        if backends.iter().any(|b| b.address() == backend.address()) {
+            return Err(format!(
+                "Backend server {} already exists",
+                backend.address()
+            ));
+        }
+
+        info!("Adding backend server {}", backend.address());
+        backends.push(backend);
+        Ok(())
+    }
+
+    /// Removes the backend server with the given address. Returns an error if there is no backend
+    /// server with this address.
+    async fn remove_backend(&self, address: &str) -> Result<(), String> {
+        let mut backends = self.backends.write().await;
+        let Some(index) = backends.iter().position(|b| b.address() == address) else {
+            return Err(format!("No backend server with address {}", address));
+        };
+
+        info!("Removing backend server {}", address);
+        backends.remove(index);
+        Ok(())
+    }
+
+    /// Starts or stops draining the backend server with the given address. Returns an error if
+    /// there is no backend server with this address.
+    async fn set_draining(&self, address: &str, draining: bool) -> Result<(), String> {
+        let backends = self.backends.read().await;
+        let Some(backend) = backends.iter().find(|b| b.address() == address) else {
+            return Err(format!("No backend server with address {}", address));
+        };
+
+        info!(
+            "Setting draining of backend server {} to {}",
+            address, draining
+        );
+        backend.set_draining(draining);
+        Ok(())
+    }
+
+    /// Sets the weight of the backend server with the given address. Returns an error if there is
+    /// no backend server with this address.
+    async fn set_weight(&self, address: &str, weight: u32) -> Result<(), String> {
+        let backends = self.backends.read().await;
+        let Some(backend) = backends.iter().find(|b| b.address() == address) else {
+            return Err(format!("No backend server with address {}", address));
+        };
+
+        info!("Setting weight of backend server {} to {}", address, weight);
+        backend.set_weight(weight);
+        Ok(())
+    }
+
+    fn strategy(&self) -> String {
+        Strategy::PowerOfTwoChoices.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend_config::BackendConfig;
+    use crate::simple_backend::SimpleBackend;
+    use rand::SeedableRng;
+
+    /// Returns a load balancer seeded with the given seed, with a healthy backend server on port
+    /// 8081, 8082 and so on for each of the given response times.
+    async fn load_balancer(seed: u64, response_times_ms: &[f32]) -> PowerOfTwoChoicesLoadBalancer {
+        let mut backends: Vec<Box<dyn Backend>> = Vec::new();
+        for (i, &response_time_ms) in response_times_ms.iter().enumerate() {
+            let address = format!("http://localhost:{}/", 8081 + i);
+            let backend =
+                SimpleBackend::new(address, Health::Healthy, &BackendConfig::default()).unwrap();
+            backend.record_response_time(response_time_ms).await;
+            backends.push(Box::new(backend));
+        }
+        PowerOfTwoChoicesLoadBalancer::new(
+            backends,
+            StdRng::seed_from_u64(seed),
+            Duration::from_secs(1),
+            Arc::new(Metrics::new()),
+            1,
+        )
+    }
+
+    #[tokio::test]
+    async fn chooses_the_faster_of_the_two_sampled_backends() {
+        let response_times_ms = [40.0, 10.0, 30.0, 20.0];
+        let load_balancer = load_balancer(42, &response_times_ms).await;
+        // Draws the same two candidates as the load balancer
+        let mut rng = StdRng::seed_from_u64(42);
+
+        for _ in 0..100 {
+            let first = rng.gen_range(0..4);
+            let second = (first + rng.gen_range(1..4)) % 4;
+            let faster = if response_times_ms[second] < response_times_ms[first] {
+                second
+            } else {
+                first
+            };
+
+            let backend = load_balancer
+                .next_available_backend(&RequestContext::default())
+                .await
+                .unwrap();
+
+            assert_eq!(
+                backend.address(),
+                format!("http://localhost:{}/", 8081 + faster)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn never_chooses_the_slowest_backend() {
+        let load_balancer = load_balancer(7, &[10.0, 20.0, 30.0]).await;
+
+        for _ in 0..100 {
+            let backend = load_balancer
+                .next_available_backend(&RequestContext::default())
+                .await
+                .unwrap();
+
+            assert_ne!(backend.address(), "http://localhost:8083/");
+        }
+    }
+}
@@ -0,0 +1,345 @@
+use actix_http::body::MessageBody;
+use actix_http::error::DispatchError;
+use actix_http::{HttpService, Protocol, Request, Response};
+use actix_service::{
+    fn_service, map_config, IntoServiceFactory, ServiceFactory, ServiceFactoryExt,
+};
+use actix_web::dev::{AppConfig, Extensions, Server, Service};
+use actix_web::rt::net::TcpStream;
+use actix_web::HttpRequest;
+use log::{debug, warn};
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, ReadBuf};
+
+/// Signature starting the binary header of the version 2 of the PROXY protocol.
+const V2_SIGNATURE: [u8; 12] = *b"\r\n\r\n\0\r\nQUIT\n";
+
+/// Length of the binary header of the version 2 of the PROXY protocol before the addresses.
+const V2_HEAD_LENGTH: usize = 16;
+
+/// Maximum length of the text header of the version 1 of the PROXY protocol, CRLF included.
+const V1_MAX_LENGTH: usize = 107;
+
+/// Maximum time given to the proxy to send the PROXY protocol header once connected. The header
+/// is awaited without holding up the other connections of the worker.
+const HEADER_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Address of the client given by the proxy in the PROXY protocol header of a connection, stored
+/// in the data of the connection.
+#[derive(Clone, Copy, Debug)]
+struct ProxiedAddress(SocketAddr);
+
+/// Connection whose PROXY protocol header was read. The bytes read after the header, the start of
+/// the first request, are given back to the server before the socket is read again.
+struct ProxiedStream {
+    stream: TcpStream,
+    pending: Vec<u8>,
+    client: Option<SocketAddr>,
+}
+
+impl AsyncRead for ProxiedStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        context: &mut Context<'_>,
+        buffer: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if this.pending.is_empty() {
+            return Pin::new(&mut this.stream).poll_read(context, buffer);
+        }
+        let length = this.pending.len().min(buffer.remaining());
+        buffer.put_slice(&this.pending[..length]);
+        this.pending.drain(..length);
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for ProxiedStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        context: &mut Context<'_>,
+        buffer: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().stream).poll_write(context, buffer)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        context: &mut Context<'_>,
+        buffers: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().stream).poll_write_vectored(context, buffers)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.stream.is_write_vectored()
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().stream).poll_flush(context)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().stream).poll_shutdown(context)
+    }
+}
+
+/// Returns the IP address of the client which sent the request: the one given by the proxy in
+/// the PROXY protocol header of the connection, if any, otherwise the address of the peer.
+pub fn client_ip(request: &HttpRequest) -> Option<IpAddr> {
+    request
+        .conn_data::<ProxiedAddress>()
+        .map(|ProxiedAddress(address)| *address)
+        .or_else(|| request.peer_addr())
+        .map(|address| address.ip())
+}
+
+/// Creates the HTTP server listening on the given addresses and serving the application of the
+/// given factory, like actix_web::HttpServer, once the PROXY protocol header of each connection
+/// has been read. The header is read asynchronously, so that a connection sending it slowly or
+/// not at all does not hold up the others.
+pub fn server<F, I, S, B>(
+    app: F,
+    addresses: &[SocketAddr],
+    workers: usize,
+    shutdown_timeout: u64,
+) -> io::Result<Server>
+where
+    F: Fn() -> I + Send + Clone + 'static,
+    I: IntoServiceFactory<S, Request>,
+    S: ServiceFactory<Request, Config = AppConfig> + 'static,
+    S::Error: Into<actix_web::Error> + 'static,
+    S::InitError: std::fmt::Debug,
+    S::Response: Into<Response<B>> + 'static,
+    <S::Service as Service<Request>>::Future: 'static,
+    S::Service: 'static,
+    B: MessageBody + 'static,
+{
+    let mut builder = Server::build()
+        .workers(workers)
+        .disable_signals()
+        .shutdown_timeout(shutdown_timeout);
+    let shutdown_signal = builder.graceful_shutdown_signal();
+    for address in addresses {
+        let address = *address;
+        let app = app.clone();
+        let shutdown_signal = shutdown_signal.clone();
+        builder = builder
+            .bind(format!("lb-{}", address), address, move || {
+                let shutdown_signal = shutdown_signal.clone();
+                let app = app().into_factory().map_err(|e| e.into().error_response());
+                let http_service = HttpService::build()
+                    .graceful_shutdown_signal(move || {
+                        let shutdown_signal = shutdown_signal.clone();
+                        async move { shutdown_signal.notified().await }
+                    })
+                    .local_addr(address)
+                    .on_connect_ext(on_connect)
+                    .finish(map_config(app, |_| AppConfig::default()));
+                fn_service(accept).and_then(http_service)
+            })
+            .map_err(|e| {
+                io::Error::new(e.kind(), format!("Failed to listen on {}: {}", address, e))
+            })?;
+    }
+    Ok(builder.run())
+}
+
+/// Reads the PROXY protocol header sent by the proxy at the start of a new connection, before the
+/// HTTP requests. The connection is closed if it does not start with a valid header in time.
+async fn accept(
+    stream: TcpStream,
+) -> Result<(ProxiedStream, Protocol, Option<SocketAddr>), DispatchError> {
+    let _ = stream.set_nodelay(true);
+    let peer_address = stream.peer_addr().ok();
+    let mut stream = ProxiedStream {
+        stream,
+        pending: Vec::new(),
+        client: None,
+    };
+    let header = tokio::time::timeout(HEADER_TIMEOUT, read_header(&mut stream))
+        .await
+        .unwrap_or_else(|_| Err("The PROXY protocol header did not come in time".to_string()));
+    match header {
+        Ok(()) => Ok((stream, Protocol::Http1, peer_address)),
+        Err(e) => {
+            warn!("Closing the connection: {}", e);
+            Err(DispatchError::Io(io::Error::other(e)))
+        }
+    }
+}
+
+/// Stores the address of the client given by the PROXY protocol header in the data of the
+/// connection. A header without address, such as the health checks of the proxy, keeps the
+/// address of the proxy.
+fn on_connect(stream: &ProxiedStream, data: &mut Extensions) {
+    match stream.client {
+        Some(address) => {
+            debug!("PROXY protocol header gives the client address {}", address);
+            data.insert(ProxiedAddress(address));
+        }
+        None => debug!("PROXY protocol header without client address"),
+    }
+}
+
+/// Reads the connection until its PROXY protocol header is complete, keeping the client address
+/// it gives and the bytes read after it in the stream.
+async fn read_header(stream: &mut ProxiedStream) -> Result<(), String> {
+    let mut buffer = Vec::new();
+    loop {
+        if let Some((length, client)) = parse_header(&buffer)? {
+            stream.client = client;
+            stream.pending = buffer.split_off(length);
+            return Ok(());
+        }
+        let read = stream
+            .stream
+            .read_buf(&mut buffer)
+            .await
+            .map_err(|e| format!("Failed to read the PROXY protocol header: {}", e))?;
+        if read == 0 {
+            return Err("The connection closed before the end of the PROXY protocol header".into());
+        }
+    }
+}
+
+/// Parses a header of the version 1 or 2 of the PROXY protocol at the start of the given bytes.
+/// Returns the length of the header and the address of the client it gives, None if the header
+/// is not complete yet, or an error if the bytes do not start with a valid header.
+fn parse_header(bytes: &[u8]) -> Result<Option<(usize, Option<SocketAddr>)>, String> {
+    if bytes.starts_with(b"PROXY") {
+        parse_v1_header(bytes)
+    } else if bytes.starts_with(&V2_SIGNATURE) {
+        parse_v2_header(bytes)
+    } else if b"PROXY".starts_with(bytes) || V2_SIGNATURE.starts_with(bytes) {
+        Ok(None)
+    } else {
+        Err("The connection does not start with a PROXY protocol header".to_string())
+    }
+}
+
+/// Parses a version 1 header, such as "PROXY TCP4 203.0.113.7 10.0.0.1 51234 80\r\n".
+fn parse_v1_header(bytes: &[u8]) -> Result<Option<(usize, Option<SocketAddr>)>, String> {
+    let Some(end) = bytes.windows(2).position(|window| window == b"\r\n") else {
+        return if bytes.len() >= V1_MAX_LENGTH {
+            Err("PROXY protocol v1 header too long".to_string())
+        } else {
+            Ok(None)
+        };
+    };
+    let length = end + 2;
+    if length > V1_MAX_LENGTH {
+        return Err("PROXY protocol v1 header too long".to_string());
+    }
+    let line = std::str::from_utf8(&bytes[..end])
+        .map_err(|_| "Invalid PROXY protocol v1 header".to_string())?;
+
+    let fields: Vec<&str> = line.split(' ').collect();
+    match fields.as_slice() {
+        ["PROXY", "UNKNOWN", ..] => Ok(Some((length, None))),
+        ["PROXY", protocol @ ("TCP4" | "TCP6"), source, _, source_port, _] => {
+            let ip = source
+                .parse::<IpAddr>()
+                .ok()
+                .filter(|ip| ip.is_ipv4() == (*protocol == "TCP4"))
+                .ok_or_else(|| format!("Invalid PROXY protocol v1 address {}", source))?;
+            let port = source_port
+                .parse::<u16>()
+                .map_err(|_| format!("Invalid PROXY protocol v1 port {}", source_port))?;
+            Ok(Some((length, Some(SocketAddr::new(ip, port)))))
+        }
+        _ => Err(format!("Invalid PROXY protocol v1 header {}", line)),
+    }
+}
+
+/// Parses a version 2 header: the signature, the version and command, the address family and
+/// protocol, the length of the addresses and the addresses. The LOCAL command and the families
+/// other than TCP and UDP over IPv4 and IPv6 give no address.
+fn parse_v2_header(bytes: &[u8]) -> Result<Option<(usize, Option<SocketAddr>)>, String> {
+    let Some(&[version_command, family, length_high, length_low]) =
+        bytes.get(V2_SIGNATURE.len()..V2_HEAD_LENGTH)
+    else {
+        return Ok(None);
+    };
+    let length = V2_HEAD_LENGTH + u16::from_be_bytes([length_high, length_low]) as usize;
+    let Some(addresses) = bytes.get(V2_HEAD_LENGTH..length) else {
+        return Ok(None);
+    };
+
+    if version_command >> 4 != 2 {
+        return Err(format!(
+            "Unsupported PROXY protocol version {}",
+            version_command >> 4
+        ));
+    }
+    let client = match (version_command & 0x0F, family >> 4) {
+        // LOCAL, sent by the proxy for its own connections
+        (0, _) => None,
+        // PROXY over IPv4: source address, destination address, source port, destination port
+        (1, 1) if addresses.len() >= 12 => {
+            let ip = Ipv4Addr::from(<[u8; 4]>::try_from(&addresses[0..4]).unwrap());
+            let port = u16::from_be_bytes([addresses[8], addresses[9]]);
+            Some(SocketAddr::new(IpAddr::V4(ip), port))
+        }
+        // PROXY over IPv6, with the same fields
+        (1, 2) if addresses.len() >= 36 => {
+            let ip = Ipv6Addr::from(<[u8; 16]>::try_from(&addresses[0..16]).unwrap());
+            let port = u16::from_be_bytes([addresses[32], addresses[33]]);
+            Some(SocketAddr::new(IpAddr::V6(ip), port))
+        }
+        (1, 0 | 3) => None,
+        (1, _) => return Err("Invalid PROXY protocol v2 addresses".to_string()),
+        (command, _) => return Err(format!("Unsupported PROXY protocol command {}", command)),
+    };
+    Ok(Some((length, client)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_a_v1_header_and_leaves_the_request_after_it() {
+        let bytes = b"PROXY TCP4 203.0.113.7 10.0.0.1 51234 80\r\nGET / HTTP/1.1\r\n";
+
+        let header = parse_header(bytes).unwrap();
+
+        let client = "203.0.113.7:51234".parse().unwrap();
+        assert_eq!(header, Some((42, Some(client))));
+        assert_eq!(&bytes[42..], b"GET / HTTP/1.1\r\n");
+    }
+
+    #[test]
+    fn parses_a_v2_header_of_an_ipv6_client() {
+        let mut bytes = V2_SIGNATURE.to_vec();
+        bytes.extend([0x21, 0x21, 0, 36]);
+        bytes.extend("2001:db8::23".parse::<Ipv6Addr>().unwrap().octets());
+        bytes.extend(Ipv6Addr::LOCALHOST.octets());
+        bytes.extend([0xC8, 0x22, 0x1F, 0x90]);
+
+        let header = parse_header(&bytes).unwrap();
+
+        let client = "[2001:db8::23]:51234".parse().unwrap();
+        assert_eq!(header, Some((52, Some(client))));
+    }
+
+    #[test]
+    fn waits_for_the_end_of_an_incomplete_header() {
+        assert_eq!(parse_header(b""), Ok(None));
+        assert_eq!(parse_header(b"PRO"), Ok(None));
+        assert_eq!(parse_header(b"PROXY TCP4 203.0.113.7"), Ok(None));
+        assert_eq!(parse_header(&V2_SIGNATURE[..7]), Ok(None));
+        assert_eq!(parse_header(&V2_SIGNATURE), Ok(None));
+    }
+
+    #[test]
+    fn rejects_a_connection_without_header() {
+        assert!(parse_header(b"GET / HTTP/1.1\r\n").is_err());
+        assert!(parse_header(&[b'P'; V1_MAX_LENGTH]).is_err());
+        assert!(parse_header(b"PROXY TCP4 2001:db8::7 10.0.0.1 51234 80\r\n").is_err());
+    }
+}
@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Maximum number of client IP addresses whose bucket is kept by the per-client rate limit.
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+/// Rate and burst of a token bucket.
+#[derive(Clone, Copy, Debug)]
+pub struct RateLimit {
+    /// Number of requests allowed per second on average.
+    pub requests_per_second: f64,
+
+    /// Maximum number of requests allowed at once, after a period without requests.
+    pub burst: u32,
+}
+
+/// Token bucket holding the requests a client can still send. Each request takes a token, and the
+/// tokens are refilled at a constant rate up to the burst.
+#[derive(Debug)]
+struct TokenBucket {
+    /// Number of tokens in the bucket, at the time of the last refill.
+    tokens: f64,
+
+    /// Time at which the bucket was last refilled.
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    /// Creates a full bucket.
+    fn new(limit: &RateLimit, now: Instant) -> Self {
+        Self {
+            tokens: f64::from(limit.burst),
+            last_refill: now,
+        }
+    }
+
+    /// Adds the tokens earned since the last refill.
+    fn refill(&mut self, limit: &RateLimit, now: Instant) {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens =
+            (self.tokens + elapsed * limit.requests_per_second).min(f64::from(limit.burst));
+        self.last_refill = now;
+    }
+
+    /// Takes a token from the bucket. Returns the time to wait until a token is available if the
+    /// bucket is empty.
+    fn take(&mut self, limit: &RateLimit, now: Instant) -> Result<(), Duration> {
+        self.refill(limit, now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - self.tokens) / limit.requests_per_second,
+            ))
+        }
+    }
+
+    /// Returns true if the bucket would be full at the given time, in which case it is the same as
+    /// a new bucket and can be forgotten.
+    fn is_full(&self, limit: &RateLimit, now: Instant) -> bool {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens + elapsed * limit.requests_per_second >= f64::from(limit.burst)
+    }
+}
+
+/// Token bucket rate limit of the requests received by the load balancer, applied to all the
+/// requests and to the requests of each client IP address.
+#[derive(Debug)]
+pub struct RateLimiter {
+    /// Limit of all the requests, with its bucket. None means no limit.
+    global: Option<(RateLimit, Mutex<TokenBucket>)>,
+
+    /// Limit of the requests of each client IP address, with the bucket of each client. None
+    /// means no limit.
+    per_client: Option<(RateLimit, Mutex<HashMap<IpAddr, TokenBucket>>)>,
+}
+
+impl RateLimiter {
+    /// Creates a rate limiter with the given global and per-client limits.
+    pub fn new(global: Option<RateLimit>, per_client: Option<RateLimit>) -> Self {
+        let now = Instant::now();
+        Self {
+            global: global.map(|limit| (limit, Mutex::new(TokenBucket::new(&limit, now)))),
+            per_client: per_client.map(|limit| (limit, Mutex::new(HashMap::new()))),
+        }
+    }
+
+    /// Lets a request of the given client through if it is within the limits. Returns the time
+    /// after which the client can retry otherwise. A request rejected by the per-client limit
+    /// does not count against the global one.
+    pub fn check(&self, client: Option<IpAddr>) -> Result<(), Duration> {
+        let now = Instant::now();
+
+        if let (Some((limit, buckets)), Some(client)) = (&self.per_client, client) {
+            let mut buckets = buckets.lock().unwrap();
+            if !buckets.contains_key(&client) && buckets.len() >= MAX_TRACKED_CLIENTS {
+                evict_clients(&mut buckets, limit, now);
+            }
+            buckets
+                .entry(client)
+                .or_insert_with(|| TokenBucket::new(limit, now))
+                .take(limit, now)?;
+        }
+
+        if let Some((limit, bucket)) = &self.global {
+            bucket.lock().unwrap().take(limit, now)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Makes room for a new client: forgets the clients whose bucket refilled, or the least recently
+/// seen one if all of them are still limited.
+fn evict_clients(buckets: &mut HashMap<IpAddr, TokenBucket>, limit: &RateLimit, now: Instant) {
+    buckets.retain(|_, bucket| !bucket.is_full(limit, now));
+    if buckets.len() < MAX_TRACKED_CLIENTS {
+        return;
+    }
+
+    if let Some(oldest) = buckets
+        .iter()
+        .min_by_key(|(_, bucket)| bucket.last_refill)
+        .map(|(client, _)| *client)
+    {
+        buckets.remove(&oldest);
+    }
+}
@@ -0,0 +1,82 @@
+use crate::config_file::ConfigFile;
+use crate::health::Health;
+use crate::load_balancer::LoadBalancer;
+use crate::load_balancer_settings::LoadBalancerSettings;
+use crate::strategy::Strategy;
+
+use log::{error, info};
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::RwLock as TokioRwLock;
+
+/// Re-reads the config file and applies it to the running load balancer, which was built from the
+/// given config. Returns the reloaded config.
+///
+/// The whole config is validated before anything is applied, so an invalid config file leaves the
+/// running load balancer untouched. When the strategy is unchanged and there are no routes nor
+/// default response, the backend servers missing from the config file are removed and the new ones
+/// are added, starting unhealthy until their first health check. Otherwise the load balancer is
+/// replaced once the in-flight requests have completed.
+pub async fn reload_config(
+    path: &Path,
+    running_config: &ConfigFile,
+    load_balancer: &TokioRwLock<Arc<dyn LoadBalancer>>,
+    settings: &LoadBalancerSettings,
+) -> Result<ConfigFile, String> {
+    let config = ConfigFile::load(path)?;
+    let new_load_balancer = settings.build_config(&config)?;
+
+    // Backend servers cannot be added to a running geo load balancer, and the groups of the routes
+    // and the default responses are not compared one by one, the load balancer is replaced instead
+    if config.strategy != running_config.strategy
+        || config.strategy == Strategy::Geo
+        || !config.routes.is_empty()
+        || !running_config.routes.is_empty()
+        || config.default_response.is_some()
+        || running_config.default_response.is_some()
+    {
+        info!(
+            "Replacing the load balancer with a {:?} one",
+            config.strategy
+        );
+        // Check the backend servers before they receive requests, as when starting up
+        new_load_balancer.check_backends_healths().await;
+        // The in-flight requests hold a read lock, so this waits for them to complete
+        *load_balancer.write().await = Arc::from(new_load_balancer);
+        return Ok(config);
+    }
+
+    let lb = load_balancer.read().await;
+    let running_addresses: Vec<String> = lb
+        .backends_snapshot()
+        .await
+        .into_iter()
+        .map(|backend| backend.address)
+        .collect();
+
+    // The requests already sent to a removed backend server still complete
+    for address in running_addresses.iter().filter(|address| {
+        !config
+            .backends
+            .iter()
+            .any(|definition| &definition.address == *address)
+    }) {
+        if let Err(e) = lb.remove_backend(address).await {
+            error!("{}", e);
+        }
+    }
+
+    for definition in config
+        .backends
+        .iter()
+        .filter(|definition| !running_addresses.contains(&definition.address))
+    {
+        // The definition was validated when building the new load balancer
+        let backend = settings.backend(definition, Health::Unhealthy)?;
+        if let Err(e) = lb.add_backend(backend).await {
+            error!("{}", e);
+        }
+    }
+
+    Ok(config)
+}
@@ -0,0 +1,45 @@
+use crate::forwarded_headers::ForwardedHeaders;
+
+use actix_web::web::Bytes;
+use reqwest::header::HeaderMap;
+use reqwest::Method;
+use std::collections::BTreeMap;
+
+/// Information about the client request that the load balancer forwards to a backend server.
+#[derive(Clone, Debug, Default)]
+pub struct RequestContext {
+    /// IP address of the client which sent the request, if known.
+    pub client_address: Option<String>,
+
+    /// HTTP method of the request.
+    pub method: Method,
+
+    /// Path of the request, used to route it to a group of backend servers and forwarded to the
+    /// backend servers having a base path.
+    pub path: String,
+
+    /// Query string of the request, without the leading ?. Empty if the request has none.
+    pub query: String,
+
+    /// ID of the request, forwarded to the backend server to correlate their logs.
+    pub request_id: String,
+
+    /// ID of the backend server to which the client is pinned by its affinity cookie, if any.
+    pub affinity: Option<String>,
+
+    /// Tags the backend server must have, asked for by the route tag headers of the request. Empty
+    /// if the request asks for none.
+    pub tags: BTreeMap<String, String>,
+
+    /// X-Forwarded-* headers, and Forwarded header if it is sent, sent to the backend server.
+    pub forwarded: ForwardedHeaders,
+
+    /// Headers of the client request, without Host and Content-Length, forwarded to the backend
+    /// server once filtered. They include the encodings accepted by the client, so that the
+    /// backend server can compress its response.
+    pub headers: HeaderMap,
+
+    /// Body of the request, forwarded to the backend server. Kept whole so that the request can be
+    /// sent again to another backend server.
+    pub body: Bytes,
+}
@@ -0,0 +1,20 @@
+use rand::Rng;
+
+/// Generates a random request ID formatted as a version 4 UUID, for example
+/// 4a1f0c3e-9b2d-4e7a-8c5f-1d2e3f405162.
+pub fn generate() -> String {
+    let mut bytes: [u8; 16] = rand::thread_rng().gen();
+    // Set the version (4) and the variant (RFC 4122) bits
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
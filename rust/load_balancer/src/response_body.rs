@@ -0,0 +1,41 @@
+use crate::in_flight::InFlightRequest;
+
+use actix_web::web::Bytes;
+use futures_core::Stream;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Body of the response of a backend server, streamed to the client as it is received. The request
+/// stays in flight until the whole body has been sent, so that the shutdown waits for it.
+pub struct ResponseBody {
+    /// Chunks of the body received from the backend server.
+    chunks: Pin<Box<dyn Stream<Item = reqwest::Result<Bytes>>>>,
+
+    /// The request whose body is streamed, `None` once the whole body has been received.
+    in_flight_request: Option<InFlightRequest>,
+}
+
+impl ResponseBody {
+    /// Creates the body streaming the response of the backend server to the given request.
+    pub fn new(response: reqwest::Response, in_flight_request: InFlightRequest) -> Self {
+        Self {
+            chunks: Box::pin(response.bytes_stream()),
+            in_flight_request: Some(in_flight_request),
+        }
+    }
+}
+
+impl Stream for ResponseBody {
+    type Item = reqwest::Result<Bytes>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let chunk = self.chunks.as_mut().poll_next(cx);
+        // A body which fails or is dropped before its end leaves the request cancelled
+        if let Poll::Ready(None) = chunk {
+            if let Some(in_flight_request) = self.in_flight_request.take() {
+                in_flight_request.finish();
+            }
+        }
+        chunk
+    }
+}
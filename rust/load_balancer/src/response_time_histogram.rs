@@ -0,0 +1,69 @@
+use hdrhistogram::Histogram;
+use serde::Serialize;
+
+/// Highest response time recorded in microseconds, 60 seconds. Slower responses are recorded as
+/// this value, so that the histogram keeps a fixed size.
+const MAX_RESPONSE_TIME_US: u64 = 60_000_000;
+
+/// Number of significant decimal digits kept for each response time, that is a relative error of
+/// at most 1%.
+const SIGNIFICANT_DIGITS: u8 = 2;
+
+/// Percentiles of the response time of a backend server in milliseconds, 0 when no response was
+/// recorded yet.
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+pub struct ResponseTimePercentiles {
+    /// Median response time.
+    pub p50_ms: f64,
+
+    /// Response time under which 95% of the responses arrived.
+    pub p95_ms: f64,
+
+    /// Response time under which 99% of the responses arrived.
+    pub p99_ms: f64,
+}
+
+/// Histogram of the response times of a backend server since the load balancer started. Recording
+/// a response time only increments a counter, and the histogram takes about 20KB whatever the
+/// number of responses.
+#[derive(Debug)]
+pub struct ResponseTimeHistogram {
+    /// Response times in microseconds, so that fast backend servers are still distinguished.
+    histogram: Histogram<u64>,
+}
+
+impl ResponseTimeHistogram {
+    /// Creates an empty histogram.
+    pub fn new() -> Self {
+        Self {
+            histogram: Histogram::new_with_bounds(1, MAX_RESPONSE_TIME_US, SIGNIFICANT_DIGITS)
+                .expect("the bounds of the response time histogram are valid"),
+        }
+    }
+
+    /// Records the given response time in milliseconds. A negative or NaN response time is
+    /// recorded as the lowest one.
+    pub fn record(&mut self, response_time_ms: f32) {
+        let response_time_us = (response_time_ms.max(0.0) * 1000.0) as u64;
+        self.histogram.saturating_record(response_time_us.max(1));
+    }
+
+    /// Returns the 50th, 95th and 99th percentiles of the recorded response times.
+    pub fn percentiles(&self) -> ResponseTimePercentiles {
+        if self.histogram.is_empty() {
+            return ResponseTimePercentiles::default();
+        }
+        let percentile_ms = |quantile| self.histogram.value_at_quantile(quantile) as f64 / 1000.0;
+        ResponseTimePercentiles {
+            p50_ms: percentile_ms(0.5),
+            p95_ms: percentile_ms(0.95),
+            p99_ms: percentile_ms(0.99),
+        }
+    }
+}
+
+impl Default for ResponseTimeHistogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
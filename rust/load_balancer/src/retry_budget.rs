@@ -0,0 +1,91 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Numbers of requests and retries counted during one second of the window.
+#[derive(Clone, Copy, Debug, Default)]
+struct Bucket {
+    /// Second since the creation of the budget counted by the bucket.
+    second: u64,
+
+    /// Number of requests received during the second.
+    requests: u64,
+
+    /// Number of retries sent during the second.
+    retries: u64,
+}
+
+/// Limit of the retries to a fraction of the requests received over a sliding window, so that the
+/// retries do not multiply the load on the backend servers during an outage. The requests and the
+/// retries are counted per second, the counts older than the window being forgotten.
+#[derive(Debug)]
+pub struct RetryBudget {
+    /// Maximum number of retries per request received over the window, greater than 0 and at most
+    /// 1.
+    ratio: f64,
+
+    /// Number of retries allowed over the window whatever the number of requests, so that the
+    /// requests can still be retried when there are few of them.
+    min_retries: u64,
+
+    /// Time from which the seconds of the buckets are counted.
+    start: Instant,
+
+    /// One bucket per second of the window, the bucket of a second being at the index of the
+    /// second modulo the number of buckets.
+    buckets: Mutex<Vec<Bucket>>,
+}
+
+impl RetryBudget {
+    /// Creates a budget allowing the given ratio of retries to requests over the given window,
+    /// rounded up to whole seconds, and at least the given number of retries over the window.
+    pub fn new(ratio: f64, window: Duration, min_retries: u32) -> Self {
+        let seconds = window.as_secs_f64().ceil().max(1.0) as usize;
+        Self {
+            ratio,
+            min_retries: u64::from(min_retries),
+            start: Instant::now(),
+            buckets: Mutex::new(vec![Bucket::default(); seconds]),
+        }
+    }
+
+    /// Counts a request received by the load balancer.
+    pub fn record_request(&self) {
+        let mut buckets = self.buckets.lock().unwrap();
+        self.current_bucket(&mut buckets).requests += 1;
+    }
+
+    /// Counts a retry and returns true if the budget allows it, returns false without counting it
+    /// otherwise.
+    pub fn try_retry(&self) -> bool {
+        let mut buckets = self.buckets.lock().unwrap();
+        let second = self.start.elapsed().as_secs();
+        let window = buckets.len() as u64;
+        let (requests, retries) = buckets
+            .iter()
+            .filter(|bucket| bucket.second + window > second)
+            .fold((0, 0), |(requests, retries), bucket| {
+                (requests + bucket.requests, retries + bucket.retries)
+            });
+
+        if retries >= self.min_retries && (retries + 1) as f64 > self.ratio * requests as f64 {
+            return false;
+        }
+        self.current_bucket(&mut buckets).retries += 1;
+        true
+    }
+
+    /// Returns the bucket of the current second, emptied if it still holds the counts of an older
+    /// second.
+    fn current_bucket<'a>(&self, buckets: &'a mut [Bucket]) -> &'a mut Bucket {
+        let second = self.start.elapsed().as_secs();
+        let index = (second % buckets.len() as u64) as usize;
+        let bucket = &mut buckets[index];
+        if bucket.second != second {
+            *bucket = Bucket {
+                second,
+                ..Bucket::default()
+            };
+        }
+        bucket
+    }
+}
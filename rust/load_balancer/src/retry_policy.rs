@@ -0,0 +1,79 @@
+use crate::request_context::RequestContext;
+use crate::retry_budget::RetryBudget;
+
+use log::warn;
+use reqwest::{Error, Method, StatusCode};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Settings deciding whether a request which failed on a backend server is retried on another
+/// backend server, and how long to wait before each retry.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// Maximum number of retries after the first attempt. 0 disables the retries.
+    pub max_retries: u32,
+
+    /// Time waited before the first retry, doubled before each following retry.
+    pub base_backoff: Duration,
+
+    /// Whether the requests with a non-idempotent method, such as POST or PATCH, are retried.
+    pub retry_non_idempotent: bool,
+
+    /// Status codes of the responses retried on another backend server, for example 503.
+    pub retry_statuses: Vec<u16>,
+
+    /// Maximum number of backend servers whose health is checked to select the backend server of
+    /// a request or of a retry, whatever the number of backend servers. Greater than 0.
+    pub max_tries: usize,
+
+    /// Budget limiting the retries to a fraction of the requests, shared by all the load
+    /// balancers. None means no limit besides the maximum number of retries of each request.
+    pub budget: Option<Arc<RetryBudget>>,
+}
+
+impl RetryPolicy {
+    /// Returns true if the request can be retried after the given error. Only transient errors,
+    /// where the backend server could not be reached or did not answer, are retried.
+    pub fn is_retryable(&self, context: &RequestContext, error: &Error) -> bool {
+        let is_transient = error.is_connect() || error.is_timeout() || error.is_request();
+        is_transient && (self.retry_non_idempotent || is_idempotent(&context.method))
+    }
+
+    /// Returns true if the request can be retried after the backend server answered it with the
+    /// given status, one of the statuses of the policy.
+    pub fn is_retryable_status(&self, context: &RequestContext, status: StatusCode) -> bool {
+        self.retry_statuses.contains(&status.as_u16())
+            && (self.retry_non_idempotent || is_idempotent(&context.method))
+    }
+
+    /// Counts a request against the retry budget, if any.
+    pub fn record_request(&self) {
+        if let Some(budget) = &self.budget {
+            budget.record_request();
+        }
+    }
+
+    /// Returns true if the retry budget, if any, allows one more retry, which is then counted.
+    /// Called once the request is known to be retryable, so that only actual retries are counted.
+    pub fn spend_retry(&self) -> bool {
+        let allowed = self.budget.as_ref().is_none_or(|budget| budget.try_retry());
+        if !allowed {
+            warn!("Retry budget exhausted, failing the request without retrying it");
+        }
+        allowed
+    }
+
+    /// Returns the time to wait before the given retry, starting at 0 for the first retry.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        self.base_backoff.saturating_mul(2u32.saturating_pow(retry))
+    }
+}
+
+/// Returns true if sending a request with the given method several times has the same effect as
+/// sending it once, as defined by RFC 9110.
+fn is_idempotent(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE | Method::PUT | Method::DELETE
+    )
+}
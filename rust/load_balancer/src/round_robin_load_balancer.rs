@@ -1,89 +1,241 @@
+use crate::affinity;
 use crate::backend::Backend;
+use crate::backend_response::BackendResponse;
+use crate::backend_snapshot::BackendSnapshot;
 use crate::health::Health;
+use crate::health_sweep;
 use crate::internal_error::InternalError;
 use crate::load_balancer::LoadBalancer;
+use crate::metrics::Metrics;
+use crate::request_context::RequestContext;
+use crate::retry_policy::RetryPolicy;
+use crate::selection::{self, Candidate};
+use crate::strategy::Strategy;
+use crate::tag_routing;
 
 use async_trait::async_trait;
-use log::{debug, info};
+use log::{debug, error, info, warn};
+use std::sync::Arc;
 use tokio::sync::RwLock as TokioRwLock;
+use tokio::time::{sleep, timeout, Duration};
 
 /// Represents a very basic load balancer. Sends the requests to healthy backend servers in a round
 /// robin fashion.
 #[derive(Debug)]
 pub struct RoundRobinLoadBalancer {
     /// List of backend servers
-    backends: Vec<Box<dyn Backend>>,
+    backends: TokioRwLock<Vec<Box<dyn Backend>>>,
 
     /// Index of the current backend server to which the next request will be sent.
     current_backend_index: TokioRwLock<usize>,
+
+    /// Maximum time spent selecting the backend server to which a request is sent.
+    selection_timeout: Duration,
+
+    /// Metrics updated on each request.
+    metrics: Arc<Metrics>,
+
+    /// Policy deciding when a failed request is retried on another backend server.
+    retry_policy: RetryPolicy,
+
+    /// Maximum number of backend servers health checked at the same time.
+    health_check_concurrency: usize,
 }
 
 impl RoundRobinLoadBalancer {
     /// Creates a new load balancer with the given list of backend servers to route the requests
-    /// to. The health check interval is the time in seconds between each health check sent to the
-    /// backends.
-    pub fn new(backends: Vec<Box<dyn Backend>>) -> Self {
+    /// to. Selecting a backend server fails if it takes longer than the selection timeout, and
+    /// failed requests are retried according to the retry policy.
+    pub fn new(
+        backends: Vec<Box<dyn Backend>>,
+        selection_timeout: Duration,
+        metrics: Arc<Metrics>,
+        retry_policy: RetryPolicy,
+        health_check_concurrency: usize,
+    ) -> Self {
         Self {
-            backends,
+            backends: TokioRwLock::new(backends),
             current_backend_index: 0.into(),
+            selection_timeout,
+            metrics,
+            retry_policy,
+            health_check_concurrency,
         }
     }
 }
 
 #[async_trait]
 impl LoadBalancer for RoundRobinLoadBalancer {
-    /// Returns the next available backend server to which the request can be sent. If none are
-    /// available, an error is returned.
-    async fn next_available_backend(&self) -> Result<Box<dyn Backend>, String> {
+    /// Returns the backend server to which the client is pinned by its affinity if it can receive
+    /// the request, otherwise the next healthy backend server which is not draining and has not
+    /// reached its maximum number of connections. The health found by the last health check or
+    /// request is used, no health check is sent. Backend servers in their slow start or reporting a
+    /// load are skipped at random, unless no other backend server is available. At most the maximum
+    /// number of tries of the retry policy are tried, unless a backup backend server is available,
+    /// in which case all the primary ones are searched before failing over. The backup backend
+    /// servers are only selected when no primary backend server is available, at random in
+    /// proportion to their weights. Only the backend servers having the tags asked for by the
+    /// request are selected, unless none of them is available. If none are available, an error is
+    /// returned.
+    async fn next_available_backend(
+        &self,
+        context: &RequestContext,
+    ) -> Result<Box<dyn Backend>, String> {
+        let backends = self.backends.read().await;
+        if backends.is_empty() {
+            return Err("No backend server available".to_string());
+        }
+
+        if let Some(backend) = backends
+            .iter()
+            .find(|backend| affinity::is_pinned(context, backend.as_ref()))
+        {
+            debug!("selected pinned backend {}", backend.address());
+            return Ok(backend.clone());
+        }
+
         debug!("trying to acquire current_backend_index write lock");
         let mut current_backend_index = self.current_backend_index.write().await;
         debug!("acquired current_backend_index write lock");
 
-        let mut tried_backends = 0;
+        let required_tags =
+            tag_routing::required_tags(context, backends.iter().map(|backend| backend.as_ref()));
+        let candidates: Vec<Candidate> = backends
+            .iter()
+            .map(|backend| Candidate::new(backend.as_ref(), required_tags))
+            .collect();
+        // The backup backend servers are only tried once no primary one is available
+        selection::primary_or_backup_index(
+            &candidates,
+            &mut current_backend_index,
+            self.retry_policy.max_tries,
+            rand::random::<f32>,
+        )
+        .map(|backend_index| backends[backend_index].clone())
+        .ok_or("No backend server available".to_string())
+    }
 
-        let mut backend_index = *current_backend_index;
-        *current_backend_index = (*current_backend_index + 1) % self.backends.len();
+    /// Sends a request to the next available backend server. When the backend server cannot be
+    /// reached, or answers with a status retried by the retry policy, the request is retried on the
+    /// following healthy backend servers as allowed by the retry policy and its retry budget.
+    /// Returns an error if no backend server is reachable, or the last response if no other backend
+    /// server can take it.
+    async fn send_request(
+        &self,
+        context: &RequestContext,
+    ) -> Result<BackendResponse, InternalError> {
+        self.metrics.record_request();
+        self.retry_policy.record_request();
 
-        self.backends[backend_index].check_health().await;
-        let mut backend_health = self.backends[backend_index].health().await;
+        // The retries go through the normal selection, the pinned backend server already failed
+        let mut context = context.clone();
+        let mut tried_backends: Vec<String> = Vec::new();
+        // Failure of the last backend server tried, an error or a response with a retried status,
+        // returned when no other one can be tried
+        let mut last_failure: Option<Result<BackendResponse, InternalError>> = None;
+        let mut retry = 0;
+        loop {
+            debug!("trying to get next available backend");
+            let backend = timeout(
+                self.selection_timeout,
+                self.next_available_backend(&context),
+            )
+            .await;
+            let backend = match backend {
+                Err(_) => {
+                    error!(
+                        "Selecting a backend took more than {}ms",
+                        self.selection_timeout.as_millis()
+                    );
+                    return Err(InternalError::SelectionTimeout);
+                }
+                Ok(Err(_)) => {
+                    return last_failure
+                        .unwrap_or(Err(InternalError::NoBackendAvailable { tried: Vec::new() }))
+                }
+                Ok(Ok(backend)) => backend,
+            };
 
-        while tried_backends < self.backends.len() {
-            if backend_health == Health::Healthy {
-                debug!("selected healthy backend {:?}", backend_index);
-                return Ok(self.backends[backend_index].clone());
+            if tried_backends
+                .iter()
+                .any(|address| address == backend.address())
+            {
+                warn!(
+                    "No other healthy backend to retry the request on, tried {}",
+                    tried_backends.join(", ")
+                );
+                return last_failure.unwrap_or(Err(InternalError::NoBackendAvailable {
+                    tried: tried_backends,
+                }));
             }
 
-            backend_index = *current_backend_index;
-            *current_backend_index = (*current_backend_index + 1) % self.backends.len();
-
-            self.backends[backend_index].check_health().await;
-            backend_health = self.backends[backend_index].health().await;
-
-            tried_backends += 1;
-        }
-
-        return Err("No backend server available".to_string());
-    }
-
-    /// Sends a request to the next available backend server. Returns an error if no backend server
-    /// is reachable.
-    async fn send_request(&self) -> Result<String, InternalError> {
-        debug!("trying to get next available backend");
-        let backend = self.next_available_backend().await;
-        match backend {
-            Ok(backend) => {
-                info!("Sending request to backend {:?}", backend);
-                let response = backend.send_request().await;
-                match response {
-                    Ok(response) => {
-                        info!("{:?}", response);
-                        let body = response.text_with_charset("utf-8").await.unwrap();
-                        Ok(body)
+            info!("Sending request to backend {:?}", backend);
+            let response = backend.send_request(&context).await;
+            match response {
+                Ok(response)
+                    if retry < self.retry_policy.max_retries
+                        && self
+                            .retry_policy
+                            .is_retryable_status(&context, response.status())
+                        && self.retry_policy.spend_retry() =>
+                {
+                    self.metrics.record_backend_error(backend.address()).await;
+                    let backoff = self.retry_policy.backoff(retry);
+                    warn!(
+                        "Retrying request in {}ms after backend {} answered {}",
+                        backoff.as_millis(),
+                        backend.address(),
+                        response.status()
+                    );
+                    sleep(backoff).await;
+                    tried_backends.push(backend.address().to_string());
+                    last_failure = Some(Ok(BackendResponse {
+                        address: backend.address().to_string(),
+                        response,
+                    }));
+                    context.affinity = None;
+                    retry += 1;
+                }
+                Ok(response) => {
+                    info!("{:?}", response);
+                    self.metrics
+                        .record_backend_response(
+                            backend.address(),
+                            backend.response_time_ms().await,
+                        )
+                        .await;
+                    return Ok(BackendResponse {
+                        address: backend.address().to_string(),
+                        response,
+                    });
+                }
+                Err(e) => {
+                    self.metrics.record_backend_error(backend.address()).await;
+                    let is_retryable = self.retry_policy.is_retryable(&context, &e);
+                    let failure = InternalError::BackendUnreachable {
+                        address: backend.address().to_string(),
+                        source: e,
+                    };
+                    if retry >= self.retry_policy.max_retries
+                        || !is_retryable
+                        || !self.retry_policy.spend_retry()
+                    {
+                        return Err(failure);
                     }
-                    Err(_) => Err(InternalError::BackendUnreachable),
+
+                    let backoff = self.retry_policy.backoff(retry);
+                    warn!(
+                        "Retrying request in {}ms after backend {} failed",
+                        backoff.as_millis(),
+                        backend.address()
+                    );
+                    sleep(backoff).await;
+                    tried_backends.push(backend.address().to_string());
+                    last_failure = Some(Err(failure));
+                    context.affinity = None;
+                    retry += 1;
                 }
             }
-            Err(_) => Err(InternalError::NoBackendAvailable),
         }
     }
 
@@ -92,13 +244,148 @@ impl LoadBalancer for RoundRobinLoadBalancer {
         // This is used for profiling only
         let start_time = std::time::Instant::now();
 
-        for backend in &self.backends {
-            backend.check_health().await;
-        }
+        // Check a copy of the list so that backend servers can be added or removed meanwhile
+        let backends = self.backends.read().await.clone();
+        let health_checks = backends
+            .iter()
+            .map(|backend| backend.check_health())
+            .collect();
+        health_sweep::check_healths(health_checks, self.health_check_concurrency).await;
 
         // For profiling only, measures how much time it took to check all backends health
         let end_time = std::time::Instant::now();
         let elapsed_time = end_time.duration_since(start_time).as_millis();
         info!("checking all backends health took {}ms", elapsed_time);
     }
+
+    /// Returns the current state of all backend servers.
+    async fn backends_snapshot(&self) -> Vec<BackendSnapshot> {
+        let backends = self.backends.read().await;
+        let mut snapshots = Vec::with_capacity(backends.len());
+        for backend in backends.iter() {
+            snapshots.push(BackendSnapshot::new(backend.as_ref()).await);
+        }
+        snapshots
+    }
+
+    /// Returns the number of healthy backend servers.
+    async fn healthy_count(&self) -> usize {
+        let backends = self.backends.read().await;
+        backends
+            .iter()
+            .filter(|backend| backend.health() == Health::Healthy)
+            .count()
+    }
+
+    /// Adds a backend server to which the requests can be sent. Returns an error if a backend
+    /// server with the same address already exists.
+    async fn add_backend(&self, backend: Box<dyn Backend>) -> Result<(), String> {
+        let mut backends = self.backends.write().await;
+        if backends.iter().any(|b| b.address() == backend.address()) {
+            return Err(format!(
+                "Backend server {} already exists",
+                backend.address()
+            ));
+        }
+
+        info!("Adding backend server {}", backend.address());
+        backends.push(backend);
+        Ok(())
+    }
+
+    /// Removes the backend server with the given address. Returns an error if there is no backend
+    /// server with this address.
+    async fn remove_backend(&self, address: &str) -> Result<(), String> {
+        let mut backends = self.backends.write().await;
+        let Some(index) = backends.iter().position(|b| b.address() == address) else {
+            return Err(format!("No backend server with address {}", address));
+        };
+
+        info!("Removing backend server {}", address);
+        backends.remove(index);
+        Ok(())
+    }
+
+    /// Starts or stops draining the backend server with the given address. Returns an error if
+    /// there is no backend server with this address.
+    async fn set_draining(&self, address: &str, draining: bool) -> Result<(), String> {
+        let backends = self.backends.read().await;
+        let Some(backend) = backends.iter().find(|b| b.address() == address) else {
+            return Err(format!("No backend server with address {}", address));
+        };
+
+        info!(
+            "Setting draining of backend server {} to {}",
+            address, draining
+        );
+        backend.set_draining(draining);
+        Ok(())
+    }
+
+    /// Sets the weight of the backend server with the given address. Returns an error if there is
+    /// no backend server with this address.
+    async fn set_weight(&self, address: &str, weight: u32) -> Result<(), String> {
+        let backends = self.backends.read().await;
+        let Some(backend) = backends.iter().find(|b| b.address() == address) else {
+            return Err(format!("No backend server with address {}", address));
+        };
+
+        info!("Setting weight of backend server {} to {}", address, weight);
+        backend.set_weight(weight);
+        Ok(())
+    }
+
+    fn strategy(&self) -> String {
+        Strategy::RoundRobin.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend_config::BackendConfig;
+    use crate::simple_backend::SimpleBackend;
+    use actix_web::http::StatusCode;
+
+    /// Returns a load balancer with a healthy backend server on port 8081, without retries, whose
+    /// selection times out after 50ms.
+    fn load_balancer() -> RoundRobinLoadBalancer {
+        let backend = SimpleBackend::new(
+            "http://localhost:8081/".to_string(),
+            Health::Healthy,
+            &BackendConfig::default(),
+        )
+        .unwrap();
+        RoundRobinLoadBalancer::new(
+            vec![Box::new(backend)],
+            Duration::from_millis(50),
+            Arc::new(Metrics::new()),
+            RetryPolicy {
+                max_retries: 0,
+                base_backoff: Duration::ZERO,
+                retry_non_idempotent: false,
+                retry_statuses: Vec::new(),
+                max_tries: 1,
+                budget: None,
+            },
+            1,
+        )
+    }
+
+    #[tokio::test]
+    async fn answers_503_when_the_selection_stalls() {
+        let load_balancer = load_balancer();
+        // The selection waits for the index of the next backend server until the timeout
+        let _stall = load_balancer.current_backend_index.write().await;
+
+        let result = load_balancer.send_request(&RequestContext::default()).await;
+
+        let error = result.err().unwrap();
+        assert!(
+            matches!(error, InternalError::SelectionTimeout),
+            "{}",
+            error
+        );
+        assert_eq!(error.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+    }
 }
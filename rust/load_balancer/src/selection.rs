@@ -0,0 +1,418 @@
+use crate::backend::Backend;
+use crate::health::Health;
+use crate::min_heap_item::MinHeapItem;
+use crate::tag_routing;
+
+use log::debug;
+use std::collections::BTreeMap;
+
+/// State of a backend server on which its selection depends, copied from the backend server so
+/// that selecting one is a pure function of the candidates, independent of the network and of the
+/// time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Candidate {
+    /// Whether the backend server can receive a request: it is healthy, not draining, not parked
+    /// with a weight of 0 and has not reached its maximum number of connections
+    pub available: bool,
+
+    /// Whether the backend server has the tags asked for by the request, if any
+    pub tagged: bool,
+
+    /// Whether the backend server is a backup one
+    pub backup: bool,
+
+    /// Weight of the backend server, by which the requests are spread among the backup ones
+    pub weight: u32,
+
+    /// Effective weight of the backend server divided by its weight, below 1 in its slow start or
+    /// while it reports a load, 0 when it is parked
+    pub share: f32,
+
+    /// Response time of the backend server divided by its weight, the lower the better
+    pub response_time: f32,
+
+    /// Whether the backend server has no request in flight, so that its response time is known
+    pub idle: bool,
+}
+
+impl Candidate {
+    /// Copies the state of the backend server, without response time.
+    pub fn new(backend: &dyn Backend, required_tags: Option<&BTreeMap<String, String>>) -> Self {
+        let weight = backend.weight();
+        Self {
+            available: backend.health() == Health::Healthy
+                && !backend.is_draining()
+                && !backend.is_saturated()
+                && weight > 0,
+            tagged: tag_routing::matches(required_tags, backend),
+            backup: backend.is_backup(),
+            weight,
+            share: if weight > 0 {
+                backend.effective_weight() / weight as f32
+            } else {
+                0.0
+            },
+            response_time: 0.0,
+            idle: backend.in_flight() == 0,
+        }
+    }
+}
+
+/// Returns the index of the next available candidate in round robin order, from the current
+/// index, which is a backup or a primary backend server as given and is tagged. A candidate with a
+/// share below 1 only takes its turn when the given random number, between 0 and 1, is below its
+/// share, and is otherwise only selected if no other candidate is available. At most the given
+/// number of candidates are tried, and the current index is moved past them.
+pub fn round_robin_index(
+    candidates: &[Candidate],
+    current_index: &mut usize,
+    backup: bool,
+    max_tries: usize,
+    mut random: impl FnMut() -> f32,
+) -> Option<usize> {
+    // First candidate skipped because of its share, used if no other one is available
+    let mut skipped_index = None;
+    let mut tries = 0;
+
+    // The index can be past the end of the list if backend servers were removed
+    let first_index = *current_index % candidates.len();
+    for offset in 0..candidates.len() {
+        let index = (first_index + offset) % candidates.len();
+        let candidate = &candidates[index];
+        if candidate.backup != backup || !candidate.tagged {
+            continue;
+        }
+        // Bounds the time spent skipping backend servers when most of them are unavailable
+        if tries == max_tries {
+            break;
+        }
+        tries += 1;
+        *current_index = (index + 1) % candidates.len();
+
+        if !candidate.available {
+            debug!("skipped unavailable backend {:?}", index);
+        } else if candidate.share < 1.0 && random() >= candidate.share {
+            debug!("skipped slow starting backend {:?}", index);
+            skipped_index.get_or_insert(index);
+        } else {
+            debug!("selected healthy backend {:?}", index);
+            return Some(index);
+        }
+    }
+
+    if let Some(index) = skipped_index {
+        debug!("selected slow starting backend {:?}", index);
+    }
+    skipped_index
+}
+
+/// Returns the index of the next available primary candidate in round robin order, at most the
+/// given number of them being tried as by round_robin_index, otherwise of a backup candidate picked
+/// by weighted_backup_index. When an available backup candidate would be selected, the primary
+/// candidates past the tried ones are searched first, so that no request fails over while a
+/// primary backend server is available.
+pub fn primary_or_backup_index(
+    candidates: &[Candidate],
+    current_index: &mut usize,
+    max_tries: usize,
+    mut random: impl FnMut() -> f32,
+) -> Option<usize> {
+    if let Some(index) = round_robin_index(candidates, current_index, false, max_tries, &mut random)
+    {
+        return Some(index);
+    }
+    if !candidates
+        .iter()
+        .any(|candidate| candidate.backup && candidate.available && candidate.tagged)
+    {
+        return None;
+    }
+    round_robin_index(
+        candidates,
+        current_index,
+        false,
+        candidates.len(),
+        &mut random,
+    )
+    .or_else(|| weighted_backup_index(candidates, random()))
+}
+
+/// Returns the index of an available and tagged backup candidate, picked in proportion to its
+/// weight times its share by the given random number, between 0 and 1, so that failing over to the
+/// backup backend servers spreads the requests among them by their weights.
+pub fn weighted_backup_index(candidates: &[Candidate], random: f32) -> Option<usize> {
+    let eligible: Vec<(usize, f32)> = candidates
+        .iter()
+        .enumerate()
+        .filter(|(_, candidate)| candidate.backup && candidate.available && candidate.tagged)
+        .map(|(index, candidate)| (index, candidate.weight as f32 * candidate.share))
+        .collect();
+    let total_weight: f32 = eligible.iter().map(|(_, weight)| weight).sum();
+
+    let mut remaining = random * total_weight;
+    for (index, weight) in &eligible {
+        if remaining < *weight {
+            debug!("selected backup backend {:?}", index);
+            return Some(*index);
+        }
+        remaining -= weight;
+    }
+    // The random number can reach the total weight by rounding
+    eligible.last().map(|(index, _)| *index)
+}
+
+/// Returns the index of the available and tagged candidate with the lowest response time, a
+/// primary backend server if any is available, otherwise a backup one, and an idle one before the
+/// busy ones, whose response time may not be known yet. A NaN response time is the worst one.
+pub fn least_response_index(candidates: &[Candidate]) -> Option<usize> {
+    candidates
+        .iter()
+        .enumerate()
+        .filter(|(_, candidate)| candidate.available && candidate.tagged)
+        .max_by_key(|(_, candidate)| {
+            let item = MinHeapItem {
+                priority: candidate.response_time,
+                element: (),
+            };
+            (!candidate.backup, candidate.idle, item)
+        })
+        .map(|(index, _)| index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend_config::BackendConfig;
+    use crate::simple_backend::SimpleBackend;
+
+    /// Returns an available, tagged and idle primary candidate with its full share.
+    fn candidate(response_time: f32) -> Candidate {
+        Candidate {
+            available: true,
+            tagged: true,
+            backup: false,
+            weight: 1,
+            share: 1.0,
+            response_time,
+            idle: true,
+        }
+    }
+
+    #[test]
+    fn round_robin_wraps_around_the_candidates() {
+        let candidates = [candidate(0.0), candidate(0.0), candidate(0.0)];
+        let mut current_index = 2;
+
+        let selected: Vec<Option<usize>> = (0..4)
+            .map(|_| round_robin_index(&candidates, &mut current_index, false, 3, || 0.0))
+            .collect();
+
+        assert_eq!(selected, [Some(2), Some(0), Some(1), Some(2)]);
+    }
+
+    #[test]
+    fn round_robin_starts_over_when_the_index_is_past_the_end() {
+        let candidates = [candidate(0.0), candidate(0.0)];
+        let mut current_index = 5;
+
+        let selected = round_robin_index(&candidates, &mut current_index, false, 2, || 0.0);
+
+        assert_eq!(selected, Some(1));
+        assert_eq!(current_index, 0);
+    }
+
+    #[test]
+    fn round_robin_selects_the_backups_only_when_asked_for() {
+        let backup = Candidate {
+            backup: true,
+            ..candidate(0.0)
+        };
+        let unavailable = Candidate {
+            available: false,
+            ..candidate(0.0)
+        };
+        let candidates = [backup, unavailable, backup];
+        let mut current_index = 0;
+
+        let primary = round_robin_index(&candidates, &mut 0, false, 3, || 0.0);
+        let first_backup = round_robin_index(&candidates, &mut current_index, true, 3, || 0.0);
+        let second_backup = round_robin_index(&candidates, &mut current_index, true, 3, || 0.0);
+
+        assert_eq!(primary, None);
+        assert_eq!(first_backup, Some(0));
+        assert_eq!(second_backup, Some(2));
+    }
+
+    #[test]
+    fn round_robin_tries_at_most_max_tries_candidates() {
+        let unavailable = Candidate {
+            available: false,
+            ..candidate(0.0)
+        };
+        let candidates = [unavailable, unavailable, candidate(0.0)];
+        let mut current_index = 0;
+
+        let selected = round_robin_index(&candidates, &mut current_index, false, 2, || 0.0);
+
+        assert_eq!(selected, None);
+        assert_eq!(current_index, 2);
+    }
+
+    #[test]
+    fn round_robin_selects_a_skipped_slow_starting_candidate_last() {
+        let slow_starting = Candidate {
+            share: 0.5,
+            ..candidate(0.0)
+        };
+        let unavailable = Candidate {
+            available: false,
+            ..candidate(0.0)
+        };
+        let mut current_index = 0;
+
+        let skipped = round_robin_index(&[slow_starting, candidate(0.0)], &mut 0, false, 2, || 0.9);
+        let alone = round_robin_index(
+            &[slow_starting, unavailable],
+            &mut current_index,
+            false,
+            2,
+            || 0.9,
+        );
+
+        assert_eq!(skipped, Some(1));
+        assert_eq!(alone, Some(0));
+    }
+
+    #[test]
+    fn searches_all_the_primaries_before_failing_over_to_a_backup() {
+        let unavailable = Candidate {
+            available: false,
+            ..candidate(0.0)
+        };
+        let backup = Candidate {
+            backup: true,
+            ..candidate(0.0)
+        };
+        let candidates = [unavailable, unavailable, candidate(0.0), backup];
+        let mut current_index = 0;
+
+        let selected = primary_or_backup_index(&candidates, &mut current_index, 2, || 0.0);
+        let failed_over = primary_or_backup_index(&[unavailable, backup], &mut 0, 2, || 0.0);
+        let without_backup = primary_or_backup_index(&candidates[..3], &mut 0, 2, || 0.0);
+
+        assert_eq!(selected, Some(2));
+        assert_eq!(current_index, 3);
+        assert_eq!(failed_over, Some(1));
+        assert_eq!(without_backup, None);
+    }
+
+    #[test]
+    fn weighted_backup_spreads_the_requests_by_weight() {
+        let backup = |weight| Candidate {
+            backup: true,
+            weight,
+            ..candidate(0.0)
+        };
+        let candidates = [candidate(0.0), backup(1), backup(3)];
+
+        let selected: Vec<Option<usize>> = [0.0, 0.2, 0.3, 0.6, 0.99, 1.0]
+            .into_iter()
+            .map(|random| weighted_backup_index(&candidates, random))
+            .collect();
+
+        assert_eq!(
+            selected,
+            [Some(1), Some(1), Some(2), Some(2), Some(2), Some(2)]
+        );
+    }
+
+    #[test]
+    fn weighted_backup_skips_the_unavailable_and_slow_starting_backups() {
+        let backup = Candidate {
+            backup: true,
+            ..candidate(0.0)
+        };
+        let unavailable = Candidate {
+            available: false,
+            ..backup
+        };
+        let slow_starting = Candidate {
+            share: 0.5,
+            ..backup
+        };
+
+        assert_eq!(weighted_backup_index(&[unavailable, backup], 0.0), Some(1));
+        assert_eq!(
+            weighted_backup_index(&[slow_starting, backup], 0.4),
+            Some(1)
+        );
+        assert_eq!(
+            weighted_backup_index(&[candidate(0.0), unavailable], 0.0),
+            None
+        );
+    }
+
+    #[test]
+    fn least_response_selects_the_lowest_response_time() {
+        let candidates = [candidate(30.0), candidate(10.0), candidate(20.0)];
+
+        assert_eq!(least_response_index(&candidates), Some(1));
+    }
+
+    #[test]
+    fn least_response_selects_a_primary_idle_candidate_first() {
+        let backup = Candidate {
+            backup: true,
+            ..candidate(1.0)
+        };
+        let busy = Candidate {
+            idle: false,
+            ..candidate(2.0)
+        };
+        let unavailable = Candidate {
+            available: false,
+            ..candidate(0.0)
+        };
+        let untagged = Candidate {
+            tagged: false,
+            ..candidate(0.0)
+        };
+
+        assert_eq!(
+            least_response_index(&[backup, busy, candidate(50.0), unavailable, untagged]),
+            Some(2)
+        );
+        assert_eq!(least_response_index(&[backup, busy]), Some(1));
+        assert_eq!(least_response_index(&[backup, unavailable]), Some(0));
+        assert_eq!(least_response_index(&[unavailable, untagged]), None);
+    }
+
+    #[test]
+    fn least_response_selects_a_nan_response_time_last() {
+        let candidates = [candidate(f32::NAN), candidate(100.0)];
+
+        assert_eq!(least_response_index(&candidates), Some(1));
+        assert_eq!(least_response_index(&candidates[..1]), Some(0));
+    }
+
+    #[test]
+    fn a_parked_backend_is_not_a_candidate() {
+        let config = BackendConfig {
+            weight: 0,
+            ..BackendConfig::default()
+        };
+        let address = "http://localhost:8081/".to_string();
+        let backend = SimpleBackend::new(address, Health::Healthy, &config).unwrap();
+
+        let candidate = Candidate::new(&backend, None);
+
+        assert!(!candidate.available);
+        assert_eq!(candidate.share, 0.0);
+
+        backend.set_weight(2);
+        let candidate = Candidate::new(&backend, None);
+
+        assert!(candidate.available);
+        assert_eq!(candidate.share, 1.0);
+    }
+}
@@ -1,9 +1,31 @@
 use crate::backend::Backend;
+use crate::backend_config::BackendConfig;
+use crate::backend_protocol::BackendProtocol;
+use crate::circuit_breaker::{CircuitBreaker, CircuitState};
+use crate::ewma::Ewma;
+use crate::header_filter::HeaderFilter;
 use crate::health::Health;
+use crate::health_check_assertion::HealthCheckAssertion;
+use crate::health_check_counter::HealthCheckCounter;
+use crate::health_check_history::HealthCheckHistory;
+use crate::health_check_kind::HealthCheckKind;
+use crate::health_check_method::HealthCheckMethod;
+use crate::health_event::{HealthEvent, HealthWebhook};
+use crate::outlier_detector::OutlierDetector;
+use crate::request_context::RequestContext;
+use crate::response_time_histogram::{ResponseTimeHistogram, ResponseTimePercentiles};
+use crate::upgrade_request::UpgradeRequest;
 use async_trait::async_trait;
-use reqwest::{Client, Error, Response, StatusCode};
-use std::sync::Arc;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::redirect::Policy;
+use reqwest::{Client, Error, Response, StatusCode, Url};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicU8, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
 use tokio::sync::RwLock as TokioRwLock;
+use tokio::time::timeout;
 
 use log::{debug, error, info, warn};
 
@@ -14,19 +36,574 @@ pub struct SimpleBackend {
     /// http://localhost:8081
     address: String,
 
-    /// Response time of the backend server in milliseconds.
-    response_time_ms: Arc<TokioRwLock<f32>>,
+    /// How the health of the backend server is checked.
+    health_check_kind: HealthCheckKind,
 
-    /// Health status of the backend server.
-    health: Arc<TokioRwLock<Health>>,
+    /// Address to which the health checks are sent. For HTTP health checks, the address of the
+    /// health check endpoint, for example http://localhost:8081/health. For TCP health checks, the
+    /// host and port of the backend server, for example localhost:8081.
+    health_check_address: String,
+
+    /// Path under which the backend server is mounted, for example /app, prepended to the path of
+    /// the forwarded requests. Empty or starting with a slash, and not ending with one. None, like
+    /// an empty one, forwards the requests with their path from the root of the backend server.
+    base_path: Option<String>,
+
+    /// Name of the header carrying the request ID sent to the backend server.
+    request_id_header: String,
+
+    /// Filter of the headers of the client requests forwarded to the backend server.
+    request_headers: HeaderFilter,
+
+    /// Headers set on every request sent to the backend server, replacing the ones of the client.
+    header_overrides: HeaderMap,
+
+    /// Moving average of the response time of the backend server to the requests in
+    /// milliseconds, the health checks excluded.
+    response_time_ms: Arc<TokioRwLock<Ewma>>,
+
+    /// Moving average of the time taken by the health checks of the backend server in
+    /// milliseconds. The lock is never held across an await point.
+    health_check_latency_ms: Arc<Mutex<Ewma>>,
+
+    /// Histogram of the response times of the requests answered by the backend server. The lock
+    /// is never held across an await point.
+    response_time_histogram: Arc<Mutex<ResponseTimeHistogram>>,
+
+    /// Health status of the backend server, stored as an atomic so that it can be read on every
+    /// request without taking a lock. See Health::to_u8 and Health::from_u8.
+    health: Arc<AtomicU8>,
+
+    /// Counter of the consecutive health checks deciding when the health status changes. The lock
+    /// is never held across an await point.
+    health_check_counter: Arc<Mutex<HealthCheckCounter>>,
+
+    /// Outcomes of the health checks since the load balancer started, the recent ones included.
+    health_check_history: Arc<HealthCheckHistory>,
+
+    /// Circuit breaker tracking the consecutive failures and successes of the requests. The lock
+    /// is never held across an await point.
+    circuit_breaker: Arc<Mutex<CircuitBreaker>>,
+
+    /// Outlier detector ejecting the backend server when too many of its recent requests fail,
+    /// None if the outlier detection is disabled. The lock is never held across an await point.
+    outlier_detector: Option<Arc<Mutex<OutlierDetector>>>,
+
+    /// Number of requests currently sent to the backend server.
+    in_flight: Arc<AtomicU32>,
+
+    /// Number of requests sent to the backend server since the load balancer started.
+    requests_total: Arc<AtomicU64>,
+
+    /// Number of failed requests sent to the backend server since the load balancer started.
+    errors_total: Arc<AtomicU64>,
+
+    /// Maximum number of requests sent to the backend server at the same time, None means no
+    /// limit.
+    max_connections: Option<u32>,
+
+    /// Whether the backend server is draining and receives no new requests.
+    draining: Arc<AtomicBool>,
+
+    /// Weight of the backend server. 0 parks it: it receives no requests but is still health
+    /// checked. Changed through the admin API, so shared by the clones of the backend server.
+    weight: Arc<AtomicU32>,
+
+    /// Time during which the effective weight of the backend server ramps up to its weight after
+    /// it becomes healthy again. Zero disables the slow start.
+    slow_start: Duration,
+
+    /// Whether the backend server is a backup, only receiving requests when no other backend
+    /// server is available.
+    backup: bool,
+
+    /// Tags of the backend server, matched against the tags asked for by the requests.
+    tags: Arc<BTreeMap<String, String>>,
+
+    /// Time at which the backend server last became healthy again, None if it has been healthy
+    /// since it was created or has never been healthy.
+    healthy_since: Arc<Mutex<Option<Instant>>>,
+
+    /// Last time the backend server was found healthy, or the time it was created if it never
+    /// was.
+    last_healthy: Arc<Mutex<Instant>>,
+
+    /// Load last reported by the backend server in the answers to its health checks, between 0
+    /// and 1. 0 if it reports none.
+    load: Arc<Mutex<f32>>,
+
+    /// Moving average of the response time of the requests, or time taken by a health check,
+    /// above which the backend server is unhealthy. None means no limit.
+    max_response_time: Option<Duration>,
+
+    /// HTTP client sending the requests and health checks, shared by the clones of the backend
+    /// server so that its connections are reused.
+    client: Client,
+
+    /// Maximum time given to the backend server to accept a connection, also used by the TCP
+    /// health checks. None leaves it to the operating system.
+    connect_timeout: Option<Duration>,
+
+    /// Maximum time given to the backend server to answer a health check. None means no limit.
+    health_check_timeout: Option<Duration>,
+
+    /// Whether the answers to the health checks other than a 200 are accepted without warning.
+    quiet_health_check: bool,
+
+    /// Whether the answers to the health checks other than a 200 were already warned about, so
+    /// that a backend server without health endpoint does not flood the logs.
+    warned_health_check: Arc<AtomicBool>,
+
+    /// Webhook to which the changes of health are posted. None only logs them.
+    health_webhook: Option<HealthWebhook>,
 }
 
 impl SimpleBackend {
-    pub fn new(address: String, health: Health) -> Self {
-        Self {
+    /// Creates a new backend server with the given address and initial health status. Returns an
+    /// error if the address is not a valid URL, if the maximum number of connections is 0, if the
+    /// body of HEAD health checks would be checked, if the base path has a query or a fragment or
+    /// if the HTTP client cannot be created.
+    pub fn new(address: String, health: Health, config: &BackendConfig) -> Result<Self, String> {
+        let health_check_address = health_check_address(&address, &config.health_check)?;
+        let base_path = match &config.base_path {
+            Some(base_path) if base_path.contains(['?', '#']) => {
+                return Err(format!(
+                    "The base path {} of backend server {} cannot have a query or a fragment",
+                    base_path, address
+                ));
+            }
+            Some(base_path) => Some(normalize_base_path(base_path)),
+            None => None,
+        };
+        let client = client(config)?;
+        if config.max_connections == Some(0) {
+            return Err(format!(
+                "The maximum number of connections of backend server {} must be greater than 0",
+                address
+            ));
+        }
+        if let HealthCheckKind::Http {
+            method: HealthCheckMethod::Head,
+            assertion,
+            load_field,
+            ..
+        } = &config.health_check
+        {
+            if assertion.reads_body() || load_field.is_some() {
+                return Err(format!(
+                    "The HEAD health checks of backend server {} have no body to check or to read \
+                     the load from",
+                    address
+                ));
+            }
+        }
+        let circuit_breaker = CircuitBreaker::new(
+            config.circuit_breaker_threshold,
+            config.circuit_breaker_cooldown,
+        );
+
+        Ok(Self {
             address,
-            response_time_ms: Arc::new(TokioRwLock::new(0.0)),
-            health: Arc::new(TokioRwLock::new(health)),
+            health_check_kind: config.health_check.clone(),
+            health_check_address,
+            base_path,
+            request_id_header: config.request_id_header.clone(),
+            request_headers: config.request_headers.clone(),
+            header_overrides: config.header_overrides.clone(),
+            response_time_ms: Arc::new(TokioRwLock::new(Ewma::new(config.response_time_smoothing))),
+            health_check_latency_ms: Arc::new(Mutex::new(Ewma::new(
+                config.response_time_smoothing,
+            ))),
+            response_time_histogram: Arc::new(Mutex::new(ResponseTimeHistogram::new())),
+            health: Arc::new(AtomicU8::new(health.to_u8())),
+            health_check_counter: Arc::new(Mutex::new(HealthCheckCounter::new(
+                config.healthy_threshold,
+                config.unhealthy_threshold,
+            ))),
+            health_check_history: Arc::new(HealthCheckHistory::new()),
+            circuit_breaker: Arc::new(Mutex::new(circuit_breaker)),
+            outlier_detector: config
+                .outlier_detection
+                .map(|detection| Arc::new(Mutex::new(OutlierDetector::new(detection)))),
+            in_flight: Arc::new(AtomicU32::new(0)),
+            requests_total: Arc::new(AtomicU64::new(0)),
+            errors_total: Arc::new(AtomicU64::new(0)),
+            max_connections: config.max_connections,
+            draining: Arc::new(AtomicBool::new(false)),
+            weight: Arc::new(AtomicU32::new(config.weight)),
+            slow_start: config.slow_start,
+            backup: config.backup,
+            tags: Arc::new(config.tags.clone()),
+            healthy_since: Arc::new(Mutex::new(None)),
+            last_healthy: Arc::new(Mutex::new(Instant::now())),
+            load: Arc::new(Mutex::new(0.0)),
+            max_response_time: config.max_response_time,
+            client,
+            connect_timeout: config.connect_timeout,
+            health_check_timeout: config.health_check_timeout,
+            quiet_health_check: config.quiet_health_check,
+            warned_health_check: Arc::new(AtomicBool::new(false)),
+            health_webhook: config.health_webhook.clone(),
+        })
+    }
+}
+
+/// Creates the HTTP client sending the requests to a backend server with the HTTP version of the
+/// given settings, failing to connect after their connect timeout, if any, following at most their
+/// number of redirects, keeping their number of idle connections open for their idle timeout and
+/// connecting to HTTPS backend servers with their TLS settings. With prior knowledge of HTTP/2,
+/// requests to a backend server only speaking HTTP/1.1 fail.
+fn client(config: &BackendConfig) -> Result<Client, String> {
+    // A redirect is otherwise passed through to the client with its Location header
+    let redirect_policy = match config.max_redirects {
+        0 => Policy::none(),
+        max_redirects => Policy::limited(max_redirects),
+    };
+    let mut builder = config.tls.apply(
+        Client::builder()
+            .redirect(redirect_policy)
+            .pool_max_idle_per_host(config.pool_max_idle_per_host)
+            .pool_idle_timeout(config.pool_idle_timeout),
+    );
+    if let Some(connect_timeout) = config.connect_timeout {
+        builder = builder.connect_timeout(connect_timeout);
+    }
+    let builder = match config.protocol {
+        BackendProtocol::Auto => builder,
+        BackendProtocol::Http1 => builder.http1_only(),
+        BackendProtocol::Http2 => builder.http2_prior_knowledge(),
+    };
+    builder
+        .build()
+        .map_err(|e| format!("Failed to create the HTTP client: {}", e))
+}
+
+/// Returns the address to which the health checks of a backend server are sent. For HTTP health
+/// checks, the path is joined to the address and resolved from the root of the server, so
+/// http://localhost:8081 and http://localhost:8081/ both give http://localhost:8081/health for the
+/// path health or /health. For TCP health checks, the host and port are taken from the address,
+/// using the default port of the scheme when there is none.
+fn health_check_address(address: &str, health_check: &HealthCheckKind) -> Result<String, String> {
+    let url =
+        Url::parse(address).map_err(|e| format!("Invalid backend address {}: {}", address, e))?;
+
+    match health_check {
+        HealthCheckKind::Http { path, .. } => {
+            let path = format!("/{}", path.trim_start_matches('/'));
+            let health_check_url = url
+                .join(&path)
+                .map_err(|e| format!("Invalid health check path {}: {}", path, e))?;
+            Ok(health_check_url.to_string())
+        }
+        HealthCheckKind::Tcp => {
+            let host = url
+                .host_str()
+                .ok_or_else(|| format!("Backend address {} has no host", address))?;
+            let port = url
+                .port_or_known_default()
+                .ok_or_else(|| format!("Backend address {} has no port", address))?;
+            Ok(format!("{}:{}", host, port))
+        }
+    }
+}
+
+/// Returns the given base path starting with a slash, without trailing slash nor empty segments,
+/// so app, /app/ and //app all give /app. A base path made of slashes only gives an empty one, the
+/// requests are then forwarded with their path from the root of the backend server.
+fn normalize_base_path(base_path: &str) -> String {
+    base_path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| format!("/{}", segment))
+        .collect()
+}
+
+/// Returns the address to which a request with the given path and query is forwarded: the scheme,
+/// host and port of the backend server followed by its base path, if any, and the path and query
+/// of the request, like the WebSocket upgrades. The leading slashes of the path are merged into the
+/// one joining it to the base path, so /users gives /app/users and / gives /app/.
+fn forwarded_address(address: &str, base_path: Option<&str>, path: &str, query: &str) -> String {
+    let Ok(mut url) = Url::parse(address) else {
+        return address.to_string();
+    };
+    url.set_path(&format!(
+        "{}/{}",
+        base_path.unwrap_or_default(),
+        path.trim_start_matches('/')
+    ));
+    url.set_query(Some(query).filter(|query| !query.is_empty()));
+    url.to_string()
+}
+
+impl SimpleBackend {
+    /// Returns the headers sent to the backend server with the request described by the context:
+    /// the filtered headers of the client, the headers overridden on the command line, the request
+    /// ID, the X-Forwarded-For, X-Forwarded-Proto and X-Forwarded-Host headers and the Forwarded
+    /// header if it is sent. The headers set by the load balancer replace the overridden ones,
+    /// which replace the ones of the client. The client does not decompress the responses, so its
+    /// accepted encodings are kept and the compressed responses are passed through.
+    fn request_headers(&self, context: &RequestContext) -> HeaderMap {
+        let mut load_balancer_headers = HeaderMap::new();
+        let forwarded_headers = context.forwarded.headers();
+        let set_by_load_balancer = forwarded_headers
+            .iter()
+            .map(|(name, value)| (name.as_str(), *value))
+            .chain([(self.request_id_header.as_str(), context.request_id.as_str())]);
+        for (name, value) in set_by_load_balancer {
+            // The names are valid and the values come from valid headers of the request
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(value),
+            ) {
+                load_balancer_headers.insert(name, value);
+            }
+        }
+
+        let mut headers = HeaderMap::new();
+        for (name, value) in self.request_headers.filter(&context.headers) {
+            if !load_balancer_headers.contains_key(name)
+                && !self.header_overrides.contains_key(name)
+            {
+                headers.append(name.clone(), value.clone());
+            }
+        }
+        for (name, value) in self.header_overrides.iter() {
+            if !load_balancer_headers.contains_key(name) {
+                headers.append(name.clone(), value.clone());
+            }
+        }
+        headers.extend(load_balancer_headers);
+        headers
+    }
+
+    /// Sends an HTTP request with the given method to the health check endpoint. Returns true if
+    /// the backend server answered and its answer meets the conditions of the given assertion.
+    /// When a load field is given, the load reported in the answer is recorded.
+    async fn check_http_health(
+        &self,
+        method: HealthCheckMethod,
+        assertion: &HealthCheckAssertion,
+        load_field: Option<&str>,
+    ) -> bool {
+        debug!(
+            "Sending {:?} health check to {}",
+            method, self.health_check_address
+        );
+        let mut request = match method {
+            HealthCheckMethod::Get => self.client.get(&self.health_check_address),
+            HealthCheckMethod::Head => self.client.head(&self.health_check_address),
+        };
+        // Overrides the absent timeout of the requests, the answer being read included
+        if let Some(health_check_timeout) = self.health_check_timeout {
+            request = request.timeout(health_check_timeout);
+        }
+        match request.send().await {
+            // Without conditions, the server is considered healthy if the health endpoint returns
+            // anything
+            Ok(r) => {
+                info!("Response: {:?}", r);
+
+                let status = r.status();
+                // The backend server answers the same at every health check, so it is only warned
+                // about once
+                if status != StatusCode::OK
+                    && assertion.status.is_none()
+                    && !self.quiet_health_check
+                    && !self.warned_health_check.swap(true, Ordering::Relaxed)
+                {
+                    warn!(
+                        "SimpleBackend server {} does not support health checks on address {}, \
+                        further answers other than a 200 are accepted without warning",
+                        self.address, self.health_check_address
+                    );
+                }
+                let body = if assertion.reads_body() || load_field.is_some() {
+                    match r.text().await {
+                        Ok(body) => body,
+                        Err(e) => {
+                            error!("Failed to read the health check answer: {:?}", e);
+                            return false;
+                        }
+                    }
+                } else {
+                    String::new()
+                };
+                match assertion.check(status, &body) {
+                    Ok(()) => {
+                        if let Some(load_field) = load_field {
+                            self.record_load(load_field, &body);
+                        }
+                        true
+                    }
+                    Err(e) => {
+                        warn!(
+                            "SimpleBackend server {} failed the health check: {}",
+                            self.address, e
+                        );
+                        false
+                    }
+                }
+            }
+            Err(e) if e.is_timeout() => {
+                error!(
+                    "Backend server {} did not answer the health check within {}ms",
+                    self.health_check_address,
+                    self.health_check_timeout.unwrap_or_default().as_millis()
+                );
+                false
+            }
+            Err(e) => {
+                error!("Failed to send request to backend server: {:?}", e);
+                false
+            }
+        }
+    }
+
+    /// Opens a TCP connection to the backend server. Returns true if the connection succeeded
+    /// within the connect timeout and the health check timeout.
+    async fn check_tcp_health(&self) -> bool {
+        debug!("Opening TCP connection to {}", self.health_check_address);
+        let connection = TcpStream::connect(&self.health_check_address);
+        let connect_timeout = [self.connect_timeout, self.health_check_timeout]
+            .into_iter()
+            .flatten()
+            .min();
+        let connection = match connect_timeout {
+            Some(connect_timeout) => match timeout(connect_timeout, connection).await {
+                Ok(connection) => connection,
+                Err(_) => {
+                    error!(
+                        "Backend server {} did not accept the connection within {}ms",
+                        self.health_check_address,
+                        connect_timeout.as_millis()
+                    );
+                    return false;
+                }
+            },
+            None => connection.await,
+        };
+        match connection {
+            Ok(_) => true,
+            Err(e) => {
+                error!(
+                    "Failed to connect to backend server {}: {:?}",
+                    self.health_check_address, e
+                );
+                false
+            }
+        }
+    }
+}
+
+impl SimpleBackend {
+    /// Returns true if the given response time in milliseconds is above the maximum response time
+    /// of the backend server.
+    fn is_too_slow(&self, response_time_ms: f32) -> bool {
+        self.max_response_time
+            .is_some_and(|max| response_time_ms > max.as_secs_f32() * 1000.0)
+    }
+
+    /// Records the load reported by the backend server in the given JSON field of the answer to
+    /// its health check, clamped between 0 and 1. A missing or non numeric field resets it to 0,
+    /// so that a backend server which stops reporting its load gets its full weight back.
+    fn record_load(&self, load_field: &str, body: &str) {
+        let load = serde_json::from_str::<serde_json::Value>(body)
+            .ok()
+            .and_then(|json| json.pointer(load_field)?.as_f64());
+        let load = match load {
+            Some(load) => (load as f32).clamp(0.0, 1.0),
+            None => {
+                warn!(
+                    "SimpleBackend server {} reported no numeric load in {}",
+                    self.address, load_field
+                );
+                0.0
+            }
+        };
+        debug!("backend {} reported a load of {}", self.address, load);
+        *self.load.lock().unwrap() = load;
+    }
+
+    /// Records that the backend server became healthy again, starting its slow start.
+    fn start_slow_start(&self) {
+        if !self.slow_start.is_zero() {
+            *self.healthy_since.lock().unwrap() = Some(Instant::now());
+        }
+    }
+
+    /// Sets the health status of the backend server and returns the previous one. Every change of
+    /// health goes through here, whether found by a health check or by a request, so that each
+    /// one is logged as a health event and posted to the webhook. The caller holds the health check
+    /// counter, so that the changes are not interleaved.
+    fn set_health(&self, new_health: Health) -> Health {
+        let health = Health::from_u8(self.health.swap(new_health.to_u8(), Ordering::Relaxed));
+        if health != new_health {
+            HealthEvent::new(&self.address, health, new_health).emit(self.health_webhook.as_ref());
+        }
+        health
+    }
+}
+
+#[cfg(test)]
+impl SimpleBackend {
+    /// Adds a response time in milliseconds to the moving average of the backend server, as if it
+    /// had answered a request this fast. The first one becomes the average.
+    pub async fn record_response_time(&self, response_time_ms: f32) {
+        self.response_time_ms.write().await.add(response_time_ms);
+    }
+}
+
+/// Fraction of its weight that a backend server has at the start of its slow start.
+const SLOW_START_MIN_FRACTION: f32 = 0.1;
+
+/// Fraction of its weight that a backend server reporting a full load keeps, so that the total
+/// weight of the backend servers is never zero.
+const LOADED_MIN_FRACTION: f32 = 0.05;
+
+/// Counts a request as in flight until it is dropped, including when the request is cancelled.
+struct InFlightGuard<'a> {
+    in_flight: &'a AtomicU32,
+}
+
+impl<'a> InFlightGuard<'a> {
+    fn new(in_flight: &'a AtomicU32) -> Self {
+        in_flight.fetch_add(1, Ordering::SeqCst);
+        Self { in_flight }
+    }
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Lets a request through as the probe of a half-open circuit, if it is one, until its result is
+/// recorded. A cancelled probe is ended when it is dropped, so that another request can probe.
+struct ProbeGuard<'a> {
+    circuit_breaker: &'a Mutex<CircuitBreaker>,
+    is_probe: bool,
+}
+
+impl<'a> ProbeGuard<'a> {
+    fn new(circuit_breaker: &'a Mutex<CircuitBreaker>) -> Self {
+        let is_probe = circuit_breaker.lock().unwrap().start_probe();
+        Self {
+            circuit_breaker,
+            is_probe,
+        }
+    }
+
+    /// Marks the result of the probe as recorded.
+    fn complete(&mut self) {
+        self.is_probe = false;
+    }
+}
+
+impl Drop for ProbeGuard<'_> {
+    fn drop(&mut self) {
+        if self.is_probe {
+            self.circuit_breaker.lock().unwrap().cancel_probe();
         }
     }
 }
@@ -35,88 +612,192 @@ impl Clone for SimpleBackend {
     fn clone(&self) -> Self {
         Self {
             address: self.address.clone(),
+            health_check_kind: self.health_check_kind.clone(),
+            health_check_address: self.health_check_address.clone(),
+            base_path: self.base_path.clone(),
+            request_id_header: self.request_id_header.clone(),
+            request_headers: self.request_headers.clone(),
+            header_overrides: self.header_overrides.clone(),
             response_time_ms: Arc::clone(&self.response_time_ms),
+            health_check_latency_ms: Arc::clone(&self.health_check_latency_ms),
+            response_time_histogram: Arc::clone(&self.response_time_histogram),
             health: Arc::clone(&self.health),
+            health_check_counter: Arc::clone(&self.health_check_counter),
+            health_check_history: Arc::clone(&self.health_check_history),
+            circuit_breaker: Arc::clone(&self.circuit_breaker),
+            outlier_detector: self.outlier_detector.clone(),
+            in_flight: Arc::clone(&self.in_flight),
+            requests_total: Arc::clone(&self.requests_total),
+            errors_total: Arc::clone(&self.errors_total),
+            max_connections: self.max_connections,
+            draining: Arc::clone(&self.draining),
+            weight: Arc::clone(&self.weight),
+            slow_start: self.slow_start,
+            backup: self.backup,
+            tags: Arc::clone(&self.tags),
+            healthy_since: Arc::clone(&self.healthy_since),
+            last_healthy: Arc::clone(&self.last_healthy),
+            load: Arc::clone(&self.load),
+            max_response_time: self.max_response_time,
+            client: self.client.clone(),
+            connect_timeout: self.connect_timeout,
+            health_check_timeout: self.health_check_timeout,
+            quiet_health_check: self.quiet_health_check,
+            warned_health_check: Arc::clone(&self.warned_health_check),
+            health_webhook: self.health_webhook.clone(),
         }
     }
 }
 
 #[async_trait]
 impl Backend for SimpleBackend {
-    /// Checks the health of the backend server by sending a request to the health check endpoint.
-    /// If the server is healthy, the health status is set to Healthy, otherwise it is set to
-    /// Unhealthy.
+    /// Checks the health of the backend server by sending a request to the health check endpoint,
+    /// or by opening a TCP connection to it. The health status is set to Healthy after the healthy
+    /// threshold of consecutive successful health checks, and to Unhealthy after the unhealthy
+    /// threshold of consecutive failed health checks. A health check not answered within the health
+    /// check timeout fails.
     async fn check_health(&self) {
         let start_time = std::time::Instant::now();
 
-        // Sends a health check
-        let health_check_address = self.address.clone() + "health";
-        debug!("Sending health check to {}", health_check_address);
-        let client = Client::new();
-        let response = client.get(&health_check_address).send().await;
+        let is_healthy = match &self.health_check_kind {
+            HealthCheckKind::Http {
+                method,
+                assertion,
+                load_field,
+                ..
+            } => {
+                self.check_http_health(*method, assertion, load_field.as_deref())
+                    .await
+            }
+            HealthCheckKind::Tcp => self.check_tcp_health().await,
+        };
 
         let end_time = std::time::Instant::now();
-        let elapsed_time_ms = end_time.duration_since(start_time).as_millis();
-        info!("checking backend health took {}ms", elapsed_time_ms);
+        // Measured below the millisecond, so that fast backend servers do not all tie at 0ms
+        let elapsed_time_ms = end_time.duration_since(start_time).as_secs_f32() * 1000.0;
+        info!("checking backend health took {:.3}ms", elapsed_time_ms);
+        // A backend server answering too slowly is as good as down
+        let is_healthy = if is_healthy && self.is_too_slow(elapsed_time_ms) {
+            warn!(
+                "SimpleBackend server {} failed the health check: it took {:.0}ms",
+                self.address, elapsed_time_ms
+            );
+            false
+        } else {
+            is_healthy
+        };
+        self.health_check_history.record(is_healthy);
 
-        debug!(
-            "[{}] trying to acquire write lock for response time",
-            self.address
-        );
-        let mut response_time = self.response_time_ms.write().await;
-        debug!("[{}] acquired write lock for response time", self.address);
-
-        *response_time = elapsed_time_ms as f32;
-        drop(response_time);
+        // Kept apart from the response time of the requests, on which the routing is based
+        self.health_check_latency_ms
+            .lock()
+            .unwrap()
+            .add(elapsed_time_ms);
 
-        debug!("[{}] trying to acquire write lock for health", self.address);
-        let mut health = self.health.write().await;
-        debug!("[{}] acquired write lock for health", self.address);
-
-        match response {
-            // The server is considered healthy if the health enpoint returns anything.
-            Ok(r) => {
-                info!("Response: {:?}", r);
+        let mut health_check_counter = self.health_check_counter.lock().unwrap();
+        let health = Health::from_u8(self.health.load(Ordering::Relaxed));
+        let new_health = health_check_counter.record(health, is_healthy);
+        self.set_health(new_health);
+        drop(health_check_counter);
+        if health == Health::Unhealthy && new_health == Health::Healthy {
+            self.start_slow_start();
+        }
+        if new_health == Health::Healthy {
+            *self.last_healthy.lock().unwrap() = Instant::now();
+        }
 
-                if r.status() != StatusCode::OK {
-                    warn!(
-                        "SimpleBackend server {} does not support health checks on address {}",
-                        self.address, health_check_address
-                    );
-                }
+        // The changes of health are logged as events by set_health, the checks leaving it unchanged
+        // would bury them
+        if new_health != health {
+            return;
+        }
+        if is_healthy {
+            debug!(
+                "SimpleBackend server {} answered the health check",
+                self.address
+            );
+        } else {
+            debug!(
+                "SimpleBackend server {} failed the health check",
+                self.address
+            );
+        }
+    }
 
-                info!("SimpleBackend server {} is healthy", self.address);
-                *health = Health::Healthy;
-            }
-            Err(e) => {
-                error!("Failed to send request to backend server: {:?}", e);
-                info!("SimpleBackend server {} is unhealthy", self.address);
-                *health = Health::Unhealthy;
-            }
+    /// Returns how long the backend server has been unhealthy, zero if it is healthy. The circuit
+    /// breaker and the outlier detection are left out, as they only stop the requests for a while.
+    fn unhealthy_for(&self) -> Duration {
+        if Health::from_u8(self.health.load(Ordering::Relaxed)) == Health::Healthy {
+            return Duration::ZERO;
         }
+        self.last_healthy.lock().unwrap().elapsed()
     }
 
-    /// Returns the health status of the backend server.
-    async fn health(&self) -> Health {
-        let h = self.health.read().await;
-        *h
+    /// Returns the health status of the backend server. A backend server whose circuit is open or
+    /// which is ejected as an outlier is reported as Unhealthy.
+    fn health(&self) -> Health {
+        if self.circuit_state() == CircuitState::Open || self.is_ejected() {
+            return Health::Unhealthy;
+        }
+
+        Health::from_u8(self.health.load(Ordering::Relaxed))
     }
 
-    /// Sends a request to the backend server and returns the response in case of success. If the
-    /// request succeeds, the health status is updated to healthy. If the request fails, the health
-    /// status of the backend server is set to Unhealthy.
-    ///
-    /// TODO: You should add arguments to this function to pass the request method, headers, body, etc.
-    async fn send_request(&self) -> Result<Response, Error> {
-        info!("Sending request to backend server {}", self.address);
+    /// Sends the request described by the context to the backend server, with its method and body,
+    /// and returns the response in case of success. With a base path, the request goes to its path
+    /// under the base path, along with its query. The request ID is sent in the request ID header,
+    /// along with the X-Forwarded-For, X-Forwarded-Proto and X-Forwarded-Host headers, the
+    /// Forwarded header if it is sent, the headers overridden on the command line and the filtered
+    /// headers of the client. If the request succeeds, the health status is updated to healthy and
+    /// the circuit is closed. If the request fails, the failure is recorded by the circuit breaker.
+    /// Failed requests and server errors are recorded by the outlier detector.
+    async fn send_request(&self, context: &RequestContext) -> Result<Response, Error> {
+        info!(
+            "Sending request {} to backend server {}",
+            context.request_id, self.address
+        );
+        let _in_flight = InFlightGuard::new(&self.in_flight);
+        let mut probe = ProbeGuard::new(&self.circuit_breaker);
+        if probe.is_probe {
+            info!(
+                "Probing the half-open circuit of backend server {}",
+                self.address
+            );
+        }
+        // The counters are only read by the admin API, so they do not need to be ordered
+        self.requests_total.fetch_add(1, Ordering::Relaxed);
         let start_time = std::time::Instant::now();
 
-        let client = Client::new();
-        let response = client.get(&self.address).send().await;
+        let address = forwarded_address(
+            &self.address,
+            self.base_path.as_deref(),
+            &context.path,
+            &context.query,
+        );
+        let request = self
+            .client
+            .request(context.method.clone(), &address)
+            .headers(self.request_headers(context))
+            .body(context.body.clone());
+        let response = request.send().await;
 
         let end_time = std::time::Instant::now();
-        let elapsed_time_ms = end_time.duration_since(start_time).as_millis();
-        info!("sending request to backend took {}ms", elapsed_time_ms);
+        let elapsed_time_ms = end_time.duration_since(start_time).as_secs_f32() * 1000.0;
+        info!("sending request to backend took {:.3}ms", elapsed_time_ms);
+
+        if let Some(outlier_detector) = &self.outlier_detector {
+            let is_error = response
+                .as_ref()
+                .map_or(true, |r| r.status().is_server_error());
+            let mut outlier_detector = outlier_detector.lock().unwrap();
+            if outlier_detector.record(is_error) {
+                warn!(
+                    "Backend server {} is ejected with an error rate of {:.0}%",
+                    self.address,
+                    outlier_detector.error_rate() * 100.0
+                );
+            }
+        }
 
         debug!(
             "[{}] trying to acquire write lock for response time",
@@ -125,37 +806,264 @@ impl Backend for SimpleBackend {
         let mut response_time = self.response_time_ms.write().await;
         debug!("[{}] acquired write lock for response time", self.address);
 
-        *response_time = elapsed_time_ms as f32;
+        response_time.add(elapsed_time_ms);
+        let average_response_time_ms = response_time.value();
 
-        let r_health = self.health.read().await;
+        drop(response_time);
+
+        if response.is_ok() {
+            self.response_time_histogram
+                .lock()
+                .unwrap()
+                .record(elapsed_time_ms);
+        }
+
+        debug!(
+            "[{}] trying to acquire lock for circuit breaker",
+            self.address
+        );
+        let mut circuit_breaker = self.circuit_breaker.lock().unwrap();
+        debug!("[{}] acquired lock for circuit breaker", self.address);
+        probe.complete();
 
         match response {
             Ok(r) => {
-                if *r_health != Health::Healthy {
-                    debug!("[{}] trying to acquire write lock for health", self.address);
-                    let mut health = self.health.write().await;
-                    debug!("[{}] acquired write lock for health", self.address);
-                    *health = Health::Healthy;
+                if circuit_breaker.state() != CircuitState::Closed {
+                    info!("Circuit of backend server {} is closed", self.address);
+                }
+                circuit_breaker.record_success();
+                drop(circuit_breaker);
+
+                // The backend server answered, but too slowly on average, so it is unhealthy until
+                // a health check finds it healthy again
+                if self.is_too_slow(average_response_time_ms) {
+                    let mut health_check_counter = self.health_check_counter.lock().unwrap();
+                    if self.set_health(Health::Unhealthy) != Health::Unhealthy {
+                        health_check_counter.reset();
+                        warn!(
+                            "Backend server {} is unhealthy, it answers in {:.0}ms on average",
+                            self.address, average_response_time_ms
+                        );
+                    }
+                    drop(health_check_counter);
+                    return Ok(r);
+                }
+
+                // The backend server answered, so it is healthy again and its health checks are
+                // counted anew
+                let mut health_check_counter = self.health_check_counter.lock().unwrap();
+                if self.set_health(Health::Healthy) != Health::Healthy {
+                    health_check_counter.reset();
+                    self.start_slow_start();
                 }
+                drop(health_check_counter);
+                *self.last_healthy.lock().unwrap() = Instant::now();
                 Ok(r)
             }
             Err(e) => {
                 error!("Failed to send request to backend server: {:?}", e);
-                if *r_health != Health::Unhealthy {
-                    debug!("[{}] trying to acquire write lock for health", self.address);
-                    let mut health = self.health.write().await;
-                    debug!("[{}] acquired write lock for health", self.address);
-                    *health = Health::Unhealthy;
+                self.errors_total.fetch_add(1, Ordering::Relaxed);
+                circuit_breaker.record_failure();
+                if circuit_breaker.state() == CircuitState::Open {
+                    warn!(
+                        "Circuit of backend server {} is open after {} consecutive failures",
+                        self.address,
+                        circuit_breaker.consecutive_failures()
+                    );
+                }
+                drop(circuit_breaker);
+
+                // The backend server did not accept the connection, so it is unhealthy until a
+                // health check finds it healthy again
+                if e.is_connect() {
+                    let mut health_check_counter = self.health_check_counter.lock().unwrap();
+                    if self.set_health(Health::Unhealthy) != Health::Unhealthy {
+                        health_check_counter.reset();
+                        warn!(
+                            "Backend server {} is unhealthy, it did not accept the connection",
+                            self.address
+                        );
+                    }
+                    drop(health_check_counter);
                 }
                 Err(e)
             }
         }
     }
 
-    /// Returns the response time in milliseconds of the last request sent to the backend server.
+    /// Returns the moving average of the response time of the backend server to the requests in
+    /// milliseconds, 0 until the first request.
     async fn response_time_ms(&self) -> f32 {
         let response_time = self.response_time_ms.read().await;
-        *response_time
+        response_time.value()
+    }
+
+    /// Moves the moving average of the response time towards 0 by the given fraction of it.
+    async fn decay_response_time(&self, decay: f32) {
+        self.response_time_ms.write().await.decay(decay);
+    }
+
+    /// Returns the moving average of the time taken by the health checks in milliseconds.
+    fn health_check_latency_ms(&self) -> f32 {
+        self.health_check_latency_ms.lock().unwrap().value()
+    }
+
+    /// Returns the state of the circuit breaker of the backend server.
+    fn circuit_state(&self) -> CircuitState {
+        let circuit_breaker = self.circuit_breaker.lock().unwrap();
+        circuit_breaker.state()
+    }
+
+    /// Returns the number of consecutive failed requests sent to the backend server.
+    fn consecutive_failures(&self) -> u32 {
+        let circuit_breaker = self.circuit_breaker.lock().unwrap();
+        circuit_breaker.consecutive_failures()
+    }
+
+    /// Returns the number of consecutive successful requests sent to the backend server.
+    fn consecutive_successes(&self) -> u32 {
+        let circuit_breaker = self.circuit_breaker.lock().unwrap();
+        circuit_breaker.consecutive_successes()
+    }
+
+    /// Returns true if the backend server is ejected as an outlier.
+    fn is_ejected(&self) -> bool {
+        self.outlier_detector
+            .as_ref()
+            .is_some_and(|outlier_detector| outlier_detector.lock().unwrap().is_ejected())
+    }
+
+    /// Returns the number of requests currently sent to the backend server, until it answers.
+    fn in_flight(&self) -> u32 {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of requests sent to the backend server since the load balancer started.
+    fn requests_total(&self) -> u64 {
+        self.requests_total.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of requests sent to the backend server which failed since the load
+    /// balancer started.
+    fn errors_total(&self) -> u64 {
+        self.errors_total.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of passed health checks of the backend server since the load balancer
+    /// started.
+    fn health_checks_passed(&self) -> u64 {
+        self.health_check_history.passed()
+    }
+
+    /// Returns the number of failed health checks of the backend server since the load balancer
+    /// started, the ones answered too slowly included.
+    fn health_checks_failed(&self) -> u64 {
+        self.health_check_history.failed()
+    }
+
+    /// Returns the fraction of the last 64 health checks of the backend server which failed.
+    fn health_check_failure_rate(&self) -> f32 {
+        self.health_check_history.failure_rate()
+    }
+
+    /// Returns the percentiles of the response time of the backend server to the requests which
+    /// it answered, whatever their status. The health checks are not included.
+    fn response_time_percentiles(&self) -> ResponseTimePercentiles {
+        self.response_time_histogram.lock().unwrap().percentiles()
+    }
+
+    /// Returns true if the backend server has as many requests in flight as its maximum number of
+    /// connections. The selection of a backend server and the start of its request are not
+    /// atomic, so concurrent requests can briefly exceed the limit.
+    fn is_saturated(&self) -> bool {
+        self.max_connections
+            .is_some_and(|max_connections| self.in_flight() >= max_connections)
+    }
+
+    /// Returns true if the backend server is draining. The clones of the backend server share the
+    /// same flag.
+    fn is_draining(&self) -> bool {
+        self.draining.load(Ordering::Relaxed)
+    }
+
+    /// Starts or stops draining the backend server.
+    fn set_draining(&self, draining: bool) {
+        self.draining.store(draining, Ordering::Relaxed);
+    }
+
+    /// Returns the weight of the backend server. The clones of the backend server share the same
+    /// weight.
+    fn weight(&self) -> u32 {
+        self.weight.load(Ordering::Relaxed)
+    }
+
+    /// Sets the weight of the backend server, 0 parking it.
+    fn set_weight(&self, weight: u32) {
+        self.weight.store(weight, Ordering::Relaxed);
+    }
+
+    /// Returns the weight of the backend server during its slow start: it ramps up linearly from a
+    /// tenth of its weight when it becomes healthy again to its weight at the end of the slow
+    /// start. It is then reduced in proportion to the load reported by the backend server.
+    fn effective_weight(&self) -> f32 {
+        let weight = self.weight() as f32 * (1.0 - self.load()).max(LOADED_MIN_FRACTION);
+        let Some(healthy_since) = *self.healthy_since.lock().unwrap() else {
+            return weight;
+        };
+        let progress = healthy_since.elapsed().as_secs_f32() / self.slow_start.as_secs_f32();
+        if progress >= 1.0 {
+            return weight;
+        }
+        weight * progress.max(SLOW_START_MIN_FRACTION)
+    }
+
+    /// Returns the load last reported by the backend server.
+    fn load(&self) -> f32 {
+        *self.load.lock().unwrap()
+    }
+
+    /// Returns true if the backend server is a backup.
+    fn is_backup(&self) -> bool {
+        self.backup
+    }
+
+    /// Returns the tags of the backend server.
+    fn tags(&self) -> &BTreeMap<String, String> {
+        &self.tags
+    }
+
+    /// Returns the WebSocket upgrade request described by the context, to the path of the request
+    /// under the base path, if any, with the headers of the other requests. Returns an error if
+    /// the backend server is not reached over plain HTTP.
+    fn upgrade_request(&self, context: &RequestContext) -> Result<UpgradeRequest, String> {
+        let url = Url::parse(&self.address)
+            .map_err(|e| format!("Invalid backend address {}: {}", self.address, e))?;
+        if url.scheme() != "http" {
+            return Err(format!(
+                "WebSockets can only be proxied to backend servers over plain HTTP, not {}",
+                self.address
+            ));
+        }
+        // The host of an IPv6 address keeps its brackets, as required in the Host header
+        let host = url
+            .host_str()
+            .ok_or_else(|| format!("Backend address {} has no host", self.address))?;
+        let port = url.port_or_known_default().unwrap_or(80);
+        let mut path = format!(
+            "{}/{}",
+            self.base_path.as_deref().unwrap_or_default(),
+            context.path.trim_start_matches('/')
+        );
+        if !context.query.is_empty() {
+            path = format!("{}?{}", path, context.query);
+        }
+
+        Ok(UpgradeRequest {
+            authority: format!("{}:{}", host, port),
+            path,
+            headers: self.request_headers(context),
+            connect_timeout: self.connect_timeout,
+        })
     }
 
     /// Returns the name of the backend server.
@@ -163,3 +1071,90 @@ impl Backend for SimpleBackend {
         self.address.as_str()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns an HTTP health check sending a GET to the given path.
+    fn http_health_check(path: &str) -> HealthCheckKind {
+        HealthCheckKind::Http {
+            method: HealthCheckMethod::default(),
+            path: path.to_string(),
+            assertion: HealthCheckAssertion::default(),
+            load_field: None,
+        }
+    }
+
+    #[test]
+    fn joins_the_health_check_path_with_or_without_trailing_slash() {
+        for address in ["http://localhost:8081", "http://localhost:8081/"] {
+            for path in ["health", "/health"] {
+                assert_eq!(
+                    health_check_address(address, &http_health_check(path)),
+                    Ok("http://localhost:8081/health".to_string()),
+                    "{} and {}",
+                    address,
+                    path
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn resolves_the_health_check_path_from_the_root_of_the_server() {
+        let health_check = http_health_check("/status/ready");
+
+        assert_eq!(
+            health_check_address("http://localhost:8081/app/", &health_check),
+            Ok("http://localhost:8081/status/ready".to_string())
+        );
+    }
+
+    #[test]
+    fn takes_the_host_and_port_for_a_tcp_health_check() {
+        for (address, expected) in [
+            ("http://localhost:8081", "localhost:8081"),
+            ("http://localhost:8081/", "localhost:8081"),
+            ("https://example.com/", "example.com:443"),
+        ] {
+            assert_eq!(
+                health_check_address(address, &HealthCheckKind::Tcp),
+                Ok(expected.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_an_invalid_backend_address() {
+        assert!(health_check_address("localhost", &http_health_check("/health")).is_err());
+    }
+
+    #[test]
+    fn forwards_the_path_and_query_without_base_path() {
+        let address = "http://localhost:8081/";
+
+        assert_eq!(
+            forwarded_address(address, None, "/users", "page=2"),
+            "http://localhost:8081/users?page=2"
+        );
+        assert_eq!(
+            forwarded_address(address, None, "/", ""),
+            "http://localhost:8081/"
+        );
+    }
+
+    #[test]
+    fn prepends_the_base_path_to_the_path_of_the_request() {
+        let address = "http://localhost:8081/";
+
+        assert_eq!(
+            forwarded_address(address, Some("/app"), "/users", "page=2"),
+            "http://localhost:8081/app/users?page=2"
+        );
+        assert_eq!(
+            forwarded_address(address, Some("/app"), "/", ""),
+            "http://localhost:8081/app/"
+        );
+    }
+}
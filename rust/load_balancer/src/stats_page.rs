@@ -0,0 +1,74 @@
+use crate::backend_snapshot::BackendSnapshot;
+
+use std::fmt::Write;
+use std::time::Duration;
+
+/// Renders the HTML dashboard of the load balancer: its strategy and a table of the given backend
+/// servers with their health, response time and number of requests and failed requests. The page
+/// reloads itself after the given time, rounded up to whole seconds, and needs no script.
+pub fn render(strategy: &str, backends: &[BackendSnapshot], refresh: Duration) -> String {
+    let mut output = String::new();
+
+    let _ = writeln!(output, "<!DOCTYPE html>");
+    let _ = writeln!(output, "<html>");
+    let _ = writeln!(output, "<head>");
+    let _ = writeln!(output, "<meta charset=\"utf-8\">");
+    let _ = writeln!(
+        output,
+        "<meta http-equiv=\"refresh\" content=\"{}\">",
+        refresh.as_secs_f64().ceil().max(1.0) as u64
+    );
+    let _ = writeln!(output, "<title>Load balancer stats</title>");
+    let _ = writeln!(
+        output,
+        "<style>body {{ font-family: sans-serif; }} table {{ border-collapse: collapse; }} \
+         th, td {{ border: 1px solid #ccc; padding: 4px 8px; text-align: right; }} \
+         th:first-child, td:first-child {{ text-align: left; }} \
+         .Healthy {{ color: green; }} .Unhealthy {{ color: red; }}</style>"
+    );
+    let _ = writeln!(output, "</head>");
+    let _ = writeln!(output, "<body>");
+    let _ = writeln!(output, "<h1>Load balancer stats</h1>");
+    let _ = writeln!(output, "<p>Strategy: {}</p>", escape_html(strategy));
+    let _ = writeln!(output, "<table>");
+    let _ = writeln!(
+        output,
+        "<tr><th>Address</th><th>Health</th><th>Response time (ms)</th><th>Requests</th>\
+         <th>Errors</th></tr>"
+    );
+    for backend in backends {
+        let health = format!("{:?}", backend.health);
+        let _ = writeln!(
+            output,
+            "<tr><td>{}</td><td class=\"{}\">{}</td><td>{:.1}</td><td>{}</td><td>{}</td></tr>",
+            escape_html(&backend.address),
+            health,
+            health,
+            backend.response_time_ms,
+            backend.requests_total,
+            backend.errors_total
+        );
+    }
+    let _ = writeln!(output, "</table>");
+    let _ = writeln!(output, "</body>");
+    let _ = writeln!(output, "</html>");
+
+    output
+}
+
+/// Escapes the characters of the given text which have a meaning in HTML, so that an address or a
+/// group name cannot inject markup into the page.
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
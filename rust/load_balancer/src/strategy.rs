@@ -0,0 +1,39 @@
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::fmt;
+
+/// Strategy used by the load balancer to choose the backend server of each request.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Strategy {
+    /// Sends the requests to the healthy backend servers in turn.
+    #[default]
+    RoundRobin,
+    /// Sends the requests to the healthy backend server with the lowest response time.
+    LeastResponse,
+    /// Sends each request to the fastest of two randomly picked healthy backend servers.
+    #[value(alias = "p2c")]
+    PowerOfTwoChoices,
+    /// Sends each request to a healthy backend server picked at random in proportion to its weight.
+    WeightedRandom,
+    /// Always sends the requests of a client IP address to the same healthy backend server.
+    ConsistentHash,
+    /// Sends the requests to the backend servers on the continent closest to the client. Requires
+    /// --geoip-database.
+    Geo,
+}
+
+impl fmt::Display for Strategy {
+    /// Writes the name of the strategy as given with --strategy.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Strategy::RoundRobin => "round-robin",
+            Strategy::LeastResponse => "least-response",
+            Strategy::PowerOfTwoChoices => "power-of-two-choices",
+            Strategy::WeightedRandom => "weighted-random",
+            Strategy::ConsistentHash => "consistent-hash",
+            Strategy::Geo => "geo",
+        };
+        f.write_str(name)
+    }
+}
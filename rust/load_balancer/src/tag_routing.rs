@@ -0,0 +1,57 @@
+use crate::backend::Backend;
+use crate::health::Health;
+use crate::request_context::RequestContext;
+
+use actix_web::http::header::HeaderName;
+use actix_web::HttpRequest;
+use std::collections::BTreeMap;
+
+/// Returns the tags asked for by the given route tag headers of the request, each header mapping
+/// to the name of a tag, such as X-Route-Version to version. The headers missing from the request
+/// or with an empty value ask for no tag.
+pub fn requested_tags(
+    request: &HttpRequest,
+    tag_headers: &[(HeaderName, String)],
+) -> BTreeMap<String, String> {
+    tag_headers
+        .iter()
+        .filter_map(|(header, tag)| {
+            let value = request.headers().get(header)?.to_str().ok()?.trim();
+            (!value.is_empty()).then(|| (tag.clone(), value.to_string()))
+        })
+        .collect()
+}
+
+/// Returns true if the backend server has all the given tags with the same values.
+pub fn has_tags(backend: &dyn Backend, tags: &BTreeMap<String, String>) -> bool {
+    tags.iter()
+        .all(|(tag, value)| backend.tags().get(tag) == Some(value))
+}
+
+/// Returns the tags a backend server must have to be selected for the request: the tags asked for
+/// by the request if at least one of the given backend servers has them and can receive it, that
+/// is it is healthy, not draining and has not reached its maximum number of connections. Otherwise
+/// None is returned, and the request falls back to all the backend servers, so that a request
+/// asking for a version which is not deployed or not available is still answered.
+pub fn required_tags<'a, 'b>(
+    context: &'a RequestContext,
+    backends: impl IntoIterator<Item = &'b dyn Backend>,
+) -> Option<&'a BTreeMap<String, String>> {
+    if context.tags.is_empty() {
+        return None;
+    }
+    backends
+        .into_iter()
+        .any(|backend| {
+            backend.health() == Health::Healthy
+                && !backend.is_draining()
+                && !backend.is_saturated()
+                && has_tags(backend, &context.tags)
+        })
+        .then_some(&context.tags)
+}
+
+/// Returns true if the backend server has the required tags, or if no tags are required.
+pub fn matches(required_tags: Option<&BTreeMap<String, String>>, backend: &dyn Backend) -> bool {
+    required_tags.is_none_or(|tags| has_tags(backend, tags))
+}
@@ -0,0 +1,51 @@
+use crate::tls_version::TlsVersion;
+
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::ServerConfig;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Builds the TLS configuration of the load balancer frontend from a PEM encoded certificate chain
+/// and private key, accepting the clients from the given TLS version upwards. Returns a readable
+/// error if the files cannot be loaded or do not match.
+pub fn load_server_config(
+    cert_path: &Path,
+    key_path: &Path,
+    min_version: TlsVersion,
+) -> Result<ServerConfig, String> {
+    let cert_file = File::open(cert_path).map_err(|e| {
+        format!(
+            "Failed to open TLS certificate {}: {}",
+            cert_path.display(),
+            e
+        )
+    })?;
+    let certificates = rustls_pemfile::certs(&mut BufReader::new(cert_file))
+        .collect::<Result<Vec<CertificateDer<'static>>, _>>()
+        .map_err(|e| {
+            format!(
+                "Failed to read TLS certificate {}: {}",
+                cert_path.display(),
+                e
+            )
+        })?;
+    if certificates.is_empty() {
+        return Err(format!("No certificate found in {}", cert_path.display()));
+    }
+
+    let key_file = File::open(key_path)
+        .map_err(|e| format!("Failed to open TLS key {}: {}", key_path.display(), e))?;
+    let private_key: PrivateKeyDer<'static> =
+        rustls_pemfile::private_key(&mut BufReader::new(key_file))
+            .map_err(|e| format!("Failed to read TLS key {}: {}", key_path.display(), e))?
+            .ok_or_else(|| format!("No private key found in {}", key_path.display()))?;
+
+    ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_protocol_versions(min_version.protocol_versions())
+        .map_err(|e| format!("Invalid TLS protocol versions: {}", e))?
+        .with_no_client_auth()
+        .with_single_cert(certificates, private_key)
+        .map_err(|e| format!("Invalid TLS certificate or key: {}", e))
+}
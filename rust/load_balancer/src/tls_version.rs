@@ -0,0 +1,57 @@
+use clap::ValueEnum;
+use rustls::SupportedProtocolVersion;
+
+/// Protocol versions accepted from TLS 1.2 upwards.
+static FROM_TLS12: [&SupportedProtocolVersion; 2] =
+    [&rustls::version::TLS13, &rustls::version::TLS12];
+
+/// Protocol versions accepted from TLS 1.3 upwards.
+static FROM_TLS13: [&SupportedProtocolVersion; 1] = [&rustls::version::TLS13];
+
+/// Minimum TLS version accepted from the clients when serving HTTPS. TLS 1.0 and 1.1 are never
+/// accepted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum TlsVersion {
+    /// TLS 1.2 and 1.3.
+    #[default]
+    #[value(name = "1.2")]
+    Tls12,
+    /// TLS 1.3 only.
+    #[value(name = "1.3")]
+    Tls13,
+}
+
+impl TlsVersion {
+    /// Returns the protocol versions accepted from the clients, from this version upwards.
+    pub fn protocol_versions(self) -> &'static [&'static SupportedProtocolVersion] {
+        match self {
+            TlsVersion::Tls12 => &FROM_TLS12,
+            TlsVersion::Tls13 => &FROM_TLS13,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_the_versions_from_the_minimum_one_upwards() {
+        assert_eq!(
+            TlsVersion::Tls12.protocol_versions(),
+            [&rustls::version::TLS13, &rustls::version::TLS12]
+        );
+        assert_eq!(
+            TlsVersion::Tls13.protocol_versions(),
+            [&rustls::version::TLS13]
+        );
+    }
+
+    #[test]
+    fn parses_the_supported_versions_only() {
+        assert_eq!(TlsVersion::from_str("1.2", false), Ok(TlsVersion::Tls12));
+        assert_eq!(TlsVersion::from_str("1.3", false), Ok(TlsVersion::Tls13));
+        assert!(TlsVersion::from_str("1.1", false).is_err());
+        assert!(TlsVersion::from_str("1.0", false).is_err());
+    }
+}
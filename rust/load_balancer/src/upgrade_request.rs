@@ -0,0 +1,23 @@
+use reqwest::header::HeaderMap;
+use std::time::Duration;
+
+/// WebSocket upgrade request to send to a backend server, built by the backend server from the
+/// request context like its other requests.
+#[derive(Clone, Debug)]
+pub struct UpgradeRequest {
+    /// Host and port of the backend server, such as localhost:8081 or [::1]:8081, to which the
+    /// connection is opened and which is sent in the Host header.
+    pub authority: String,
+
+    /// Path and query of the request, under the base path of the backend server if it has one.
+    pub path: String,
+
+    /// Headers of the request, filtered and overridden like the ones of the other requests, with
+    /// the request ID and the X-Forwarded-* headers. The hop-by-hop headers are left out, the
+    /// Connection and Upgrade headers of the upgrade included.
+    pub headers: HeaderMap,
+
+    /// Maximum time given to the backend server to accept the connection. None leaves it to the
+    /// operating system.
+    pub connect_timeout: Option<Duration>,
+}
@@ -0,0 +1,308 @@
+use crate::affinity;
+use crate::forwarded_headers::ForwardedHeaders;
+use crate::header_filter;
+use crate::in_flight::{InFlightRequest, InFlightRequests};
+use crate::load_balancer::LoadBalancer;
+use crate::proxy_protocol;
+use crate::rate_limiter::RateLimiter;
+use crate::request_context::RequestContext;
+use crate::request_id;
+use crate::tag_routing;
+use crate::upgrade_request::UpgradeRequest;
+use crate::ResponseHeaders;
+
+use actix_web::guard::GuardContext;
+use actix_web::http::header::{self, HeaderName, HeaderValue};
+use actix_web::http::StatusCode;
+use actix_web::web::{Bytes, Data, Payload};
+use actix_web::{HttpRequest, HttpResponse};
+use futures_core::Stream;
+use log::{error, info, warn};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt, ReadBuf};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::TcpStream;
+use tokio::sync::RwLock as TokioRwLock;
+use tokio::time::timeout;
+
+/// Maximum size of the head of the response of a backend server to the upgrade request.
+const MAX_RESPONSE_HEAD_SIZE: usize = 16 * 1024;
+
+/// Returns true if the request asks to upgrade the connection to a WebSocket.
+pub fn is_upgrade(context: &GuardContext) -> bool {
+    context
+        .head()
+        .headers()
+        .get(header::UPGRADE)
+        .is_some_and(|upgrade| upgrade.as_bytes().eq_ignore_ascii_case(b"websocket"))
+}
+
+/// WebSocket route of the load balancer. Selects a backend server, sends it the upgrade request of
+/// the client and, once the backend server accepts it, tunnels the bytes of the connection in both
+/// directions until either side closes it. The frames are not decoded. The upgrade request is sent
+/// like the other requests, under the base path of the backend server and with the filtered and
+/// overridden headers of the client. The WebSocket counts as a request in flight until it is
+/// closed, so that it is bounded by the maximum number of concurrent requests and drained at
+/// shutdown. Answers with a 503 when too many requests are in flight or no backend server is
+/// available and with a 502 when the backend server cannot be reached over plain HTTP or refuses
+/// the upgrade.
+pub async fn proxy(
+    load_balancer: Data<Arc<TokioRwLock<Arc<dyn LoadBalancer>>>>,
+    in_flight_requests: Data<Arc<InFlightRequests>>,
+    response_headers: Data<ResponseHeaders>,
+    rate_limiter: Data<RateLimiter>,
+    request: HttpRequest,
+    payload: Payload,
+) -> HttpResponse {
+    if rate_limiter
+        .check(proxy_protocol::client_ip(&request))
+        .is_err()
+    {
+        return HttpResponse::TooManyRequests().body("Too many requests");
+    }
+
+    let in_flight_request = match in_flight_requests.start().await {
+        Ok(in_flight_request) => in_flight_request,
+        Err(e) => {
+            warn!("Rejected WebSocket, too many requests in flight: {}", e);
+            return HttpResponse::ServiceUnavailable().body("Too many requests in flight");
+        }
+    };
+
+    let request_id = request
+        .headers()
+        .get(&response_headers.request_id)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+        .unwrap_or_else(request_id::generate);
+    // Read before the connection info, which borrows the request until the context is built
+    let affinity = affinity::requested_backend_id(
+        &request,
+        response_headers.affinity_cookie.as_deref(),
+        response_headers.affinity_ttl,
+    );
+    let context = RequestContext {
+        client_address: proxy_protocol::client_ip(&request).map(|ip| ip.to_string()),
+        method: reqwest::Method::GET,
+        path: request.path().to_string(),
+        query: request.query_string().to_string(),
+        request_id: request_id.clone(),
+        affinity,
+        tags: tag_routing::requested_tags(&request, &response_headers.route_tags),
+        forwarded: ForwardedHeaders::new(&request, response_headers.forwarded_header),
+        headers: header_filter::request_headers(&request),
+        body: Default::default(),
+    };
+
+    let backend = load_balancer
+        .read()
+        .await
+        .next_available_backend(&context)
+        .await;
+    let backend = match backend {
+        Ok(backend) => backend,
+        Err(e) => {
+            warn!("Failed to select a backend server for a WebSocket: {}", e);
+            return HttpResponse::ServiceUnavailable().body("No backend server available");
+        }
+    };
+    info!(
+        "Proxying WebSocket {} to backend server {}",
+        request_id,
+        backend.address()
+    );
+
+    let upgraded = match backend.upgrade_request(&context) {
+        Ok(upgrade_request) => upgrade(backend.address(), upgrade_request).await,
+        Err(e) => Err(e),
+    };
+    let upgraded = match upgraded {
+        Ok(upgraded) => upgraded,
+        Err(e) => {
+            error!("{}", e);
+            return HttpResponse::BadGateway().body("Failed to upgrade the connection");
+        }
+    };
+    if upgraded.status != StatusCode::SWITCHING_PROTOCOLS {
+        warn!(
+            "Backend server {} refused the WebSocket upgrade with a {}",
+            backend.address(),
+            upgraded.status
+        );
+        return HttpResponse::BadGateway().body("The backend server refused the upgrade");
+    }
+
+    // The frames of the client are sent to the backend server as they are received
+    actix_web::rt::spawn(forward(payload, upgraded.writer));
+
+    let mut response = HttpResponse::SwitchingProtocols();
+    response.upgrade("websocket");
+    for (name, value) in upgraded.headers {
+        if name != header::CONNECTION && name != header::UPGRADE {
+            response.append_header((name, value));
+        }
+    }
+    response
+        .insert_header((response_headers.request_id.clone(), request_id))
+        .streaming(BackendStream {
+            rest: Some(upgraded.rest),
+            reader: upgraded.reader,
+            in_flight_request: Some(in_flight_request),
+        })
+}
+
+/// Connection to a backend server after it answered the upgrade request.
+struct Upgraded {
+    /// Half of the connection receiving the bytes of the backend server.
+    reader: OwnedReadHalf,
+
+    /// Half of the connection sending bytes to the backend server.
+    writer: OwnedWriteHalf,
+
+    /// Status of the response to the upgrade request, 101 if the backend server accepted it.
+    status: StatusCode,
+
+    /// Headers of the response to the upgrade request.
+    headers: Vec<(HeaderName, HeaderValue)>,
+
+    /// Bytes received after the head of the response, already part of the WebSocket.
+    rest: Bytes,
+}
+
+/// Connects to the backend server with the given address within the connect timeout of the
+/// upgrade request, sends it the upgrade request and reads the head of its response.
+async fn upgrade(address: &str, upgrade_request: UpgradeRequest) -> Result<Upgraded, String> {
+    let connect = TcpStream::connect(upgrade_request.authority.as_str());
+    let stream = match upgrade_request.connect_timeout {
+        Some(connect_timeout) => timeout(connect_timeout, connect).await.map_err(|_| {
+            format!(
+                "Failed to connect to backend server {} within {}ms",
+                address,
+                connect_timeout.as_millis()
+            )
+        })?,
+        None => connect.await,
+    }
+    .map_err(|e| format!("Failed to connect to backend server {}: {}", address, e))?;
+    let (mut reader, mut writer) = stream.into_split();
+
+    // The hop-by-hop headers of the upgrade are not among the forwarded ones and are set again
+    let mut head = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nConnection: Upgrade\r\nUpgrade: websocket\r\n",
+        upgrade_request.path, upgrade_request.authority
+    )
+    .into_bytes();
+    for (name, value) in &upgrade_request.headers {
+        head.extend_from_slice(name.as_str().as_bytes());
+        head.extend_from_slice(b": ");
+        head.extend_from_slice(value.as_bytes());
+        head.extend_from_slice(b"\r\n");
+    }
+    head.extend_from_slice(b"\r\n");
+    writer
+        .write_all(&head)
+        .await
+        .map_err(|e| format!("Failed to send the upgrade to {}: {}", address, e))?;
+
+    let mut response = Vec::new();
+    let head_end = loop {
+        if let Some(position) = response.windows(4).position(|window| window == b"\r\n\r\n") {
+            break position;
+        }
+        if response.len() > MAX_RESPONSE_HEAD_SIZE {
+            return Err(format!("Response head of {} is too large", address));
+        }
+        let mut buffer = [0; 4096];
+        let read = reader
+            .read(&mut buffer)
+            .await
+            .map_err(|e| format!("Failed to read the upgrade from {}: {}", address, e))?;
+        if read == 0 {
+            return Err(format!("Backend server {} closed the connection", address));
+        }
+        response.extend_from_slice(&buffer[..read]);
+    };
+
+    let head = String::from_utf8_lossy(&response[..head_end]);
+    let mut lines = head.split("\r\n");
+    let status = lines
+        .next()
+        .and_then(|status_line| status_line.split(' ').nth(1))
+        .and_then(|status| status.parse::<u16>().ok())
+        .and_then(|status| StatusCode::from_u16(status).ok())
+        .ok_or_else(|| format!("Invalid response of {}: {}", address, head))?;
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .filter_map(|(name, value)| {
+            Some((
+                HeaderName::from_bytes(name.trim().as_bytes()).ok()?,
+                HeaderValue::from_str(value.trim()).ok()?,
+            ))
+        })
+        .collect();
+    let rest = Bytes::copy_from_slice(&response[head_end + 4..]);
+
+    Ok(Upgraded {
+        reader,
+        writer,
+        status,
+        headers,
+        rest,
+    })
+}
+
+/// Sends the bytes received from the client to the backend server until either side closes the
+/// connection.
+async fn forward(mut payload: Payload, mut writer: OwnedWriteHalf) {
+    while let Some(chunk) = std::future::poll_fn(|cx| Pin::new(&mut payload).poll_next(cx)).await {
+        let Ok(chunk) = chunk else {
+            break;
+        };
+        if writer.write_all(&chunk).await.is_err() {
+            break;
+        }
+    }
+    let _ = writer.shutdown().await;
+}
+
+/// Bytes sent by the backend server after accepting the upgrade, streamed to the client. The
+/// WebSocket stays in flight until the backend server closes the connection.
+struct BackendStream {
+    /// Bytes received with the head of the response of the backend server, sent first.
+    rest: Option<Bytes>,
+
+    /// Connection to the backend server.
+    reader: OwnedReadHalf,
+
+    /// The WebSocket, `None` once the backend server closed the connection.
+    in_flight_request: Option<InFlightRequest>,
+}
+
+impl Stream for BackendStream {
+    type Item = std::io::Result<Bytes>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if let Some(rest) = self.rest.take().filter(|rest| !rest.is_empty()) {
+            return Poll::Ready(Some(Ok(rest)));
+        }
+
+        let mut buffer = [0; 8192];
+        let mut read_buffer = ReadBuf::new(&mut buffer);
+        match Pin::new(&mut self.reader).poll_read(cx, &mut read_buffer) {
+            Poll::Ready(Ok(())) if read_buffer.filled().is_empty() => {
+                if let Some(in_flight_request) = self.in_flight_request.take() {
+                    in_flight_request.finish();
+                }
+                Poll::Ready(None)
+            }
+            Poll::Ready(Ok(())) => {
+                Poll::Ready(Some(Ok(Bytes::copy_from_slice(read_buffer.filled()))))
+            }
+            Poll::Ready(Err(e)) => Poll::Ready(Some(Err(e))),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
@@ -0,0 +1,240 @@
+use crate::affinity;
+use crate::backend::Backend;
+use crate::backend_response::BackendResponse;
+use crate::backend_snapshot::BackendSnapshot;
+use crate::forwarding;
+use crate::health::Health;
+use crate::health_sweep;
+use crate::internal_error::InternalError;
+use crate::load_balancer::LoadBalancer;
+use crate::metrics::Metrics;
+use crate::request_context::RequestContext;
+use crate::strategy::Strategy;
+use crate::tag_routing;
+
+use async_trait::async_trait;
+use log::{debug, info};
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::sync::{Arc, Mutex};
+use tokio::sync::RwLock as TokioRwLock;
+use tokio::time::Duration;
+
+/// Load balancer sending each request to a healthy backend server picked at random, with a
+/// probability proportional to its weight. Unlike the round robin, the requests of a heavier
+/// backend server are spread over time instead of coming in turns.
+#[derive(Debug)]
+pub struct WeightedRandomLoadBalancer {
+    /// List of backend servers
+    backends: TokioRwLock<Vec<Box<dyn Backend>>>,
+
+    /// Random number generator used to pick the backend servers.
+    rng: Mutex<StdRng>,
+
+    /// Maximum time spent selecting the backend server to which a request is sent.
+    selection_timeout: Duration,
+
+    /// Metrics updated on each request.
+    metrics: Arc<Metrics>,
+
+    /// Maximum number of backend servers health checked at the same time.
+    health_check_concurrency: usize,
+}
+
+impl WeightedRandomLoadBalancer {
+    /// Creates a new load balancer with the given list of backend servers to route the requests
+    /// to, picked with the given random number generator. Selecting a backend server fails if it
+    /// takes longer than the selection timeout.
+    pub fn new(
+        backends: Vec<Box<dyn Backend>>,
+        rng: StdRng,
+        selection_timeout: Duration,
+        metrics: Arc<Metrics>,
+        health_check_concurrency: usize,
+    ) -> Self {
+        Self {
+            backends: TokioRwLock::new(backends),
+            rng: Mutex::new(rng),
+            selection_timeout,
+            metrics,
+            health_check_concurrency,
+        }
+    }
+}
+
+/// Returns the index of the weight in which the sample falls, given the cumulative weights and a
+/// sample between 0 and the total weight.
+fn weighted_index(cumulative_weights: &[f32], sample: f32) -> usize {
+    cumulative_weights
+        .partition_point(|&cumulative_weight| cumulative_weight <= sample)
+        .min(cumulative_weights.len() - 1)
+}
+
+#[async_trait]
+impl LoadBalancer for WeightedRandomLoadBalancer {
+    /// Returns the backend server to which the client is pinned by its affinity if it can receive
+    /// the request, otherwise a healthy backend server which is not draining, picked at random
+    /// with a probability proportional to its effective weight. The parked backend servers, with a
+    /// weight of 0, are never picked. Only the backend servers having the tags asked for by the
+    /// request are picked, unless none of them is available. If none are available, an error is
+    /// returned.
+    async fn next_available_backend(
+        &self,
+        context: &RequestContext,
+    ) -> Result<Box<dyn Backend>, String> {
+        let backends = self.backends.read().await;
+        if let Some(backend) = backends
+            .iter()
+            .find(|backend| affinity::is_pinned(context, backend.as_ref()))
+        {
+            return Ok(backend.clone());
+        }
+
+        // The cumulative weights are built from the backend servers healthy at the time of the
+        // request, so that a backend server leaves or joins the distribution with its health
+        let required_tags =
+            tag_routing::required_tags(context, backends.iter().map(|backend| backend.as_ref()));
+        let mut healthy_backends = Vec::new();
+        let mut cumulative_weights = Vec::new();
+        let mut total_weight = 0.0;
+        for backend in backends.iter() {
+            if backend.health() == Health::Healthy
+                && !backend.is_draining()
+                && backend.weight() > 0
+                && tag_routing::matches(required_tags, backend.as_ref())
+            {
+                total_weight += backend.effective_weight();
+                healthy_backends.push(backend);
+                cumulative_weights.push(total_weight);
+            }
+        }
+        if healthy_backends.is_empty() {
+            return Err("No backend server available".to_string());
+        }
+
+        let sample = self.rng.lock().unwrap().gen_range(0.0..total_weight);
+        let backend = healthy_backends[weighted_index(&cumulative_weights, sample)];
+        debug!(
+            "picked {} with a weight of {} out of {}",
+            backend.address(),
+            backend.effective_weight(),
+            total_weight
+        );
+        Ok(backend.clone())
+    }
+
+    /// Sends a request to the selected backend server. Returns an error if no backend server is
+    /// reachable.
+    async fn send_request(
+        &self,
+        context: &RequestContext,
+    ) -> Result<BackendResponse, InternalError> {
+        forwarding::send_to_selected_backend(
+            self.next_available_backend(context),
+            self.selection_timeout,
+            &self.metrics,
+            context,
+        )
+        .await
+    }
+
+    /// Checks and update the health status of all backend servers.
+    async fn check_backends_healths(&self) {
+        // This is used for profiling only
+        let start_time = std::time::Instant::now();
+
+        // Check a copy of the list so that backend servers can be added or removed meanwhile
+        let backends = self.backends.read().await.clone();
+        let health_checks = backends
+            .iter()
+            .map(|backend| backend.check_health())
+            .collect();
+        health_sweep::check_healths(health_checks, self.health_check_concurrency).await;
+
+        // For profiling only, measures how much time it took to check all backends health
+        let end_time = std::time::Instant::now();
+        let elapsed_time = end_time.duration_since(start_time).as_millis();
+        info!("checking all backends health took {}ms", elapsed_time);
+    }
+
+    /// Returns the current state of all backend servers.
+    async fn backends_snapshot(&self) -> Vec<BackendSnapshot> {
+        let backends = self.backends.read().await;
+        let mut snapshots = Vec::with_capacity(backends.len());
+        for backend in backends.iter() {
+            snapshots.push(BackendSnapshot::new(backend.as_ref()).await);
+        }
+        snapshots
+    }
+
+    /// Returns the number of healthy backend servers.
+    async fn healthy_count(&self) -> usize {
+        let backends = self.backends.read().await;
+        backends
+            .iter()
+            .filter(|backend| backend.health() == Health::Healthy)
+            .count()
+    }
+
+    /// Adds a backend server to which the requests can be sent. Returns an error if a backend
+    /// server with the same address already exists.
+    async fn add_backend(&self, backend: Box<dyn Backend>) -> Result<(), String> {
+        let mut backends = self.backends.write().await;
+        if backends.iter().any(|b| b.address() == backend.address()) {
+            return Err(format!(
+                "Backend server {} already exists",
+                backend.address()
+            ));
+        }
+
+        info!("Adding backend server {}", backend.address());
+        backends.push(backend);
+        Ok(())
+    }
+
+    /// Removes the backend server with the given address. Returns an error if there is no backend
+    /// server with this address.
+    async fn remove_backend(&self, address: &str) -> Result<(), String> {
+        let mut backends = self.backends.write().await;
+        let Some(index) = backends.iter().position(|b| b.address() == address) else {
+            return Err(format!("No backend server with address {}", address));
+        };
+
+        info!("Removing backend server {}", address);
+        backends.remove(index);
+        Ok(())
+    }
+
+    /// Starts or stops draining the backend server with the given address. Returns an error if
+    /// there is no backend server with this address.
+    async fn set_draining(&self, address: &str, draining: bool) -> Result<(), String> {
+        let backends = self.backends.read().await;
+        let Some(backend) = backends.iter().find(|b| b.address() == address) else {
+            return Err(format!("No backend server with address {}", address));
+        };
+
+        info!(
+            "Setting draining of backend server {} to {}",
+            address, draining
+        );
+        backend.set_draining(draining);
+        Ok(())
+    }
+
+    /// Sets the weight of the backend server with the given address. Returns an error if there is
+    /// no backend server with this address.
+    async fn set_weight(&self, address: &str, weight: u32) -> Result<(), String> {
+        let backends = self.backends.read().await;
+        let Some(backend) = backends.iter().find(|b| b.address() == address) else {
+            return Err(format!("No backend server with address {}", address));
+        };
+
+        info!("Setting weight of backend server {} to {}", address, weight);
+        backend.set_weight(weight);
+        Ok(())
+    }
+
+    fn strategy(&self) -> String {
+        Strategy::WeightedRandom.to_string()
+    }
+}
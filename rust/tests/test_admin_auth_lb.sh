@@ -0,0 +1,93 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that the admin API only answers the requests carrying the token given in
+# LB_ADMIN_TOKEN, and that the load balancer itself does not require it
+# ------------------------------------------------------------------------------
+
+# Prints the status of a request to the admin API with the given curl options
+admin_status() {
+    curl --silent --output /dev/null --write-out "%{http_code}" "$@" \
+        http://localhost:9090/admin/backends
+}
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend server...${NC}"
+cargo run -p be -- -n "backend1" -p 8081 > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+LB_ADMIN_TOKEN=secret cargo run -p lb -- --admin-port 9090 "http://localhost:8081/" \
+    &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+wait_for_server "admin API" 9090
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+authorized_status=$(admin_status --header "Authorization: Bearer secret")
+authorized_backends=$(curl --silent --header "Authorization: Bearer secret" \
+    http://localhost:9090/admin/backends)
+missing_status=$(admin_status)
+missing_challenge=$(curl --silent --include http://localhost:9090/admin/backends \
+    | grep -i "^www-authenticate")
+wrong_status=$(admin_status --header "Authorization: Bearer secreT")
+prefix_status=$(admin_status --header "Authorization: Bearer secret2")
+basic_status=$(admin_status --user "admin:secret")
+unauthorized_add_status=$(admin_status --header "Content-Type: application/json" \
+    --data '{"address": "http://localhost:8082/"}')
+backends_after_add=$(curl --silent --header "Authorization: Bearer secret" \
+    http://localhost:9090/admin/backends)
+lb_answer=$(curl --silent http://localhost:8080/)
+
+# Assert -----------------------------------------------------------------------
+if [[ $authorized_status -eq 200 && $authorized_backends == *"localhost:8081"* ]]; then
+    echo -e "${GREEN}The request with the token was answered.${NC}"
+else
+    echo -e "${RED}With the token, the admin API answered ${authorized_status}: ${authorized_backends}.${NC}"
+    test_passed=false
+fi
+
+if [[ $missing_status -eq 401 && $missing_challenge == *"Bearer"* ]]; then
+    echo -e "${GREEN}The request without token was rejected.${NC}"
+else
+    echo -e "${RED}Without token, the admin API answered ${missing_status} (${missing_challenge}).${NC}"
+    test_passed=false
+fi
+
+if [[ $wrong_status -eq 401 && $prefix_status -eq 401 && $basic_status -eq 401 ]]; then
+    echo -e "${GREEN}The requests with a wrong token were rejected.${NC}"
+else
+    echo -e "${RED}With wrong tokens, the admin API answered ${wrong_status}, ${prefix_status} and ${basic_status}.${NC}"
+    test_passed=false
+fi
+
+if [[ $unauthorized_add_status -eq 401 && $backends_after_add != *"localhost:8082"* ]]; then
+    echo -e "${GREEN}The backend server was not added without token.${NC}"
+else
+    echo -e "${RED}Without token, adding a backend server answered ${unauthorized_add_status}: ${backends_after_add}.${NC}"
+    test_passed=false
+fi
+
+if [[ $lb_answer == *"backend1"* ]]; then
+    echo -e "${GREEN}The load balancer answered without token.${NC}"
+else
+    echo -e "${RED}The load balancer answered ${lb_answer}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend server and load balancer...${NC}"
+kill_pids $backend1_pid $lb_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,81 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that POST /admin/health-check checks the health of the backend servers
+# without waiting for the next health check and returns their new health, and
+# that it requires the admin token
+# ------------------------------------------------------------------------------
+
+# Forces a health check through the admin API with the given curl options and
+# prints the health of each backend server, by address
+check_health() {
+    curl --silent --request POST "$@" http://localhost:9090/admin/health-check | python3 -c '
+import json, sys
+print(" ".join(backend["address"] + "=" + backend["health"] for backend in json.load(sys.stdin)))
+'
+}
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+cargo run -p be -- -n "backend1" -p 8081 > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+cargo run -p be -- -n "backend2" -p 8082 > /dev/null 2>&1 &
+backend2_pid=$!
+wait_for_server "backend2" 8082
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+# The periodic health checks are too rare to run during the test
+LB_ADMIN_TOKEN=secret cargo run -p lb -- -i 60 --health-check-status 200 --admin-port 9090 \
+    "http://localhost:8081/" "http://localhost:8082/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+wait_for_server "admin API" 9090
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+curl --silent --output /dev/null --request POST "localhost:8081/admin/fail?mode=health"
+failed_healths=$(check_health --header "Authorization: Bearer secret")
+unauthorized_status=$(curl --silent --output /dev/null --write-out "%{http_code}" \
+    --request POST http://localhost:9090/admin/health-check)
+
+curl --silent --output /dev/null --request POST localhost:8081/admin/recover
+recovered_healths=$(check_health --header "Authorization: Bearer secret")
+
+# Assert -----------------------------------------------------------------------
+if [[ $failed_healths == "http://localhost:8081/=Unhealthy http://localhost:8082/=Healthy" ]]; then
+    echo -e "${GREEN}The failing backend server was found unhealthy at once.${NC}"
+else
+    echo -e "${RED}After backend1 failed, the health check returned ${failed_healths}.${NC}"
+    test_passed=false
+fi
+
+if [[ $recovered_healths == "http://localhost:8081/=Healthy http://localhost:8082/=Healthy" ]]; then
+    echo -e "${GREEN}The recovered backend server was found healthy at once.${NC}"
+else
+    echo -e "${RED}After backend1 recovered, the health check returned ${recovered_healths}.${NC}"
+    test_passed=false
+fi
+
+if [[ $unauthorized_status -eq 401 ]]; then
+    echo -e "${GREEN}The health check without token was rejected.${NC}"
+else
+    echo -e "${RED}Without token, the health check answered ${unauthorized_status}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $backend2_pid $lb_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
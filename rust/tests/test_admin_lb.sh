@@ -0,0 +1,95 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test adding and removing backend servers through the admin API
+# ------------------------------------------------------------------------------
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+cargo run -p be -- -n "backend1" -p 8081 > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+cargo run -p be -- -n "backend2" -p 8082 > /dev/null 2>&1 &
+backend2_pid=$!
+wait_for_server "backend2" 8082
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+cargo run -p lb -- -i 1 "http://localhost:8081/" --admin-port 9090 &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+wait_for_server "admin API" 9090
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+add_status=$(curl --silent --output /dev/null --write-out "%{http_code}" \
+    --header "Content-Type: application/json" \
+    --data '{"address": "http://localhost:8082/"}' \
+    http://localhost:9090/admin/backends)
+duplicate_status=$(curl --silent --output /dev/null --write-out "%{http_code}" \
+    --header "Content-Type: application/json" \
+    --data '{"address": "http://localhost:8082/"}' \
+    http://localhost:9090/admin/backends)
+# Wait for a health check of the new backend server
+sleep 2
+backends_after_add=$(curl --silent http://localhost:9090/admin/backends)
+
+remove_status=$(curl --silent --output /dev/null --write-out "%{http_code}" \
+    --request DELETE http://localhost:9090/admin/backends/http://localhost:8081/)
+remove_missing_status=$(curl --silent --output /dev/null --write-out "%{http_code}" \
+    --request DELETE http://localhost:9090/admin/backends/http://localhost:8083/)
+backends_after_remove=$(curl --silent http://localhost:9090/admin/backends)
+
+result=""
+for i in $(seq 1 4); do
+    result+=$(curl --silent http://localhost:8080/)
+done
+count_backend1=$(echo $result | grep -o "backend1" | wc -l)
+count_backend2=$(echo $result | grep -o "backend2" | wc -l)
+
+# Assert -----------------------------------------------------------------------
+if [[ $add_status -eq 201 && $duplicate_status -eq 409 && $backends_after_add == *"localhost:8082"* ]]; then
+    echo -e "${GREEN}Backend 2 was added.${NC}"
+else
+    echo -e "${RED}Backend 2 was not added as expected
+    add=${add_status}, duplicate=${duplicate_status}, backends=${backends_after_add}.${NC}"
+    test_passed=false
+fi
+
+if [[ $remove_status -eq 204 && $backends_after_remove != *"localhost:8081"* ]]; then
+    echo -e "${GREEN}Backend 1 was removed.${NC}"
+else
+    echo -e "${RED}Backend 1 was not removed as expected
+    remove=${remove_status}, backends=${backends_after_remove}.${NC}"
+    test_passed=false
+fi
+
+if [[ $remove_missing_status -eq 404 ]]; then
+    echo -e "${GREEN}Removing an unknown backend was rejected.${NC}"
+else
+    echo -e "${RED}Removing an unknown backend returned ${remove_missing_status}.${NC}"
+    test_passed=false
+fi
+
+if [[ $count_backend1 -eq 0 && $count_backend2 -eq 4 ]]; then
+    echo -e "${GREEN}Only received answers from backend 2.${NC}"
+else
+    echo -e "${RED}Did not receive the expected amount of answers
+    backend1=${count_backend1}, backend2=${count_backend2}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $backend2_pid $lb_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
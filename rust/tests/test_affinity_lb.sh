@@ -0,0 +1,92 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that a client is pinned to a backend server by the affinity cookie, and
+# moved to another backend server when its backend server is down
+# ------------------------------------------------------------------------------
+
+for strategy in round-robin least-response; do
+    echo -e "${GREEN}Testing the ${strategy} strategy...${NC}"
+
+    # Arrange ------------------------------------------------------------------
+    echo -e "${GREEN}Starting backend servers...${NC}"
+    cargo run -p be -- -n "backend1" -p 8081 > /dev/null 2>&1 &
+    backend1_pid=$!
+    wait_for_server "backend1" 8081
+
+    cargo run -p be -- -n "backend2" -p 8082 > /dev/null 2>&1 &
+    backend2_pid=$!
+    wait_for_server "backend2" 8082
+
+    echo -e "${GREEN}Starting load balancer...${NC}"
+    cargo run -p lb -- -i 10 --strategy $strategy --affinity-cookie LB_BACKEND \
+        "http://localhost:8081/" "http://localhost:8082/" &> /dev/null 2>&1 &
+    lb_pid=$!
+    wait_for_server "load balancer" 8080
+
+    cookie_jar=$(mktemp)
+
+    # Act ----------------------------------------------------------------------
+    echo -e "${GREEN}Running tests...${NC}"
+    pinned_backend=$(curl --silent --cookie-jar "$cookie_jar" http://localhost:8080/ | grep -o "backend[0-9]")
+    cookie=$(grep "LB_BACKEND" "$cookie_jar" | cut -f 7)
+    sticky_answers=""
+    for i in $(seq 1 6); do
+        sticky_answers+=$(curl --silent --cookie "$cookie_jar" http://localhost:8080/ | grep -o "backend[0-9]")
+    done
+
+    # The backend server is still healthy until the next health check, the
+    # request fails over to the other one
+    if [[ $pinned_backend == "backend1" ]]; then
+        kill_pids $backend1_pid > /dev/null
+        other_backend="backend2"
+    else
+        kill_pids $backend2_pid > /dev/null
+        other_backend="backend1"
+    fi
+    fallback_answers=""
+    for i in $(seq 1 3); do
+        fallback_answers+=$(curl --silent --cookie "$cookie_jar" --cookie-jar "$cookie_jar" \
+            http://localhost:8080/ | grep -o "backend[0-9]")
+    done
+    fallback_cookie=$(grep "LB_BACKEND" "$cookie_jar" | cut -f 7)
+
+    # Assert -------------------------------------------------------------------
+    if [[ -n $cookie && $cookie != *localhost* ]]; then
+        echo -e "${GREEN}The affinity cookie ${cookie} does not disclose the address.${NC}"
+    else
+        echo -e "${RED}The affinity cookie is '${cookie}'.${NC}"
+        test_passed=false
+    fi
+
+    if [[ $sticky_answers == "$(printf "${pinned_backend}%.0s" $(seq 1 6))" ]]; then
+        echo -e "${GREEN}All the requests went to the pinned ${pinned_backend}.${NC}"
+    else
+        echo -e "${RED}The requests went to ${sticky_answers} instead of ${pinned_backend}.${NC}"
+        test_passed=false
+    fi
+
+    if [[ $fallback_answers == "$(printf "${other_backend}%.0s" $(seq 1 3))" \
+        && -n $fallback_cookie && $fallback_cookie != "$cookie" ]]; then
+        echo -e "${GREEN}Once ${pinned_backend} was down, the client was pinned to ${other_backend}.${NC}"
+    else
+        echo -e "${RED}Once ${pinned_backend} was down, the requests went to '${fallback_answers}' with the cookie ${fallback_cookie}.${NC}"
+        test_passed=false
+    fi
+
+    echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+    kill_pids $backend1_pid $backend2_pid $lb_pid
+    rm -f "$cookie_jar"
+done
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,80 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that a client pinned by the affinity cookie is balanced again once the
+# affinity TTL elapsed, even if it keeps sending its cookie
+# ------------------------------------------------------------------------------
+
+# Prints the backend servers which answered 4 requests with the given cookie
+answers() {
+    for i in $(seq 1 4); do
+        curl --silent --cookie "LB_BACKEND=$1" http://localhost:8080/ | grep -o "backend[0-9]"
+    done | sort -u | tr "\n" " "
+}
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+cargo run -p be -- -n "backend1" -p 8081 > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+cargo run -p be -- -n "backend2" -p 8082 > /dev/null 2>&1 &
+backend2_pid=$!
+wait_for_server "backend2" 8082
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+cargo run -p lb -- -i 10 --affinity-cookie LB_BACKEND --affinity-ttl 3s \
+    "http://localhost:8081/" "http://localhost:8082/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+set_cookie=$(curl --silent --include http://localhost:8080/ | tr -d "\r" \
+    | grep -i "^set-cookie: LB_BACKEND=")
+cookie=$(echo "$set_cookie" | grep -o "LB_BACKEND=[^;]*" | cut -d "=" -f 2)
+pinned_answers=$(answers "$cookie")
+
+# The cookie is kept by the client past its Max-Age
+sleep 4
+expired_answers=$(answers "$cookie")
+renewed_set_cookie=$(curl --silent --include --cookie "LB_BACKEND=${cookie}" \
+    http://localhost:8080/ | tr -d "\r" | grep -i "^set-cookie: LB_BACKEND=")
+
+# Assert -----------------------------------------------------------------------
+if [[ $cookie =~ ^[0-9a-f]{16}\.[0-9]+$ && $set_cookie == *"Max-Age=3"* ]]; then
+    echo -e "${GREEN}The affinity cookie ${cookie} carries the time of the pin and expires with the TTL.${NC}"
+else
+    echo -e "${RED}The affinity cookie was set as ${set_cookie}.${NC}"
+    test_passed=false
+fi
+
+if [[ $pinned_answers == "backend1 " || $pinned_answers == "backend2 " ]]; then
+    echo -e "${GREEN}Before the TTL, the requests went to the pinned ${pinned_answers% }.${NC}"
+else
+    echo -e "${RED}Before the TTL, the requests went to ${pinned_answers}.${NC}"
+    test_passed=false
+fi
+
+if [[ $expired_answers == "backend1 backend2 " && -n $renewed_set_cookie \
+    && $renewed_set_cookie != *"${cookie}"* ]]; then
+    echo -e "${GREEN}After the TTL, the requests were balanced again and the client pinned again.${NC}"
+else
+    echo -e "${RED}After the TTL, the requests went to ${expired_answers}and the cookie was set as ${renewed_set_cookie}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $backend2_pid $lb_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,79 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that the load balancer started before its backend server checks its
+# health again until it comes up before accepting requests, and accepts them
+# anyway once the retries are exhausted
+# ------------------------------------------------------------------------------
+
+# Prints the status of the readiness route of the load balancer
+readiness() {
+    curl --silent --output /dev/null --write-out "%{http_code}" http://localhost:8080/readyz
+}
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+echo -e "${GREEN}Starting load balancer before the backend server...${NC}"
+# The periodic health checks are too rare to find the backend server healthy
+cargo run -p lb -- -i 60 --backend-connect-retries 20 --backend-connect-retry-delay 500ms \
+    "http://localhost:8081/" &> /dev/null 2>&1 &
+lb_pid=$!
+sleep 2
+if nc -z localhost 8080 2> /dev/null; then
+    listening_before_backend=true
+else
+    listening_before_backend=false
+fi
+
+echo -e "${GREEN}Starting backend server...${NC}"
+cargo run -p be -- -n "backend1" -p 8081 > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+wait_for_server "load balancer" 8080
+retried_readiness=$(readiness)
+retried_answer=$(curl --silent http://localhost:8080/)
+kill_pids $lb_pid $backend1_pid > /dev/null
+
+echo -e "${GREEN}Starting load balancer without backend server...${NC}"
+cargo run -p lb -- -i 60 --backend-connect-retries 2 --backend-connect-retry-delay 200ms \
+    "http://localhost:8081/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+exhausted_readiness=$(readiness)
+
+# Assert -----------------------------------------------------------------------
+if [[ $listening_before_backend == false ]]; then
+    echo -e "${GREEN}The load balancer waited for the backend server.${NC}"
+else
+    echo -e "${RED}The load balancer accepted requests before the backend server was up.${NC}"
+    test_passed=false
+fi
+
+if [[ $retried_readiness == "200" && $retried_answer == *"backend1"* ]]; then
+    echo -e "${GREEN}The backend server was healthy once it came up.${NC}"
+else
+    echo -e "${RED}The load balancer was ready with ${retried_readiness} and answered ${retried_answer}.${NC}"
+    test_passed=false
+fi
+
+if [[ $exhausted_readiness == "503" ]]; then
+    echo -e "${GREEN}The load balancer started unready once the retries were exhausted.${NC}"
+else
+    echo -e "${RED}Without backend server, the load balancer was ready with ${exhausted_readiness}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing load balancer...${NC}"
+kill_pids $lb_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
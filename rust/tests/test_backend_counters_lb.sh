@@ -0,0 +1,93 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test the request and error counters of the backend servers in the admin API
+# ------------------------------------------------------------------------------
+
+# Prints the requests and errors counters of the given backend server from the
+# admin API, for example 3/0
+counters() {
+    curl --silent http://localhost:9090/admin/backends | python3 -c '
+import json, sys
+for backend in json.load(sys.stdin):
+    if backend["address"] == sys.argv[1]:
+        print("{}/{}".format(backend["requests_total"], backend["errors_total"]))
+' "$1"
+}
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+cargo run -p be -- -n "backend1" -p 8081 > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+# backend2 answers the health checks but closes the connection of any other
+# request without answering
+python3 -c '
+import http.server
+
+class Handler(http.server.BaseHTTPRequestHandler):
+    def do_GET(self):
+        if self.path == "/health":
+            self.send_response(200)
+            self.end_headers()
+        else:
+            self.close_connection = True
+
+http.server.HTTPServer(("localhost", 8082), Handler).serve_forever()
+' > /dev/null 2>&1 &
+backend2_pid=$!
+wait_for_server "backend2" 8082
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+cargo run -p lb -- -i 10 "http://localhost:8081/" "http://localhost:8082/" \
+    --max-retries 0 --circuit-breaker-threshold 0 --admin-port 9090 &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+wait_for_server "admin API" 9090
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+counters_before=$(counters "http://localhost:8081/")
+for i in $(seq 1 6); do
+    curl --silent --output /dev/null http://localhost:8080/
+done
+counters_backend1=$(counters "http://localhost:8081/")
+counters_backend2=$(counters "http://localhost:8082/")
+
+# Assert -----------------------------------------------------------------------
+if [[ $counters_before == "0/0" ]]; then
+    echo -e "${GREEN}The counters start at 0.${NC}"
+else
+    echo -e "${RED}The counters of backend 1 started at ${counters_before}.${NC}"
+    test_passed=false
+fi
+
+if [[ $counters_backend1 == "3/0" ]]; then
+    echo -e "${GREEN}Backend 1 received 3 requests without error.${NC}"
+else
+    echo -e "${RED}Backend 1 has the counters ${counters_backend1} instead of 3/0.${NC}"
+    test_passed=false
+fi
+
+if [[ $counters_backend2 == "3/3" ]]; then
+    echo -e "${GREEN}Backend 2 received 3 requests which all failed.${NC}"
+else
+    echo -e "${RED}Backend 2 has the counters ${counters_backend2} instead of 3/3.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $backend2_pid $lb_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
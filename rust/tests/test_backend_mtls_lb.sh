@@ -0,0 +1,139 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that the load balancer presents its client certificate to an HTTPS
+# backend server requiring mutual TLS, only trusts the given certificate
+# authority, and fails fast on an invalid client certificate
+# ------------------------------------------------------------------------------
+
+# Arrange ----------------------------------------------------------------------
+tls_dir=$(mktemp -d)
+
+# Creates a certificate authority with the given name
+create_ca() {
+    openssl req -x509 -newkey rsa:2048 -nodes -days 1 -subj "/CN=$1" \
+        -keyout "$tls_dir/$1_key.pem" -out "$tls_dir/$1.pem" > /dev/null 2>&1
+}
+
+# Creates a certificate with the given name and common name, signed by the given
+# certificate authority, with a PKCS #8 private key
+create_cert() {
+    openssl req -newkey rsa:2048 -nodes -subj "/CN=$2" \
+        -keyout "$tls_dir/$1_key.pem" -out "$tls_dir/$1.csr" > /dev/null 2>&1
+    echo "subjectAltName=DNS:$2" > "$tls_dir/$1.ext"
+    openssl x509 -req -days 1 -in "$tls_dir/$1.csr" -CA "$tls_dir/$3.pem" \
+        -CAkey "$tls_dir/$3_key.pem" -CAcreateserial -extfile "$tls_dir/$1.ext" \
+        -out "$tls_dir/$1.pem" > /dev/null 2>&1
+}
+
+create_ca "ca"
+create_ca "other_ca"
+create_cert "server" "localhost" "ca"
+create_cert "client" "lb" "ca"
+
+echo -e "${GREEN}Starting backend servers...${NC}"
+# backend1 only accepts the connections presenting a client certificate signed
+# by the certificate authority
+python3 -c '
+import http.server, ssl, sys
+
+class Handler(http.server.BaseHTTPRequestHandler):
+    def do_GET(self):
+        body = b"backend1"
+        self.send_response(200)
+        self.send_header("Content-Length", str(len(body)))
+        self.end_headers()
+        self.wfile.write(body)
+
+tls_dir = sys.argv[1]
+context = ssl.SSLContext(ssl.PROTOCOL_TLS_SERVER)
+context.load_cert_chain(tls_dir + "/server.pem", tls_dir + "/server_key.pem")
+context.load_verify_locations(tls_dir + "/ca.pem")
+context.verify_mode = ssl.CERT_REQUIRED
+server = http.server.ThreadingHTTPServer(("localhost", 8443), Handler)
+server.socket = context.wrap_socket(server.socket, server_side=True)
+server.serve_forever()
+' "$tls_dir" > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8443
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+client_args=(--backend-tls-cert "$tls_dir/client.pem" --backend-tls-key "$tls_dir/client_key.pem")
+
+RUST_LOG=off cargo run -p lb -- --check "${client_args[@]}" --backend-ca "$tls_dir/ca.pem" \
+    "https://localhost:8443/" > /dev/null 2>&1
+mtls_status=$?
+
+RUST_LOG=off cargo run -p lb -- --check --backend-ca "$tls_dir/ca.pem" \
+    "https://localhost:8443/" > /dev/null 2>&1
+no_cert_status=$?
+
+RUST_LOG=off cargo run -p lb -- --check "${client_args[@]}" --backend-ca "$tls_dir/other_ca.pem" \
+    "https://localhost:8443/" > /dev/null 2>&1
+other_ca_status=$?
+
+invalid_key_output=$(timeout 10 cargo run -p lb -- --backend-tls-cert "$tls_dir/client.pem" \
+    --backend-tls-key "$tls_dir/missing_key.pem" "https://localhost:8443/" 2>&1)
+invalid_key_status=$?
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+cargo run -p lb -- -i 1 "${client_args[@]}" --backend-ca "$tls_dir/ca.pem" \
+    "https://localhost:8443/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+sleep 2
+answer=$(curl --silent http://localhost:8080/)
+
+# Assert -----------------------------------------------------------------------
+if [[ $mtls_status -eq 0 ]]; then
+    echo -e "${GREEN}The check succeeded with the client certificate.${NC}"
+else
+    echo -e "${RED}The check exited with ${mtls_status} with the client certificate.${NC}"
+    test_passed=false
+fi
+
+if [[ $no_cert_status -ne 0 ]]; then
+    echo -e "${GREEN}The check failed without the client certificate.${NC}"
+else
+    echo -e "${RED}The check succeeded without the client certificate.${NC}"
+    test_passed=false
+fi
+
+if [[ $other_ca_status -ne 0 ]]; then
+    echo -e "${GREEN}The check failed with another certificate authority.${NC}"
+else
+    echo -e "${RED}The check succeeded with another certificate authority.${NC}"
+    test_passed=false
+fi
+
+if [[ $invalid_key_status -ne 0 && $invalid_key_status -ne 124 \
+    && $invalid_key_output == *"missing_key.pem"* ]]; then
+    echo -e "${GREEN}The load balancer failed fast on a missing client key.${NC}"
+else
+    echo -e "${RED}The load balancer exited with ${invalid_key_status} on a missing client key: ${invalid_key_output}.${NC}"
+    test_passed=false
+fi
+
+if [[ $answer == "backend1" ]]; then
+    echo -e "${GREEN}The request was forwarded over mutual TLS.${NC}"
+else
+    echo -e "${RED}The load balancer answered ${answer}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $lb_pid
+rm -rf "$tls_dir"
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
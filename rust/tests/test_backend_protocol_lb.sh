@@ -0,0 +1,88 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test sending the requests to the backend servers with HTTP/1.1 and HTTP/2
+# ------------------------------------------------------------------------------
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+# backend1 only speaks HTTP/1.1
+cargo run -p be -- -n "backend1" -p 8081 > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+# backend2 only speaks HTTP/2 over plain HTTP, it needs node
+if command -v node > /dev/null; then
+    node -e '
+require("http2")
+    .createServer((request, response) => {
+        response.end(`Hello from backend server: backend2 over HTTP/${request.httpVersion}`);
+    })
+    .listen(8082);
+' > /dev/null 2>&1 &
+    backend2_pid=$!
+    wait_for_server "backend2" 8082
+else
+    echo -e "${YELLOW}node is not installed, skipping the HTTP/2 backend server.${NC}"
+fi
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+cargo run -p lb -- -i 10 "http://localhost:8081/" --backend-protocol http1 &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+http1_result=$(curl --silent http://localhost:8080/)
+kill_pids $lb_pid
+
+cargo run -p lb -- -i 10 "http://localhost:8081/" --backend-protocol http2 &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+http2_to_http1_status=$(curl --silent --output /dev/null --write-out "%{http_code}" http://localhost:8080/)
+kill_pids $lb_pid
+
+if [[ -n $backend2_pid ]]; then
+    cargo run -p lb -- -i 10 "http://localhost:8082/" --backend-protocol http2 &> /dev/null 2>&1 &
+    lb_pid=$!
+    wait_for_server "load balancer" 8080
+    http2_result=$(curl --silent http://localhost:8080/)
+    kill_pids $lb_pid
+fi
+
+# Assert -----------------------------------------------------------------------
+if [[ $http1_result == *"backend1"* ]]; then
+    echo -e "${GREEN}Received an answer from backend 1 over HTTP/1.1.${NC}"
+else
+    echo -e "${RED}Did not receive an answer from backend 1 over HTTP/1.1: ${http1_result}.${NC}"
+    test_passed=false
+fi
+
+if [[ $http2_to_http1_status -ge 500 ]]; then
+    echo -e "${GREEN}Sending HTTP/2 to backend 1 failed with ${http2_to_http1_status}.${NC}"
+else
+    echo -e "${RED}Sending HTTP/2 to backend 1 returned ${http2_to_http1_status}.${NC}"
+    test_passed=false
+fi
+
+if [[ -n $backend2_pid ]]; then
+    if [[ $http2_result == *"backend2 over HTTP/2.0"* ]]; then
+        echo -e "${GREEN}Received an answer from backend 2 over HTTP/2.${NC}"
+    else
+        echo -e "${RED}Did not receive an answer from backend 2 over HTTP/2: ${http2_result}.${NC}"
+        test_passed=false
+    fi
+fi
+
+echo -e "${YELLOW}Killing backend servers...${NC}"
+kill_pids $backend1_pid $backend2_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
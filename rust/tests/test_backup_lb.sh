@@ -0,0 +1,100 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that a backup backend server only receives requests while all the primary
+# backend servers are unhealthy
+# ------------------------------------------------------------------------------
+
+# Prints the backend servers which answered the given number of requests
+answers() {
+    for i in $(seq 1 $1); do
+        curl --silent http://localhost:8080/ | grep -o "backend[0-9]"
+    done | sort -u | tr "\n" " "
+}
+
+for strategy in round-robin least-response; do
+    echo -e "${GREEN}Testing the ${strategy} strategy...${NC}"
+
+    # Arrange ------------------------------------------------------------------
+    echo -e "${GREEN}Starting backend servers...${NC}"
+    cargo run -p be -- -n "backend1" -p 8081 > /dev/null 2>&1 &
+    backend1_pid=$!
+    wait_for_server "backend1" 8081
+
+    cargo run -p be -- -n "backend2" -p 8082 > /dev/null 2>&1 &
+    backend2_pid=$!
+    wait_for_server "backend2" 8082
+
+    cargo run -p be -- -n "backend3" -p 8083 > /dev/null 2>&1 &
+    backend3_pid=$!
+    wait_for_server "backend3" 8083
+
+    echo -e "${GREEN}Starting load balancer...${NC}"
+    cargo run -p lb -- -i 1 --strategy $strategy "http://localhost:8081/" "http://localhost:8082/" \
+        --backup "http://localhost:8083/" &> /dev/null 2>&1 &
+    lb_pid=$!
+    wait_for_server "load balancer" 8080
+
+    # Act ----------------------------------------------------------------------
+    echo -e "${GREEN}Running tests...${NC}"
+    primaries_answers=$(answers 10)
+
+    # Once a health check found them down
+    kill_pids $backend1_pid > /dev/null
+    sleep 2
+    one_primary_answers=$(answers 10)
+
+    kill_pids $backend2_pid > /dev/null
+    sleep 2
+    backup_answers=$(answers 10)
+
+    cargo run -p be -- -n "backend1" -p 8081 > /dev/null 2>&1 &
+    backend1_pid=$!
+    wait_for_server "backend1" 8081
+    sleep 2
+    recovered_answers=$(answers 10)
+
+    # Assert -------------------------------------------------------------------
+    if [[ $primaries_answers == "backend1 backend2 " ]]; then
+        echo -e "${GREEN}The backup did not receive requests while the primaries were healthy.${NC}"
+    else
+        echo -e "${RED}The requests went to ${primaries_answers}with both primaries healthy.${NC}"
+        test_passed=false
+    fi
+
+    if [[ $one_primary_answers == "backend2 " ]]; then
+        echo -e "${GREEN}The backup did not receive requests while a primary was healthy.${NC}"
+    else
+        echo -e "${RED}The requests went to ${one_primary_answers}with backend2 healthy.${NC}"
+        test_passed=false
+    fi
+
+    if [[ $backup_answers == "backend3 " ]]; then
+        echo -e "${GREEN}The backup received the requests once the primaries were down.${NC}"
+    else
+        echo -e "${RED}The requests went to ${backup_answers}with the primaries down.${NC}"
+        test_passed=false
+    fi
+
+    if [[ $recovered_answers == "backend1 " ]]; then
+        echo -e "${GREEN}The requests went back to the primary once it recovered.${NC}"
+    else
+        echo -e "${RED}The requests went to ${recovered_answers}once backend1 recovered.${NC}"
+        test_passed=false
+    fi
+
+    echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+    kill_pids $backend1_pid $backend3_pid $lb_pid
+done
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
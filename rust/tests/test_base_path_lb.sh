@@ -0,0 +1,113 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that the base path of a backend server is prepended to the path of the
+# forwarded requests, whatever the slashes around the base path and the request
+# path, and that a backend server without base path receives the requests with
+# their path and query from its root
+# ------------------------------------------------------------------------------
+
+# Arrange ----------------------------------------------------------------------
+config_file=$(mktemp --suffix .toml)
+cat > "$config_file" << EOF
+backends = [
+    { address = "http://localhost:8081/", base_path = "/app", tags = { case = "1" } },
+    { address = "http://localhost:8082/ignored/", base_path = "//app/v1/", tags = { case = "2" } },
+    { address = "http://localhost:8083/", base_path = "/", tags = { case = "3" } },
+    { address = "http://localhost:8084/root/", tags = { case = "4" } },
+]
+EOF
+
+invalid_config_file=$(mktemp --suffix .toml)
+cat > "$invalid_config_file" << EOF
+backends = [{ address = "http://localhost:8081/", base_path = "/app?debug=1" }]
+EOF
+
+echo -e "${GREEN}Starting backend servers...${NC}"
+# Each backend server answers with its name and the path of the request
+backend_pids=()
+for i in 1 2 3 4; do
+    python3 -c '
+import http.server, sys
+
+name, port = sys.argv[1], int(sys.argv[2])
+
+class Handler(http.server.BaseHTTPRequestHandler):
+    def do_GET(self):
+        body = (name + " " + self.path).encode()
+        self.send_response(200)
+        self.send_header("Content-Length", str(len(body)))
+        self.end_headers()
+        self.wfile.write(body)
+
+http.server.ThreadingHTTPServer(("localhost", port), Handler).serve_forever()
+' "backend${i}" "808${i}" > /dev/null 2>&1 &
+    backend_pids+=($!)
+    wait_for_server "backend${i}" 808${i}
+done
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+cargo run -p lb -- -i 10 --route-tag-header X-Case=case --config "$config_file" \
+    &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+# Each backend server and request path with the answer expected
+cases=(
+    "1 / backend1 /app/"
+    "1 /users backend1 /app/users"
+    "1 /users/ backend1 /app/users/"
+    "1 /users?page=2 backend1 /app/users?page=2"
+    "2 / backend2 /app/v1/"
+    "2 /users backend2 /app/v1/users"
+    "2 //users backend2 /app/v1/users"
+    "3 / backend3 /"
+    "3 /users backend3 /users"
+    "4 / backend4 /"
+    "4 /users?page=2 backend4 /users?page=2"
+)
+results=()
+for case in "${cases[@]}"; do
+    read -r tag path expected_backend expected_path <<< "$case"
+    answer=$(curl --silent --path-as-is --header "X-Case: ${tag}" "http://localhost:8080${path}")
+    results+=("${tag} ${path}|${answer}|${expected_backend} ${expected_path}")
+done
+
+invalid_output=$(cargo run -p lb -- --check --config "$invalid_config_file" 2>&1)
+invalid_status=$?
+
+# Assert -----------------------------------------------------------------------
+for result in "${results[@]}"; do
+    IFS="|" read -r request answer expected <<< "$result"
+    if [[ $answer == "$expected" ]]; then
+        echo -e "${GREEN}Request ${request} was forwarded as ${answer}.${NC}"
+    else
+        echo -e "${RED}Request ${request} was answered ${answer}, expected ${expected}.${NC}"
+        test_passed=false
+    fi
+done
+
+if [[ $invalid_status -ne 0 && $invalid_output == *"cannot have a query or a fragment"* ]]; then
+    echo -e "${GREEN}The base path with a query was rejected.${NC}"
+else
+    echo -e "${RED}The base path with a query exited with ${invalid_status}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids "${backend_pids[@]}" $lb_pid
+rm -f "$config_file" "$invalid_config_file"
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
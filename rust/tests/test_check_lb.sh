@@ -0,0 +1,67 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that --check prints which backend servers are reachable and exits with an
+# error if one of them is unreachable
+# ------------------------------------------------------------------------------
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+cargo run -p be -- -n "backend1" -p 8081 > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+reachable_output=$(RUST_LOG=off cargo run -p lb -- --check "http://localhost:8081/" 2>&1)
+reachable_status=$?
+
+# Nothing listens on port 8082
+unreachable_output=$(RUST_LOG=off cargo run -p lb -- --check \
+    "http://localhost:8081/" "http://localhost:8082/" 2>&1)
+unreachable_status=$?
+
+invalid_output=$(cargo run -p lb -- --check "localhost:8081" 2>&1)
+invalid_status=$?
+
+# Assert -----------------------------------------------------------------------
+if [[ $reachable_status -eq 0 ]] \
+    && echo "$reachable_output" | grep -q "^http://localhost:8081/ *reachable" \
+    && echo "$reachable_output" | grep -q "1 of 1 backend servers are reachable"; then
+    echo -e "${GREEN}The check succeeded with all the backend servers reachable.${NC}"
+else
+    echo -e "${RED}The check exited with ${reachable_status}: ${reachable_output}.${NC}"
+    test_passed=false
+fi
+
+if [[ $unreachable_status -ne 0 ]] \
+    && echo "$unreachable_output" | grep -q "^http://localhost:8081/ *reachable" \
+    && echo "$unreachable_output" | grep -q "^http://localhost:8082/ *unreachable" \
+    && echo "$unreachable_output" | grep -q "1 of 2 backend servers are reachable"; then
+    echo -e "${GREEN}The check failed with a backend server unreachable.${NC}"
+else
+    echo -e "${RED}The check exited with ${unreachable_status}: ${unreachable_output}.${NC}"
+    test_passed=false
+fi
+
+if [[ $invalid_status -ne 0 ]] && ! echo "$invalid_output" | grep -q "BACKEND"; then
+    echo -e "${GREEN}The check failed on an invalid backend server address.${NC}"
+else
+    echo -e "${RED}The check exited with ${invalid_status} on an invalid address: ${invalid_output}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers...${NC}"
+kill_pids $backend1_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,58 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that the dynamic load balancer checks the backend servers before receiving
+# the first request, without changing the response time used for routing
+# ------------------------------------------------------------------------------
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+cargo run -p be -- -n "backend1" -p 8081 > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+cargo run -p be -- -n "backend2" -p 8082 > /dev/null 2>&1 &
+backend2_pid=$!
+wait_for_server "backend2" 8082
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+cargo run -p lb -- -i 10 "http://localhost:8081/" "http://localhost:8082/" -d --admin-port 9090 &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+wait_for_server "admin API" 9090
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+# Prints the health, the response time and whether the health check latency was
+# measured of each backend server, for example Healthy/0.0/true
+backends=$(curl --silent http://localhost:9090/admin/backends | python3 -c '
+import json, sys
+for backend in json.load(sys.stdin):
+    print("{}/{}/{}".format(backend["health"], backend["response_time_ms"],
+                            str(backend["health_check_latency_ms"] > 0).lower()))
+')
+
+# Assert -----------------------------------------------------------------------
+expected_backends="Healthy/0.0/true
+Healthy/0.0/true"
+if [[ $backends == "$expected_backends" ]]; then
+    echo -e "${GREEN}The backend servers were checked before the first request.${NC}"
+else
+    echo -e "${RED}The backend servers were not checked as expected: ${backends}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $backend2_pid $lb_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,92 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that a compressed response of a backend server is passed through to the
+# client byte for byte, with its Content-Encoding and Vary headers
+# ------------------------------------------------------------------------------
+
+# Arrange ----------------------------------------------------------------------
+work_dir=$(mktemp -d)
+
+echo -e "${GREEN}Starting backend servers...${NC}"
+# backend1 answers with its body compressed with gzip when the client accepts it,
+# and writes the compressed body it sends to expected.gz
+python3 -c '
+import gzip
+import http.server
+import sys
+
+body = ("Hello from backend server: backend1\n" * 100).encode()
+compressed_body = gzip.compress(body, mtime=0)
+with open(sys.argv[1], "wb") as expected:
+    expected.write(compressed_body)
+
+class Handler(http.server.BaseHTTPRequestHandler):
+    def do_GET(self):
+        accepts_gzip = "gzip" in self.headers.get("Accept-Encoding", "")
+        self.send_response(200)
+        self.send_header("Content-Type", "text/plain")
+        self.send_header("Vary", "Accept-Encoding")
+        if accepts_gzip:
+            self.send_header("Content-Encoding", "gzip")
+        response_body = compressed_body if accepts_gzip else body
+        self.send_header("Content-Length", str(len(response_body)))
+        self.end_headers()
+        self.wfile.write(response_body)
+
+http.server.ThreadingHTTPServer(("localhost", 8081), Handler).serve_forever()
+' "$work_dir/expected.gz" > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+cargo run -p lb -- -i 10 "http://localhost:8081/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+# The body is not decompressed by curl
+curl --silent --header "Accept-Encoding: gzip" --dump-header "$work_dir/gzip_headers" \
+    --output "$work_dir/received.gz" http://localhost:8080/
+identity_body=$(curl --silent --dump-header "$work_dir/identity_headers" http://localhost:8080/)
+
+# Assert -----------------------------------------------------------------------
+if cmp --silent "$work_dir/expected.gz" "$work_dir/received.gz"; then
+    echo -e "${GREEN}The compressed body was delivered byte for byte.${NC}"
+else
+    echo -e "${RED}The compressed body was altered: $(wc -c < "$work_dir/received.gz") bytes received, $(wc -c < "$work_dir/expected.gz") sent.${NC}"
+    test_passed=false
+fi
+
+if grep -qi "^content-encoding: gzip" "$work_dir/gzip_headers" \
+    && grep -qi "^vary: Accept-Encoding" "$work_dir/gzip_headers"; then
+    echo -e "${GREEN}The Content-Encoding and Vary headers were preserved.${NC}"
+else
+    echo -e "${RED}The headers were not preserved: $(cat "$work_dir/gzip_headers").${NC}"
+    test_passed=false
+fi
+
+if [[ $identity_body == *"Hello from backend server: backend1"* ]] \
+    && ! grep -qi "^content-encoding" "$work_dir/identity_headers"; then
+    echo -e "${GREEN}A client not accepting gzip received an uncompressed body.${NC}"
+else
+    echo -e "${RED}A client not accepting gzip received: $(cat "$work_dir/identity_headers").${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $lb_pid
+rm -rf "$work_dir"
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,58 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that concurrent requests reading the health of every backend server are
+# all answered
+# ------------------------------------------------------------------------------
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+cargo run -p be -- -n "backend1" -p 8081 > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+cargo run -p be -- -n "backend2" -p 8082 > /dev/null 2>&1 &
+backend2_pid=$!
+wait_for_server "backend2" 8082
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+# The power of two choices load balancer reads the health of the backend servers
+# on every request
+cargo run -p lb -- -i 1 --power-of-two-choices "http://localhost:8081/" "http://localhost:8082/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+requests=300
+urls=$(mktemp)
+for i in $(seq 1 $requests); do
+    echo "url = \"http://localhost:8080/\"" >> "$urls"
+done
+# The backend servers do not accept many more simultaneous connections
+answered=$(curl --silent --no-progress-meter --parallel --parallel-max 10 --max-time 10 \
+    --config "$urls" --write-out "%{http_code}\n" | grep -c "200$")
+rm -f "$urls"
+
+# Assert -----------------------------------------------------------------------
+if [[ $answered -eq $requests ]]; then
+    echo -e "${GREEN}All the ${requests} concurrent requests were answered.${NC}"
+else
+    echo -e "${RED}Only ${answered} of the ${requests} concurrent requests were answered.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $backend2_pid $lb_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,106 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that a backend server which does not accept connections fails the health
+# checks and the requests within --connect-timeout, and is then unhealthy
+# ------------------------------------------------------------------------------
+
+# Prints the health of each backend server from the admin API, for example
+# http://localhost:8081/=Healthy
+backend_healths() {
+    curl --silent http://localhost:9090/admin/backends | python3 -c '
+import json, sys
+print(" ".join(sorted("{}={}".format(backend["address"], backend["health"]) for backend in json.load(sys.stdin))))
+'
+}
+
+# Prints true if the given value is in the given range [min, max)
+in_range() {
+    python3 -c "import sys; print(str($2 <= float('$1') < $3).lower())"
+}
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+cargo run -p be -- -n "backend1" -p 8081 > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+# Port 8085 never accepts the connections and its backlog is full, so that the
+# new connections hang as with an unroutable address
+python3 -c '
+import socket
+import time
+
+server = socket.socket()
+server.setsockopt(socket.SOL_SOCKET, socket.SO_REUSEADDR, 1)
+server.bind(("127.0.0.1", 8085))
+server.listen(0)
+clients = []
+for i in range(3):
+    client = socket.socket()
+    client.setblocking(False)
+    client.connect_ex(("127.0.0.1", 8085))
+    clients.append(client)
+time.sleep(3600)
+' > /dev/null 2>&1 &
+blackhole_pid=$!
+sleep 1
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+check_start=$(date +%s.%N)
+cargo run -p lb -- --check --connect-timeout 500ms "http://127.0.0.1:8085/" > /dev/null 2>&1
+check_status=$?
+check_time=$(python3 -c "print($(date +%s.%N) - $check_start)")
+
+# The failed health check before accepting requests does not make the backend
+# server unhealthy, the first request sent to it does
+echo -e "${GREEN}Starting load balancer...${NC}"
+cargo run -p lb -- -i 60 --connect-timeout 500ms --unhealthy-threshold 10 --admin-port 9090 \
+    "http://127.0.0.1:8085/" "http://localhost:8081/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+wait_for_server "admin API" 9090
+
+healths_before=$(backend_healths)
+result=$(curl --silent --write-out " %{http_code} %{time_total}" http://localhost:8080/)
+read -r _ _ _ _ _ status time_total <<< "$result"
+healths_after=$(backend_healths)
+
+# Assert -----------------------------------------------------------------------
+if [[ $check_status -ne 0 && $(in_range "$check_time" 0.5 1.5) == true ]]; then
+    echo -e "${GREEN}The health check gave up after ${check_time}s.${NC}"
+else
+    echo -e "${RED}The check exited with ${check_status} after ${check_time}s.${NC}"
+    test_passed=false
+fi
+
+if [[ $result == *"backend1"* && $status -eq 200 && $(in_range "$time_total" 0.5 1.5) == true ]]; then
+    echo -e "${GREEN}The request was retried on backend1 after ${time_total}s.${NC}"
+else
+    echo -e "${RED}The request was answered with '${result}'.${NC}"
+    test_passed=false
+fi
+
+if [[ $healths_before == "http://127.0.0.1:8085/=Healthy http://localhost:8081/=Healthy" \
+    && $healths_after == "http://127.0.0.1:8085/=Unhealthy http://localhost:8081/=Healthy" ]]; then
+    echo -e "${GREEN}The backend server not accepting connections is unhealthy.${NC}"
+else
+    echo -e "${RED}The backend servers went from ${healths_before} to ${healths_after}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $blackhole_pid $lb_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
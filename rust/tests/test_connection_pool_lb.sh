@@ -0,0 +1,104 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that the idle connections to the backend servers are reused by the
+# following requests, that --pool-max-idle-per-host 0 opens a connection per
+# request and that --pool-idle-timeout closes the idle connections
+# ------------------------------------------------------------------------------
+
+# Sends 3 requests, waiting the given number of seconds after each one, and
+# prints the number of connections on which the backend server received them
+connections() {
+    : > "$ports_file"
+    for i in 1 2 3; do
+        curl --silent --output /dev/null http://localhost:8080/
+        sleep "$1"
+    done
+    sort -u "$ports_file" | wc -l
+}
+
+# Starts the load balancer with the given options
+start_lb() {
+    cargo run -p lb -- -i 60 "$@" "http://localhost:8081/" &> /dev/null 2>&1 &
+    lb_pid=$!
+    wait_for_server "load balancer" 8080
+}
+
+# Arrange ----------------------------------------------------------------------
+ports_file=$(mktemp)
+
+echo -e "${GREEN}Starting backend server...${NC}"
+# The backend server keeps the connections alive and logs the client port of
+# each request other than the health checks
+python3 -c '
+import http.server, sys
+
+ports_file = sys.argv[1]
+
+class Handler(http.server.BaseHTTPRequestHandler):
+    protocol_version = "HTTP/1.1"
+
+    def do_GET(self):
+        if self.path != "/health":
+            with open(ports_file, "a") as f:
+                f.write(str(self.client_address[1]) + "\n")
+        self.send_response(200)
+        self.send_header("Content-Length", "2")
+        self.end_headers()
+        self.wfile.write(b"ok")
+
+http.server.ThreadingHTTPServer(("localhost", 8081), Handler).serve_forever()
+' "$ports_file" > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+start_lb
+default_connections=$(connections 0)
+kill_pids $lb_pid > /dev/null
+
+start_lb --pool-max-idle-per-host 0
+no_idle_connections=$(connections 0)
+kill_pids $lb_pid > /dev/null
+
+start_lb --pool-idle-timeout 1s
+timed_out_connections=$(connections 2)
+
+# Assert -----------------------------------------------------------------------
+if [[ $default_connections -eq 1 ]]; then
+    echo -e "${GREEN}By default, the requests reused the same connection.${NC}"
+else
+    echo -e "${RED}By default, the requests used ${default_connections} connections.${NC}"
+    test_passed=false
+fi
+
+if [[ $no_idle_connections -eq 3 ]]; then
+    echo -e "${GREEN}Without idle connections, each request opened a connection.${NC}"
+else
+    echo -e "${RED}Without idle connections, the requests used ${no_idle_connections} connections.${NC}"
+    test_passed=false
+fi
+
+if [[ $timed_out_connections -eq 3 ]]; then
+    echo -e "${GREEN}The connections idle for longer than the idle timeout were closed.${NC}"
+else
+    echo -e "${RED}With an idle timeout, the requests used ${timed_out_connections} connections.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend server and load balancer...${NC}"
+kill_pids $backend1_pid $lb_pid
+rm -f "$ports_file"
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
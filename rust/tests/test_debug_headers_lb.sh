@@ -0,0 +1,76 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that with --debug-headers the X-Upstream header of each response gives
+# the backend server which answered it, and that it is not sent by default
+# ------------------------------------------------------------------------------
+
+# Prints the backend server which answered each of 6 requests and the port given
+# by its X-Upstream header, for example backend1=8081
+upstreams() {
+    for i in $(seq 1 6); do
+        response=$(curl --silent --include http://localhost:8080/)
+        name=$(echo "$response" | grep -o "backend[0-9]" | head -1)
+        port=$(echo "$response" | grep -i "^x-upstream:" | grep -o ":[0-9][0-9]*/" | tr -d ":/")
+        echo "${name}=${port}"
+    done | tr "\n" " "
+}
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+cargo run -p be -- -n "backend1" -p 8081 > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+cargo run -p be -- -n "backend2" -p 8082 > /dev/null 2>&1 &
+backend2_pid=$!
+wait_for_server "backend2" 8082
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+echo -e "${GREEN}Starting load balancer with the debug headers...${NC}"
+cargo run -p lb -- --debug-headers "http://localhost:8081/" "http://localhost:8082/" \
+    &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+debug_upstreams=$(upstreams)
+mismatches=$(echo "$debug_upstreams" | tr " " "\n" | grep -v -e "^$" -e "^backend1=8081$" \
+    -e "^backend2=8082$" | wc -l)
+kill_pids $lb_pid > /dev/null
+
+echo -e "${GREEN}Starting load balancer without the debug headers...${NC}"
+cargo run -p lb -- "http://localhost:8081/" "http://localhost:8082/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+default_headers=$(curl --silent --include http://localhost:8080/ | grep -i "^x-upstream:")
+
+# Assert -----------------------------------------------------------------------
+if [[ $debug_upstreams == *"backend1=8081"* && $debug_upstreams == *"backend2=8082"* \
+    && $mismatches -eq 0 ]]; then
+    echo -e "${GREEN}The X-Upstream header gave the backend server which answered.${NC}"
+else
+    echo -e "${RED}The backend servers and their X-Upstream headers were ${debug_upstreams}.${NC}"
+    test_passed=false
+fi
+
+if [[ -z $default_headers ]]; then
+    echo -e "${GREEN}The X-Upstream header was not sent by default.${NC}"
+else
+    echo -e "${RED}By default, the load balancer sent ${default_headers}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $backend2_pid $lb_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,89 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that the requests matching no route get the default response of the
+# config file instead of being forwarded, and that the routed ones still are
+# ------------------------------------------------------------------------------
+
+# Arrange ----------------------------------------------------------------------
+config_file=$(mktemp --suffix .toml)
+cat > "$config_file" << EOF
+[groups.api]
+backends = ["http://localhost:8082/"]
+
+[routes]
+"/api" = "api"
+
+[default_response]
+status = 418
+body = "Nothing here"
+EOF
+
+both_config_file=$(mktemp --suffix .toml)
+cat > "$both_config_file" << EOF
+backends = ["http://localhost:8081/"]
+
+[default_response]
+body = "Nothing here"
+EOF
+
+echo -e "${GREEN}Starting backend servers...${NC}"
+cargo run -p be -- -n "backend1" -p 8081 > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+cargo run -p be -- -n "backend2" -p 8082 > /dev/null 2>&1 &
+backend2_pid=$!
+wait_for_server "backend2" 8082
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+cargo run -p lb -- -i 10 --config "$config_file" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+root_response=$(curl --silent --write-out " %{http_code}" http://localhost:8080/)
+unmatched_response=$(curl --silent --write-out " %{http_code}" http://localhost:8080/apiary)
+routed_response=$(curl --silent --write-out " %{http_code}" http://localhost:8080/api/users)
+
+both_output=$(cargo run -p lb -- --check --config "$both_config_file" 2>&1)
+both_status=$?
+
+# Assert -----------------------------------------------------------------------
+if [[ $root_response == "Nothing here 418" && $unmatched_response == "Nothing here 418" ]]; then
+    echo -e "${GREEN}The requests matching no route got the default response.${NC}"
+else
+    echo -e "${RED}The requests matching no route got ${root_response} and ${unmatched_response}.${NC}"
+    test_passed=false
+fi
+
+if [[ $routed_response == *"backend2"*" 200" ]]; then
+    echo -e "${GREEN}The routed request was forwarded to its group.${NC}"
+else
+    echo -e "${RED}The routed request got ${routed_response}.${NC}"
+    test_passed=false
+fi
+
+if [[ $both_status -ne 0 && $both_output == *"only one of them can be given"* ]]; then
+    echo -e "${GREEN}A config file with both backend servers and a default response was rejected.${NC}"
+else
+    echo -e "${RED}A config file with both backend servers and a default response exited with ${both_status}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $backend2_pid $lb_pid
+rm -f "$config_file" "$both_config_file"
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
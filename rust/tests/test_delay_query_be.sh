@@ -0,0 +1,70 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that the backend server waits for the delay given by the delay_ms query
+# parameter of a request, instead of its default delay, for that request only
+# ------------------------------------------------------------------------------
+
+# Prints the time in milliseconds taken to answer a request to the given URL
+response_time_ms() {
+    curl --silent --output /dev/null --write-out "%{time_total}" "$1" \
+        | awk '{ printf "%d", $1 * 1000 }'
+}
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend server...${NC}"
+cargo run -p be -- -n "backend1" -p 8081 -d 200 > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+default_time=$(response_time_ms "http://localhost:8081/")
+slow_time=$(response_time_ms "http://localhost:8081/?delay_ms=1000")
+fast_time=$(response_time_ms "http://localhost:8081/?delay_ms=0")
+invalid_time=$(response_time_ms "http://localhost:8081/?delay_ms=slow")
+after_time=$(response_time_ms "http://localhost:8081/")
+
+# Assert -----------------------------------------------------------------------
+if [[ $default_time -ge 200 && $default_time -lt 1000 ]]; then
+    echo -e "${GREEN}The default delay was applied without query parameter.${NC}"
+else
+    echo -e "${RED}Without query parameter, the request took ${default_time}ms.${NC}"
+    test_passed=false
+fi
+
+if [[ $slow_time -ge 1000 && $fast_time -lt 200 ]]; then
+    echo -e "${GREEN}The delay of the query parameter replaced the default one.${NC}"
+else
+    echo -e "${RED}The requests asking for 1000ms and 0ms took ${slow_time}ms and ${fast_time}ms.${NC}"
+    test_passed=false
+fi
+
+if [[ $invalid_time -ge 200 && $invalid_time -lt 1000 ]]; then
+    echo -e "${GREEN}An invalid delay was ignored.${NC}"
+else
+    echo -e "${RED}The request with an invalid delay took ${invalid_time}ms.${NC}"
+    test_passed=false
+fi
+
+if [[ $after_time -ge 200 && $after_time -lt 1000 ]]; then
+    echo -e "${GREEN}The default delay was applied again to the next request.${NC}"
+else
+    echo -e "${RED}After the requests asking for a delay, the request took ${after_time}ms.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend server...${NC}"
+kill_pids $backend1_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
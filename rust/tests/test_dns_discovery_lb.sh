@@ -0,0 +1,173 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that the backend servers are resolved from the A and SRV records of a DNS
+# name, that the new addresses are added and the disappeared ones removed when
+# the records change, and that the last known backend servers are kept when the
+# resolution fails
+# ------------------------------------------------------------------------------
+
+# Prints the distinct backend servers which answered 20 requests
+answers() {
+    for i in $(seq 1 20); do
+        curl --silent http://localhost:8080/
+        echo
+    done | sort -u | tr "\n" " "
+}
+
+# Sets the records served by the mock DNS server
+set_records() {
+    printf "%s\n" "$@" > "$dns_dir/records"
+}
+
+# Arrange ----------------------------------------------------------------------
+dns_dir=$(mktemp -d)
+set_records "A 127.0.0.1"
+
+echo -e "${GREEN}Starting mock DNS server...${NC}"
+# Answers the A and SRV queries with the records of the records file, read on
+# every query, or with a server failure if it contains fail
+python3 -c '
+import socket, struct, sys
+
+def name(host):
+    return b"".join(bytes([len(label)]) + label.encode() for label in host.split(".") if label) + b"\0"
+
+sock = socket.socket(socket.AF_INET, socket.SOCK_DGRAM)
+sock.bind(("127.0.0.1", 5353))
+while True:
+    query, client = sock.recvfrom(512)
+    end = query.index(b"\0", 12) + 1
+    question = query[12:end + 4]
+    qtype = struct.unpack(">H", query[end:end + 2])[0]
+    records = [line.split() for line in open(sys.argv[1]) if line.strip()]
+    answers = []
+    for record in records:
+        if record[0] == "A" and qtype == 1:
+            answers.append((1, socket.inet_aton(record[1])))
+        elif record[0] == "SRV" and qtype == 33:
+            answers.append((33, struct.pack(">HHH", 0, 1, int(record[2])) + name(record[1])))
+    rcode = 2 if ["fail"] in records else 0
+    header = struct.pack(">HHHHHH", struct.unpack(">H", query[:2])[0], 0x8180 | rcode, 1,
+                         0 if rcode else len(answers), 0, 0)
+    body = b"" if rcode else b"".join(
+        struct.pack(">HHHIH", 0xc00c, rtype, 1, 0, len(rdata)) + rdata for rtype, rdata in answers)
+    sock.sendto(header + question + body, client)
+' "$dns_dir/records" > /dev/null 2>&1 &
+dns_pid=$!
+sleep 1
+
+echo -e "${GREEN}Starting backend servers...${NC}"
+# The backend servers listen on the same port of two loopback addresses
+for i in 1 2; do
+    python3 -c '
+import http.server, sys
+
+class Handler(http.server.BaseHTTPRequestHandler):
+    def do_GET(self):
+        body = sys.argv[1].encode()
+        self.send_response(200)
+        self.send_header("Content-Length", str(len(body)))
+        self.end_headers()
+        self.wfile.write(body)
+
+http.server.ThreadingHTTPServer(("127.0.0.%s" % sys.argv[1][-1], 8081), Handler).serve_forever()
+' "backend$i" > /dev/null 2>&1 &
+    eval "backend${i}_pid=$!"
+done
+wait_for_server "backend1" 8081
+sleep 1
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+cargo run -p lb -- -i 1 --resolve dns://service.test:8081 --resolve-interval 1s \
+    --dns-server 127.0.0.1:5353 &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+initial_answers=$(answers)
+
+# Scale up
+set_records "A 127.0.0.1" "A 127.0.0.2"
+sleep 4
+scaled_up_answers=$(answers)
+
+set_records "fail"
+sleep 3
+failed_answers=$(answers)
+
+# Scale down
+set_records "A 127.0.0.2"
+sleep 4
+scaled_down_answers=$(answers)
+
+set_records "SRV localhost 8081"
+RUST_LOG=off cargo run -p lb -- --check --resolve dns+srv://_http._tcp.service.test \
+    --dns-server 127.0.0.1:5353 > /dev/null 2>&1
+srv_status=$?
+
+set_records "fail"
+startup_failure_output=$(timeout 20 cargo run -p lb -- --listen-port 8090 \
+    --resolve dns://service.test:8081 --dns-server 127.0.0.1:5353 2>&1)
+startup_failure_status=$?
+
+# Assert -----------------------------------------------------------------------
+if [[ $initial_answers == "backend1 " ]]; then
+    echo -e "${GREEN}The load balancer started with the resolved backend server.${NC}"
+else
+    echo -e "${RED}The requests were answered by ${initial_answers}at startup.${NC}"
+    test_passed=false
+fi
+
+if [[ $scaled_up_answers == "backend1 backend2 " ]]; then
+    echo -e "${GREEN}The new address became a backend server.${NC}"
+else
+    echo -e "${RED}The requests were answered by ${scaled_up_answers}after the scale up.${NC}"
+    test_passed=false
+fi
+
+if [[ $failed_answers == "backend1 backend2 " ]]; then
+    echo -e "${GREEN}The backend servers were kept when the resolution failed.${NC}"
+else
+    echo -e "${RED}The requests were answered by ${failed_answers}while the resolution failed.${NC}"
+    test_passed=false
+fi
+
+if [[ $scaled_down_answers == "backend2 " ]]; then
+    echo -e "${GREEN}The disappeared address was removed.${NC}"
+else
+    echo -e "${RED}The requests were answered by ${scaled_down_answers}after the scale down.${NC}"
+    test_passed=false
+fi
+
+if [[ $srv_status -eq 0 ]]; then
+    echo -e "${GREEN}The backend servers were resolved from the SRV records.${NC}"
+else
+    echo -e "${RED}The check of the SRV records exited with ${srv_status}.${NC}"
+    test_passed=false
+fi
+
+if [[ $startup_failure_status -ne 0 && $startup_failure_status -ne 124 \
+    && $startup_failure_output == *"Failed to resolve dns://service.test:8081"* ]]; then
+    echo -e "${GREEN}The load balancer did not start without resolving the DNS name.${NC}"
+else
+    echo -e "${RED}The load balancer exited with ${startup_failure_status}: ${startup_failure_output}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers, DNS server and load balancer...${NC}"
+kill_pids $backend1_pid $backend2_pid $dns_pid $lb_pid
+rm -rf "$dns_dir"
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
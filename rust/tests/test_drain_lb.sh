@@ -0,0 +1,101 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that a draining backend server receives no new requests until it is
+# enabled again
+# ------------------------------------------------------------------------------
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+cargo run -p be -- -n "backend1" -p 8081 > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+cargo run -p be -- -n "backend2" -p 8082 > /dev/null 2>&1 &
+backend2_pid=$!
+wait_for_server "backend2" 8082
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+cargo run -p lb -- -i 1 "http://localhost:8081/" "http://localhost:8082/" --admin-port 9090 &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+wait_for_server "admin API" 9090
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+drain_status=$(curl --silent --output /dev/null --write-out "%{http_code}" \
+    --request PUT http://localhost:9090/admin/draining/http://localhost:8081/)
+drain_missing_status=$(curl --silent --output /dev/null --write-out "%{http_code}" \
+    --request PUT http://localhost:9090/admin/draining/http://localhost:8083/)
+backends_while_draining=$(curl --silent http://localhost:9090/admin/backends)
+
+result_while_draining=""
+for i in $(seq 1 4); do
+    result_while_draining+=$(curl --silent http://localhost:8080/)
+done
+count_backend1_while_draining=$(echo $result_while_draining | grep -o "backend1" | wc -l)
+
+# Wait for a health check of the draining backend server
+sleep 2
+backends_after_health_check=$(curl --silent http://localhost:9090/admin/backends)
+
+enable_status=$(curl --silent --output /dev/null --write-out "%{http_code}" \
+    --request DELETE http://localhost:9090/admin/draining/http://localhost:8081/)
+result_after_enable=""
+for i in $(seq 1 4); do
+    result_after_enable+=$(curl --silent http://localhost:8080/)
+done
+count_backend1_after_enable=$(echo $result_after_enable | grep -o "backend1" | wc -l)
+
+# Assert -----------------------------------------------------------------------
+if [[ $drain_status -eq 204 && $backends_while_draining == *'"address":"http://localhost:8081/"'*'"draining":true'* ]]; then
+    echo -e "${GREEN}Backend 1 is draining.${NC}"
+else
+    echo -e "${RED}Backend 1 is not draining
+    drain=${drain_status}, backends=${backends_while_draining}.${NC}"
+    test_passed=false
+fi
+
+if [[ $drain_missing_status -eq 404 ]]; then
+    echo -e "${GREEN}Draining an unknown backend was rejected.${NC}"
+else
+    echo -e "${RED}Draining an unknown backend returned ${drain_missing_status}.${NC}"
+    test_passed=false
+fi
+
+if [[ $count_backend1_while_draining -eq 0 ]]; then
+    echo -e "${GREEN}Backend 1 received no requests while draining.${NC}"
+else
+    echo -e "${RED}Backend 1 received ${count_backend1_while_draining} requests while draining.${NC}"
+    test_passed=false
+fi
+
+if [[ $backends_after_health_check == *'"address":"http://localhost:8081/","health":"Healthy"'* ]]; then
+    echo -e "${GREEN}Backend 1 is still health checked while draining.${NC}"
+else
+    echo -e "${RED}Backend 1 is not healthy while draining: ${backends_after_health_check}.${NC}"
+    test_passed=false
+fi
+
+if [[ $enable_status -eq 204 && $count_backend1_after_enable -gt 0 ]]; then
+    echo -e "${GREEN}Backend 1 receives requests again once enabled.${NC}"
+else
+    echo -e "${RED}Backend 1 was not enabled as expected
+    enable=${enable_status}, requests=${count_backend1_after_enable}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $backend2_pid $lb_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -34,8 +34,11 @@ count_backend2=$(echo $result | grep -o "backend2" | wc -l)
 count_backend3=$(echo $result | grep -o "backend3" | wc -l)
 
 # Assert -----------------------------------------------------------------------
-if [[ $count_backend1 -ne $count_backend2 && $count_backend2 -ne $count_backend3 ]]; then
-    echo -e "${GREEN}All backend servers received the same number of requests.${NC}"
+# The routing only uses the response times of the requests, not the latency of
+# the health checks, so the first requests go to the idle backend servers. Then
+# backend1 stays busy for 10s and backend3 answers faster than backend2
+if [[ $count_backend1 -le 1 && $count_backend3 -gt $count_backend2 ]]; then
+    echo -e "${GREEN}The fastest backend server received the most requests.${NC}"
 else
     echo -e "${RED}Did not receive the expected amount of answers
     backend1=${count_backend1}, backend2=${count_backend2},
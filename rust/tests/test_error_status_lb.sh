@@ -0,0 +1,91 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test the status codes of the responses when a request cannot be forwarded
+# ------------------------------------------------------------------------------
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+# backend1 answers the health checks but closes the connection of any other
+# request without answering
+python3 -c '
+import http.server
+
+class Handler(http.server.BaseHTTPRequestHandler):
+    def do_GET(self):
+        if self.path == "/health":
+            self.send_response(200)
+            self.end_headers()
+        else:
+            self.close_connection = True
+
+http.server.HTTPServer(("localhost", 8081), Handler).serve_forever()
+' > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+# Nothing listens on port 8082, so backend2 is never available
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+cargo run -p lb -- -i 5 "http://localhost:8081/" --max-retries 0 --error-header X-Error &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+unreachable_response=$(curl --silent --include http://localhost:8080/)
+kill_pids $lb_pid
+
+cargo run -p lb -- -i 5 "http://localhost:8082/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+unavailable_response=$(curl --silent --include http://localhost:8080/)
+kill_pids $lb_pid
+
+cargo run -p lb -- -i 5 "http://localhost:8082/" --error-header X-Error &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+unavailable_error=$(curl --silent --include http://localhost:8080/ | grep -i "^x-error:")
+kill_pids $lb_pid
+
+# Assert -----------------------------------------------------------------------
+if [[ $unreachable_response == "HTTP/1.1 502"* ]] \
+    && echo "$unreachable_response" | grep -qi "^x-error: Backend server http://localhost:8081/ unreachable"; then
+    echo -e "${GREEN}An unreachable backend server is answered with a 502 and its address.${NC}"
+else
+    echo -e "${RED}An unreachable backend server is answered with: ${unreachable_response}.${NC}"
+    test_passed=false
+fi
+
+if [[ $unavailable_response == "HTTP/1.1 503"* ]] \
+    && echo "$unavailable_response" | grep -qi "^retry-after: 5"; then
+    echo -e "${GREEN}No backend server available is answered with a 503 and a Retry-After.${NC}"
+else
+    echo -e "${RED}No backend server available is answered with: ${unavailable_response}.${NC}"
+    test_passed=false
+fi
+
+if echo "$unavailable_response" | grep -qi "^x-error:"; then
+    echo -e "${RED}The cause of the failure is sent without an error header.${NC}"
+    test_passed=false
+fi
+
+if [[ $unavailable_error == *"No backend server available"* ]]; then
+    echo -e "${GREEN}The error header tells that no backend server is available.${NC}"
+else
+    echo -e "${RED}The error header is: ${unavailable_error}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers...${NC}"
+kill_pids $backend1_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
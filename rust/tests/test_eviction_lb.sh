@@ -0,0 +1,96 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that the backend servers unhealthy for longer than the eviction timeout
+# are removed from the load balancer and listed by the admin API, and that they
+# are kept when no eviction timeout is given
+# ------------------------------------------------------------------------------
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+cargo run -p be -- -n "backend1" -p 8081 > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+cargo run -p be -- -n "backend2" -p 8082 > /dev/null 2>&1 &
+backend2_pid=$!
+wait_for_server "backend2" 8082
+
+# Nothing listens on port 8089, the backend server is decommissioned
+echo -e "${GREEN}Starting load balancers...${NC}"
+cargo run -p lb -- -i 1 --eviction-timeout 6s --admin-port 9090 "http://localhost:8081/" \
+    "http://localhost:8082/" "http://localhost:8089/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+wait_for_server "admin API" 9090
+
+cargo run -p lb -- -i 1 --listen-port 8090 --admin-port 9091 "http://localhost:8081/" \
+    "http://localhost:8082/" "http://localhost:8089/" &> /dev/null 2>&1 &
+no_eviction_lb_pid=$!
+wait_for_server "load balancer without eviction" 8090
+wait_for_server "admin API without eviction" 9091
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+# backend2 dies after being healthy
+kill_pids $backend2_pid > /dev/null
+sleep 1
+backends_before_timeout=$(curl --silent http://localhost:9090/admin/backends)
+sleep 9
+backends_after_timeout=$(curl --silent http://localhost:9090/admin/backends)
+evicted=$(curl --silent http://localhost:9090/admin/evicted)
+kept_backends=$(curl --silent http://localhost:9091/admin/backends)
+kept_evicted=$(curl --silent http://localhost:9091/admin/evicted)
+answer=$(curl --silent http://localhost:8080/)
+
+# Assert -----------------------------------------------------------------------
+if [[ $backends_before_timeout == *"localhost:8082"* && $backends_before_timeout == *"localhost:8089"* ]]; then
+    echo -e "${GREEN}The unhealthy backend servers were kept before the eviction timeout.${NC}"
+else
+    echo -e "${RED}The backend servers before the eviction timeout were ${backends_before_timeout}.${NC}"
+    test_passed=false
+fi
+
+if [[ $backends_after_timeout == *"localhost:8081"* && $backends_after_timeout != *"localhost:8082"* \
+    && $backends_after_timeout != *"localhost:8089"* ]]; then
+    echo -e "${GREEN}The dead backend servers were removed after the eviction timeout.${NC}"
+else
+    echo -e "${RED}The backend servers after the eviction timeout were ${backends_after_timeout}.${NC}"
+    test_passed=false
+fi
+
+if [[ $evicted == *"localhost:8082"* && $evicted == *"localhost:8089"* && $evicted != *"localhost:8081"* ]]; then
+    echo -e "${GREEN}The admin API listed the evicted backend servers.${NC}"
+else
+    echo -e "${RED}The admin API listed the evicted backend servers ${evicted}.${NC}"
+    test_passed=false
+fi
+
+if [[ $kept_backends == *"localhost:8082"* && $kept_backends == *"localhost:8089"* && $kept_evicted == "[]" ]]; then
+    echo -e "${GREEN}The dead backend servers were kept without an eviction timeout.${NC}"
+else
+    echo -e "${RED}Without an eviction timeout, the backend servers were ${kept_backends} and the evicted ones ${kept_evicted}.${NC}"
+    test_passed=false
+fi
+
+if [[ $answer == *"backend1"* ]]; then
+    echo -e "${GREEN}The remaining backend server kept answering.${NC}"
+else
+    echo -e "${RED}The load balancer answered ${answer}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancers...${NC}"
+kill_pids $backend1_pid $lb_pid $no_eviction_lb_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,98 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that the admin endpoints of the backend server make its requests or its
+# health checks fail on demand until it recovers, and that the load balancer
+# stops sending requests to it while its health checks fail
+# ------------------------------------------------------------------------------
+
+# Prints the status of a request to the given path of backend1
+status() {
+    curl --silent --output /dev/null --write-out "%{http_code}" "http://localhost:8081$1"
+}
+
+# Prints the backend servers which answered 10 requests to the load balancer
+answers() {
+    for i in $(seq 1 10); do
+        curl --silent http://localhost:8080/ | grep -o "backend[0-9]"
+    done | sort -u | tr "\n" " "
+}
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+cargo run -p be -- -n "backend1" -p 8081 > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+cargo run -p be -- -n "backend2" -p 8082 > /dev/null 2>&1 &
+backend2_pid=$!
+wait_for_server "backend2" 8082
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+# Any answer to a health check is healthy by default
+cargo run -p lb -- -i 1 --health-check-status 200-299 "http://localhost:8081/" "http://localhost:8082/" \
+    &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+initial_statuses="$(status /health) $(status /)"
+
+curl --silent --request POST "http://localhost:8081/admin/fail?mode=health" > /dev/null
+health_failure_statuses="$(status /health) $(status /)"
+# Once a health check found it unhealthy
+sleep 2
+health_failure_answers=$(answers)
+
+curl --silent --request POST "http://localhost:8081/admin/fail" > /dev/null
+failure_statuses="$(status /health) $(status /)"
+
+curl --silent --request POST "http://localhost:8081/admin/recover" > /dev/null
+recovered_statuses="$(status /health) $(status /)"
+sleep 2
+recovered_answers=$(answers)
+
+# Assert -----------------------------------------------------------------------
+if [[ $initial_statuses == "200 200" ]]; then
+    echo -e "${GREEN}The backend server answered normally at first.${NC}"
+else
+    echo -e "${RED}At first, the health check and request statuses were ${initial_statuses}.${NC}"
+    test_passed=false
+fi
+
+if [[ $health_failure_statuses == "500 200" && $health_failure_answers == "backend2 " ]]; then
+    echo -e "${GREEN}The failing health checks made the backend server unhealthy.${NC}"
+else
+    echo -e "${RED}With failing health checks, the statuses were ${health_failure_statuses} and the requests were answered by ${health_failure_answers}.${NC}"
+    test_passed=false
+fi
+
+if [[ $failure_statuses == "500 500" ]]; then
+    echo -e "${GREEN}The requests and health checks failed together.${NC}"
+else
+    echo -e "${RED}Failing everything, the statuses were ${failure_statuses}.${NC}"
+    test_passed=false
+fi
+
+if [[ $recovered_statuses == "200 200" && $recovered_answers == "backend1 backend2 " ]]; then
+    echo -e "${GREEN}The backend server recovered.${NC}"
+else
+    echo -e "${RED}After recovering, the statuses were ${recovered_statuses} and the requests were answered by ${recovered_answers}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $backend2_pid $lb_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,117 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that the backend server receives the address of the client in
+# X-Forwarded-For, appended to the one given by the client, and the protocol and
+# host used by the client in X-Forwarded-Proto and X-Forwarded-Host, and in the
+# Forwarded header when asked for
+# ------------------------------------------------------------------------------
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+# backend1 answers with the forwarded headers it received, one per line
+python3 -c '
+import http.server
+
+class Handler(http.server.BaseHTTPRequestHandler):
+    def do_GET(self):
+        body = "".join(
+            "{}={}\n".format(name, self.headers.get(name, ""))
+            for name in ["X-Forwarded-For", "X-Forwarded-Proto", "X-Forwarded-Host", "Forwarded"]
+        ).encode()
+        self.send_response(200)
+        self.send_header("Content-Length", str(len(body)))
+        self.end_headers()
+        self.wfile.write(body)
+
+http.server.ThreadingHTTPServer(("localhost", 8081), Handler).serve_forever()
+' > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+tls_dir=$(mktemp -d)
+openssl req -x509 -newkey rsa:2048 -nodes -days 1 -subj "/CN=localhost" \
+    -keyout "$tls_dir/key.pem" -out "$tls_dir/cert.pem" > /dev/null 2>&1
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+cargo run -p lb -- -i 10 "http://localhost:8081/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+
+cargo run -p lb -- -i 10 --listen-port 8443 --tls-cert "$tls_dir/cert.pem" \
+    --tls-key "$tls_dir/key.pem" --forwarded-header "http://localhost:8081/" &> /dev/null 2>&1 &
+tls_lb_pid=$!
+wait_for_server "HTTPS load balancer" 8443
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+plain_headers=$(curl --silent http://127.0.0.1:8080/)
+# The protocol and host given by the client are not trusted
+proxied_headers=$(curl --silent --header "Host: example.com" \
+    --header "X-Forwarded-For: 203.0.113.7" --header "X-Forwarded-For: 198.51.100.2" \
+    --header "X-Forwarded-Proto: https" --header "X-Forwarded-Host: evil.example.com" \
+    http://127.0.0.1:8080/)
+tls_headers=$(curl --silent --insecure https://localhost:8443/)
+proxied_tls_headers=$(curl --silent --insecure --header "Forwarded: for=203.0.113.7;proto=http" \
+    https://localhost:8443/)
+
+# Assert -----------------------------------------------------------------------
+expected_plain_headers="X-Forwarded-For=127.0.0.1
+X-Forwarded-Proto=http
+X-Forwarded-Host=127.0.0.1:8080
+Forwarded="
+if [[ $plain_headers == "$expected_plain_headers" ]]; then
+    echo -e "${GREEN}The backend server received the address, protocol and host of the client.${NC}"
+else
+    echo -e "${RED}The backend server received:\n${plain_headers}${NC}"
+    test_passed=false
+fi
+
+expected_proxied_headers="X-Forwarded-For=203.0.113.7, 198.51.100.2, 127.0.0.1
+X-Forwarded-Proto=http
+X-Forwarded-Host=example.com
+Forwarded="
+if [[ $proxied_headers == "$expected_proxied_headers" ]]; then
+    echo -e "${GREEN}The address of the client was appended to its X-Forwarded-For.${NC}"
+else
+    echo -e "${RED}The backend server received:\n${proxied_headers}${NC}"
+    test_passed=false
+fi
+
+expected_tls_headers="X-Forwarded-For=127.0.0.1
+X-Forwarded-Proto=https
+X-Forwarded-Host=localhost:8443
+Forwarded=for=127.0.0.1;host=\"localhost:8443\";proto=https"
+if [[ $tls_headers == "$expected_tls_headers" ]]; then
+    echo -e "${GREEN}The backend server was told that the client used HTTPS, also in Forwarded.${NC}"
+else
+    echo -e "${RED}The backend server received:\n${tls_headers}${NC}"
+    test_passed=false
+fi
+
+expected_proxied_tls_headers="X-Forwarded-For=127.0.0.1
+X-Forwarded-Proto=https
+X-Forwarded-Host=localhost:8443
+Forwarded=for=203.0.113.7;proto=http, for=127.0.0.1;host=\"localhost:8443\";proto=https"
+if [[ $proxied_tls_headers == "$expected_proxied_tls_headers" ]]; then
+    echo -e "${GREEN}The element of the client was appended to its Forwarded header.${NC}"
+else
+    echo -e "${RED}The backend server received:\n${proxied_tls_headers}${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $lb_pid $tls_lb_pid
+rm -rf "$tls_dir"
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,117 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that the denied headers and the hop-by-hop headers are removed from the
+# client requests and from the backend responses, and that the allow lists only
+# let the allowed headers through
+# ------------------------------------------------------------------------------
+
+# Sends a request with the test headers, writes the response headers to the
+# given file and prints the names of the headers received by the backend server
+send_request() {
+    curl --silent --dump-header "$1" \
+        --header "X-Internal-Auth: secret" \
+        --header "X-Public: hello" \
+        --header "Connection: X-Hop" \
+        --header "X-Hop: 1" \
+        http://localhost:8080/
+}
+
+# Arrange ----------------------------------------------------------------------
+work_dir=$(mktemp -d)
+
+echo -e "${GREEN}Starting backend servers...${NC}"
+# backend1 answers with the names of the headers it received, and with a Server,
+# an X-Debug and an X-Public-Response header
+python3 -c '
+import http.server
+
+class Handler(http.server.BaseHTTPRequestHandler):
+    server_version = "SecretServer/1.0"
+
+    def do_GET(self):
+        body = " ".join(sorted(name.lower() for name in self.headers.keys())).encode()
+        self.send_response(200)
+        self.send_header("X-Debug", "internal")
+        self.send_header("X-Public-Response", "ok")
+        self.send_header("Content-Length", str(len(body)))
+        self.end_headers()
+        self.wfile.write(body)
+
+http.server.ThreadingHTTPServer(("localhost", 8081), Handler).serve_forever()
+' > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+echo -e "${GREEN}Starting load balancer with deny lists...${NC}"
+# The names are given in another case than the headers
+cargo run -p lb -- -i 10 --deny-request-header x-INTERNAL-auth \
+    --deny-response-header SERVER --deny-response-header x-debug \
+    "http://localhost:8081/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+denied_request_headers=$(send_request "$work_dir/denied_headers")
+kill_pids $lb_pid > /dev/null
+
+echo -e "${GREEN}Starting load balancer with allow lists...${NC}"
+cargo run -p lb -- -i 10 --allow-request-header X-Public --allow-response-header X-Public-Response \
+    "http://localhost:8081/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+allowed_request_headers=$(send_request "$work_dir/allowed_headers")
+
+# Assert -----------------------------------------------------------------------
+if [[ " $denied_request_headers " == *" x-public "* && " $denied_request_headers " != *" x-internal-auth "* \
+    && " $denied_request_headers " != *" x-hop "* ]]; then
+    echo -e "${GREEN}The denied and hop-by-hop request headers were removed.${NC}"
+else
+    echo -e "${RED}The backend server received ${denied_request_headers}.${NC}"
+    test_passed=false
+fi
+
+if grep -qi "^x-public-response: ok" "$work_dir/denied_headers" \
+    && ! grep -qi "^server: SecretServer" "$work_dir/denied_headers" \
+    && ! grep -qi "^x-debug" "$work_dir/denied_headers"; then
+    echo -e "${GREEN}The denied response headers were removed.${NC}"
+else
+    echo -e "${RED}The client received $(cat "$work_dir/denied_headers").${NC}"
+    test_passed=false
+fi
+
+# The request ID, the X-Forwarded-* headers, Host and Accept are set by the load
+# balancer
+received_client_headers=$(echo " $allowed_request_headers " | tr " " "\n" \
+    | grep -v "^x-request-id$\|^x-forwarded-\|^host$\|^accept$\|^$" | tr "\n" " ")
+if [[ $received_client_headers == "x-public " ]]; then
+    echo -e "${GREEN}Only the allowed request header was forwarded.${NC}"
+else
+    echo -e "${RED}The backend server received the client headers ${received_client_headers}.${NC}"
+    test_passed=false
+fi
+
+if grep -qi "^x-public-response: ok" "$work_dir/allowed_headers" \
+    && ! grep -qi "^server: SecretServer" "$work_dir/allowed_headers" \
+    && ! grep -qi "^x-debug" "$work_dir/allowed_headers"; then
+    echo -e "${GREEN}Only the allowed response header was passed through.${NC}"
+else
+    echo -e "${RED}The client received $(cat "$work_dir/allowed_headers").${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $lb_pid
+rm -rf "$work_dir"
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,110 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that the status, body and JSON field conditions of the health checks
+# find the backend servers which answer in a bad state unhealthy, globally and
+# per backend server
+# ------------------------------------------------------------------------------
+
+# Prints the backend servers found reachable by a check with the given options
+reachable_backends() {
+    RUST_LOG=off cargo run -p lb -- --check "$@" 2>/dev/null | grep " reachable" \
+        | grep -o "localhost:808[0-9]" | tr "\n" " "
+}
+
+# Starts a backend server on the given port answering its health checks with
+# the given status and body
+start_backend() {
+    python3 -c '
+import http.server, sys
+
+class Handler(http.server.BaseHTTPRequestHandler):
+    def do_GET(self):
+        body = sys.argv[3].encode()
+        self.send_response(int(sys.argv[2]))
+        self.send_header("Content-Type", "application/json")
+        self.send_header("Content-Length", str(len(body)))
+        self.end_headers()
+        self.wfile.write(body)
+
+http.server.ThreadingHTTPServer(("localhost", int(sys.argv[1])), Handler).serve_forever()
+' "$@" > /dev/null 2>&1 &
+}
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+start_backend 8081 200 '{"status": "ok"}'
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+start_backend 8082 200 '{"status": "degraded"}'
+backend2_pid=$!
+wait_for_server "backend2" 8082
+
+start_backend 8083 500 '{"status": "ok"}'
+backend3_pid=$!
+wait_for_server "backend3" 8083
+
+backends=("http://localhost:8081/" "http://localhost:8082/" "http://localhost:8083/")
+config_file=$(mktemp --suffix .toml)
+cat > "$config_file" << EOF
+backends = [
+    "http://localhost:8081/",
+    { address = "http://localhost:8082/", health_check = { json = "status=degraded" } },
+    "http://localhost:8083/",
+]
+EOF
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+any_answer=$(reachable_backends "${backends[@]}")
+status_and_json=$(reachable_backends --health-check-status 200-299 --health-check-json status=ok \
+    "${backends[@]}")
+body=$(reachable_backends --health-check-body degraded "${backends[@]}")
+per_backend=$(reachable_backends --health-check-status 200-299 --health-check-json status=ok \
+    --config "$config_file")
+
+# Assert -----------------------------------------------------------------------
+if [[ $any_answer == "localhost:8081 localhost:8082 localhost:8083 " ]]; then
+    echo -e "${GREEN}Any answer was healthy without conditions.${NC}"
+else
+    echo -e "${RED}Without conditions, the reachable backend servers were ${any_answer}.${NC}"
+    test_passed=false
+fi
+
+if [[ $status_and_json == "localhost:8081 " ]]; then
+    echo -e "${GREEN}The wrong status and JSON field were unhealthy.${NC}"
+else
+    echo -e "${RED}With status and JSON field conditions, the reachable backend servers were ${status_and_json}.${NC}"
+    test_passed=false
+fi
+
+if [[ $body == "localhost:8082 " ]]; then
+    echo -e "${GREEN}The bodies without the text were unhealthy.${NC}"
+else
+    echo -e "${RED}With a body condition, the reachable backend servers were ${body}.${NC}"
+    test_passed=false
+fi
+
+if [[ $per_backend == "localhost:8081 localhost:8082 " ]]; then
+    echo -e "${GREEN}The conditions of a backend server replaced the global ones.${NC}"
+else
+    echo -e "${RED}With conditions per backend server, the reachable backend servers were ${per_backend}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers...${NC}"
+kill_pids $backend1_pid $backend2_pid $backend3_pid
+rm -f "$config_file"
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
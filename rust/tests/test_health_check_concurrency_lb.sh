@@ -0,0 +1,83 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that the health checks of the backend servers run concurrently, up to
+# --health-check-concurrency at the same time
+# ------------------------------------------------------------------------------
+
+# Prints true if the given value is in the given range [min, max)
+in_range() {
+    python3 -c "import sys; print(str($2 <= float('$1') < $3).lower())"
+}
+
+# Prints the number of seconds taken by a --check of the backend servers with
+# the given extra arguments, and its exit status
+timed_check() {
+    local start
+    start=$(date +%s.%N)
+    cargo run -p lb -- --check "$@" "${backends[@]}" > /dev/null 2>&1
+    local status=$?
+    echo "$(python3 -c "print($(date +%s.%N) - $start)") $status"
+}
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+# Each backend server takes 2s to answer a health check
+backend_pids=()
+backends=()
+for port in $(seq 8081 8090); do
+    python3 -c '
+import http.server
+import sys
+import time
+
+class Handler(http.server.BaseHTTPRequestHandler):
+    def do_GET(self):
+        if self.path == "/health":
+            time.sleep(2)
+        self.send_response(200)
+        self.send_header("Content-Length", "0")
+        self.end_headers()
+
+http.server.ThreadingHTTPServer(("localhost", int(sys.argv[1])), Handler).serve_forever()
+' $port > /dev/null 2>&1 &
+    backend_pids+=($!)
+    backends+=("http://localhost:${port}/")
+    wait_for_server "backend on port ${port}" $port
+done
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+# One after the other, the health checks would take 20s
+read -r concurrent_time concurrent_status <<< "$(timed_check)"
+read -r bounded_time bounded_status <<< "$(timed_check --health-check-concurrency 5)"
+
+# Assert -----------------------------------------------------------------------
+if [[ $concurrent_status -eq 0 && $(in_range "$concurrent_time" 2 5) == true ]]; then
+    echo -e "${GREEN}The 10 health checks took ${concurrent_time}s, about one check.${NC}"
+else
+    echo -e "${RED}The 10 health checks exited with ${concurrent_status} after ${concurrent_time}s.${NC}"
+    test_passed=false
+fi
+
+if [[ $bounded_status -eq 0 && $(in_range "$bounded_time" 4 7) == true ]]; then
+    echo -e "${GREEN}5 at a time, the 10 health checks took ${bounded_time}s, about two checks.${NC}"
+else
+    echo -e "${RED}5 at a time, the 10 health checks exited with ${bounded_status} after ${bounded_time}s.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers...${NC}"
+kill_pids "${backend_pids[@]}"
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,98 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that the passed and failed health checks of a flapping backend server,
+# and the failure rate of its recent health checks, are counted by the admin API
+# and the metrics
+# ------------------------------------------------------------------------------
+
+# Prints the given fields, separated by spaces, of the backend server from the
+# admin API
+admin_fields() {
+    curl --silent http://localhost:9090/admin/backends | python3 -c '
+import json, sys
+backend = json.load(sys.stdin)[0]
+print(" ".join(str(backend[name]) for name in sys.argv[1:]))
+' "$@"
+}
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend server...${NC}"
+# backend1 alternately passes and fails its first 6 health checks, then stops
+# answering them so that no more health checks are counted during the test
+python3 -c '
+import http.server, time
+
+health_checks = 0
+
+class Handler(http.server.BaseHTTPRequestHandler):
+    def do_GET(self):
+        global health_checks
+        status = 200
+        if self.path == "/health":
+            health_checks += 1
+            if health_checks > 6:
+                time.sleep(3600)
+            status = 200 if health_checks % 2 == 1 else 500
+        self.send_response(status)
+        self.send_header("Content-Length", "0")
+        self.end_headers()
+
+http.server.ThreadingHTTPServer(("localhost", 8081), Handler).serve_forever()
+' > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+cargo run -p lb -- -i 200ms --health-check-status 200 --health-check-timeout 60s \
+    --admin-port 9090 "http://localhost:8081/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+wait_for_server "admin API" 9090
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+# Let the 6 answered health checks run
+sleep 3
+read -r passed failed failure_rate <<< \
+    "$(admin_fields health_checks_passed health_checks_failed health_check_failure_rate)"
+metrics=$(curl --silent http://localhost:8080/metrics)
+
+# Assert -----------------------------------------------------------------------
+if [[ $passed == "3" && $failed == "3" ]]; then
+    echo -e "${GREEN}The admin API counted 3 passed and 3 failed health checks.${NC}"
+else
+    echo -e "${RED}The admin API counted ${passed} passed and ${failed} failed health checks.${NC}"
+    test_passed=false
+fi
+
+if [[ $failure_rate == "0.5" ]]; then
+    echo -e "${GREEN}The failure rate of the recent health checks is 0.5.${NC}"
+else
+    echo -e "${RED}The failure rate of the recent health checks is ${failure_rate}.${NC}"
+    test_passed=false
+fi
+
+if echo "$metrics" | grep -q "^lb_backend_health_checks_total{backend=\"http://localhost:8081/\",outcome=\"passed\"} 3$" \
+    && echo "$metrics" | grep -q "^lb_backend_health_checks_total{backend=\"http://localhost:8081/\",outcome=\"failed\"} 3$" \
+    && echo "$metrics" | grep -q "^lb_backend_health_check_failure_rate{backend=\"http://localhost:8081/\"} 0.5$"; then
+    echo -e "${GREEN}The metrics expose the same counters.${NC}"
+else
+    echo -e "${RED}The metrics do not expose the same counters: $(echo "$metrics" | grep health_check).${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend server and load balancer...${NC}"
+kill_pids $backend1_pid $lb_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,111 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that --health-check-jitter spreads the health checks around the interval,
+# within the given fraction of it, and that they are regular without it
+# ------------------------------------------------------------------------------
+
+# Prints the smallest and the largest time between the health checks recorded
+# in the given file, in seconds, separated by a space
+interval_bounds() {
+    python3 -c '
+import sys
+times = [float(line) for line in open(sys.argv[1])]
+intervals = [b - a for a, b in zip(times, times[1:])]
+print("{:.3f} {:.3f}".format(min(intervals), max(intervals)))
+' "$1"
+}
+
+# Prints true if the given value is in the given range [min, max)
+in_range() {
+    python3 -c "import sys; print(str($2 <= float('$1') < $3).lower())"
+}
+
+# Starts a backend server on port 8081 recording the time of its health checks
+# in the given file
+start_backend() {
+    python3 -c '
+import http.server, sys, time
+
+class Handler(http.server.BaseHTTPRequestHandler):
+    def do_GET(self):
+        if self.path == "/health":
+            with open(sys.argv[1], "a") as times:
+                times.write("{}\n".format(time.time()))
+        self.send_response(200)
+        self.send_header("Content-Length", "0")
+        self.end_headers()
+
+http.server.ThreadingHTTPServer(("localhost", 8081), Handler).serve_forever()
+' "$1" > /dev/null 2>&1 &
+    backend1_pid=$!
+    wait_for_server "backend1" 8081
+}
+
+# Arrange ----------------------------------------------------------------------
+jittered_times=$(mktemp)
+regular_times=$(mktemp)
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+echo -e "${GREEN}Starting load balancer with a jitter of 50%...${NC}"
+start_backend "$jittered_times"
+cargo run -p lb -- -i 500ms --health-check-jitter 0.5 "http://localhost:8081/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+sleep 8
+kill_pids $lb_pid $backend1_pid > /dev/null
+read -r jittered_min jittered_max <<< "$(interval_bounds "$jittered_times")"
+
+echo -e "${GREEN}Starting load balancer without jitter...${NC}"
+start_backend "$regular_times"
+cargo run -p lb -- -i 500ms "http://localhost:8081/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+sleep 4
+read -r regular_min regular_max <<< "$(interval_bounds "$regular_times")"
+
+invalid_jitter_output=$(timeout 20 cargo run -p lb -- --health-check-jitter 1 --listen-port 8090 \
+    "http://localhost:8081/" 2>&1)
+invalid_jitter_status=$?
+
+# Assert -----------------------------------------------------------------------
+# 0.5s ± 50%, with some slack for the time taken by the health checks
+if [[ $(in_range "$jittered_min" 0.24 0.5) == true && $(in_range "$jittered_max" 0.5 0.8) == true \
+    && $(in_range "$(python3 -c "print($jittered_max - $jittered_min)")" 0.1 1) == true ]]; then
+    echo -e "${GREEN}The jittered intervals varied from ${jittered_min}s to ${jittered_max}s.${NC}"
+else
+    echo -e "${RED}With a jitter of 50%, the intervals went from ${jittered_min}s to ${jittered_max}s.${NC}"
+    test_passed=false
+fi
+
+if [[ $(in_range "$regular_min" 0.45 0.55) == true && $(in_range "$regular_max" 0.45 0.55) == true ]]; then
+    echo -e "${GREEN}Without jitter, the intervals stayed at 0.5s.${NC}"
+else
+    echo -e "${RED}Without jitter, the intervals went from ${regular_min}s to ${regular_max}s.${NC}"
+    test_passed=false
+fi
+
+if [[ $invalid_jitter_status -ne 0 && $invalid_jitter_status -ne 124 \
+    && $invalid_jitter_output == *"--health-check-jitter"* ]]; then
+    echo -e "${GREEN}A jitter of 100% was rejected.${NC}"
+else
+    echo -e "${RED}With a jitter of 100%, the load balancer exited with ${invalid_jitter_status}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend server and load balancer...${NC}"
+kill_pids $backend1_pid $lb_pid
+rm -f "$jittered_times" "$regular_times"
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
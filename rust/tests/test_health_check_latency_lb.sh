@@ -0,0 +1,83 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that the health checks do not change the response time used for routing,
+# and that their latency is reported separately
+# ------------------------------------------------------------------------------
+
+# Prints the response time and the health check latency of the backend server
+# from the admin API, separated by a space
+latencies() {
+    curl --silent http://localhost:9090/admin/backends | python3 -c '
+import json, sys
+backend = json.load(sys.stdin)[0]
+print(backend["response_time_ms"], backend["health_check_latency_ms"])
+'
+}
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+# backend1 answers the requests at once and the health checks after 300ms
+python3 -c '
+import http.server
+import time
+
+class Handler(http.server.BaseHTTPRequestHandler):
+    def do_GET(self):
+        if self.path == "/health":
+            time.sleep(0.3)
+        self.send_response(200)
+        self.send_header("Content-Length", "0")
+        self.end_headers()
+
+http.server.ThreadingHTTPServer(("localhost", 8081), Handler).serve_forever()
+' > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+cargo run -p lb -- -i 1 --strategy least-response "http://localhost:8081/" --admin-port 9090 &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+wait_for_server "admin API" 9090
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+for i in $(seq 1 3); do
+    curl --silent --output /dev/null http://localhost:8080/
+done
+read -r response_time_before health_check_latency_before <<< "$(latencies)"
+# Let at least two health checks run
+sleep 2.5
+read -r response_time_after health_check_latency_after <<< "$(latencies)"
+
+# Assert -----------------------------------------------------------------------
+if [[ $response_time_after == "$response_time_before" ]] \
+    && python3 -c "import sys; sys.exit(0 if 0 < $response_time_after < 300 else 1)"; then
+    echo -e "${GREEN}The response time stayed at ${response_time_after}ms across the health checks.${NC}"
+else
+    echo -e "${RED}The response time went from ${response_time_before}ms to ${response_time_after}ms.${NC}"
+    test_passed=false
+fi
+
+if python3 -c "import sys; sys.exit(0 if $health_check_latency_after >= 300 else 1)"; then
+    echo -e "${GREEN}The health checks took ${health_check_latency_after}ms.${NC}"
+else
+    echo -e "${RED}The health check latency is ${health_check_latency_after}ms, before ${health_check_latency_before}ms.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $lb_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,106 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that a backend server reporting a high load in the answers to its health
+# checks receives proportionally fewer requests, and its full share again once
+# the load is not read
+# ------------------------------------------------------------------------------
+
+# Prints the number of requests out of 100 answered by each backend server
+answers() {
+    for i in $(seq 1 100); do
+        curl --silent http://localhost:8080/
+        echo
+    done | sort | uniq -c | awk '{ print $2 "=" $1 }' | tr "\n" " "
+}
+
+# Prints the number of requests answered by the given backend server in the
+# given answers
+count() {
+    echo "$1" | grep -o "$2=[0-9]*" | cut -d= -f2
+}
+
+# Starts a backend server on the given port answering its health checks with
+# the given load
+start_backend() {
+    python3 -c '
+import http.server, json, sys
+
+class Handler(http.server.BaseHTTPRequestHandler):
+    def do_GET(self):
+        if self.path == "/health":
+            body = json.dumps({"status": "ok", "load": float(sys.argv[3])}).encode()
+        else:
+            body = sys.argv[2].encode()
+        self.send_response(200)
+        self.send_header("Content-Length", str(len(body)))
+        self.end_headers()
+        self.wfile.write(body)
+
+http.server.ThreadingHTTPServer(("localhost", int(sys.argv[1])), Handler).serve_forever()
+' "$@" > /dev/null 2>&1 &
+}
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+start_backend 8081 backend1 0.0
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+start_backend 8082 backend2 0.75
+backend2_pid=$!
+wait_for_server "backend2" 8082
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+echo -e "${GREEN}Starting load balancer reading the load...${NC}"
+cargo run -p lb -- -i 1 --strategy weighted-random --random-seed 42 --health-check-load-field load \
+    "http://localhost:8081/" "http://localhost:8082/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+# Once a health check read the load
+sleep 2
+load_answers=$(answers)
+kill_pids $lb_pid > /dev/null
+
+echo -e "${GREEN}Starting load balancer without reading the load...${NC}"
+cargo run -p lb -- -i 1 --strategy weighted-random --random-seed 42 \
+    "http://localhost:8081/" "http://localhost:8082/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+sleep 2
+no_load_answers=$(answers)
+
+# Assert -----------------------------------------------------------------------
+# With a load of 0.75, backend2 has a quarter of the weight of backend1, so it
+# receives about a fifth of the requests
+loaded_count=$(count "$load_answers" backend2)
+if [[ -n $loaded_count && $loaded_count -ge 5 && $loaded_count -le 35 ]]; then
+    echo -e "${GREEN}The loaded backend server received fewer requests.${NC}"
+else
+    echo -e "${RED}Reading the load, the requests were answered by ${load_answers}.${NC}"
+    test_passed=false
+fi
+
+unloaded_count=$(count "$no_load_answers" backend2)
+if [[ -n $unloaded_count && $unloaded_count -ge 35 && $unloaded_count -le 65 ]]; then
+    echo -e "${GREEN}Without reading the load, the backend servers received the same share.${NC}"
+else
+    echo -e "${RED}Without reading the load, the requests were answered by ${no_load_answers}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $backend2_pid $lb_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,109 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that backend servers can be added and the load balancer can be replaced
+# while a slow health check is running
+# ------------------------------------------------------------------------------
+
+# Returns the current time in milliseconds
+now_ms() {
+    echo $(($(date +%s%N) / 1000000))
+}
+
+# Arrange ----------------------------------------------------------------------
+config_file=$(mktemp --suffix .toml)
+cat > "$config_file" << EOF
+strategy = "least-response"
+backends = ["http://localhost:8081/"]
+EOF
+
+echo -e "${GREEN}Starting backend servers...${NC}"
+# backend1 takes 3 seconds to answer the health checks, so that a health check
+# is always running
+python3 -c '
+import http.server
+import socketserver
+import time
+
+class Handler(http.server.BaseHTTPRequestHandler):
+    def do_GET(self):
+        if self.path == "/health":
+            time.sleep(3)
+        self.send_response(200)
+        self.end_headers()
+        self.wfile.write(b"Hello from backend server: backend1")
+
+class Server(socketserver.ThreadingMixIn, http.server.HTTPServer):
+    daemon_threads = True
+
+Server(("localhost", 8081), Handler).serve_forever()
+' > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+cargo run -p be -- -n "backend2" -p 8082 > /dev/null 2>&1 &
+backend2_pid=$!
+wait_for_server "backend2" 8082
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+# Run the binary directly so that SIGHUP is sent to the load balancer and not to cargo
+cargo build -p lb > /dev/null 2>&1
+../target/debug/lb -i 1 --config "$config_file" --admin-port 9090 &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+wait_for_server "admin API" 9090
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+# Let the next health check start
+sleep 1.5
+start=$(now_ms)
+add_status=$(curl --silent --output /dev/null --write-out "%{http_code}" --max-time 10 \
+    -H "Content-Type: application/json" -d '{"address": "http://localhost:8082/"}' \
+    http://localhost:9090/admin/backends)
+add_duration=$(($(now_ms) - start))
+
+cat > "$config_file" << EOF
+backends = ["http://localhost:8082/"]
+EOF
+start=$(now_ms)
+kill -HUP $lb_pid
+reload_duration=""
+for i in $(seq 1 100); do
+    if [[ $(curl --silent --max-time 10 http://localhost:8080/) == *"backend2"* ]]; then
+        reload_duration=$(($(now_ms) - start))
+        break
+    fi
+    sleep 0.05
+done
+
+# Assert -----------------------------------------------------------------------
+if [[ $add_status -eq 201 && $add_duration -lt 1000 ]]; then
+    echo -e "${GREEN}Added backend 2 in ${add_duration}ms during a health check.${NC}"
+else
+    echo -e "${RED}Adding backend 2 returned ${add_status} after ${add_duration}ms.${NC}"
+    test_passed=false
+fi
+
+if [[ -n $reload_duration && $reload_duration -lt 1000 ]]; then
+    echo -e "${GREEN}Replaced the load balancer in ${reload_duration}ms during a health check.${NC}"
+else
+    echo -e "${RED}Replacing the load balancer took ${reload_duration:-more than 5000}ms.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $backend2_pid $lb_pid
+rm -f "$config_file"
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,115 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that the health checks of a backend server with health_check_method =
+# "head" are HEAD requests whose status decides its health, that the other
+# backend servers are checked with GET, and that the body of HEAD health checks
+# cannot be checked
+# ------------------------------------------------------------------------------
+
+# Prints the methods of the health checks received by the given backend server,
+# without duplicates
+methods() {
+    grep "$1" "$methods_file" | cut -d " " -f 2 | sort -u | tr "\n" " "
+}
+
+# Arrange ----------------------------------------------------------------------
+methods_file=$(mktemp)
+config_file=$(mktemp --suffix .toml)
+cat > "$config_file" << EOF
+backends = [
+    { address = "http://localhost:8081/", health_check_method = "head" },
+    { address = "http://localhost:8082/", health_check_method = "head" },
+    "http://localhost:8083/",
+]
+EOF
+
+echo -e "${GREEN}Starting backend servers...${NC}"
+# Each backend server logs the method of its health checks. backend1 and backend3
+# answer them with a 200, except backend1 fails the GET ones. backend2 answers
+# them with a 503
+backend_pids=()
+for i in 1 2 3; do
+    python3 -c '
+import http.server, sys
+
+name, port, methods_file = sys.argv[1], int(sys.argv[2]), sys.argv[3]
+
+class Handler(http.server.BaseHTTPRequestHandler):
+    def answer(self, method):
+        status = 200
+        if self.path == "/health":
+            with open(methods_file, "a") as f:
+                f.write(name + " " + method + "\n")
+            if name == "backend2" or (name == "backend1" and method == "GET"):
+                status = 503
+        self.send_response(status)
+        self.send_header("Content-Length", "0")
+        self.end_headers()
+
+    def do_GET(self):
+        self.answer("GET")
+
+    def do_HEAD(self):
+        self.answer("HEAD")
+
+http.server.ThreadingHTTPServer(("localhost", port), Handler).serve_forever()
+' "backend${i}" "808${i}" "$methods_file" > /dev/null 2>&1 &
+    backend_pids+=($!)
+    wait_for_server "backend${i}" 808${i}
+done
+# Forget the requests made while waiting for the backend servers
+: > "$methods_file"
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+check_output=$(RUST_LOG=off cargo run -p lb -- --check --health-check-status 200 \
+    --config "$config_file" 2>/dev/null)
+backend1_methods=$(methods backend1)
+backend2_methods=$(methods backend2)
+backend3_methods=$(methods backend3)
+
+body_output=$(cargo run -p lb -- --check --health-check-method head --health-check-body ok \
+    "http://localhost:8083/" 2>&1)
+body_status=$?
+
+# Assert -----------------------------------------------------------------------
+if [[ $backend1_methods == "HEAD " && $backend2_methods == "HEAD " \
+    && $backend3_methods == "GET " ]]; then
+    echo -e "${GREEN}The health checks used the method of each backend server.${NC}"
+else
+    echo -e "${RED}The health checks used ${backend1_methods}, ${backend2_methods}and ${backend3_methods}.${NC}"
+    test_passed=false
+fi
+
+if echo "$check_output" | grep -q "localhost:8081/ *reachable" \
+    && echo "$check_output" | grep -q "localhost:8082/ *unreachable" \
+    && echo "$check_output" | grep -q "localhost:8083/ *reachable"; then
+    echo -e "${GREEN}The status of the HEAD health checks decided the health.${NC}"
+else
+    echo -e "${RED}The backend servers were checked as ${check_output}.${NC}"
+    test_passed=false
+fi
+
+if [[ $body_status -ne 0 && $body_output == *"no body to check"* ]]; then
+    echo -e "${GREEN}Checking the body of HEAD health checks was rejected.${NC}"
+else
+    echo -e "${RED}Checking the body of HEAD health checks exited with ${body_status}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers...${NC}"
+kill_pids "${backend_pids[@]}"
+rm -f "$methods_file" "$config_file"
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
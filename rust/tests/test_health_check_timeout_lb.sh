@@ -0,0 +1,102 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that a backend server which accepts the connections but hangs on its
+# health checks fails them within --health-check-timeout and is unhealthy, while
+# its requests are still answered
+# ------------------------------------------------------------------------------
+
+# Prints the health of backend1 given by the admin API
+backend1_health() {
+    curl --silent http://localhost:9090/admin/backends \
+        | grep -o '"address":"http://localhost:8081/","health":"[A-Za-z]*"' | grep -o "[A-Za-z]*\"$" \
+        | tr -d '"'
+}
+
+# Prints true if the given value is in the given range [min, max)
+in_range() {
+    python3 -c "import sys; print(str($2 <= float('$1') < $3).lower())"
+}
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+# backend1 answers the requests at once, but its health checks after 30s
+python3 -c '
+import http.server, time
+
+class Handler(http.server.BaseHTTPRequestHandler):
+    def do_GET(self):
+        if self.path.startswith("/health"):
+            time.sleep(30)
+        body = b"backend1"
+        self.send_response(200)
+        self.send_header("Content-Length", str(len(body)))
+        self.end_headers()
+        self.wfile.write(body)
+
+http.server.ThreadingHTTPServer(("localhost", 8081), Handler).serve_forever()
+' > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+cargo run -p be -- -n "backend2" -p 8082 > /dev/null 2>&1 &
+backend2_pid=$!
+wait_for_server "backend2" 8082
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+check_start=$(date +%s.%N)
+timeout 20 cargo run -p lb -- --check --health-check-timeout 500ms "http://localhost:8081/" \
+    > /dev/null 2>&1
+check_status=$?
+check_time=$(python3 -c "print($(date +%s.%N) - $check_start)")
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+cargo run -p lb -- -i 1 --health-check-timeout 500ms --admin-port 9090 \
+    "http://localhost:8081/" "http://localhost:8082/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+wait_for_server "admin API" 9090
+sleep 2
+health=$(backend1_health)
+answers=$(for i in $(seq 1 6); do
+    curl --silent http://localhost:8080/ | grep -o "backend[0-9]"
+done | sort -u | tr "\n" " ")
+direct_answer=$(curl --silent --max-time 5 http://localhost:8081/)
+
+# Assert -----------------------------------------------------------------------
+if [[ $check_status -ne 0 && $check_status -ne 124 && $(in_range "$check_time" 0.5 3) == true ]]; then
+    echo -e "${GREEN}The health check gave up after ${check_time}s.${NC}"
+else
+    echo -e "${RED}The check exited with ${check_status} after ${check_time}s.${NC}"
+    test_passed=false
+fi
+
+if [[ $health == "Unhealthy" && $answers == "backend2 " ]]; then
+    echo -e "${GREEN}The backend server hanging on its health checks was unhealthy.${NC}"
+else
+    echo -e "${RED}The backend server was ${health} and the requests were answered by ${answers}.${NC}"
+    test_passed=false
+fi
+
+if [[ $direct_answer == "backend1" ]]; then
+    echo -e "${GREEN}The backend server still answered its requests.${NC}"
+else
+    echo -e "${RED}The backend server answered ${direct_answer}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $backend2_pid $lb_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,96 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that the health of a backend server only changes after the threshold of
+# consecutive health checks is reached
+# ------------------------------------------------------------------------------
+
+# Returns the number of times the admin API reports backend1 as unhealthy while
+# polling it for 3 seconds
+count_unhealthy() {
+    local count=0
+    for i in $(seq 1 30); do
+        if curl --silent http://localhost:9090/admin/backends | grep -q '"health":"Unhealthy"'; then
+            count=$((count + 1))
+        fi
+        sleep 0.1
+    done
+    echo $count
+}
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+# backend1 alternately answers the health checks and closes their connection
+# without answering
+python3 -c '
+import http.server
+
+class Handler(http.server.BaseHTTPRequestHandler):
+    health_checks = 0
+
+    def do_GET(self):
+        Handler.health_checks += 1
+        if Handler.health_checks % 2 == 0:
+            self.close_connection = True
+            return
+        self.send_response(200)
+        self.end_headers()
+
+http.server.HTTPServer(("localhost", 8081), Handler).serve_forever()
+' > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+cargo run -p lb -- -i 200ms "http://localhost:8081/" --admin-port 9090 &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+wait_for_server "admin API" 9090
+unhealthy_without_threshold=$(count_unhealthy)
+kill_pids $lb_pid
+
+cargo run -p lb -- -i 200ms "http://localhost:8081/" --admin-port 9090 \
+    --unhealthy-threshold 2 --healthy-threshold 2 &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+wait_for_server "admin API" 9090
+unhealthy_with_threshold=$(count_unhealthy)
+
+kill_pids $backend1_pid
+unhealthy_when_down=$(count_unhealthy)
+kill_pids $lb_pid
+
+# Assert -----------------------------------------------------------------------
+if [[ $unhealthy_without_threshold -gt 0 ]]; then
+    echo -e "${GREEN}Without threshold, a single failed health check makes backend 1 unhealthy.${NC}"
+else
+    echo -e "${RED}Without threshold, backend 1 never became unhealthy.${NC}"
+    test_passed=false
+fi
+
+if [[ $unhealthy_with_threshold -eq 0 ]]; then
+    echo -e "${GREEN}With a threshold of 2, backend 1 stayed healthy.${NC}"
+else
+    echo -e "${RED}With a threshold of 2, backend 1 was unhealthy ${unhealthy_with_threshold} times.${NC}"
+    test_passed=false
+fi
+
+if [[ $unhealthy_when_down -gt 0 ]]; then
+    echo -e "${GREEN}With a threshold of 2, backend 1 became unhealthy once down.${NC}"
+else
+    echo -e "${RED}With a threshold of 2, backend 1 never became unhealthy once down.${NC}"
+    test_passed=false
+fi
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
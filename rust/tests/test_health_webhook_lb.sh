@@ -0,0 +1,143 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that a health event is logged and posted to the health webhook when a
+# backend server changes health, found by a health check or by a request, and
+# not on the health checks leaving its health unchanged
+# ------------------------------------------------------------------------------
+
+# Prints the health events received by the webhook, one per line, as ADDRESS
+# OLD_HEALTH NEW_HEALTH
+events() {
+    python3 -c '
+import json, sys
+for line in open(sys.argv[1]):
+    event = json.loads(line)
+    assert event["timestamp"].endswith("Z")
+    print(event["address"], event["old_health"], event["new_health"])
+' "$events_file"
+}
+
+# Arrange ----------------------------------------------------------------------
+events_file=$(mktemp)
+log_file=$(mktemp)
+
+echo -e "${GREEN}Starting webhook...${NC}"
+# The webhook appends the body of each POST to the events file
+python3 -c '
+import http.server, sys
+
+events_file = sys.argv[1]
+
+class Handler(http.server.BaseHTTPRequestHandler):
+    def do_POST(self):
+        body = self.rfile.read(int(self.headers["Content-Length"]))
+        with open(events_file, "a") as f:
+            f.write(body.decode() + "\n")
+        self.send_response(204)
+        self.end_headers()
+
+http.server.ThreadingHTTPServer(("localhost", 9099), Handler).serve_forever()
+' "$events_file" > /dev/null 2>&1 &
+webhook_pid=$!
+wait_for_server "webhook" 9099
+
+echo -e "${GREEN}Starting backend servers...${NC}"
+cargo run -p be -- -n "backend1" -p 8081 > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+cargo run -p be -- -n "backend2" -p 8082 > /dev/null 2>&1 &
+backend2_pid=$!
+wait_for_server "backend2" 8082
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+cargo run -p lb -- -i 200ms --health-check-status 200 --log-level info \
+    --health-webhook http://localhost:9099/events \
+    "http://localhost:8081/" "http://localhost:8082/" > "$log_file" 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+# Several health checks find both backend servers healthy
+sleep 2
+steady_events=$(events)
+
+curl --silent --output /dev/null --request POST "localhost:8081/admin/fail?mode=health"
+sleep 2
+failed_events=$(events)
+
+curl --silent --output /dev/null --request POST localhost:8081/admin/recover
+sleep 2
+recovered_events=$(events)
+logged_events=$(grep "Health event:" "$log_file")
+kill_pids $lb_pid
+
+# Without health checks for a minute, only the requests can find backend2 down
+> "$events_file"
+cargo run -p lb -- -i 60s --health-check-status 200 \
+    --health-webhook http://localhost:9099/events \
+    "http://localhost:8081/" "http://localhost:8082/" > /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+kill_pids $backend2_pid
+for _ in {1..4}; do
+    curl --silent --output /dev/null http://localhost:8080/
+done
+sleep 1
+request_events=$(events)
+
+# Assert -----------------------------------------------------------------------
+if [[ -z $steady_events ]]; then
+    echo -e "${GREEN}No event was sent while the backend servers stayed healthy.${NC}"
+else
+    echo -e "${RED}While the backend servers stayed healthy, the webhook received ${steady_events}.${NC}"
+    test_passed=false
+fi
+
+if [[ $failed_events == "http://localhost:8081/ Healthy Unhealthy" ]]; then
+    echo -e "${GREEN}A single event was sent when backend1 became unhealthy.${NC}"
+else
+    echo -e "${RED}After backend1 failed, the webhook received ${failed_events}.${NC}"
+    test_passed=false
+fi
+
+if [[ $recovered_events == "http://localhost:8081/ Healthy Unhealthy"$'\n'"http://localhost:8081/ Unhealthy Healthy" ]]; then
+    echo -e "${GREEN}A single event was sent when backend1 became healthy again.${NC}"
+else
+    echo -e "${RED}After backend1 recovered, the webhook received ${recovered_events}.${NC}"
+    test_passed=false
+fi
+
+if [[ $(echo "$logged_events" | grep -c "http://localhost:8081/ changed from Healthy to Unhealthy") -eq 1 \
+    && $(echo "$logged_events" | grep -c "http://localhost:8081/ changed from Unhealthy to Healthy") -eq 1 \
+    && $(echo "$logged_events" | wc -l) -eq 2 ]]; then
+    echo -e "${GREEN}The events were logged on their own lines.${NC}"
+else
+    echo -e "${RED}The logged events were ${logged_events}.${NC}"
+    test_passed=false
+fi
+
+if [[ $request_events == "http://localhost:8082/ Healthy Unhealthy" ]]; then
+    echo -e "${GREEN}An event was sent when a request found backend2 down.${NC}"
+else
+    echo -e "${RED}After backend2 went down, the webhook received ${request_events}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing webhook, backend servers and load balancer...${NC}"
+kill_pids $webhook_pid $backend1_pid $backend2_pid $lb_pid
+rm -f "$events_file" "$log_file"
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,69 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test giving the weights of the backend servers on the command line
+# ------------------------------------------------------------------------------
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+cargo run -p be -- -n "backend1" -p 8081 > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+cargo run -p be -- -n "backend2" -p 8082 > /dev/null 2>&1 &
+backend2_pid=$!
+wait_for_server "backend2" 8082
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+# backend1 has the default weight
+cargo run -p lb -- -i 10 -d "http://localhost:8081/" "http://localhost:8082/|10" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+result=""
+for i in $(seq 1 10); do
+    result+=$(curl --silent http://localhost:8080/)
+done
+count_backend1=$(echo $result | grep -o "backend1" | wc -l)
+count_backend2=$(echo $result | grep -o "backend2" | wc -l)
+kill_pids $lb_pid
+
+malformed_weights_rejected=true
+for backend in "http://localhost:8081/|-1" "http://localhost:8081/|abc" "http://localhost:8081/|"; do
+    if timeout 10 cargo run -p lb -- "$backend" &> /dev/null; then
+        echo -e "${RED}The backend server ${backend} was accepted.${NC}"
+        malformed_weights_rejected=false
+    fi
+done
+
+# Assert -----------------------------------------------------------------------
+if [[ $((count_backend1 + count_backend2)) -eq 10 && $count_backend2 -gt $count_backend1 ]]; then
+    echo -e "${GREEN}Backend 2 received most of the requests.${NC}"
+else
+    echo -e "${RED}Backend 2 did not receive most of the requests
+    backend1=${count_backend1}, backend2=${count_backend2}.${NC}"
+    test_passed=false
+fi
+
+if [[ "$malformed_weights_rejected" == true ]]; then
+    echo -e "${GREEN}The malformed weights were rejected.${NC}"
+else
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers...${NC}"
+kill_pids $backend1_pid $backend2_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
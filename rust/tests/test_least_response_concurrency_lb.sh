@@ -0,0 +1,64 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that the least response load balancer sends concurrent requests to the
+# backend servers in parallel instead of one after the other
+# ------------------------------------------------------------------------------
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+# Both backend servers answer in 1s
+cargo run -p be -- -n "backend1" -p 8081 -d 1000 > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+cargo run -p be -- -n "backend2" -p 8082 -d 1000 > /dev/null 2>&1 &
+backend2_pid=$!
+wait_for_server "backend2" 8082
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+cargo run -p lb -- -i 10 --strategy least-response --selection-timeout 15s \
+    "http://localhost:8081/" "http://localhost:8082/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+# One after the other, the 6 requests would take 6s
+start=$(date +%s%N)
+result=$(curl --silent --parallel --parallel-immediate --parallel-max 6 \
+    $(for i in $(seq 1 6); do echo "http://localhost:8080/"; done))
+elapsed_ms=$((($(date +%s%N) - start) / 1000000))
+count_answers=$(echo "$result" | grep -o "backend[0-9]" | wc -l)
+count_backend1=$(echo "$result" | grep -o "backend1" | wc -l)
+count_backend2=$(echo "$result" | grep -o "backend2" | wc -l)
+
+# Assert -----------------------------------------------------------------------
+if [[ $count_answers -eq 6 && $elapsed_ms -lt 3000 ]]; then
+    echo -e "${GREEN}The 6 requests were answered in parallel in ${elapsed_ms}ms.${NC}"
+else
+    echo -e "${RED}${count_answers} requests were answered in ${elapsed_ms}ms, expected 6 in less than 3000ms.${NC}"
+    test_passed=false
+fi
+
+if [[ $count_backend1 -ge 1 && $count_backend2 -ge 1 ]]; then
+    echo -e "${GREEN}Both backend servers received requests.${NC}"
+else
+    echo -e "${RED}The backend servers received ${count_backend1} and ${count_backend2} requests.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $backend2_pid $lb_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,78 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that the load balancer listens on every IPv4 and IPv6 address given with
+# --listen, and rejects the invalid ones
+# ------------------------------------------------------------------------------
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+cargo run -p be -- -n "backend1" -p 8081 > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+lb_log=$(mktemp)
+cargo run -p lb -- -i 10 --listen "127.0.0.1:8080,[::1]:8090" --listen "[::1]:8091" \
+    "http://localhost:8081/" > "$lb_log" 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+answers=""
+for url in "http://127.0.0.1:8080/" "http://[::1]:8090/" "http://[::1]:8091/"; do
+    answers+=$(curl --silent --globoff "$url" | grep -o "backend1")
+done
+serving_lines=$(grep -o "Serving HTTP on .*" "$lb_log")
+
+invalid_output=$(cargo run -p lb -- --listen "127.0.0.1:8092,localhost:8093" "http://localhost:8081/" 2>&1)
+duplicate_output=$(cargo run -p lb -- --listen "[::1]:8092,[0:0::1]:8092" "http://localhost:8081/" 2>&1)
+
+# Assert -----------------------------------------------------------------------
+if [[ $answers == "backend1backend1backend1" ]]; then
+    echo -e "${GREEN}The load balancer answered on all the IPv4 and IPv6 addresses.${NC}"
+else
+    echo -e "${RED}The load balancer answered ${answers} on the 3 addresses.${NC}"
+    test_passed=false
+fi
+
+expected_serving_lines="Serving HTTP on 127.0.0.1:8080
+Serving HTTP on [::1]:8090
+Serving HTTP on [::1]:8091"
+if [[ $(echo "$serving_lines" | sort) == "$expected_serving_lines" ]]; then
+    echo -e "${GREEN}All the bound sockets were logged.${NC}"
+else
+    echo -e "${RED}The bound sockets logged are: ${serving_lines}.${NC}"
+    test_passed=false
+fi
+
+if echo "$invalid_output" | grep -q "invalid listen address localhost:8093"; then
+    echo -e "${GREEN}An address without an IP is rejected.${NC}"
+else
+    echo -e "${RED}An address without an IP was not rejected: ${invalid_output}.${NC}"
+    test_passed=false
+fi
+
+if echo "$duplicate_output" | grep -q "The listen address \[::1\]:8092 is given twice"; then
+    echo -e "${GREEN}An address given twice is rejected.${NC}"
+else
+    echo -e "${RED}An address given twice was not rejected: ${duplicate_output}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $lb_pid
+rm -f "$lb_log"
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,94 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that --log-level sets the maximum level of the logged messages, taking
+# precedence over RUST_LOG, and that an unknown level is rejected
+# ------------------------------------------------------------------------------
+
+# Prints the levels of the messages logged in the given log file, for example
+# "INFO WARN"
+logged_levels() {
+    grep -o -E " (TRACE|DEBUG|INFO|WARN|ERROR) " "$1" | tr -d " " | sort -u | tr "\n" " "
+}
+
+# Starts the load balancer with the given RUST_LOG and arguments, logging to
+# the given log file, sends it a request and stops it
+run_load_balancer() {
+    local log_file=$1
+    local rust_log=$2
+    shift 2
+    RUST_LOG=$rust_log cargo run -p lb -- "$@" "http://localhost:8081/" > "$log_file" 2>&1 &
+    lb_pid=$!
+    wait_for_server "load balancer" 8080
+    curl --silent --output /dev/null http://localhost:8080/
+    kill_pids $lb_pid > /dev/null
+}
+
+# Arrange ----------------------------------------------------------------------
+log_file=$(mktemp)
+
+echo -e "${GREEN}Starting backend server...${NC}"
+cargo run -p be -- -n "backend1" -p 8081 > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+run_load_balancer "$log_file" info
+env_levels=$(logged_levels "$log_file")
+
+run_load_balancer "$log_file" info --log-level warn
+warn_levels=$(logged_levels "$log_file")
+
+run_load_balancer "$log_file" error --log-level DEBUG
+debug_levels=$(logged_levels "$log_file")
+
+invalid_level_output=$(timeout 20 cargo run -p lb -- --log-level verbose --listen-port 8090 \
+    "http://localhost:8081/" 2>&1)
+invalid_level_status=$?
+
+# Assert -----------------------------------------------------------------------
+if [[ $env_levels == *"INFO"* && $env_levels != *"DEBUG"* ]]; then
+    echo -e "${GREEN}Without --log-level, RUST_LOG set the level.${NC}"
+else
+    echo -e "${RED}With RUST_LOG=info, the levels ${env_levels}were logged.${NC}"
+    test_passed=false
+fi
+
+if [[ $warn_levels != *"INFO"* && $warn_levels != *"DEBUG"* ]]; then
+    echo -e "${GREEN}--log-level warn took precedence over RUST_LOG=info.${NC}"
+else
+    echo -e "${RED}With --log-level warn, the levels ${warn_levels}were logged.${NC}"
+    test_passed=false
+fi
+
+if [[ $debug_levels == *"DEBUG"* && $debug_levels == *"INFO"* && $debug_levels != *"TRACE"* ]]; then
+    echo -e "${GREEN}--log-level DEBUG took precedence over RUST_LOG=error.${NC}"
+else
+    echo -e "${RED}With --log-level DEBUG, the levels ${debug_levels}were logged.${NC}"
+    test_passed=false
+fi
+
+if [[ $invalid_level_status -ne 0 && $invalid_level_status -ne 124 \
+    && $invalid_level_output == *"invalid log level verbose"* ]]; then
+    echo -e "${GREEN}An unknown log level was rejected.${NC}"
+else
+    echo -e "${RED}With --log-level verbose, the load balancer exited with ${invalid_level_status}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend server...${NC}"
+kill_pids $backend1_pid
+rm -f "$log_file"
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,97 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that --maintenance-page answers the requests with the given page and
+# --maintenance-status when no backend server is available, and only then
+# ------------------------------------------------------------------------------
+
+# Prints the status, the content type and the body of a request to the load
+# balancer, separated by spaces
+answer() {
+    curl --silent --write-out " %{http_code} %{content_type}" http://localhost:8080/ | tr -d "\n"
+}
+
+# Arrange ----------------------------------------------------------------------
+page_file=$(mktemp --suffix .html)
+echo "<h1>Down for maintenance</h1>" > "$page_file"
+
+echo -e "${GREEN}Starting backend servers...${NC}"
+cargo run -p be -- -n "backend1" -p 8081 > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+echo -e "${GREEN}Starting load balancer with a maintenance page...${NC}"
+cargo run -p lb -- -i 1 --maintenance-page "$page_file" "http://localhost:8081/" \
+    &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+up_answer=$(answer)
+
+kill_pids $backend1_pid > /dev/null
+# Once a health check found backend1 down
+sleep 2
+# The page was read at startup
+rm -f "$page_file"
+down_answer=$(answer)
+kill_pids $lb_pid > /dev/null
+
+echo -e "${GREEN}Starting load balancer with a maintenance status...${NC}"
+echo "<h1>Back soon</h1>" > "$page_file"
+cargo run -p lb -- -i 1 --maintenance-page "$page_file" --maintenance-status 502 \
+    "http://localhost:8081/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+status_answer=$(answer)
+kill_pids $lb_pid > /dev/null
+
+missing_page_output=$(timeout 20 cargo run -p lb -- --maintenance-page /nonexistent/page.html \
+    "http://localhost:8081/" 2>&1)
+missing_page_status=$?
+
+# Assert -----------------------------------------------------------------------
+if [[ $up_answer == *"backend1"*" 200 "* ]]; then
+    echo -e "${GREEN}The backend server answered while it was up.${NC}"
+else
+    echo -e "${RED}While the backend server was up, the answer was ${up_answer}.${NC}"
+    test_passed=false
+fi
+
+if [[ $down_answer == "<h1>Down for maintenance</h1> 503 text/html"* ]]; then
+    echo -e "${GREEN}The maintenance page answered once the backend server was down.${NC}"
+else
+    echo -e "${RED}Once the backend server was down, the answer was ${down_answer}.${NC}"
+    test_passed=false
+fi
+
+if [[ $status_answer == "<h1>Back soon</h1> 502 text/html"* ]]; then
+    echo -e "${GREEN}The maintenance page was sent with the given status.${NC}"
+else
+    echo -e "${RED}With --maintenance-status 502, the answer was ${status_answer}.${NC}"
+    test_passed=false
+fi
+
+if [[ $missing_page_status -ne 0 && $missing_page_status -ne 124 \
+    && $missing_page_output == *"/nonexistent/page.html"* ]]; then
+    echo -e "${GREEN}The load balancer did not start without its maintenance page.${NC}"
+else
+    echo -e "${RED}With a missing maintenance page, the load balancer exited with ${missing_page_status}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing load balancer...${NC}"
+kill_pids $lb_pid
+rm -f "$page_file"
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
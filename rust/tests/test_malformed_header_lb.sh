@@ -0,0 +1,57 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that a request with a header value which is not valid UTF-8 is answered
+# and does not crash a worker of the load balancer
+# ------------------------------------------------------------------------------
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+cargo run -p be -- -n "backend1" -p 8081 > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+cargo run -p lb -- -i 10 "http://localhost:8081/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+# caf\xe9 is café in Latin-1, which is not valid UTF-8
+malformed_statuses=""
+for i in $(seq 1 8); do
+    malformed_statuses+=$(curl --silent --output /dev/null --write-out "%{http_code} " \
+        --header $'X-Test: caf\xe9' http://localhost:8080/)
+done
+status=$(curl --silent --output /dev/null --write-out "%{http_code}" http://localhost:8080/)
+
+# Assert -----------------------------------------------------------------------
+if [[ $malformed_statuses == "$(printf "200 %.0s" $(seq 1 8))" ]]; then
+    echo -e "${GREEN}The requests with a header which is not valid UTF-8 were answered.${NC}"
+else
+    echo -e "${RED}The requests with a header which is not valid UTF-8 were answered with ${malformed_statuses}.${NC}"
+    test_passed=false
+fi
+
+if [[ $status -eq 200 ]]; then
+    echo -e "${GREEN}The load balancer kept answering the next requests.${NC}"
+else
+    echo -e "${RED}The next request was answered with ${status}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $lb_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,57 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that the requests with a body larger than the maximum body size are
+# answered with a 413
+# ------------------------------------------------------------------------------
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+cargo run -p be -- -n "backend1" -p 8081 > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+cargo run -p lb -- -i 10 "http://localhost:8081/" --max-body-size 1024 &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+under_limit_status=$(head -c 1024 /dev/zero | curl --silent --output /dev/null --write-out "%{http_code}" \
+    --data-binary @- http://localhost:8080/)
+over_limit_status=$(head -c 1025 /dev/zero | curl --silent --output /dev/null --write-out "%{http_code}" \
+    --data-binary @- http://localhost:8080/)
+# Without Content-Length, the body is only known to be too large while reading it
+chunked_over_limit_status=$(head -c 4096 /dev/zero | curl --silent --output /dev/null --write-out "%{http_code}" \
+    --header "Transfer-Encoding: chunked" --data-binary @- http://localhost:8080/)
+
+# Assert -----------------------------------------------------------------------
+if [[ $under_limit_status -eq 200 ]]; then
+    echo -e "${GREEN}A body at the limit is accepted.${NC}"
+else
+    echo -e "${RED}A body at the limit is answered with a ${under_limit_status}.${NC}"
+    test_passed=false
+fi
+
+if [[ $over_limit_status -eq 413 && $chunked_over_limit_status -eq 413 ]]; then
+    echo -e "${GREEN}A body over the limit is answered with a 413.${NC}"
+else
+    echo -e "${RED}A body over the limit is answered with a ${over_limit_status}, or ${chunked_over_limit_status} when chunked.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $lb_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,87 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that the requests over --max-concurrent-requests wait up to
+# --queue-timeout for a request to end, and are then answered with a 503
+# ------------------------------------------------------------------------------
+
+# Prints true if the given value is in the given range [min, max)
+in_range() {
+    python3 -c "import sys; print(str($2 <= float('$1') < $3).lower())"
+}
+
+# Sends 2 requests in the background, taking the 2 slots of the load balancer
+# for 2s
+fill_slots() {
+    for i in $(seq 1 2); do
+        curl --silent --output /dev/null http://localhost:8080/ &
+    done
+    sleep 0.3
+}
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+cargo run -p be -- -n "backend1" -p 8081 -d 2000 > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+echo -e "${GREEN}Starting load balancer with a short queue timeout...${NC}"
+cargo run -p lb -- -i 60 --max-concurrent-requests 2 --queue-timeout 500ms \
+    "http://localhost:8081/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+fill_slots
+read -r rejected_status rejected_time <<< "$(curl --silent --output /dev/null \
+    --write-out "%{http_code} %{time_total}" http://localhost:8080/)"
+# Once the 2 requests ended
+sleep 2
+freed_status=$(curl --silent --output /dev/null --write-out "%{http_code}" http://localhost:8080/)
+kill_pids $lb_pid > /dev/null
+
+echo -e "${GREEN}Starting load balancer with a long queue timeout...${NC}"
+cargo run -p lb -- -i 60 --max-concurrent-requests 2 --queue-timeout 5s \
+    "http://localhost:8081/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+fill_slots
+read -r queued_status queued_time <<< "$(curl --silent --output /dev/null \
+    --write-out "%{http_code} %{time_total}" http://localhost:8080/)"
+
+# Assert -----------------------------------------------------------------------
+if [[ $rejected_status == "503" && $(in_range "$rejected_time" 0.5 1.5) == true ]]; then
+    echo -e "${GREEN}The request over the limit was rejected after ${rejected_time}s.${NC}"
+else
+    echo -e "${RED}The request over the limit was answered with ${rejected_status} after ${rejected_time}s.${NC}"
+    test_passed=false
+fi
+
+if [[ $freed_status == "200" ]]; then
+    echo -e "${GREEN}The requests were accepted again once the slots were freed.${NC}"
+else
+    echo -e "${RED}Once the slots were freed, the request was answered with ${freed_status}.${NC}"
+    test_passed=false
+fi
+
+if [[ $queued_status == "200" && $(in_range "$queued_time" 3 5) == true ]]; then
+    echo -e "${GREEN}The queued request was answered after ${queued_time}s.${NC}"
+else
+    echo -e "${RED}The queued request was answered with ${queued_status} after ${queued_time}s.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend server and load balancer...${NC}"
+kill_pids $backend1_pid $lb_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,81 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that the requests are sent to another backend server when one reached its
+# maximum number of connections
+# ------------------------------------------------------------------------------
+
+config_file=$(mktemp --suffix .toml)
+
+for strategy in round-robin power-of-two-choices; do
+    echo -e "${GREEN}Testing the ${strategy} strategy...${NC}"
+
+    # Arrange ------------------------------------------------------------------
+    cat > "$config_file" << EOF
+strategy = "${strategy}"
+backends = [
+    { address = "http://localhost:8081/", max_connections = 1 },
+    "http://localhost:8082/",
+]
+EOF
+
+    echo -e "${GREEN}Starting backend servers...${NC}"
+    # backend1 is slow, so that its only connection stays in flight during the test
+    cargo run -p be -- -n "backend1" -p 8081 -d 3000 > /dev/null 2>&1 &
+    backend1_pid=$!
+    wait_for_server "backend1" 8081
+
+    cargo run -p be -- -n "backend2" -p 8082 > /dev/null 2>&1 &
+    backend2_pid=$!
+    wait_for_server "backend2" 8082
+
+    echo -e "${GREEN}Starting load balancer...${NC}"
+    cargo run -p lb -- -i 10 --config "$config_file" &> /dev/null 2>&1 &
+    lb_pid=$!
+    wait_for_server "load balancer" 8080
+
+    # Act ----------------------------------------------------------------------
+    echo -e "${GREEN}Running tests...${NC}"
+    # One of these requests is sent to backend1 and keeps its only connection
+    # busy: with two choices, the second one goes to backend1 once backend2 has a
+    # response time
+    curl --silent http://localhost:8080/ > /dev/null &
+    slow_request1_pid=$!
+    sleep 0.2
+    curl --silent http://localhost:8080/ > /dev/null &
+    slow_request2_pid=$!
+    sleep 1
+
+    result=""
+    for i in $(seq 1 4); do
+        result+=$(curl --silent http://localhost:8080/)
+    done
+    count_backend1=$(echo $result | grep -o "backend1" | wc -l)
+    count_backend2=$(echo $result | grep -o "backend2" | wc -l)
+    wait $slow_request1_pid $slow_request2_pid
+
+    # Assert -------------------------------------------------------------------
+    if [[ $count_backend1 -eq 0 && $count_backend2 -eq 4 ]]; then
+        echo -e "${GREEN}The requests were sent to backend 2 while backend 1 was saturated.${NC}"
+    else
+        echo -e "${RED}Backend 1 received $count_backend1 requests and backend 2 received $count_backend2 requests.${NC}"
+        test_passed=false
+    fi
+
+    echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+    kill_pids $backend1_pid $backend2_pid $lb_pid
+done
+
+rm -f "$config_file"
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
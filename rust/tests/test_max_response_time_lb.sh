@@ -0,0 +1,128 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that a backend server answering its requests or its health checks more
+# slowly than the maximum response time is unhealthy, unless its own maximum
+# response time in the config file is higher
+# ------------------------------------------------------------------------------
+
+# Prints the health of backend1 given by the admin API
+backend1_health() {
+    curl --silent http://localhost:9090/admin/backends \
+        | grep -o '"address":"http://localhost:8081/","health":"[A-Za-z]*"' | grep -o "[A-Za-z]*\"$" \
+        | tr -d '"'
+}
+
+# Prints the backend servers which answered 4 requests
+answers() {
+    for i in $(seq 1 4); do
+        curl --silent http://localhost:8080/ | grep -o "backend[0-9]"
+    done | sort -u | tr "\n" " "
+}
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+# backend1 answers the requests in 1.5s
+cargo run -p be -- -n "backend1" -p 8081 -d 1500 > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+cargo run -p be -- -n "backend2" -p 8082 > /dev/null 2>&1 &
+backend2_pid=$!
+wait_for_server "backend2" 8082
+
+# backend3 answers its health checks in 1.5s
+python3 -c '
+import http.server, time
+
+class Handler(http.server.BaseHTTPRequestHandler):
+    def do_GET(self):
+        time.sleep(1.5)
+        self.send_response(200)
+        self.send_header("Content-Length", "0")
+        self.end_headers()
+
+http.server.ThreadingHTTPServer(("localhost", 8083), Handler).serve_forever()
+' > /dev/null 2>&1 &
+backend3_pid=$!
+wait_for_server "backend3" 8083
+
+config_file=$(mktemp --suffix .toml)
+cat > "$config_file" << EOF
+backends = [
+    { address = "http://localhost:8081/", max_response_time_ms = 3000 },
+    "http://localhost:8082/",
+]
+EOF
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+echo -e "${GREEN}Starting load balancer...${NC}"
+# The health checks are too rare to find backend1 healthy again during the test
+cargo run -p lb -- -i 60 --max-response-time-ms 1000 --admin-port 9090 \
+    "http://localhost:8081/" "http://localhost:8082/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+wait_for_server "admin API" 9090
+initial_health=$(backend1_health)
+slow_answers=$(answers)
+slow_health=$(backend1_health)
+following_answers=$(answers)
+kill_pids $lb_pid > /dev/null
+
+echo -e "${GREEN}Starting load balancer with a higher limit for backend1...${NC}"
+cargo run -p lb -- -i 60 --max-response-time-ms 1000 --admin-port 9090 --config "$config_file" \
+    &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+wait_for_server "admin API" 9090
+answers > /dev/null
+overridden_health=$(backend1_health)
+
+slow_health_check_output=$(RUST_LOG=off cargo run -p lb -- --check --max-response-time-ms 1000 \
+    "http://localhost:8082/" "http://localhost:8083/" 2>/dev/null | grep "localhost:8083")
+
+# Assert -----------------------------------------------------------------------
+if [[ $initial_health == "Healthy" && $slow_health == "Unhealthy" ]]; then
+    echo -e "${GREEN}The slow backend server became unhealthy after answering.${NC}"
+else
+    echo -e "${RED}The slow backend server went from ${initial_health} to ${slow_health}.${NC}"
+    test_passed=false
+fi
+
+if [[ $slow_answers == "backend1 backend2 " && $following_answers == "backend2 " ]]; then
+    echo -e "${GREEN}The requests went to the fast backend server once the slow one was unhealthy.${NC}"
+else
+    echo -e "${RED}The requests were answered by ${slow_answers}then by ${following_answers}.${NC}"
+    test_passed=false
+fi
+
+if [[ $overridden_health == "Healthy" ]]; then
+    echo -e "${GREEN}The maximum response time of the backend server replaced the global one.${NC}"
+else
+    echo -e "${RED}With its own maximum response time, the slow backend server was ${overridden_health}.${NC}"
+    test_passed=false
+fi
+
+if [[ $slow_health_check_output == *"unreachable"* ]]; then
+    echo -e "${GREEN}The backend server with slow health checks was unhealthy.${NC}"
+else
+    echo -e "${RED}The backend server with slow health checks was checked as ${slow_health_check_output}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $backend2_pid $backend3_pid $lb_pid
+rm -f "$config_file"
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,55 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that the round robin load balancer gives up on a request after trying
+# --max-tries unreachable backend servers, instead of trying all of them
+# ------------------------------------------------------------------------------
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+# Nothing listens on ports 8100 to 8119, backend1 comes after them
+cargo run -p be -- -n "backend1" -p 8081 > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+dead_backends=()
+for port in $(seq 8100 8119); do
+    dead_backends+=("http://localhost:${port}/")
+done
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+cargo run -p lb -- -i 10 --max-tries 5 "${dead_backends[@]}" "http://localhost:8081/" \
+    &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+# Each request tries the next 5 backend servers
+statuses=""
+for i in $(seq 1 5); do
+    statuses+=$(curl --silent --output /dev/null --write-out "%{http_code} " http://localhost:8080/)
+done
+
+# Assert -----------------------------------------------------------------------
+if [[ $statuses == "503 503 503 503 200 " ]]; then
+    echo -e "${GREEN}Each request gave up after 5 unreachable backend servers.${NC}"
+else
+    echo -e "${RED}The requests were answered with ${statuses}, expected 503 503 503 503 200.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $lb_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,53 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that the load balancer refuses to start without backend servers
+# ------------------------------------------------------------------------------
+
+# Arrange ----------------------------------------------------------------------
+config_file=$(mktemp --suffix .toml)
+cat > "$config_file" << EOF
+strategy = "least-response"
+backends = []
+EOF
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+cargo build -p lb > /dev/null 2>&1
+command_line_output=$(timeout 10 ../target/debug/lb -i 1 2>&1)
+command_line_status=$?
+config_file_output=$(timeout 10 ../target/debug/lb -i 1 --config "$config_file" 2>&1)
+config_file_status=$?
+
+# Assert -----------------------------------------------------------------------
+if [[ $command_line_status -ne 0 && $command_line_status -ne 124 \
+    && $command_line_output == *"At least one backend server is required"* \
+    && $command_line_output != *"panicked"* ]]; then
+    echo -e "${GREEN}Refused to start without backend servers on the command line.${NC}"
+else
+    echo -e "${RED}Exited with ${command_line_status}: ${command_line_output}.${NC}"
+    test_passed=false
+fi
+
+if [[ $config_file_status -ne 0 && $config_file_status -ne 124 \
+    && $config_file_output == *"At least one backend server is required"* \
+    && $config_file_output != *"panicked"* ]]; then
+    echo -e "${GREEN}Refused to start without backend servers in the config file.${NC}"
+else
+    echo -e "${RED}Exited with ${config_file_status}: ${config_file_output}.${NC}"
+    test_passed=false
+fi
+
+rm -f "$config_file"
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,89 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that a backend server answering too many requests with an error is
+# ejected, and re-admitted after its cooldown once it answers again
+# ------------------------------------------------------------------------------
+
+# Sends the given number of requests and prints how many backend 2 answered
+count_backend2() {
+    result=""
+    for i in $(seq 1 $1); do
+        result+=$(curl --silent http://localhost:8080/)
+    done
+    echo $result | grep -o "backend2" | wc -l
+}
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+cargo run -p be -- -n "backend1" -p 8081 > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+# backend2 passes its health checks but answers the requests with a 500 until
+# it is sent a request on /recover
+python3 -c '
+import http.server
+
+class Handler(http.server.BaseHTTPRequestHandler):
+    degraded = True
+
+    def do_GET(self):
+        if self.path == "/recover":
+            Handler.degraded = False
+        status = 500 if Handler.degraded and self.path != "/health" else 200
+        self.send_response(status)
+        self.end_headers()
+        self.wfile.write(b"Hello from backend server: backend2")
+
+http.server.HTTPServer(("localhost", 8082), Handler).serve_forever()
+' > /dev/null 2>&1 &
+backend2_pid=$!
+wait_for_server "backend2" 8082
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+cargo run -p lb -- -i 10 "http://localhost:8081/" "http://localhost:8082/" --admin-port 9090 \
+    --outlier-error-rate 0.5 --outlier-window 4 --outlier-cooldown 2s &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+wait_for_server "admin API" 9090
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+degraded_count=$(count_backend2 20)
+backends_degraded=$(curl --silent http://localhost:9090/admin/backends)
+
+curl --silent --output /dev/null http://localhost:8082/recover
+# Wait for the cooldown to elapse
+sleep 2.5
+recovered_count=$(count_backend2 10)
+
+# Assert -----------------------------------------------------------------------
+if [[ $degraded_count -eq 4 && $backends_degraded == *'"ejected":true'* ]]; then
+    echo -e "${GREEN}Backend 2 was ejected after 4 errors.${NC}"
+else
+    echo -e "${RED}Backend 2 answered ${degraded_count} requests out of 20: ${backends_degraded}.${NC}"
+    test_passed=false
+fi
+
+if [[ $recovered_count -ge 4 ]]; then
+    echo -e "${GREEN}Backend 2 was re-admitted after its cooldown.${NC}"
+else
+    echo -e "${RED}Backend 2 answered ${recovered_count} requests out of 10 after its cooldown.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $backend2_pid $lb_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
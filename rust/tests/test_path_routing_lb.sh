@@ -0,0 +1,106 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test routing the requests to the backend groups of the config file by the
+# prefix of their path, the longest matching prefix winning
+# ------------------------------------------------------------------------------
+
+# Arrange ----------------------------------------------------------------------
+config_file=$(mktemp --suffix .toml)
+cat > "$config_file" << EOF
+backends = ["http://localhost:8081/"]
+
+[groups.api]
+backends = ["http://localhost:8082/"]
+
+[groups.api-v2]
+strategy = "least-response"
+backends = ["http://localhost:8083/"]
+
+[groups.static]
+backends = ["http://localhost:8084/"]
+
+[routes]
+"/api" = "api"
+"/api/v2" = "api-v2"
+"/static/" = "static"
+EOF
+
+invalid_config_file=$(mktemp --suffix .toml)
+cat > "$invalid_config_file" << EOF
+backends = ["http://localhost:8081/"]
+
+[routes]
+"/api" = "missing"
+EOF
+
+echo -e "${GREEN}Starting backend servers...${NC}"
+backend_pids=()
+for i in 1 2 3 4; do
+    cargo run -p be -- -n "backend${i}" -p 808${i} > /dev/null 2>&1 &
+    backend_pids+=($!)
+    wait_for_server "backend${i}" 808${i}
+done
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+cargo run -p lb -- -i 10 --config "$config_file" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+# Each path with the backend server expected to answer it
+paths=(
+    "/ backend1"
+    "/apiary backend1"
+    "/api backend2"
+    "/api/users backend2"
+    "/api/v2x backend2"
+    "/api/v2 backend3"
+    "/api/v2/users backend3"
+    "/static/app.js backend4"
+    "/static backend1"
+)
+routed=()
+for entry in "${paths[@]}"; do
+    read -r path expected <<< "$entry"
+    backend=$(curl --silent "http://localhost:8080${path}" | grep -o "backend[0-9]")
+    routed+=("${path} ${expected} ${backend}")
+done
+
+invalid_output=$(cargo run -p lb -- --check --config "$invalid_config_file" 2>&1)
+invalid_status=$?
+
+# Assert -----------------------------------------------------------------------
+for entry in "${routed[@]}"; do
+    read -r path expected backend <<< "$entry"
+    if [[ $backend == "$expected" ]]; then
+        echo -e "${GREEN}${path} was routed to ${expected}.${NC}"
+    else
+        echo -e "${RED}${path} was routed to '${backend}', expected ${expected}.${NC}"
+        test_passed=false
+    fi
+done
+
+if [[ $invalid_status -ne 0 && $invalid_output == *"unknown backend group missing"* ]]; then
+    echo -e "${GREEN}A route to an unknown backend group was rejected.${NC}"
+else
+    echo -e "${RED}A route to an unknown backend group exited with ${invalid_status}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids "${backend_pids[@]}" $lb_pid
+rm -f "$config_file" "$invalid_config_file"
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
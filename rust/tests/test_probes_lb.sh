@@ -0,0 +1,57 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test the liveness and readiness routes of the load balancer
+# ------------------------------------------------------------------------------
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+cargo run -p be -- -n "backend1" -p 8081 > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+cargo run -p lb -- -i 1 "http://localhost:8081/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+livez_healthy=$(curl --silent --output /dev/null --write-out "%{http_code}" http://localhost:8080/livez)
+readyz_healthy=$(curl --silent --output /dev/null --write-out "%{http_code}" http://localhost:8080/readyz)
+
+kill_pids $backend1_pid
+# Wait for a health check to find backend 1 unhealthy
+sleep 2
+livez_unhealthy=$(curl --silent --output /dev/null --write-out "%{http_code}" http://localhost:8080/livez)
+readyz_unhealthy=$(curl --silent --output /dev/null --write-out "%{http_code}" http://localhost:8080/readyz)
+
+# Assert -----------------------------------------------------------------------
+if [[ $livez_healthy -eq 200 && $readyz_healthy -eq 200 ]]; then
+    echo -e "${GREEN}The load balancer is live and ready with a healthy backend server.${NC}"
+else
+    echo -e "${RED}With a healthy backend server, /livez returned ${livez_healthy} and /readyz ${readyz_healthy}.${NC}"
+    test_passed=false
+fi
+
+if [[ $livez_unhealthy -eq 200 && $readyz_unhealthy -eq 503 ]]; then
+    echo -e "${GREEN}The load balancer is live but not ready without a healthy backend server.${NC}"
+else
+    echo -e "${RED}Without a healthy backend server, /livez returned ${livez_unhealthy} and /readyz ${readyz_unhealthy}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing load balancer...${NC}"
+kill_pids $lb_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,152 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that with --proxy-protocol, the client address given by the PROXY
+# protocol v1 and v2 headers is forwarded to the backend servers in
+# X-Forwarded-For, that a connection without header is closed, and that a
+# connection not sending its header does not hold up the others
+# ------------------------------------------------------------------------------
+
+# Connects to the load balancer, sends the PROXY protocol header of the given
+# version (none, v1, v2 or v2-local) with the given client address, then a
+# request, and prints the body of the response, empty if the connection closed
+proxied_request() {
+    python3 -c '
+import ipaddress, socket, struct, sys
+
+version, client = sys.argv[1], sys.argv[2]
+ip = ipaddress.ip_address(client)
+header = b""
+if version == "v1":
+    protocol = "TCP4" if ip.version == 4 else "TCP6"
+    destination = "127.0.0.1" if ip.version == 4 else "::1"
+    header = f"PROXY {protocol} {client} {destination} 51234 8080\r\n".encode()
+elif version.startswith("v2"):
+    command = 0x20 if version == "v2-local" else 0x21
+    family = 0x11 if ip.version == 4 else 0x21
+    destination = ipaddress.ip_address("127.0.0.1" if ip.version == 4 else "::1")
+    addresses = ip.packed + destination.packed + struct.pack("!HH", 51234, 8080)
+    header = b"\r\n\r\n\0\r\nQUIT\n" + bytes([command, family]) + struct.pack("!H", len(addresses)) + addresses
+
+connection = socket.create_connection(("localhost", 8080))
+connection.sendall(header + b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+response = b""
+try:
+    while chunk := connection.recv(4096):
+        response += chunk
+except ConnectionResetError:
+    pass
+print(response.partition(b"\r\n\r\n")[2].decode())
+' "$1" "$2"
+}
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend server...${NC}"
+# The backend server answers with the X-Forwarded-For header of the request
+python3 -c '
+import http.server
+
+class Handler(http.server.BaseHTTPRequestHandler):
+    def do_GET(self):
+        body = ("forwarded for " + str(self.headers.get("X-Forwarded-For"))).encode()
+        self.send_response(200)
+        self.send_header("Content-Length", str(len(body)))
+        self.end_headers()
+        self.wfile.write(body)
+
+http.server.ThreadingHTTPServer(("localhost", 8081), Handler).serve_forever()
+' > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+# A single worker serves the connections, so that a connection holding it up
+# would delay the others
+cargo run -p lb -- -i 10 --workers 1 --proxy-protocol "http://localhost:8081/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+v1_answer=$(proxied_request v1 203.0.113.7)
+v1_ipv6_answer=$(proxied_request v1 2001:db8::7)
+v2_answer=$(proxied_request v2 198.51.100.23)
+v2_ipv6_answer=$(proxied_request v2 2001:db8::23)
+v2_local_answer=$(proxied_request v2-local 198.51.100.23)
+no_header_answer=$(proxied_request none 127.0.0.1)
+after_answer=$(proxied_request v1 203.0.113.8)
+
+# A connection sending nothing for 3 seconds, while another one is served
+python3 -c '
+import socket, time
+connection = socket.create_connection(("localhost", 8080))
+time.sleep(3)
+' &
+silent_pid=$!
+sleep 0.2
+start=$(date +%s%N)
+beside_silent_answer=$(proxied_request v1 203.0.113.9)
+beside_silent_ms=$((($(date +%s%N) - start) / 1000000))
+wait $silent_pid
+
+tls_output=$(cargo run -p lb -- --proxy-protocol --tls-cert cert.pem --tls-key key.pem \
+    "http://localhost:8081/" 2>&1)
+tls_status=$?
+
+# Assert -----------------------------------------------------------------------
+if [[ $v1_answer == "forwarded for 203.0.113.7" && $v1_ipv6_answer == "forwarded for 2001:db8::7" ]]; then
+    echo -e "${GREEN}The client addresses of the PROXY protocol v1 headers were forwarded.${NC}"
+else
+    echo -e "${RED}With PROXY protocol v1 headers, the backend server answered ${v1_answer} and ${v1_ipv6_answer}.${NC}"
+    test_passed=false
+fi
+
+if [[ $v2_answer == "forwarded for 198.51.100.23" && $v2_ipv6_answer == "forwarded for 2001:db8::23" ]]; then
+    echo -e "${GREEN}The client addresses of the PROXY protocol v2 headers were forwarded.${NC}"
+else
+    echo -e "${RED}With PROXY protocol v2 headers, the backend server answered ${v2_answer} and ${v2_ipv6_answer}.${NC}"
+    test_passed=false
+fi
+
+if [[ $v2_local_answer == "forwarded for 127.0.0.1" ]]; then
+    echo -e "${GREEN}A LOCAL PROXY protocol v2 header kept the address of the proxy.${NC}"
+else
+    echo -e "${RED}With a LOCAL PROXY protocol v2 header, the backend server answered ${v2_local_answer}.${NC}"
+    test_passed=false
+fi
+
+if [[ -z $no_header_answer && $after_answer == "forwarded for 203.0.113.8" ]]; then
+    echo -e "${GREEN}The connection without PROXY protocol header was closed.${NC}"
+else
+    echo -e "${RED}Without PROXY protocol header, the load balancer answered ${no_header_answer}.${NC}"
+    test_passed=false
+fi
+
+if [[ $beside_silent_answer == "forwarded for 203.0.113.9" && $beside_silent_ms -lt 500 ]]; then
+    echo -e "${GREEN}A connection not sending its header did not hold up the others.${NC}"
+else
+    echo -e "${RED}Beside a silent connection, the backend server answered ${beside_silent_answer} in ${beside_silent_ms}ms.${NC}"
+    test_passed=false
+fi
+
+if [[ $tls_status -ne 0 && $tls_output == *"--tls-cert"* ]]; then
+    echo -e "${GREEN}The PROXY protocol with HTTPS was rejected.${NC}"
+else
+    echo -e "${RED}The PROXY protocol with HTTPS exited with ${tls_status}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend server and load balancer...${NC}"
+kill_pids $backend1_pid $lb_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
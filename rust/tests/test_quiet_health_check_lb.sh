@@ -0,0 +1,89 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that a backend server without health endpoint is warned about at most
+# once, and not at all with quiet_health_check in the config file
+# ------------------------------------------------------------------------------
+
+# Prints the number of warnings about the health endpoint in the given log file
+warnings() {
+    grep -c "does not support health checks" "$1"
+}
+
+# Arrange ----------------------------------------------------------------------
+log_file=$(mktemp)
+config_file=$(mktemp --suffix .toml)
+cat > "$config_file" << EOF
+backends = [
+    { address = "http://localhost:8081/", quiet_health_check = true },
+]
+EOF
+
+echo -e "${GREEN}Starting backend server...${NC}"
+# backend1 answers the requests, and its missing health endpoint with a 404
+python3 -c '
+import http.server
+
+class Handler(http.server.BaseHTTPRequestHandler):
+    def do_GET(self):
+        status, body = (404, b"Not found") if self.path == "/health" else (200, b"backend1")
+        self.send_response(status)
+        self.send_header("Content-Length", str(len(body)))
+        self.end_headers()
+        self.wfile.write(body)
+
+http.server.ThreadingHTTPServer(("localhost", 8081), Handler).serve_forever()
+' > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+echo -e "${GREEN}Starting load balancer...${NC}"
+RUST_LOG=warn cargo run -p lb -- -i 500ms "http://localhost:8081/" > "$log_file" 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+# Let several health checks run
+sleep 3
+default_warnings=$(warnings "$log_file")
+default_answer=$(curl --silent http://localhost:8080/)
+kill_pids $lb_pid > /dev/null
+
+echo -e "${GREEN}Starting load balancer with quiet health checks...${NC}"
+RUST_LOG=warn cargo run -p lb -- -i 500ms --config "$config_file" > "$log_file" 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+sleep 3
+quiet_warnings=$(warnings "$log_file")
+quiet_answer=$(curl --silent http://localhost:8080/)
+
+# Assert -----------------------------------------------------------------------
+if [[ $default_warnings -eq 1 && $default_answer == "backend1" ]]; then
+    echo -e "${GREEN}The missing health endpoint was warned about once.${NC}"
+else
+    echo -e "${RED}The missing health endpoint was warned about ${default_warnings} times and the answer was ${default_answer}.${NC}"
+    test_passed=false
+fi
+
+if [[ $quiet_warnings -eq 0 && $quiet_answer == "backend1" ]]; then
+    echo -e "${GREEN}With quiet_health_check, the missing health endpoint was not warned about.${NC}"
+else
+    echo -e "${RED}With quiet_health_check, the missing health endpoint was warned about ${quiet_warnings} times and the answer was ${quiet_answer}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend server and load balancer...${NC}"
+kill_pids $backend1_pid $lb_pid
+rm -f "$log_file" "$config_file"
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,65 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that the requests over the rate limit are answered with a 429
+# ------------------------------------------------------------------------------
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+cargo run -p be -- -n "backend1" -p 8081 > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+cargo run -p lb -- -i 10 "http://localhost:8081/" --client-rate-limit 1 --client-rate-limit-burst 3 &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+statuses=""
+for i in $(seq 1 5); do
+    statuses+=$(curl --silent --output /dev/null --write-out "%{http_code} " http://localhost:8080/)
+done
+retry_after=$(curl --silent --include http://localhost:8080/ | grep -i "^retry-after:")
+
+# Wait for the bucket to refill
+sleep 2
+status_after_wait=$(curl --silent --output /dev/null --write-out "%{http_code}" http://localhost:8080/)
+
+# Assert -----------------------------------------------------------------------
+if [[ $statuses == "200 200 200 429 429 " ]]; then
+    echo -e "${GREEN}The requests over the burst were rejected.${NC}"
+else
+    echo -e "${RED}The requests were answered with: ${statuses}.${NC}"
+    test_passed=false
+fi
+
+if [[ -n $retry_after ]]; then
+    echo -e "${GREEN}The rejected request has a Retry-After header.${NC}"
+else
+    echo -e "${RED}The rejected request has no Retry-After header.${NC}"
+    test_passed=false
+fi
+
+if [[ $status_after_wait == "200" ]]; then
+    echo -e "${GREEN}The requests are accepted again after waiting.${NC}"
+else
+    echo -e "${RED}The request after waiting was answered with ${status_after_wait}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $lb_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
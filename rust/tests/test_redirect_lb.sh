@@ -0,0 +1,83 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that a redirect of a backend server is passed through to the client with
+# its Location header, and followed by the load balancer with --max-redirects
+# ------------------------------------------------------------------------------
+
+# Prints the status and the Location header of the answer of the load balancer,
+# followed by its body
+answer() {
+    curl --silent --include http://localhost:8080/ | tr -d "\r" \
+        | grep -i -E "^HTTP/|^location:|^followed" | sed -E "s/^HTTP\/[0-9.]+ ([0-9]+).*/\1/" \
+        | tr "\n" " "
+}
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend server...${NC}"
+# backend1 redirects / to /target, which it answers
+python3 -c '
+import http.server
+
+class Handler(http.server.BaseHTTPRequestHandler):
+    def do_GET(self):
+        if self.path == "/":
+            self.send_response(302)
+            self.send_header("Location", "http://localhost:8081/target")
+            body = b""
+        else:
+            self.send_response(200)
+            body = b"followed " + self.path.encode()
+        self.send_header("Content-Length", str(len(body)))
+        self.end_headers()
+        self.wfile.write(body)
+
+http.server.ThreadingHTTPServer(("localhost", 8081), Handler).serve_forever()
+' > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+echo -e "${GREEN}Starting load balancer...${NC}"
+cargo run -p lb -- "http://localhost:8081/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+default_answer=$(answer)
+kill_pids $lb_pid > /dev/null
+
+echo -e "${GREEN}Starting load balancer following the redirects...${NC}"
+cargo run -p lb -- --max-redirects 1 "http://localhost:8081/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+following_answer=$(answer)
+
+# Assert -----------------------------------------------------------------------
+if [[ $default_answer == "302 location: http://localhost:8081/target " ]]; then
+    echo -e "${GREEN}The redirect was passed through to the client.${NC}"
+else
+    echo -e "${RED}The client received ${default_answer}instead of the redirect.${NC}"
+    test_passed=false
+fi
+
+if [[ $following_answer == "200 followed /target " ]]; then
+    echo -e "${GREEN}With --max-redirects, the redirect was followed.${NC}"
+else
+    echo -e "${RED}With --max-redirects, the client received ${following_answer}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend server and load balancer...${NC}"
+kill_pids $backend1_pid $lb_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
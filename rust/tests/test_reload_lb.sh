@@ -0,0 +1,98 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test reloading the backend servers of the config file on SIGHUP
+# ------------------------------------------------------------------------------
+
+# Arrange ----------------------------------------------------------------------
+config_file=$(mktemp --suffix .toml)
+cat > "$config_file" << EOF
+backends = ["http://localhost:8081/"]
+EOF
+
+echo -e "${GREEN}Starting backend servers...${NC}"
+cargo run -p be -- -n "backend1" -p 8081 > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+cargo run -p be -- -n "backend2" -p 8082 > /dev/null 2>&1 &
+backend2_pid=$!
+wait_for_server "backend2" 8082
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+# Run the binary directly so that SIGHUP is sent to the load balancer and not to cargo
+cargo build -p lb > /dev/null 2>&1
+../target/debug/lb -i 1 --config "$config_file" --admin-port 9090 &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+wait_for_server "admin API" 9090
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+backends_before_reload=$(curl --silent http://localhost:9090/admin/backends)
+
+cat > "$config_file" << EOF
+backends = ["http://localhost:8082/"]
+EOF
+kill -HUP $lb_pid
+# Wait for a health check of the new backend server
+sleep 2
+backends_after_reload=$(curl --silent http://localhost:9090/admin/backends)
+
+echo "backends = [" > "$config_file"
+kill -HUP $lb_pid
+sleep 1
+backends_after_invalid_reload=$(curl --silent http://localhost:9090/admin/backends)
+
+result=""
+for i in $(seq 1 4); do
+    result+=$(curl --silent http://localhost:8080/)
+done
+count_backend1=$(echo $result | grep -o "backend1" | wc -l)
+count_backend2=$(echo $result | grep -o "backend2" | wc -l)
+
+# Assert -----------------------------------------------------------------------
+if [[ $backends_before_reload == *"localhost:8081"* && $backends_before_reload != *"localhost:8082"* ]]; then
+    echo -e "${GREEN}Started with backend 1.${NC}"
+else
+    echo -e "${RED}Did not start with backend 1 only: ${backends_before_reload}.${NC}"
+    test_passed=false
+fi
+
+if [[ $backends_after_reload == *"localhost:8082"* && $backends_after_reload != *"localhost:8081"* ]]; then
+    echo -e "${GREEN}Replaced backend 1 by backend 2.${NC}"
+else
+    echo -e "${RED}Did not replace backend 1 by backend 2: ${backends_after_reload}.${NC}"
+    test_passed=false
+fi
+
+if [[ $backends_after_invalid_reload == *"localhost:8082"* ]]; then
+    echo -e "${GREEN}Kept backend 2 after an invalid config file.${NC}"
+else
+    echo -e "${RED}Did not keep backend 2: ${backends_after_invalid_reload}.${NC}"
+    test_passed=false
+fi
+
+if [[ $count_backend1 -eq 0 && $count_backend2 -eq 4 ]]; then
+    echo -e "${GREEN}Only received answers from backend 2.${NC}"
+else
+    echo -e "${RED}Did not receive the expected amount of answers
+    backend1=${count_backend1}, backend2=${count_backend2}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $backend2_pid $lb_pid
+rm -f "$config_file"
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
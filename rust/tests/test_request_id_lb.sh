@@ -0,0 +1,57 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that the request ID is forwarded to the backend server and returned
+# ------------------------------------------------------------------------------
+
+# Arrange ----------------------------------------------------------------------
+backend1_log=$(mktemp)
+
+echo -e "${GREEN}Starting backend servers...${NC}"
+cargo run -p be -- -n "backend1" -p 8081 > "$backend1_log" 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+cargo run -p lb -- -i 10 "http://localhost:8081/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+given_id_headers=$(curl --silent --dump-header - --output /dev/null \
+    --header "X-Request-Id: test-request-id-42" http://localhost:8080/)
+generated_id_headers=$(curl --silent --dump-header - --output /dev/null http://localhost:8080/)
+generated_id=$(echo "$generated_id_headers" | grep -i "^x-request-id:" | cut -d " " -f 2 | tr -d "\r")
+
+# Assert -----------------------------------------------------------------------
+if [[ $given_id_headers == *"test-request-id-42"* ]] \
+    && grep -q "x-request-id: test-request-id-42" "$backend1_log"; then
+    echo -e "${GREEN}The request ID of the client was forwarded and returned.${NC}"
+else
+    echo -e "${RED}The request ID of the client was not forwarded and returned.${NC}"
+    test_passed=false
+fi
+
+if [[ -n $generated_id ]] && grep -q "x-request-id: $generated_id" "$backend1_log"; then
+    echo -e "${GREEN}A request ID was generated, forwarded and returned.${NC}"
+else
+    echo -e "${RED}No request ID was generated, forwarded and returned.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $lb_pid
+rm -f "$backend1_log"
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,79 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that the requests are forwarded to the backend servers with their method
+# and their body
+# ------------------------------------------------------------------------------
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend server...${NC}"
+# The backend server answers with the method, the Content-Length header and the
+# body of the request
+python3 -c '
+import http.server
+
+class Handler(http.server.BaseHTTPRequestHandler):
+    def answer(self):
+        length = self.headers.get("Content-Length")
+        body = self.rfile.read(int(length)) if length else b""
+        answer = f"{self.command} {length} ".encode() + body
+        self.send_response(200)
+        self.send_header("Content-Length", str(len(answer)))
+        self.end_headers()
+        self.wfile.write(answer)
+
+    do_GET = do_POST = do_PUT = do_DELETE = do_PATCH = answer
+
+http.server.ThreadingHTTPServer(("localhost", 8081), Handler).serve_forever()
+' > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+cargo run -p lb -- -i 10 "http://localhost:8081/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+get_answer=$(curl --silent http://localhost:8080/)
+post_answer=$(curl --silent --request POST --data 'name=lb' http://localhost:8080/)
+put_answer=$(curl --silent --request PUT --data '{"weight": 2}' http://localhost:8080/)
+delete_answer=$(curl --silent --request DELETE http://localhost:8080/)
+
+# Assert -----------------------------------------------------------------------
+if [[ $get_answer == "GET None " ]]; then
+    echo -e "${GREEN}The GET request was forwarded without body.${NC}"
+else
+    echo -e "${RED}The backend server answered the GET request with ${get_answer}.${NC}"
+    test_passed=false
+fi
+
+if [[ $post_answer == "POST 7 name=lb" && $put_answer == 'PUT 13 {"weight": 2}' ]]; then
+    echo -e "${GREEN}The POST and PUT requests were forwarded with their body.${NC}"
+else
+    echo -e "${RED}The backend server answered the POST and PUT requests with ${post_answer} and ${put_answer}.${NC}"
+    test_passed=false
+fi
+
+if [[ $delete_answer == "DELETE "* ]]; then
+    echo -e "${GREEN}The DELETE request was forwarded with its method.${NC}"
+else
+    echo -e "${RED}The backend server answered the DELETE request with ${delete_answer}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend server and load balancer...${NC}"
+kill_pids $backend1_pid $lb_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,76 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that a request which no backend server answers within the request timeout
+# is answered with a 504, and that the requests sent to the backend servers are
+# cancelled
+# ------------------------------------------------------------------------------
+
+# Prints the number of requests in flight and the health of each backend server
+# from the admin API, for example 0/Healthy 0/Healthy
+backend_states() {
+    curl --silent http://localhost:9090/admin/backends | python3 -c '
+import json, sys
+print(" ".join("{}/{}".format(backend["in_flight"], backend["health"]) for backend in json.load(sys.stdin)))
+'
+}
+
+for strategy in round-robin least-response; do
+    echo -e "${GREEN}Testing the ${strategy} strategy...${NC}"
+
+    # Arrange ------------------------------------------------------------------
+    echo -e "${GREEN}Starting backend servers...${NC}"
+    # Both backend servers answer after 3 seconds
+    cargo run -p be -- -n "backend1" -p 8081 -d 3000 > /dev/null 2>&1 &
+    backend1_pid=$!
+    wait_for_server "backend1" 8081
+
+    cargo run -p be -- -n "backend2" -p 8082 -d 3000 > /dev/null 2>&1 &
+    backend2_pid=$!
+    wait_for_server "backend2" 8082
+
+    echo -e "${GREEN}Starting load balancer...${NC}"
+    cargo run -p lb -- -i 10 --strategy $strategy --request-timeout 1s --admin-port 9090 \
+        "http://localhost:8081/" "http://localhost:8082/" &> /dev/null 2>&1 &
+    lb_pid=$!
+    wait_for_server "load balancer" 8080
+    wait_for_server "admin API" 9090
+
+    # Act ----------------------------------------------------------------------
+    echo -e "${GREEN}Running tests...${NC}"
+    result=$(curl --silent --output /dev/null --write-out "%{http_code} %{time_total}" \
+        http://localhost:8080/)
+    read -r status time_total <<< "$result"
+    states=$(backend_states)
+
+    # Assert -------------------------------------------------------------------
+    if [[ $status -eq 504 ]] \
+        && python3 -c "import sys; sys.exit(0 if 1 <= float('$time_total') < 2 else 1)"; then
+        echo -e "${GREEN}The request was answered with a 504 after ${time_total}s.${NC}"
+    else
+        echo -e "${RED}The request was answered with a ${status} after ${time_total}s.${NC}"
+        test_passed=false
+    fi
+
+    if [[ $states == "0/Healthy 0/Healthy" ]]; then
+        echo -e "${GREEN}The requests to the backend servers were cancelled.${NC}"
+    else
+        echo -e "${RED}The backend servers are ${states} after the timeout.${NC}"
+        test_passed=false
+    fi
+
+    echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+    kill_pids $backend1_pid $backend2_pid $lb_pid
+done
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,75 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that with --response-time-decay the least response load balancer sends
+# requests again to a backend server starved after answering slowly, and that
+# the backend server stays starved without it
+# ------------------------------------------------------------------------------
+
+# Prints the number of requests answered by backend1 out of requests sent every
+# 200ms for 6s
+backend1_answers() {
+    for i in $(seq 1 30); do
+        curl --silent http://localhost:8080/
+        sleep 0.2
+    done | grep -o "backend1" | wc -l
+}
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+# backend1 answers in 1.5s, once in a while only
+cargo run -p be -- -n "backend1" -p 8081 -d 1500 > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+cargo run -p be -- -n "backend2" -p 8082 > /dev/null 2>&1 &
+backend2_pid=$!
+wait_for_server "backend2" 8082
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+echo -e "${GREEN}Starting load balancer without decay...${NC}"
+cargo run -p lb -- -i 1 --strategy least-response "http://localhost:8081/" "http://localhost:8082/" \
+    &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+starved_answers=$(backend1_answers)
+kill_pids $lb_pid > /dev/null
+
+echo -e "${GREEN}Starting load balancer with a decay of 90%...${NC}"
+cargo run -p lb -- -i 1 --strategy least-response --response-time-decay 0.9 \
+    "http://localhost:8081/" "http://localhost:8082/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+decayed_answers=$(backend1_answers)
+
+# Assert -----------------------------------------------------------------------
+# The first request may go to backend1, before its response time is known
+if [[ $starved_answers -le 1 ]]; then
+    echo -e "${GREEN}Without decay, the slow backend server stayed starved.${NC}"
+else
+    echo -e "${RED}Without decay, the slow backend server answered ${starved_answers} requests.${NC}"
+    test_passed=false
+fi
+
+if [[ $decayed_answers -ge 2 ]]; then
+    echo -e "${GREEN}With decay, the slow backend server answered ${decayed_answers} requests.${NC}"
+else
+    echo -e "${RED}With decay, the slow backend server answered ${decayed_answers} requests.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $backend2_pid $lb_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,102 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that the response time percentiles of a backend server are exposed by the
+# admin API and the metrics, and match the response times of the backend server
+# ------------------------------------------------------------------------------
+
+# Prints the given percentiles, separated by spaces, of the backend server from
+# the admin API
+admin_percentiles() {
+    curl --silent http://localhost:9090/admin/backends | python3 -c '
+import json, sys
+percentiles = json.load(sys.stdin)[0]["response_time_percentiles"]
+print(" ".join(str(percentiles[name]) for name in sys.argv[1:]))
+' "$@"
+}
+
+# Prints true if the given value is in the given range [min, max)
+in_range() {
+    python3 -c "import sys; print(str($2 <= float('$1') < $3).lower())"
+}
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+# Out of every 100 requests, backend1 answers 90 after 10ms, 8 after 100ms and 2
+# after 300ms
+python3 -c '
+import http.server
+import time
+
+requests = 0
+
+class Handler(http.server.BaseHTTPRequestHandler):
+    def do_GET(self):
+        global requests
+        if self.path != "/health":
+            position = requests % 100
+            requests += 1
+            time.sleep(0.01 if position < 90 else 0.1 if position < 98 else 0.3)
+        self.send_response(200)
+        self.send_header("Content-Length", "0")
+        self.end_headers()
+
+http.server.HTTPServer(("localhost", 8081), Handler).serve_forever()
+' > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+cargo run -p lb -- -i 10 "http://localhost:8081/" --admin-port 9090 &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+wait_for_server "admin API" 9090
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+percentiles_before=$(admin_percentiles p50_ms p95_ms p99_ms)
+for i in $(seq 1 100); do
+    curl --silent --output /dev/null http://localhost:8080/
+done
+read -r p50 p95 p99 <<< "$(admin_percentiles p50_ms p95_ms p99_ms)"
+metrics=$(curl --silent http://localhost:8080/metrics)
+
+# Assert -----------------------------------------------------------------------
+if [[ $percentiles_before == "0.0 0.0 0.0" ]]; then
+    echo -e "${GREEN}The percentiles start at 0.${NC}"
+else
+    echo -e "${RED}The percentiles started at ${percentiles_before}.${NC}"
+    test_passed=false
+fi
+
+if [[ $(in_range "$p50" 10 50) == true && $(in_range "$p95" 100 200) == true \
+    && $(in_range "$p99" 300 400) == true ]]; then
+    echo -e "${GREEN}The percentiles are p50=${p50}ms, p95=${p95}ms and p99=${p99}ms.${NC}"
+else
+    echo -e "${RED}The percentiles are p50=${p50}ms, p95=${p95}ms and p99=${p99}ms, expected about 10ms, 100ms and 300ms.${NC}"
+    test_passed=false
+fi
+
+if echo "$metrics" | grep -q "^lb_backend_response_time_percentile_ms{backend=\"http://localhost:8081/\",quantile=\"0.5\"} ${p50}$" \
+    && echo "$metrics" | grep -q "^lb_backend_response_time_percentile_ms{backend=\"http://localhost:8081/\",quantile=\"0.95\"} ${p95}$" \
+    && echo "$metrics" | grep -q "^lb_backend_response_time_percentile_ms{backend=\"http://localhost:8081/\",quantile=\"0.99\"} ${p99}$"; then
+    echo -e "${GREEN}The metrics expose the same percentiles.${NC}"
+else
+    echo -e "${RED}The metrics do not expose the same percentiles: $(echo "$metrics" | grep percentile).${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $lb_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,102 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that once the retries reach the retry budget, the failed requests are
+# answered without retry, and that they are retried again once the window of the
+# budget moved past the former retries
+# ------------------------------------------------------------------------------
+
+# Prints the status and body of the responses to the given number of requests
+answers() {
+    for i in $(seq 1 "$1"); do
+        curl --silent --write-out " %{http_code}" http://localhost:8080/ \
+            | grep -o "backend[0-9]\| [0-9]*$" | tr -d "\n"
+        echo -n ";"
+    done
+}
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+# backend1 answers its health checks but overloaded answers the requests with a 503
+python3 -c '
+import http.server
+
+class Handler(http.server.BaseHTTPRequestHandler):
+    def do_GET(self):
+        status, body = (200, b"ok") if self.path == "/health" else (503, b"overloaded")
+        self.send_response(status)
+        self.send_header("Content-Length", str(len(body)))
+        self.end_headers()
+        self.wfile.write(body)
+
+http.server.ThreadingHTTPServer(("localhost", 8081), Handler).serve_forever()
+' > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+cargo run -p be -- -n "backend2" -p 8082 > /dev/null 2>&1 &
+backend2_pid=$!
+wait_for_server "backend2" 8082
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+# While the requests are retried, every request is first sent to backend1 and
+# needs a retry. 2 retries are allowed, then 1 per 4 requests, so the following
+# requests sent to backend1 fail and the ones in between go to backend2
+cargo run -p lb -- -i 10 --retry-budget 0.25 --retry-budget-min-retries 2 \
+    --retry-budget-window 3s "http://localhost:8081/" "http://localhost:8082/" \
+    &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+budget_answers=$(answers 8)
+sleep 4
+renewed_answers=$(answers 1)
+
+invalid_output=$(cargo run -p lb -- --retry-budget 0 "http://localhost:8081/" 2>&1)
+invalid_status=$?
+
+# Assert -----------------------------------------------------------------------
+if [[ $budget_answers == "backend2 200;backend2 200;"* ]]; then
+    echo -e "${GREEN}The first requests were retried within the minimum number of retries.${NC}"
+else
+    echo -e "${RED}The requests were answered with ${budget_answers}.${NC}"
+    test_passed=false
+fi
+
+if [[ ${budget_answers#backend2 200;backend2 200;} == " 503;backend2 200; 503;backend2 200; 503;backend2 200;" ]]; then
+    echo -e "${GREEN}Once the retry budget was exhausted, the requests sent to backend1 failed without retry.${NC}"
+else
+    echo -e "${RED}After the first 2 retries, the requests were answered with ${budget_answers}.${NC}"
+    test_passed=false
+fi
+
+if [[ $renewed_answers == "backend2 200;" ]]; then
+    echo -e "${GREEN}Once the former retries left the window, the requests were retried again.${NC}"
+else
+    echo -e "${RED}After the window, the request was answered with ${renewed_answers}.${NC}"
+    test_passed=false
+fi
+
+if [[ $invalid_status -ne 0 && $invalid_output == *"invalid retry budget"* ]]; then
+    echo -e "${GREEN}A retry budget of 0 was rejected.${NC}"
+else
+    echo -e "${RED}A retry budget of 0 exited with ${invalid_status}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $backend2_pid $lb_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,67 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that requests failing on a backend server are retried on the next one
+# ------------------------------------------------------------------------------
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+# backend1 answers the health checks but closes the connection of any other
+# request without answering
+python3 -c '
+import http.server
+
+class Handler(http.server.BaseHTTPRequestHandler):
+    def do_GET(self):
+        if self.path == "/health":
+            self.send_response(200)
+            self.end_headers()
+        else:
+            self.close_connection = True
+
+http.server.HTTPServer(("localhost", 8081), Handler).serve_forever()
+' > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+cargo run -p be -- -n "backend2" -p 8082 > /dev/null 2>&1 &
+backend2_pid=$!
+wait_for_server "backend2" 8082
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+cargo run -p lb -- -i 10 "http://localhost:8081/" "http://localhost:8082/" --max-retries 1 &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+count_success=0
+for i in $(seq 1 4); do
+    result=$(curl --silent --fail http://localhost:8080/)
+    if [[ $result == *"backend2"* ]]; then
+        count_success=$((count_success + 1))
+    fi
+done
+
+# Assert -----------------------------------------------------------------------
+if [[ $count_success -eq 4 ]]; then
+    echo -e "${GREEN}All requests were answered by backend 2.${NC}"
+else
+    echo -e "${RED}Only ${count_success}/4 requests were answered by backend 2.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $backend2_pid $lb_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,95 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that a request answered with a 503 is retried on another backend server,
+# unless its method is not idempotent or no status is retried
+# ------------------------------------------------------------------------------
+
+# Prints the status and body of the responses to 4 requests with the given
+# method
+answers() {
+    for i in $(seq 1 4); do
+        curl --silent --request "$1" --write-out " %{http_code}" http://localhost:8080/ \
+            | grep -o "backend[0-9]\| [0-9]*$" | tr -d "\n"
+        echo -n ";"
+    done
+}
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+# backend1 answers its health checks but overloaded answers the requests with a 503
+python3 -c '
+import http.server
+
+class Handler(http.server.BaseHTTPRequestHandler):
+    def do_GET(self):
+        status, body = (200, b"ok") if self.path == "/health" else (503, b"overloaded")
+        self.send_response(status)
+        self.send_header("Content-Length", str(len(body)))
+        self.end_headers()
+        self.wfile.write(body)
+
+    do_POST = do_GET
+
+http.server.ThreadingHTTPServer(("localhost", 8081), Handler).serve_forever()
+' > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+cargo run -p be -- -n "backend2" -p 8082 > /dev/null 2>&1 &
+backend2_pid=$!
+wait_for_server "backend2" 8082
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+echo -e "${GREEN}Starting load balancer...${NC}"
+cargo run -p lb -- -i 10 "http://localhost:8081/" "http://localhost:8082/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+get_answers=$(answers GET)
+post_answers=$(answers POST)
+kill_pids $lb_pid > /dev/null
+
+echo -e "${GREEN}Starting load balancer without retried statuses...${NC}"
+cargo run -p lb -- -i 10 --retry-on-status "" "http://localhost:8081/" "http://localhost:8082/" \
+    &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+no_retry_answers=$(answers GET)
+
+# Assert -----------------------------------------------------------------------
+if [[ $get_answers == "backend2 200;backend2 200;backend2 200;backend2 200;" ]]; then
+    echo -e "${GREEN}The requests answered with a 503 succeeded on the other backend server.${NC}"
+else
+    echo -e "${RED}The GET requests were answered with ${get_answers}.${NC}"
+    test_passed=false
+fi
+
+if [[ $post_answers == *" 503;"* ]]; then
+    echo -e "${GREEN}The POST requests answered with a 503 were not retried.${NC}"
+else
+    echo -e "${RED}The POST requests were answered with ${post_answers}.${NC}"
+    test_passed=false
+fi
+
+if [[ $no_retry_answers == *" 503;"* ]]; then
+    echo -e "${GREEN}The 503 was returned to the client without retried statuses.${NC}"
+else
+    echo -e "${RED}Without retried statuses, the requests were answered with ${no_retry_answers}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $backend2_pid $lb_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
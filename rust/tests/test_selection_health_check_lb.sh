@@ -0,0 +1,80 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that the round robin load balancer selects the backend servers from their
+# last known health, without sending them a health check on each request
+# ------------------------------------------------------------------------------
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+# Each backend server answers /health-count with the number of health checks it
+# received
+backend_pids=()
+for port in 8081 8082; do
+    python3 -c '
+import http.server
+import sys
+
+health_checks = 0
+
+class Handler(http.server.BaseHTTPRequestHandler):
+    def do_GET(self):
+        global health_checks
+        if self.path == "/health":
+            health_checks += 1
+        body = str(health_checks).encode() if self.path == "/health-count" else b""
+        self.send_response(200)
+        self.send_header("Content-Length", str(len(body)))
+        self.end_headers()
+        self.wfile.write(body)
+
+http.server.HTTPServer(("localhost", int(sys.argv[1])), Handler).serve_forever()
+' $port > /dev/null 2>&1 &
+    backend_pids+=($!)
+    wait_for_server "backend on port ${port}" $port
+done
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+# Only the health checks before accepting requests run during the test
+cargo run -p lb -- -i 60 --strategy round-robin "http://localhost:8081/" "http://localhost:8082/" \
+    &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+statuses=""
+for i in $(seq 1 10); do
+    statuses+=$(curl --silent --output /dev/null --write-out "%{http_code} " http://localhost:8080/)
+done
+health_checks="$(curl --silent http://localhost:8081/health-count) $(curl --silent http://localhost:8082/health-count)"
+
+# Assert -----------------------------------------------------------------------
+if [[ $statuses == "$(printf "200 %.0s" $(seq 1 10))" ]]; then
+    echo -e "${GREEN}All the requests were answered.${NC}"
+else
+    echo -e "${RED}The requests were answered with ${statuses}.${NC}"
+    test_passed=false
+fi
+
+if [[ $health_checks == "1 1" ]]; then
+    echo -e "${GREEN}No health check was sent while selecting the backend servers.${NC}"
+else
+    echo -e "${RED}The backend servers received ${health_checks} health checks, expected 1 1.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids "${backend_pids[@]}" $lb_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,77 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that a backend server becoming healthy again receives a reduced share of
+# the requests during its slow start
+# ------------------------------------------------------------------------------
+
+# Waits until the admin API reports the given backend server with the given
+# health
+wait_for_health() {
+    for i in $(seq 1 50); do
+        if curl --silent http://localhost:9090/admin/backends \
+            | grep -q "\"address\":\"$1\",\"health\":\"$2\""; then
+            return
+        fi
+        sleep 0.1
+    done
+}
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+cargo run -p be -- -n "backend1" -p 8081 > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+cargo run -p be -- -n "backend2" -p 8082 > /dev/null 2>&1 &
+backend2_pid=$!
+wait_for_server "backend2" 8082
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+cargo run -p lb -- -i 500ms "http://localhost:8081/" "http://localhost:8082/" \
+    --slow-start 60s --admin-port 9090 &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+wait_for_server "admin API" 9090
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+kill_pids $backend2_pid
+wait_for_health "http://localhost:8082/" "Unhealthy"
+
+cargo run -p be -- -n "backend2" -p 8082 > /dev/null 2>&1 &
+backend2_pid=$!
+wait_for_server "backend2" 8082
+wait_for_health "http://localhost:8082/" "Healthy"
+
+result=""
+for i in $(seq 1 40); do
+    result+=$(curl --silent http://localhost:8080/)
+done
+count_backend1=$(echo $result | grep -o "backend1" | wc -l)
+count_backend2=$(echo $result | grep -o "backend2" | wc -l)
+
+# Assert -----------------------------------------------------------------------
+# Without slow start, each backend server would receive 20 requests
+if [[ $((count_backend1 + count_backend2)) -eq 40 && $count_backend2 -lt 10 ]]; then
+    echo -e "${GREEN}Backend 2 received ${count_backend2} requests out of 40 after its recovery.${NC}"
+else
+    echo -e "${RED}Backend 2 did not receive a reduced share of the requests
+    backend1=${count_backend1}, backend2=${count_backend2}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $backend2_pid $lb_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,88 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that /admin/stats answers with an HTML page reloading itself at every
+# health check, listing the strategy and each backend server with its health,
+# and that it is only served by the admin API, behind the admin token
+# ------------------------------------------------------------------------------
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+cargo run -p be -- -n "backend1" -p 8081 > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+cargo run -p be -- -n "backend2" -p 8082 > /dev/null 2>&1 &
+backend2_pid=$!
+wait_for_server "backend2" 8082
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+LB_ADMIN_TOKEN=secret cargo run -p lb -- -i 5 --strategy least-response --admin-port 9090 \
+    "http://localhost:8081/" "http://localhost:8082/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+wait_for_server "admin API" 9090
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+curl --silent --output /dev/null http://localhost:8080/
+content_type=$(curl --silent --output /dev/null --write-out "%{content_type}" \
+    -H "Authorization: Bearer secret" http://localhost:9090/admin/stats)
+page=$(curl --silent -H "Authorization: Bearer secret" http://localhost:9090/admin/stats)
+unauthorized_status=$(curl --silent --output /dev/null --write-out "%{http_code}" \
+    http://localhost:9090/admin/stats)
+# Forwarded to a backend server like any other path
+public_page=$(curl --silent http://localhost:8080/stats)
+
+# Assert -----------------------------------------------------------------------
+if [[ $content_type == text/html* && $page == *'<meta http-equiv="refresh" content="5">'* ]]; then
+    echo -e "${GREEN}The stats page is HTML reloading itself every 5s.${NC}"
+else
+    echo -e "${RED}The stats page was answered as ${content_type}.${NC}"
+    test_passed=false
+fi
+
+if [[ $page == *"Strategy: least-response"* ]]; then
+    echo -e "${GREEN}The stats page shows the strategy.${NC}"
+else
+    echo -e "${RED}The stats page does not show the strategy.${NC}"
+    test_passed=false
+fi
+
+for address in "http://localhost:8081/" "http://localhost:8082/"; do
+    if echo "$page" | grep -q "<td>${address}</td><td class=\"Healthy\">Healthy</td>"; then
+        echo -e "${GREEN}The stats page lists ${address} as healthy.${NC}"
+    else
+        echo -e "${RED}The stats page does not list ${address} as healthy.${NC}"
+        test_passed=false
+    fi
+done
+
+if [[ $unauthorized_status -eq 401 ]]; then
+    echo -e "${GREEN}The stats page without the admin token was answered with a 401.${NC}"
+else
+    echo -e "${RED}The stats page without the admin token was answered with a ${unauthorized_status}.${NC}"
+    test_passed=false
+fi
+
+if [[ $public_page != *"Strategy:"* ]]; then
+    echo -e "${GREEN}The stats page is not served by the load balancer.${NC}"
+else
+    echo -e "${RED}The stats page is served by the load balancer.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $backend2_pid $lb_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,99 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that each value of --strategy, and each deprecated flag, starts the
+# corresponding load balancer
+# ------------------------------------------------------------------------------
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+cargo run -p be -- -n "backend1" -p 8081 > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+lb_log=$(mktemp)
+
+# Starts the load balancer with the given flags and prints the type of the load
+# balancer it started
+started_load_balancer() {
+    cargo run -p lb -- -i 10 "$@" "http://localhost:8081/" > "$lb_log" 2>&1 &
+    local lb_pid=$!
+    wait_for_server "load balancer" 8080 > /dev/null
+    grep -o "Starting a [A-Za-z]* load balancer" "$lb_log" | cut -d " " -f 3
+    kill_pids $lb_pid > /dev/null
+}
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+declare -A expected_load_balancers=(
+    ["--strategy round-robin"]="RoundRobin"
+    ["--strategy least-response"]="LeastResponse"
+    ["--strategy power-of-two-choices"]="PowerOfTwoChoices"
+    ["--strategy p2c"]="PowerOfTwoChoices"
+    ["--strategy consistent-hash"]="ConsistentHash"
+    ["--dynamic"]="LeastResponse"
+    ["--power-of-two-choices"]="PowerOfTwoChoices"
+    ["--consistent-hash"]="ConsistentHash"
+)
+declare -A started_load_balancers
+for flags in "${!expected_load_balancers[@]}"; do
+    started_load_balancers[$flags]=$(started_load_balancer $flags)
+done
+default_load_balancer=$(started_load_balancer)
+
+# The geo load balancer needs a GeoIP database, --strategy and a deprecated flag
+# cannot be combined
+cargo run -p lb -- -i 10 --strategy geo "http://localhost:8081/" > "$lb_log" 2>&1
+geo_output=$(cat "$lb_log")
+cargo run -p lb -- -i 10 --strategy round-robin --dynamic "http://localhost:8081/" > "$lb_log" 2>&1
+conflict_output=$(cat "$lb_log")
+
+# Assert -----------------------------------------------------------------------
+for flags in "${!expected_load_balancers[@]}"; do
+    expected=${expected_load_balancers[$flags]}
+    started=${started_load_balancers[$flags]}
+    if [[ $started == "$expected" ]]; then
+        echo -e "${GREEN}${flags} started a ${started} load balancer.${NC}"
+    else
+        echo -e "${RED}${flags} started a '${started}' load balancer instead of a ${expected} one.${NC}"
+        test_passed=false
+    fi
+done
+
+if [[ $default_load_balancer == "RoundRobin" ]]; then
+    echo -e "${GREEN}A RoundRobin load balancer is started by default.${NC}"
+else
+    echo -e "${RED}A '${default_load_balancer}' load balancer is started by default.${NC}"
+    test_passed=false
+fi
+
+if echo "$geo_output" | grep -q "Starting a Geo load balancer" \
+    && echo "$geo_output" | grep -q "requires a GeoIP database"; then
+    echo -e "${GREEN}--strategy geo selects the Geo load balancer, which requires a GeoIP database.${NC}"
+else
+    echo -e "${RED}--strategy geo did not select the Geo load balancer: ${geo_output}.${NC}"
+    test_passed=false
+fi
+
+if echo "$conflict_output" | grep -q "cannot be used with"; then
+    echo -e "${GREEN}--strategy cannot be combined with a deprecated flag.${NC}"
+else
+    echo -e "${RED}--strategy was combined with a deprecated flag: ${conflict_output}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid
+rm -f "$lb_log"
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,120 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that the response of a backend server is streamed to the client as it is
+# received, and arrives intact
+# ------------------------------------------------------------------------------
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+# The backend servers answer with an 8MB body, sending the first megabyte,
+# waiting for 2 seconds, then sending the rest. backend1 gives the length of the
+# body and backend2 sends it in chunks
+backend_script='
+import http.server
+import sys
+import time
+
+BODY = bytes(range(256)) * 4096 * 8
+
+class Handler(http.server.BaseHTTPRequestHandler):
+    protocol_version = "HTTP/1.1"
+
+    def do_GET(self):
+        self.send_response(200)
+        if self.path == "/health":
+            self.send_header("Content-Length", "0")
+            self.end_headers()
+            return
+        self.send_header("Content-Type", "application/octet-stream")
+        chunked = sys.argv[2] == "chunked"
+        if chunked:
+            self.send_header("Transfer-Encoding", "chunked")
+        else:
+            self.send_header("Content-Length", str(len(BODY)))
+        self.end_headers()
+        for start, end in [(0, 1024 * 1024), (1024 * 1024, len(BODY))]:
+            if start > 0:
+                time.sleep(2)
+            if chunked:
+                self.wfile.write(b"%x\r\n" % (end - start) + BODY[start:end] + b"\r\n")
+            else:
+                self.wfile.write(BODY[start:end])
+            self.wfile.flush()
+        if chunked:
+            self.wfile.write(b"0\r\n\r\n")
+
+http.server.HTTPServer(("localhost", int(sys.argv[1])), Handler).serve_forever()
+'
+python3 -c "$backend_script" 8081 length > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+python3 -c "$backend_script" 8082 chunked > /dev/null 2>&1 &
+backend2_pid=$!
+wait_for_server "backend2" 8082
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+cargo run -p lb -- -i 10 "http://localhost:8081/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+
+expected_sha=$(python3 -c 'import hashlib; print(hashlib.sha256(bytes(range(256)) * 4096 * 8).hexdigest())')
+body_file=$(mktemp)
+headers_file=$(mktemp)
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+first_byte_time=$(curl --silent --dump-header "$headers_file" --output "$body_file" \
+    --write-out "%{time_starttransfer}" http://localhost:8080/)
+body_sha=$(sha256sum "$body_file" | cut -d " " -f 1)
+headers=$(cat "$headers_file")
+
+kill_pids $lb_pid
+
+cargo run -p lb -- -i 10 "http://localhost:8082/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+curl --silent --dump-header "$headers_file" --output "$body_file" http://localhost:8080/
+chunked_body_sha=$(sha256sum "$body_file" | cut -d " " -f 1)
+chunked_headers=$(cat "$headers_file")
+
+# Assert -----------------------------------------------------------------------
+if [[ $body_sha == "$expected_sha" && $chunked_body_sha == "$expected_sha" ]]; then
+    echo -e "${GREEN}The body of 8MB arrived intact.${NC}"
+else
+    echo -e "${RED}The body of 8MB did not arrive intact.${NC}"
+    test_passed=false
+fi
+
+if python3 -c "import sys; sys.exit(0 if float('$first_byte_time') < 1.5 else 1)"; then
+    echo -e "${GREEN}The first byte arrived after ${first_byte_time}s, before the end of the body.${NC}"
+else
+    echo -e "${RED}The first byte arrived after ${first_byte_time}s, the body was buffered.${NC}"
+    test_passed=false
+fi
+
+if echo "$headers" | grep -qi "^content-length: 8388608" \
+    && echo "$headers" | grep -qi "^content-type: application/octet-stream" \
+    && echo "$chunked_headers" | grep -qi "^transfer-encoding: chunked"; then
+    echo -e "${GREEN}The Content-Type and the framing of the body were kept.${NC}"
+else
+    echo -e "${RED}The headers were not kept: ${headers} ${chunked_headers}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $backend2_pid $lb_pid
+rm -f "$body_file" "$headers_file"
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,65 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that concurrent requests do not deadlock the load balancer while the
+# health of a backend server changes
+# ------------------------------------------------------------------------------
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+cargo run -p be -- -n "backend1" -p 8081 > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+cargo build -p be > /dev/null 2>&1
+../target/debug/be -n "backend2" -p 8082 > /dev/null 2>&1 &
+backend2_pid=$!
+wait_for_server "backend2" 8082
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+cargo run -p lb -- -i 1 "http://localhost:8081/" "http://localhost:8082/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+urls=$(mktemp)
+for i in $(seq 1 500); do
+    echo "url = \"http://localhost:8080/\"" >> "$urls"
+done
+curl --silent --no-progress-meter --parallel --parallel-max 50 --max-time 10 --config "$urls" > /dev/null &
+curl_pid=$!
+
+# Restart backend2 while the requests are sent so that its health changes
+sleep 1
+kill $backend2_pid
+sleep 2
+../target/debug/be -n "backend2" -p 8082 > /dev/null 2>&1 &
+backend2_pid=$!
+wait $curl_pid
+
+result=$(curl --silent --max-time 5 http://localhost:8080/)
+rm -f "$urls"
+
+# Assert -----------------------------------------------------------------------
+if [[ $result == *"backend"* ]]; then
+    echo -e "${GREEN}The load balancer still answers after the concurrent requests.${NC}"
+else
+    echo -e "${RED}The load balancer did not answer after the concurrent requests.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $backend2_pid $lb_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
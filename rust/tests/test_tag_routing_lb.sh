@@ -0,0 +1,108 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that a request asking for a tag with a route tag header only goes to the
+# backend servers having it, and falls back to all the backend servers when none
+# of them has it or is available
+# ------------------------------------------------------------------------------
+
+# Prints the backend servers which answered 20 requests with the given version
+# in the route tag header, none if empty
+answers() {
+    for i in $(seq 1 20); do
+        curl --silent --header "X-Route-Version: $1" http://localhost:8080/ | grep -o "backend[0-9]"
+    done | sort -u | tr "\n" " "
+}
+
+config_file=$(mktemp --suffix .toml)
+
+for strategy in round-robin weighted-random; do
+    echo -e "${GREEN}Testing the ${strategy} strategy...${NC}"
+
+    # Arrange ------------------------------------------------------------------
+    echo -e "${GREEN}Starting backend servers...${NC}"
+    cargo run -p be -- -n "backend1" -p 8081 > /dev/null 2>&1 &
+    backend1_pid=$!
+    wait_for_server "backend1" 8081
+
+    cargo run -p be -- -n "backend2" -p 8082 > /dev/null 2>&1 &
+    backend2_pid=$!
+    wait_for_server "backend2" 8082
+
+    cargo run -p be -- -n "backend3" -p 8083 > /dev/null 2>&1 &
+    backend3_pid=$!
+    wait_for_server "backend3" 8083
+
+    cat > "$config_file" << EOF
+strategy = "$strategy"
+backends = [
+    { address = "http://localhost:8081/", tags = { version = "stable" } },
+    { address = "http://localhost:8082/", tags = { version = "canary" } },
+    "http://localhost:8083/",
+]
+EOF
+
+    echo -e "${GREEN}Starting load balancer...${NC}"
+    cargo run -p lb -- -i 1 --route-tag-header X-Route-Version=version --config "$config_file" \
+        &> /dev/null 2>&1 &
+    lb_pid=$!
+    wait_for_server "load balancer" 8080
+
+    # Act ----------------------------------------------------------------------
+    echo -e "${GREEN}Running tests...${NC}"
+    untagged_answers=$(answers "")
+    canary_answers=$(answers canary)
+    stable_answers=$(answers stable)
+    unknown_answers=$(answers beta)
+
+    # Once a health check found it down
+    kill_pids $backend2_pid > /dev/null
+    sleep 2
+    unavailable_answers=$(answers canary)
+
+    # Assert -------------------------------------------------------------------
+    if [[ $untagged_answers == "backend1 backend2 backend3 " ]]; then
+        echo -e "${GREEN}The requests without tag went to all the backend servers.${NC}"
+    else
+        echo -e "${RED}The requests without tag were answered by ${untagged_answers}.${NC}"
+        test_passed=false
+    fi
+
+    if [[ $canary_answers == "backend2 " && $stable_answers == "backend1 " ]]; then
+        echo -e "${GREEN}The requests with a tag went to the backend servers having it.${NC}"
+    else
+        echo -e "${RED}The canary requests were answered by ${canary_answers}and the stable ones by ${stable_answers}.${NC}"
+        test_passed=false
+    fi
+
+    if [[ $unknown_answers == "backend1 backend2 backend3 " ]]; then
+        echo -e "${GREEN}The requests with an unknown tag fell back to all the backend servers.${NC}"
+    else
+        echo -e "${RED}The requests with an unknown tag were answered by ${unknown_answers}.${NC}"
+        test_passed=false
+    fi
+
+    if [[ $unavailable_answers == "backend1 backend3 " ]]; then
+        echo -e "${GREEN}The requests fell back to the other backend servers when the tagged one was down.${NC}"
+    else
+        echo -e "${RED}With the canary down, the canary requests were answered by ${unavailable_answers}.${NC}"
+        test_passed=false
+    fi
+
+    echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+    kill_pids $backend1_pid $backend3_pid $lb_pid
+done
+
+rm -f "$config_file"
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,63 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test the TCP health checks with a reachable and a refused backend server
+# ------------------------------------------------------------------------------
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+# backend1 accepts TCP connections but does not speak HTTP, so it has no /health
+python3 -c '
+import socket
+
+server = socket.create_server(("localhost", 8081))
+while True:
+    connection, _ = server.accept()
+    connection.close()
+' > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+# Nothing listens on port 8082, so the connections to backend2 are refused
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+cargo run -p lb -- -i 1 "http://localhost:8081/" "http://localhost:8082/" --tcp-health-check --admin-port 9090 &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+wait_for_server "admin API" 9090
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+# Wait for a health check of both backend servers
+sleep 2
+backends=$(curl --silent http://localhost:9090/admin/backends)
+
+# Assert -----------------------------------------------------------------------
+if [[ $backends == *'"address":"http://localhost:8081/","health":"Healthy"'* ]]; then
+    echo -e "${GREEN}Backend 1 is healthy.${NC}"
+else
+    echo -e "${RED}Backend 1 is not healthy: ${backends}.${NC}"
+    test_passed=false
+fi
+
+if [[ $backends == *'"address":"http://localhost:8082/","health":"Unhealthy"'* ]]; then
+    echo -e "${GREEN}Backend 2 is unhealthy.${NC}"
+else
+    echo -e "${RED}Backend 2 is not unhealthy: ${backends}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $lb_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,86 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that the load balancer serving HTTPS rejects the clients using a TLS
+# version below the minimum one and accepts the others, and that it refuses to
+# start with an unsupported minimum version
+# ------------------------------------------------------------------------------
+
+# Sends a request over HTTPS with the given curl options and prints the answer,
+# or "rejected" if the TLS handshake failed
+https_request() {
+    curl --silent --insecure "$@" https://localhost:8443/ || echo "rejected"
+}
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+cargo run -p be -- -n "backend1" -p 8081 > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+tls_dir=$(mktemp -d)
+openssl req -x509 -newkey rsa:2048 -nodes -days 1 -subj "/CN=localhost" \
+    -keyout "$tls_dir/key.pem" -out "$tls_dir/cert.pem" > /dev/null 2>&1
+
+echo -e "${GREEN}Starting load balancer with the default minimum version...${NC}"
+cargo run -p lb -- -i 10 --listen-port 8443 --tls-cert "$tls_dir/cert.pem" \
+    --tls-key "$tls_dir/key.pem" "http://localhost:8081/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8443
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+default_tls12_answer=$(https_request --tlsv1.2 --tls-max 1.2)
+default_tls11_answer=$(https_request --tlsv1.1 --tls-max 1.1)
+kill_pids $lb_pid
+
+echo -e "${GREEN}Starting load balancer with TLS 1.3 as minimum version...${NC}"
+cargo run -p lb -- -i 10 --listen-port 8443 --tls-cert "$tls_dir/cert.pem" \
+    --tls-key "$tls_dir/key.pem" --tls-min-version 1.3 "http://localhost:8081/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8443
+tls12_answer=$(https_request --tlsv1.2 --tls-max 1.2)
+tls13_answer=$(https_request --tlsv1.3)
+kill_pids $lb_pid
+
+cargo run -p lb -- -i 10 --listen-port 8443 --tls-cert "$tls_dir/cert.pem" \
+    --tls-key "$tls_dir/key.pem" --tls-min-version 1.1 "http://localhost:8081/" &> /dev/null
+unsupported_version_status=$?
+
+# Assert -----------------------------------------------------------------------
+if [[ $default_tls12_answer == *"backend1"* && $default_tls11_answer == "rejected" ]]; then
+    echo -e "${GREEN}By default, TLS 1.2 was accepted and TLS 1.1 rejected.${NC}"
+else
+    echo -e "${RED}By default, TLS 1.2 got ${default_tls12_answer} and TLS 1.1 got ${default_tls11_answer}.${NC}"
+    test_passed=false
+fi
+
+if [[ $tls12_answer == "rejected" && $tls13_answer == *"backend1"* ]]; then
+    echo -e "${GREEN}With TLS 1.3 as minimum version, TLS 1.2 was rejected and TLS 1.3 accepted.${NC}"
+else
+    echo -e "${RED}With TLS 1.3 as minimum version, TLS 1.2 got ${tls12_answer} and TLS 1.3 got ${tls13_answer}.${NC}"
+    test_passed=false
+fi
+
+if [[ $unsupported_version_status -ne 0 ]]; then
+    echo -e "${GREEN}The load balancer refused to start with TLS 1.1 as minimum version.${NC}"
+else
+    echo -e "${RED}The load balancer started with TLS 1.1 as minimum version.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers...${NC}"
+kill_pids $backend1_pid
+rm -rf "$tls_dir"
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,81 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that the User-Agent of the client is passed through to the backend server
+# by default, and that the configured User-Agent and headers replace the ones of
+# the client
+# ------------------------------------------------------------------------------
+
+# Sends a request as the given User-Agent and prints the User-Agent and X-Env
+# headers received by the backend server
+send_request() {
+    curl --silent --user-agent "$1" --header "X-Env: dev" http://localhost:8080/
+}
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+# backend1 answers with the User-Agent and X-Env headers it received
+python3 -c '
+import http.server
+
+class Handler(http.server.BaseHTTPRequestHandler):
+    def do_GET(self):
+        body = "{}|{}".format(
+            ",".join(self.headers.get_all("User-Agent", [])),
+            ",".join(self.headers.get_all("X-Env", [])),
+        ).encode()
+        self.send_response(200)
+        self.send_header("Content-Length", str(len(body)))
+        self.end_headers()
+        self.wfile.write(body)
+
+http.server.ThreadingHTTPServer(("localhost", 8081), Handler).serve_forever()
+' > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+echo -e "${GREEN}Starting load balancer...${NC}"
+cargo run -p lb -- -i 10 "http://localhost:8081/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+passed_through=$(send_request "client/1.0")
+kill_pids $lb_pid > /dev/null
+
+echo -e "${GREEN}Starting load balancer with a User-Agent and header overrides...${NC}"
+cargo run -p lb -- -i 10 --user-agent "lb/2.0 (internal)" --set-request-header "X-Env: production" \
+    "http://localhost:8081/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+overridden=$(send_request "client/1.0")
+
+# Assert -----------------------------------------------------------------------
+if [[ $passed_through == "client/1.0|dev" ]]; then
+    echo -e "${GREEN}The User-Agent of the client was passed through.${NC}"
+else
+    echo -e "${RED}The backend server received ${passed_through}.${NC}"
+    test_passed=false
+fi
+
+if [[ $overridden == "lb/2.0 (internal)|production" ]]; then
+    echo -e "${GREEN}The configured User-Agent and header replaced the ones of the client.${NC}"
+else
+    echo -e "${RED}The backend server received ${overridden}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $lb_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,161 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that the WebSocket frames are proxied in both directions between the
+# client and the backend server, and that the upgrade request is sent like the
+# other requests: under the base path of the backend server, with the filtered
+# and overridden headers, also to a backend server with an IPv6 address
+# ------------------------------------------------------------------------------
+
+# Opens a WebSocket to the load balancer on /echo, sends the given text frames,
+# prints the status of the upgrade and the replies and closes the WebSocket
+websocket() {
+    timeout 10 python3 -c '
+import os
+import socket
+import sys
+
+connection = socket.create_connection(("localhost", 8080))
+connection.sendall(b"GET /echo HTTP/1.1\r\nHost: localhost:8080\r\nUpgrade: websocket\r\n"
+                   b"Connection: Upgrade\r\nSec-WebSocket-Version: 13\r\n"
+                   b"X-Internal-Auth: secret\r\n"
+                   b"Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n")
+file = connection.makefile("rb")
+print(file.readline().decode().strip())
+while file.readline().strip():
+    pass
+
+def send(opcode, payload):
+    mask = os.urandom(4)
+    masked = bytes(b ^ mask[i % 4] for i, b in enumerate(payload))
+    connection.sendall(bytes([0x80 | opcode, 0x80 | len(payload)]) + mask + masked)
+
+for message in sys.argv[1:]:
+    send(1, message.encode())
+    opcode, length = file.read(2)
+    print(file.read(length & 0x7f).decode())
+send(8, b"")
+print("closed" if file.read(2) == b"\x88\x00" else "not closed")
+' "$@"
+}
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+# backend1 listens on IPv4 and IPv6. It echoes the text frames it receives on a
+# path ending with /echo, prefixed by its name, and answers the "request" frame
+# with the path and some headers of the upgrade request
+python3 -c '
+import base64
+import hashlib
+import socket
+import socketserver
+import struct
+
+GUID = b"258EAFA5-E914-47DA-95CA-C5AB0DC85B11"
+
+class Handler(socketserver.StreamRequestHandler):
+    def handle(self):
+        request_line = self.rfile.readline().decode()
+        headers = {}
+        while (line := self.rfile.readline().decode().strip()):
+            name, value = line.split(":", 1)
+            headers[name.strip().lower()] = value.strip()
+        if "/health" in request_line:
+            self.wfile.write(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+            return
+        path = request_line.split(" ")[1]
+        if not path.endswith("/echo") or headers.get("upgrade") != "websocket":
+            self.wfile.write(b"HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\n\r\n")
+            return
+        accept = base64.b64encode(hashlib.sha1(headers["sec-websocket-key"].encode() + GUID).digest())
+        self.wfile.write(b"HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\n"
+                         b"Connection: Upgrade\r\nSec-WebSocket-Accept: " + accept + b"\r\n\r\n")
+        while True:
+            opcode, length = struct.unpack("!BB", self.rfile.read(2))
+            length &= 0x7f
+            if length == 126:
+                length = struct.unpack("!H", self.rfile.read(2))[0]
+            mask = self.rfile.read(4)
+            payload = bytes(b ^ mask[i % 4] for i, b in enumerate(self.rfile.read(length)))
+            if opcode & 0x0f == 8:
+                self.wfile.write(b"\x88\x00")
+                return
+            reply = b"backend1: " + payload
+            if payload == b"request":
+                reply = (path + " user-agent=" + str(headers.get("user-agent"))
+                         + " x-internal-auth=" + str(headers.get("x-internal-auth"))).encode()
+            self.wfile.write(bytes([0x81, len(reply)]) + reply)
+
+class Server(socketserver.ThreadingMixIn, socketserver.TCPServer):
+    daemon_threads = True
+    allow_reuse_address = True
+    address_family = socket.AF_INET6
+
+    def server_bind(self):
+        self.socket.setsockopt(socket.IPPROTO_IPV6, socket.IPV6_V6ONLY, 0)
+        super().server_bind()
+
+Server(("::", 8081), Handler).serve_forever()
+' > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+config_file=$(mktemp --suffix .toml)
+cat > "$config_file" << EOF
+backends = [{ address = "http://[::1]:8081/", base_path = "/app" }]
+EOF
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+cargo run -p lb -- -i 10 "http://localhost:8081/" &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+result=$(websocket hello world)
+kill_pids $lb_pid
+
+echo -e "${GREEN}Starting load balancer with a base path and header settings...${NC}"
+cargo run -p lb -- -i 10 --config "$config_file" --deny-request-header X-Internal-Auth \
+    --user-agent lb-test &> /dev/null 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+request_result=$(websocket request)
+
+# Assert -----------------------------------------------------------------------
+expected="HTTP/1.1 101 Switching Protocols
+backend1: hello
+backend1: world
+closed"
+if [[ $result == "$expected" ]]; then
+    echo -e "${GREEN}The frames were echoed through the load balancer.${NC}"
+else
+    echo -e "${RED}The frames were not echoed through the load balancer: ${result}.${NC}"
+    test_passed=false
+fi
+
+expected="HTTP/1.1 101 Switching Protocols
+/app/echo user-agent=lb-test x-internal-auth=None
+closed"
+if [[ $request_result == "$expected" ]]; then
+    echo -e "${GREEN}The upgrade was sent to the IPv6 address under the base path, with the filtered and overridden headers.${NC}"
+else
+    echo -e "${RED}The upgrade was not sent like the other requests: ${request_result}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $lb_pid
+rm -f "$config_file"
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,81 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that once the primary backend server is down, the requests are spread
+# among the backup backend servers by their weights
+# ------------------------------------------------------------------------------
+
+config_file=$(mktemp --suffix .toml)
+
+for strategy in round-robin least-response; do
+    echo -e "${GREEN}Testing the ${strategy} strategy...${NC}"
+
+    # Arrange ------------------------------------------------------------------
+    echo -e "${GREEN}Starting backend servers...${NC}"
+    cargo run -p be -- -n "backend1" -p 8081 > /dev/null 2>&1 &
+    backend1_pid=$!
+    wait_for_server "backend1" 8081
+
+    cargo run -p be -- -n "backend2" -p 8082 > /dev/null 2>&1 &
+    backend2_pid=$!
+    wait_for_server "backend2" 8082
+
+    cargo run -p be -- -n "backend3" -p 8083 > /dev/null 2>&1 &
+    backend3_pid=$!
+    wait_for_server "backend3" 8083
+
+    cat > "$config_file" << EOF
+strategy = "${strategy}"
+backends = [
+    "http://localhost:8081/",
+    { address = "http://localhost:8082/", backup = true, weight = 1 },
+    { address = "http://localhost:8083/", backup = true, weight = 3 },
+]
+EOF
+
+    echo -e "${GREEN}Starting load balancer...${NC}"
+    cargo run -p lb -- -i 1 --config "$config_file" &> /dev/null 2>&1 &
+    lb_pid=$!
+    wait_for_server "load balancer" 8080
+
+    # Act ----------------------------------------------------------------------
+    echo -e "${GREEN}Running tests...${NC}"
+    # Once a health check found the primary down
+    kill_pids $backend1_pid > /dev/null
+    sleep 2
+    result=""
+    for i in $(seq 1 200); do
+        result+=$(curl --silent http://localhost:8080/)
+        result+=$'\n'
+    done
+    count_backend1=$(echo "$result" | grep -c "backend1")
+    count_backend2=$(echo "$result" | grep -c "backend2")
+    count_backend3=$(echo "$result" | grep -c "backend3")
+
+    # Assert -------------------------------------------------------------------
+    # backend2 has a quarter of the weight of the backups, about 50 requests
+    if [[ $count_backend1 -eq 0 && $count_backend2 -ge 25 && $count_backend2 -le 75 \
+        && $((count_backend2 + count_backend3)) -eq 200 ]]; then
+        echo -e "${GREEN}The requests were spread among the backups by their weights.${NC}"
+    else
+        echo -e "${RED}The requests went ${count_backend1} to backend1, ${count_backend2} to backend2 and ${count_backend3} to backend3.${NC}"
+        test_passed=false
+    fi
+
+    echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+    kill_pids $backend2_pid $backend3_pid $lb_pid
+done
+
+rm -f "$config_file"
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
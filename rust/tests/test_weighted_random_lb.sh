@@ -0,0 +1,93 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that the weighted random load balancer spreads the requests in proportion
+# to the weights of the healthy backend servers, reproducibly with a seed
+# ------------------------------------------------------------------------------
+
+# Prints the backend server which answered each of the given number of requests
+answers() {
+    for i in $(seq 1 $1); do
+        curl --silent http://localhost:8080/ | grep -o "backend[0-9]"
+    done | tr "\n" " "
+}
+
+# Starts the load balancer with the given seed
+start_lb() {
+    cargo run -p lb -- -i 1 --strategy weighted-random --random-seed $1 \
+        "http://localhost:8081/|1" "http://localhost:8082/|3" &> /dev/null 2>&1 &
+    lb_pid=$!
+    wait_for_server "load balancer" 8080
+}
+
+# Prints true if the given value is in the given range [min, max)
+in_range() {
+    python3 -c "import sys; print(str($2 <= float('$1') < $3).lower())"
+}
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+cargo run -p be -- -n "backend1" -p 8081 > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+cargo run -p be -- -n "backend2" -p 8082 > /dev/null 2>&1 &
+backend2_pid=$!
+wait_for_server "backend2" 8082
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+echo -e "${GREEN}Starting load balancer...${NC}"
+start_lb 42
+first_run=$(answers 400)
+kill_pids $lb_pid > /dev/null
+
+echo -e "${GREEN}Starting load balancer...${NC}"
+start_lb 42
+second_run=$(answers 400)
+
+# Once a health check found it down
+kill_pids $backend2_pid > /dev/null
+sleep 2
+unhealthy_answers=$(answers 20 | tr " " "\n" | sort -u | tr "\n" " ")
+
+count_backend2=$(echo $first_run | grep -o "backend2" | wc -l)
+share_backend2=$(python3 -c "print($count_backend2 / 400)")
+
+# Assert -----------------------------------------------------------------------
+# backend2 has 3 of the 4 weights, the tolerance is about 4 standard deviations
+if [[ $(in_range "$share_backend2" 0.66 0.84) == true ]]; then
+    echo -e "${GREEN}backend2 received ${share_backend2} of the requests for 0.75 of the weights.${NC}"
+else
+    echo -e "${RED}backend2 received ${share_backend2} of the requests for 0.75 of the weights.${NC}"
+    test_passed=false
+fi
+
+if [[ $first_run == "$second_run" ]]; then
+    echo -e "${GREEN}The same seed picked the same backend servers.${NC}"
+else
+    echo -e "${RED}The same seed picked different backend servers.${NC}"
+    test_passed=false
+fi
+
+if [[ $unhealthy_answers == "backend1 " ]]; then
+    echo -e "${GREEN}The unhealthy backend server was left out of the distribution.${NC}"
+else
+    echo -e "${RED}The requests went to ${unhealthy_answers}with backend2 down.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend servers and load balancer...${NC}"
+kill_pids $backend1_pid $lb_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
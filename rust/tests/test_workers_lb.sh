@@ -0,0 +1,80 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that the load balancer serves the requests with the number of workers
+# given by --workers, with the number of CPUs by default, and rejects 0 workers
+# ------------------------------------------------------------------------------
+
+# Prints the number of workers logged by the load balancer in the given log file
+logged_workers() {
+    grep -o "Serving the requests with [0-9]* workers" "$1" | grep -o "[0-9]*"
+}
+
+# Arrange ----------------------------------------------------------------------
+log_file=$(mktemp)
+
+echo -e "${GREEN}Starting backend server...${NC}"
+cargo run -p be -- -n "backend1" -p 8081 > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+# Act --------------------------------------------------------------------------
+echo -e "${GREEN}Running tests...${NC}"
+echo -e "${GREEN}Starting load balancer with 2 workers...${NC}"
+RUST_LOG=info cargo run -p lb -- --workers 2 "http://localhost:8081/" > "$log_file" 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+given_workers=$(logged_workers "$log_file")
+given_answer=$(curl --silent http://localhost:8080/)
+kill_pids $lb_pid > /dev/null
+
+echo -e "${GREEN}Starting load balancer with the default workers...${NC}"
+RUST_LOG=info cargo run -p lb -- "http://localhost:8081/" > "$log_file" 2>&1 &
+lb_pid=$!
+wait_for_server "load balancer" 8080
+default_workers=$(logged_workers "$log_file")
+default_answer=$(curl --silent http://localhost:8080/)
+
+zero_workers_output=$(timeout 20 cargo run -p lb -- --workers 0 --listen-port 8090 \
+    "http://localhost:8081/" 2>&1)
+zero_workers_status=$?
+
+# Assert -----------------------------------------------------------------------
+if [[ $given_workers == "2" && $given_answer == *"backend1"* ]]; then
+    echo -e "${GREEN}The load balancer served the requests with the given workers.${NC}"
+else
+    echo -e "${RED}With --workers 2, the load balancer had ${given_workers} workers and answered ${given_answer}.${NC}"
+    test_passed=false
+fi
+
+if [[ -n $default_workers && $default_workers -ge 1 && $default_workers -le $(nproc --all) \
+    && $default_answer == *"backend1"* ]]; then
+    echo -e "${GREEN}The load balancer had one worker per CPU by default (${default_workers}).${NC}"
+else
+    echo -e "${RED}By default, the load balancer had ${default_workers} workers and answered ${default_answer}.${NC}"
+    test_passed=false
+fi
+
+if [[ $zero_workers_status -ne 0 && $zero_workers_status -ne 124 \
+    && $zero_workers_output == *"--workers"* ]]; then
+    echo -e "${GREEN}The load balancer did not start without workers.${NC}"
+else
+    echo -e "${RED}With --workers 0, the load balancer exited with ${zero_workers_status}: ${zero_workers_output}.${NC}"
+    test_passed=false
+fi
+
+echo -e "${YELLOW}Killing backend server and load balancer...${NC}"
+kill_pids $backend1_pid $lb_pid
+rm -f "$log_file"
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi
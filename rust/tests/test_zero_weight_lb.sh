@@ -0,0 +1,98 @@
+#!/bin/bash
+
+source ./common.sh
+
+test_passed=true
+
+# Test that a backend server with a weight of 0 is parked with the weighted
+# strategies: it receives no requests but is still health checked, and receives
+# requests again once its weight is raised through the admin API
+# ------------------------------------------------------------------------------
+
+# Prints the number of health checks passed by backend2, read from the admin API
+backend2_health_checks() {
+    curl --silent http://localhost:9090/admin/backends | python3 -c '
+import json, sys
+for backend in json.load(sys.stdin):
+    if backend["address"] == "http://localhost:8082/":
+        print(backend["health_checks_passed"])
+'
+}
+
+# Sends 10 requests to the load balancer and prints the number answered by
+# backend2
+count_backend2() {
+    local result=""
+    for i in $(seq 1 10); do
+        result+=$(curl --silent http://localhost:8080/)
+    done
+    echo "$result" | grep -o "backend2" | wc -l
+}
+
+# Arrange ----------------------------------------------------------------------
+echo -e "${GREEN}Starting backend servers...${NC}"
+cargo run -p be -- -n "backend1" -p 8081 > /dev/null 2>&1 &
+backend1_pid=$!
+wait_for_server "backend1" 8081
+
+cargo run -p be -- -n "backend2" -p 8082 > /dev/null 2>&1 &
+backend2_pid=$!
+wait_for_server "backend2" 8082
+
+for strategy in round-robin least-response weighted-random; do
+    echo -e "${GREEN}Starting ${strategy} load balancer...${NC}"
+    cargo run -p lb -- -i 500ms --strategy $strategy --admin-port 9090 \
+        "http://localhost:8081/|1" "http://localhost:8082/|0" &> /dev/null 2>&1 &
+    lb_pid=$!
+    wait_for_server "load balancer" 8080
+    wait_for_server "admin API" 9090
+
+    # Act ----------------------------------------------------------------------
+    echo -e "${GREEN}Running tests...${NC}"
+    health_checks_before=$(backend2_health_checks)
+    count_while_parked=$(count_backend2)
+    sleep 2
+    health_checks_after=$(backend2_health_checks)
+
+    weight_status=$(curl --silent --output /dev/null --write-out "%{http_code}" \
+        --request PUT --data 1 http://localhost:9090/admin/weight/http://localhost:8082/)
+    invalid_weight_status=$(curl --silent --output /dev/null --write-out "%{http_code}" \
+        --request PUT --data heavy http://localhost:9090/admin/weight/http://localhost:8082/)
+    count_after_raise=$(count_backend2)
+
+    kill_pids $lb_pid
+
+    # Assert -------------------------------------------------------------------
+    if [[ $count_while_parked -eq 0 ]]; then
+        echo -e "${GREEN}${strategy}: the parked backend server received no requests.${NC}"
+    else
+        echo -e "${RED}${strategy}: the parked backend server received ${count_while_parked} requests.${NC}"
+        test_passed=false
+    fi
+
+    if [[ $health_checks_after -gt $health_checks_before ]]; then
+        echo -e "${GREEN}${strategy}: the parked backend server is still health checked.${NC}"
+    else
+        echo -e "${RED}${strategy}: the parked backend server passed ${health_checks_before} then ${health_checks_after} health checks.${NC}"
+        test_passed=false
+    fi
+
+    if [[ $weight_status -eq 204 && $invalid_weight_status -eq 400 && $count_after_raise -gt 0 ]]; then
+        echo -e "${GREEN}${strategy}: the backend server receives requests once its weight is raised.${NC}"
+    else
+        echo -e "${RED}${strategy}: raising the weight gave ${weight_status}, an invalid weight ${invalid_weight_status}, and backend2 answered ${count_after_raise} requests.${NC}"
+        test_passed=false
+    fi
+done
+
+echo -e "${YELLOW}Killing backend servers...${NC}"
+kill_pids $backend1_pid $backend2_pid
+
+echo "-------------------------------------------------------------------"
+if [[ "$test_passed" == true ]]; then
+    echo -e "${GREEN}Test passed.${NC}"
+    exit 0
+else
+    echo -e "${RED}Test failed.${NC}"
+    exit 1
+fi